@@ -7,8 +7,10 @@ use std::collections::BTreeMap;
 use std::fmt::Write as _;
 use std::fs::{self, File};
 use std::io::{BufRead, BufReader, Write};
-use std::net::TcpListener;
+use std::net::{SocketAddr, TcpListener, TcpStream};
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
 use std::thread;
 use tar::{Builder, Header};
 use url::Url;
@@ -83,6 +85,9 @@ pub struct RegistryBuilder {
     alt_api_url: Option<String>,
     /// If `true`, configures `.cargo/credentials` with some tokens.
     add_tokens: bool,
+    /// If set, crates.io is replaced with this sparse registry URL instead of
+    /// the git index at `registry_url()`.
+    sparse_index_url: Option<Url>,
 }
 
 impl RegistryBuilder {
@@ -92,6 +97,7 @@ impl RegistryBuilder {
             alternative: false,
             alt_api_url: None,
             add_tokens: true,
+            sparse_index_url: None,
         }
     }
 
@@ -146,7 +152,7 @@ impl RegistryBuilder {
                     [source.dummy-registry]
                     registry = '{}'
                 ",
-                registry_url()
+                self.sparse_index_url.clone().unwrap_or_else(registry_url)
             )
             .unwrap();
         }
@@ -242,6 +248,149 @@ impl RegistryBuilder {
 
         t
     }
+
+    /// Initializes the registries, and serves the crates.io replacement index
+    /// over plain HTTP so that it's used as a sparse registry.
+    ///
+    /// Packages are still published into the git repository at
+    /// `registry_path()`, the server just serves the files in its working
+    /// directory. The server is shut down when the returned handle is dropped.
+    pub fn build_http_index(&mut self) -> HttpServerHandle {
+        let server = HttpServer::start(registry_path());
+        self.sparse_index_url = Some(server.index_url());
+        self.build();
+        server
+    }
+}
+
+/// A handle to an HTTP server started with
+/// [`RegistryBuilder::build_http_index`].
+pub struct HttpServerHandle {
+    addr: SocketAddr,
+    requests: Arc<Mutex<Vec<String>>>,
+    stop: Arc<AtomicBool>,
+    thread: Option<thread::JoinHandle<()>>,
+}
+
+impl HttpServerHandle {
+    /// The URL to use for the index in a cargo configuration.
+    pub fn index_url(&self) -> Url {
+        Url::parse(&format!("sparse+http://{}/", self.addr)).unwrap()
+    }
+
+    /// Returns the requests received since the last call, sorted.
+    ///
+    /// Each request is formatted as the method and path followed by the
+    /// status of the response, such as `GET /3/b/bar 200`.
+    pub fn take_requests(&self) -> Vec<String> {
+        let mut requests = std::mem::take(&mut *self.requests.lock().unwrap());
+        requests.sort();
+        requests
+    }
+}
+
+impl Drop for HttpServerHandle {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::SeqCst);
+        // Wake up the listener so it notices it should stop.
+        let _ = TcpStream::connect(self.addr);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+struct HttpServer;
+
+impl HttpServer {
+    fn start(root: PathBuf) -> HttpServerHandle {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread = {
+            let requests = Arc::clone(&requests);
+            let stop = Arc::clone(&stop);
+            thread::spawn(move || {
+                for conn in listener.incoming() {
+                    if stop.load(Ordering::SeqCst) {
+                        break;
+                    }
+                    let conn = match conn {
+                        Ok(conn) => conn,
+                        Err(_) => continue,
+                    };
+                    let root = root.clone();
+                    let requests = Arc::clone(&requests);
+                    thread::spawn(move || HttpServer::handle(conn, &root, &requests));
+                }
+            })
+        };
+        HttpServerHandle {
+            addr,
+            requests,
+            stop,
+            thread: Some(thread),
+        }
+    }
+
+    /// Answers a single request, serving the file it names from `root`.
+    ///
+    /// Responses carry an `ETag` header, and `If-None-Match` requests for
+    /// unchanged files get a `304 Not Modified`.
+    fn handle(conn: TcpStream, root: &Path, requests: &Mutex<Vec<String>>) {
+        let mut conn = BufReader::new(conn);
+        let mut lines = (&mut conn)
+            .lines()
+            .map(|s| s.unwrap_or_default())
+            .take_while(|s| !s.trim().is_empty());
+        let request_line = match lines.next() {
+            Some(line) => line,
+            None => return,
+        };
+        let mut if_none_match = None;
+        for line in lines {
+            if let Some((name, value)) = line.split_once(':') {
+                if name.eq_ignore_ascii_case("if-none-match") {
+                    if_none_match = Some(value.trim().to_string());
+                }
+            }
+        }
+        let mut parts = request_line.split_whitespace();
+        let method = parts.next().unwrap_or_default();
+        let path = parts.next().unwrap_or_default();
+
+        let file = root.join(path.trim_start_matches('/'));
+        let (status, headers, body) = match fs::read(&file) {
+            Ok(body) if file.is_file() => {
+                let etag = format!("\"{}\"", cksum(&body));
+                if if_none_match.as_deref() == Some(etag.as_str()) {
+                    ("304 Not Modified", String::new(), Vec::new())
+                } else {
+                    ("200 OK", format!("ETag: {}\r\n", etag), body)
+                }
+            }
+            _ => ("404 Not Found", String::new(), Vec::new()),
+        };
+        requests
+            .lock()
+            .unwrap()
+            .push(format!("{} {} {}", method, path, &status[..3]));
+
+        let stream = conn.get_mut();
+        let _ = write!(
+            stream,
+            "HTTP/1.1 {}\r\n\
+             {}\
+             Content-Length: {}\r\n\
+             Connection: close\r\n\
+             \r\n",
+            status,
+            headers,
+            body.len()
+        );
+        let _ = stream.write_all(&body);
+    }
 }
 
 /// A builder for creating a new package in a registry.
@@ -622,6 +771,9 @@ impl Package {
             json["features2"] = serde_json::json!(f2);
             json["v"] = serde_json::json!(2);
         }
+        if let Some(rust_version) = &self.rust_version {
+            json["rust_version"] = serde_json::json!(rust_version);
+        }
         if let Some(v) = self.v {
             json["v"] = serde_json::json!(v);
         }
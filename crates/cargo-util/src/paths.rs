@@ -105,6 +105,37 @@ pub fn normalize_path(path: &Path) -> PathBuf {
     ret
 }
 
+/// Computes a path from `base` to `target`, both of which must be absolute.
+///
+/// Like [`normalize_path`], this works purely lexically and does not access
+/// the filesystem.
+pub fn relative_path(base: &Path, target: &Path) -> PathBuf {
+    let base = normalize_path(base);
+    let target = normalize_path(target);
+    let mut base_components = base.components().peekable();
+    let mut target_components = target.components().peekable();
+    while let (Some(a), Some(b)) = (base_components.peek(), target_components.peek()) {
+        if a != b {
+            break;
+        }
+        base_components.next();
+        target_components.next();
+    }
+    let mut result = PathBuf::new();
+    for component in base_components {
+        if let Component::Normal(_) = component {
+            result.push("..");
+        }
+    }
+    for component in target_components {
+        result.push(component.as_os_str());
+    }
+    if result.as_os_str().is_empty() {
+        result.push(".");
+    }
+    result
+}
+
 /// Returns the absolute path of where the given executable is located based
 /// on searching the `PATH` environment variable.
 ///
@@ -1,5 +1,5 @@
 use anyhow::anyhow;
-use cargo::core::{features, CliUnstable};
+use cargo::core::{features, gc, CliUnstable};
 use cargo::{self, drop_print, drop_println, CliResult, Config};
 use clap::{AppSettings, Arg, ArgMatches};
 use itertools::Itertools;
@@ -359,7 +359,11 @@ fn execute_subcommand(
     subcommand_args: &ArgMatches<'_>,
 ) -> CliResult {
     if let Some(exec) = commands::builtin_exec(cmd) {
-        return exec(config, subcommand_args);
+        exec(config, subcommand_args)?;
+        if downloads_packages(cmd) {
+            gc::auto_gc(config);
+        }
+        return Ok(());
     }
 
     let mut ext_args: Vec<&str> = vec![cmd];
@@ -367,6 +371,31 @@ fn execute_subcommand(
     super::execute_external_subcommand(config, cmd, &ext_args)
 }
 
+/// Whether the builtin command `cmd` may download or extract packages into
+/// the global cache, after which it's automatically cleaned.
+fn downloads_packages(cmd: &str) -> bool {
+    matches!(
+        cmd,
+        "add"
+            | "bench"
+            | "build"
+            | "check"
+            | "doc"
+            | "fetch"
+            | "fix"
+            | "generate-lockfile"
+            | "install"
+            | "package"
+            | "publish"
+            | "run"
+            | "rustc"
+            | "rustdoc"
+            | "test"
+            | "update"
+            | "vendor"
+    )
+}
+
 #[derive(Default)]
 struct GlobalArgs {
     verbose: u32,
@@ -0,0 +1,263 @@
+use crate::command_prelude::*;
+
+use cargo::core::Workspace;
+use cargo::ops::{self, AddOptions, DepOp};
+use cargo::util::print_available_packages;
+use cargo::CargoResult;
+
+pub fn cli() -> App {
+    subcommand("add")
+        .about("Add dependencies to a Cargo.toml manifest file")
+        .usage(
+            "\
+    cargo add [OPTIONS] <DEP>[@<VERSION>] ...
+    cargo add [OPTIONS] --path <PATH> ...
+    cargo add [OPTIONS] --git <URL> ...",
+        )
+        .arg_quiet()
+        .arg(
+            Arg::with_name("crates")
+                .value_name("DEP_ID")
+                .multiple(true)
+                .help("Reference to a package to add as a dependency"),
+        )
+        .arg(
+            multi_opt(
+                "features",
+                "FEATURES",
+                "Space or comma separated list of features to activate",
+            )
+            .short("F"),
+        )
+        .arg(opt("no-default-features", "Disable the default features"))
+        .arg(
+            opt("default-features", "Re-enable the default features")
+                .hidden(true)
+                .conflicts_with("no-default-features"),
+        )
+        .arg(opt("optional", "Mark the dependency as optional"))
+        .arg(
+            opt("no-optional", "Mark the dependency as required")
+                .hidden(true)
+                .conflicts_with("optional"),
+        )
+        .arg(
+            opt(
+                "rename",
+                "Rename the dependency, e.g. to depend on multiple versions of a crate",
+            )
+            .value_name("NAME"),
+        )
+        .arg(opt("registry", "Package registry for this dependency").value_name("NAME"))
+        .arg(
+            opt("path", "Filesystem path to local crate to add")
+                .value_name("PATH")
+                .conflicts_with_all(&["git", "registry"]),
+        )
+        .arg(
+            opt("git", "Git repository location")
+                .value_name("URI")
+                .conflicts_with_all(&["path", "registry"]),
+        )
+        .arg(
+            opt("branch", "Git branch to download the crate from")
+                .value_name("BRANCH")
+                .requires("git")
+                .conflicts_with_all(&["tag", "rev"]),
+        )
+        .arg(
+            opt("tag", "Git tag to download the crate from")
+                .value_name("TAG")
+                .requires("git")
+                .conflicts_with_all(&["branch", "rev"]),
+        )
+        .arg(
+            opt("rev", "Git reference to download the crate from")
+                .value_name("REV")
+                .requires("git")
+                .conflicts_with_all(&["branch", "tag"]),
+        )
+        .arg(
+            opt("dev", "Add as development dependency")
+                .short("D")
+                .conflicts_with("build"),
+        )
+        .arg(
+            opt("build", "Add as build dependency")
+                .short("B")
+                .conflicts_with("dev"),
+        )
+        .arg(opt("target", "Add as dependency to the given target platform").value_name("TARGET"))
+        .arg_manifest_path()
+        .arg_package("Package to modify")
+        .arg_dry_run("Don't actually write the manifest")
+        .after_help("Run `cargo help add` for more detailed information.\n")
+}
+
+pub fn exec(config: &mut Config, args: &ArgMatches<'_>) -> CliResult {
+    config
+        .cli_unstable()
+        .fail_if_stable_command(config, "add", 5586)?;
+
+    let dry_run = args.is_present("dry-run");
+    let section = args.dep_table()?;
+
+    let ws = args.workspace(config)?;
+
+    if args.is_present_with_zero_values("package") {
+        print_available_packages(&ws)?;
+    }
+
+    let packages = ws.members().collect::<Vec<_>>();
+    let spec = match args.value_of("package") {
+        Some(name) => packages
+            .iter()
+            .find(|p| p.name().as_str() == name)
+            .copied()
+            .ok_or_else(|| anyhow::format_err!("package `{}` is not a workspace member", name))?,
+        None => ws.current().map_err(|_| {
+            anyhow::format_err!(
+                "`cargo add` could not determine which package to modify. \
+                 Use the `--package` option to specify a package.\n\
+                 available packages: {}",
+                packages
+                    .iter()
+                    .map(|p| p.name().as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )
+        })?,
+    };
+
+    let dependencies = parse_dependencies(config, args)?;
+
+    let options = AddOptions {
+        config,
+        spec,
+        dependencies,
+        section,
+        dry_run,
+    };
+    ops::add(&ws, &options)?;
+
+    if !dry_run {
+        // Reload the workspace since we've changed dependencies
+        let ws = Workspace::new(ws.root_manifest(), config)?;
+        ops::resolve_ws(&ws)?;
+    }
+
+    Ok(())
+}
+
+fn parse_dependencies(config: &Config, args: &ArgMatches<'_>) -> CargoResult<Vec<DepOp>> {
+    let path = args.value_of("path");
+    let git = args.value_of("git");
+    let branch = args.value_of("branch");
+    let rev = args.value_of("rev");
+    let tag = args.value_of("tag");
+    let rename = args.value_of("rename");
+    let registry = args.registry(config)?;
+    let default_features = default_features(args);
+    let optional = optional(args);
+
+    let mut crates = values(args, "crates")
+        .into_iter()
+        .map(|c| (Some(c), Vec::new()))
+        .collect::<Vec<_>>();
+    if crates.is_empty() {
+        if path.is_some() || git.is_some() {
+            crates.push((None, Vec::new()));
+        } else {
+            anyhow::bail!("at least one dependency must be specified, or use `--path` or `--git`");
+        }
+    }
+
+    if crates.len() > 1 {
+        for (flag, present) in [
+            ("--path", path.is_some()),
+            ("--git", git.is_some()),
+            ("--rename", rename.is_some()),
+        ] {
+            if present {
+                anyhow::bail!("cannot specify multiple crates with `{}`", flag);
+            }
+        }
+    }
+
+    for feature in values(args, "features")
+        .iter()
+        .flat_map(|s| s.split_whitespace())
+        .flat_map(|s| s.split(','))
+        .filter(|s| !s.is_empty())
+    {
+        match feature.split_once('/') {
+            Some((dep, feature)) => {
+                let entry = crates
+                    .iter_mut()
+                    .find(|(spec, _)| {
+                        spec.as_deref()
+                            .map(|s| s.split('@').next().unwrap() == dep)
+                            .unwrap_or(false)
+                    })
+                    .ok_or_else(|| {
+                        anyhow::format_err!(
+                            "feature `{}` must be qualified by a dependency that is being added, \
+                             `{}` is not one of them",
+                            feature,
+                            dep
+                        )
+                    })?;
+                entry.1.push(feature.to_owned());
+            }
+            None if crates.len() == 1 => {
+                crates[0].1.push(feature.to_owned());
+            }
+            None => anyhow::bail!(
+                "feature `{}` must be qualified with the dependency it applies to \
+                 (`<dep>/<feature>`) when adding multiple crates",
+                feature
+            ),
+        }
+    }
+
+    Ok(crates
+        .into_iter()
+        .map(|(crate_spec, features)| DepOp {
+            crate_spec,
+            rename: rename.map(String::from),
+            features: if features.is_empty() {
+                None
+            } else {
+                Some(features)
+            },
+            default_features,
+            optional,
+            registry: registry.clone(),
+            path: path.map(String::from),
+            git: git.map(String::from),
+            branch: branch.map(String::from),
+            tag: tag.map(String::from),
+            rev: rev.map(String::from),
+        })
+        .collect())
+}
+
+fn default_features(args: &ArgMatches<'_>) -> Option<bool> {
+    resolve_bool_arg(
+        args.is_present("default-features"),
+        args.is_present("no-default-features"),
+    )
+}
+
+fn optional(args: &ArgMatches<'_>) -> Option<bool> {
+    resolve_bool_arg(args.is_present("optional"), args.is_present("no-optional"))
+}
+
+fn resolve_bool_arg(yes: bool, no: bool) -> Option<bool> {
+    match (yes, no) {
+        (true, false) => Some(true),
+        (false, true) => Some(false),
+        (false, false) => None,
+        (true, true) => unreachable!("clap should make this impossible"),
+    }
+}
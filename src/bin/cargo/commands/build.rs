@@ -44,6 +44,10 @@ pub fn cli() -> App {
         .arg_build_plan()
         .arg_unit_graph()
         .arg_future_incompat_report()
+        .arg(opt(
+            "explain-rebuilds",
+            "Print why each unit is rebuilt (unstable)",
+        ))
         .after_help("Run `cargo help build` for more detailed information.\n")
 }
 
@@ -67,6 +71,12 @@ pub fn exec(config: &mut Config, args: &ArgMatches<'_>) -> CliResult {
             .cli_unstable()
             .fail_if_stable_opt("--out-dir", 6790)?;
     }
+    if args.is_present("explain-rebuilds") {
+        config
+            .cli_unstable()
+            .fail_if_stable_opt("--explain-rebuilds", 10985)?;
+        compile_opts.build_config.explain_rebuilds = true;
+    }
     ops::compile(&ws, &compile_opts)?;
     Ok(())
 }
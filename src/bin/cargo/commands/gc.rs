@@ -0,0 +1,49 @@
+use crate::command_prelude::*;
+
+use cargo::core::gc::{self, GcOptions};
+
+pub fn cli() -> App {
+    subcommand("gc")
+        .about("Remove unused entries from the global cache in CARGO_HOME")
+        .arg_quiet()
+        .arg(
+            opt(
+                "max-age",
+                "Remove entries which haven't been used for longer than this (e.g. \"30 days\")",
+            )
+            .value_name("DURATION"),
+        )
+        .arg(
+            opt(
+                "max-size",
+                "Remove the least recently used entries until the cache is at most this large (e.g. \"10 GiB\")",
+            )
+            .value_name("SIZE"),
+        )
+        .arg_dry_run("Display what would be removed without removing anything")
+        .after_help("Run `cargo help gc` for more detailed information.\n")
+}
+
+pub fn exec(config: &mut Config, args: &ArgMatches<'_>) -> CliResult {
+    config
+        .cli_unstable()
+        .fail_if_stable_command(config, "gc", 12633)?;
+
+    let max_age = args
+        .value_of("max-age")
+        .map(gc::parse_duration)
+        .transpose()?;
+    let max_size = args.value_of("max-size").map(gc::parse_size).transpose()?;
+    let mut opts = if max_age.is_none() && max_size.is_none() {
+        GcOptions::from_config(config)?
+    } else {
+        GcOptions {
+            max_age,
+            max_size,
+            dry_run: false,
+        }
+    };
+    opts.dry_run = args.is_present("dry-run");
+    gc::gc(config, &opts)?;
+    Ok(())
+}
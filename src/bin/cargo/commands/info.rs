@@ -0,0 +1,42 @@
+use crate::command_prelude::*;
+
+use cargo::core::Workspace;
+use cargo::ops;
+
+pub fn cli() -> App {
+    subcommand("info")
+        .about("Display information about a package in the registry")
+        .arg_quiet()
+        .arg(
+            Arg::with_name("package")
+                .value_name("SPEC")
+                .required(true)
+                .help("Package to inspect, as `name` or `name@version`"),
+        )
+        .arg_index()
+        .arg(opt("registry", "Registry to search packages in").value_name("REGISTRY"))
+        .arg_manifest_path()
+        .after_help("Run `cargo help info` for more detailed information.\n")
+}
+
+pub fn exec(config: &mut Config, args: &ArgMatches<'_>) -> CliResult {
+    config
+        .cli_unstable()
+        .fail_if_stable_command(config, "info", 948)?;
+
+    let registry = args.registry(config)?;
+    let index = args.index()?;
+    // Outside of a workspace there is simply no locked version to show.
+    let ws = args
+        .root_manifest(config)
+        .ok()
+        .and_then(|path| Workspace::new(&path, config).ok());
+    ops::info(
+        args.value_of("package").unwrap(),
+        ws.as_ref(),
+        config,
+        index,
+        registry,
+    )?;
+    Ok(())
+}
@@ -18,7 +18,11 @@ pub fn cli() -> App {
         ))
         .arg_target_triple("Build for the target triple")
         .arg_target_dir()
-        .arg_package("Package to publish")
+        .arg_package_spec_no_all(
+            "Package(s) to publish",
+            "Publish all packages in the workspace",
+            "Don't publish specified packages",
+        )
         .arg_manifest_path()
         .arg_features()
         .arg_jobs()
@@ -0,0 +1,83 @@
+use crate::command_prelude::*;
+
+use cargo::core::Workspace;
+use cargo::ops::{self, RemoveOptions};
+use cargo::util::print_available_packages;
+
+pub fn cli() -> App {
+    subcommand("remove")
+        .about("Remove dependencies from a Cargo.toml manifest file")
+        .arg_quiet()
+        .arg(
+            Arg::with_name("dependencies")
+                .value_name("DEP_ID")
+                .multiple(true)
+                .required(true)
+                .help("Dependencies to be removed"),
+        )
+        .arg(
+            opt("dev", "Remove as development dependency")
+                .short("D")
+                .conflicts_with("build"),
+        )
+        .arg(
+            opt("build", "Remove as build dependency")
+                .short("B")
+                .conflicts_with("dev"),
+        )
+        .arg(
+            opt(
+                "target",
+                "Remove as dependency from the given target platform",
+            )
+            .value_name("TARGET"),
+        )
+        .arg_manifest_path()
+        .arg_package("Package to remove from")
+        .arg_dry_run("Don't actually write the manifest")
+        .after_help("Run `cargo help remove` for more detailed information.\n")
+}
+
+pub fn exec(config: &mut Config, args: &ArgMatches<'_>) -> CliResult {
+    config
+        .cli_unstable()
+        .fail_if_stable_command(config, "remove", 5586)?;
+
+    let dry_run = args.is_present("dry-run");
+
+    let ws = args.workspace(config)?;
+
+    if args.is_present_with_zero_values("package") {
+        print_available_packages(&ws)?;
+    }
+
+    let packages = ws.members().collect::<Vec<_>>();
+    let spec = match args.value_of("package") {
+        Some(name) => packages
+            .iter()
+            .find(|p| p.name().as_str() == name)
+            .copied()
+            .ok_or_else(|| anyhow::format_err!("package `{}` is not a workspace member", name))?,
+        None => ws.current()?,
+    };
+
+    let dependencies = values(args, "dependencies");
+    let section = args.dep_table()?;
+
+    let options = RemoveOptions {
+        config,
+        spec,
+        dependencies,
+        section,
+        dry_run,
+    };
+    ops::remove(&options)?;
+
+    if !dry_run {
+        // Reload the workspace since we've changed dependencies
+        let ws = Workspace::new(ws.root_manifest(), config)?;
+        ops::resolve_ws(&ws)?;
+    }
+
+    Ok(())
+}
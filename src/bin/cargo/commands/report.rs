@@ -1,6 +1,9 @@
 use crate::command_prelude::*;
 use cargo::core::compiler::future_incompat::{OnDiskReports, REPORT_PREAMBLE};
+use cargo::core::dependency::DepKind;
 use cargo::drop_println;
+use cargo::ops::tree::{self, EdgeKind};
+use std::str::FromStr;
 
 pub fn cli() -> App {
     subcommand("report")
@@ -20,11 +23,44 @@ pub fn cli() -> App {
                 )
                 .arg_package("Package to display a report for"),
         )
+        .subcommand(
+            subcommand("licenses")
+                .about("Reports the licenses of all packages in the dependency graph")
+                .arg_quiet()
+                .arg_manifest_path()
+                .arg_package_spec_no_all(
+                    "Package to report the dependencies of",
+                    "Report the dependencies of all packages in the workspace",
+                    "Exclude specific workspace members",
+                )
+                .arg_features()
+                .arg_target_triple(
+                    "Filter dependencies matching the given target-triple (default host platform). \
+                     Pass `all` to include all targets.",
+                )
+                .arg(
+                    multi_opt(
+                        "edges",
+                        "KINDS",
+                        "The kinds of dependencies to include \
+                         (normal, build, dev, all, no-normal, no-build, no-dev, no-proc-macro), \
+                         defaults to normal and build dependencies",
+                    )
+                    .short("e"),
+                )
+                .arg(
+                    opt("format", "Output format of the report")
+                        .value_name("FORMAT")
+                        .possible_values(&["text", "json", "spdx"])
+                        .default_value("text"),
+                ),
+        )
 }
 
 pub fn exec(config: &mut Config, args: &ArgMatches<'_>) -> CliResult {
     match args.subcommand() {
         ("future-incompatibilities", Some(args)) => report_future_incompatibilies(config, args),
+        ("licenses", Some(args)) => report_licenses(config, args),
         (cmd, _) => panic!("unexpected command `{}`", cmd),
     }
 }
@@ -41,3 +77,47 @@ fn report_future_incompatibilies(config: &Config, args: &ArgMatches<'_>) -> CliR
     drop(config.shell().print_ansi_stdout(report.as_bytes()));
     Ok(())
 }
+
+fn report_licenses(config: &Config, args: &ArgMatches<'_>) -> CliResult {
+    config
+        .cli_unstable()
+        .fail_if_stable_command(config, "report licenses", 10895)?;
+
+    let ws = args.workspace(config)?;
+    let (edge_kinds, no_proc_macro) = if args.is_present("edges") {
+        super::tree::parse_edge_kinds(config, args)?
+    } else {
+        let kinds = [
+            EdgeKind::Dep(DepKind::Normal),
+            EdgeKind::Dep(DepKind::Build),
+        ];
+        (kinds.into_iter().collect(), false)
+    };
+    if edge_kinds.contains(&EdgeKind::Feature) {
+        return Err(anyhow::format_err!(
+            "`-e features` is not supported by `cargo report licenses`"
+        )
+        .into());
+    }
+    let opts = tree::TreeOptions {
+        cli_features: args.cli_features()?,
+        packages: args.packages_from_flags()?,
+        target: tree::Target::from_cli(args._values_of("target")),
+        edge_kinds,
+        invert: Vec::new(),
+        pkgs_to_prune: Vec::new(),
+        prefix: tree::Prefix::None,
+        no_dedupe: false,
+        duplicates: false,
+        charset: tree::Charset::Utf8,
+        format: String::new(),
+        output_format: tree::OutputFormat::Text,
+        graph_features: false,
+        max_display_depth: u32::MAX,
+        no_proc_macro,
+    };
+    let format = tree::LicenseFormat::from_str(args.value_of("format").unwrap())
+        .map_err(|e| anyhow::anyhow!("{}", e))?;
+    tree::report_licenses(&ws, &opts, format)?;
+    Ok(())
+}
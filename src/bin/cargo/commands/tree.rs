@@ -98,10 +98,14 @@ pub fn cli() -> App {
                 .default_value("utf8"),
         )
         .arg(
-            opt("format", "Format string used for printing dependencies")
-                .value_name("FORMAT")
-                .short("f")
-                .default_value("{p}"),
+            opt(
+                "format",
+                "Format string used for printing dependencies, or `json` or `dot` \
+                 to print the graph (unstable)",
+            )
+            .value_name("FORMAT")
+            .short("f")
+            .default_value("{p}"),
         )
         .arg(
             // Backwards compatibility with old cargo-tree.
@@ -198,6 +202,35 @@ subtree of the package given to -p.\n\
 
     let charset = tree::Charset::from_str(args.value_of("charset").unwrap())
         .map_err(|e| anyhow::anyhow!("{}", e))?;
+    let format = args.value_of("format").unwrap();
+    let output_format = match format {
+        "json" | "dot" => {
+            config
+                .cli_unstable()
+                .fail_if_stable_opt(&format!("--format {}", format), 10936)?;
+            if format == "json" {
+                tree::OutputFormat::Json
+            } else {
+                tree::OutputFormat::Dot
+            }
+        }
+        _ => tree::OutputFormat::Text,
+    };
+    if !matches!(output_format, tree::OutputFormat::Text) {
+        // These only change how the tree is drawn.
+        let text_only = [
+            ("--prefix", args.occurrences_of("prefix") > 0),
+            ("--no-dedupe", no_dedupe),
+            ("--charset", args.occurrences_of("charset") > 0),
+        ];
+        for (flag, present) in text_only.iter() {
+            if *present {
+                return Err(
+                    format_err!("`--format {}` does not support `{}`", format, flag).into(),
+                );
+            }
+        }
+    }
     let opts = tree::TreeOptions {
         cli_features: args.cli_features()?,
         packages,
@@ -209,7 +242,8 @@ subtree of the package given to -p.\n\
         no_dedupe,
         duplicates: args.is_present("duplicates"),
         charset,
-        format: args.value_of("format").unwrap().to_string(),
+        format: format.to_string(),
+        output_format,
         graph_features,
         max_display_depth: args.value_of_u32("depth")?.unwrap_or(u32::MAX),
         no_proc_macro,
@@ -226,7 +260,7 @@ subtree of the package given to -p.\n\
 /// Parses `--edges` option.
 ///
 /// Returns a tuple of `EdgeKind` map and `no_proc_marco` flag.
-fn parse_edge_kinds(
+pub fn parse_edge_kinds(
     config: &Config,
     args: &ArgMatches<'_>,
 ) -> CargoResult<(HashSet<EdgeKind>, bool)> {
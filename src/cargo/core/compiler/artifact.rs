@@ -0,0 +1,57 @@
+//! Generate artifact information from unit dependencies for configuring the
+//! compiler environment.
+
+use crate::core::compiler::unit_graph::UnitDep;
+use crate::core::compiler::{Context, CrateType, FileFlavor};
+use crate::core::dependency::ArtifactKind;
+use crate::CargoResult;
+use std::collections::HashMap;
+use std::ffi::OsString;
+
+/// Returns the environment variables that point to the files of the
+/// artifact dependencies in `unit_deps`.
+///
+/// Each artifact is available as `CARGO_<KIND>_FILE_<DEP>_<NAME>`, where
+/// `KIND` is `BIN`, `CDYLIB` or `STATICLIB`, `DEP` is the name of the
+/// dependency and `NAME` the name of the target. The artifact named after the
+/// package is also available as `CARGO_<KIND>_FILE_<DEP>`.
+pub fn get_env(
+    cx: &Context<'_, '_>,
+    unit_deps: &[UnitDep],
+) -> CargoResult<HashMap<String, OsString>> {
+    let mut env = HashMap::new();
+    for unit_dep in unit_deps {
+        let artifact_kind = match &unit_dep.artifact {
+            Some(kind) => kind,
+            None => continue,
+        };
+        let crate_type = match artifact_kind {
+            ArtifactKind::AllBinaries | ArtifactKind::SelectedBinary(_) => CrateType::Bin,
+            ArtifactKind::Cdylib => CrateType::Cdylib,
+            ArtifactKind::Staticlib => CrateType::Staticlib,
+        };
+        let outputs = cx.outputs(&unit_dep.unit)?;
+        let output = match outputs
+            .iter()
+            .find(|o| o.flavor == FileFlavor::Normal && o.crate_type.as_ref() == Some(&crate_type))
+        {
+            Some(output) => output,
+            None => continue,
+        };
+
+        let target = &unit_dep.unit.target;
+        let var = format!(
+            "CARGO_{}_FILE_{}",
+            artifact_kind.crate_type().to_uppercase(),
+            super::envify(&unit_dep.extern_crate_name)
+        );
+        env.insert(
+            format!("{}_{}", var, target.name()),
+            output.path.clone().into_os_string(),
+        );
+        if !target.is_bin() || target.name() == unit_dep.unit.pkg.name().as_str() {
+            env.insert(var, output.path.clone().into_os_string());
+        }
+    }
+    Ok(env)
+}
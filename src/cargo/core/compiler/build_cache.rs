@@ -0,0 +1,244 @@
+//! A cache of compiled artifacts shared by every workspace of the user,
+//! enabled with `-Z build-cache`.
+//!
+//! Before running rustc for a unit of a non-local package, Cargo looks the
+//! unit up in the cache. On a hit the cached outputs, along with rustc's
+//! dep-info file, are copied into the target directory instead of compiling,
+//! and on a miss they are stored in the cache once rustc succeeded. The
+//! remaining steps of a build, like writing the fingerprint, are the same in
+//! both cases.
+//!
+//! Entries are keyed by the `Metadata` hash of the unit, which covers the
+//! package, its features and dependencies, the profile and the target, plus
+//! the locked revision of git sources, the flags and the exact version of
+//! rustc, which the `Metadata` hash only partially covers. Only libraries that neither have a build script nor
+//! depend on a package with one are cached, as the output of build scripts
+//! affects how a crate is compiled without being part of the key.
+//!
+//! The cache is accessed through the `BuildCache` trait so that
+//! `Executor::build_cache` can provide another backend, like a remote one. The
+//! default is `LocalBuildCache`, a directory with one subdirectory per entry:
+//!
+//! ```text
+//! $CARGO_HOME/build-cache/
+//!     .cache-lock
+//!     serde-6c3c8fb2ac8e1d5e-a0d54ec2e1b6a9b0/
+//!         libserde-6c3c8fb2ac8e1d5e.rlib
+//!         libserde-6c3c8fb2ac8e1d5e.rmeta
+//!         serde-6c3c8fb2ac8e1d5e.d
+//! ```
+//!
+//! Every build holds a shared lock on `.cache-lock` while it runs. Entries are
+//! written to a temporary directory first and renamed into place, so that
+//! concurrent builds never see partial entries. When the cache is opened and
+//! no other build holds the lock, an exclusive lock is taken to evict the
+//! least recently used entries until the cache fits in
+//! `build.cache-max-size`. Otherwise eviction is left to a later build, so
+//! concurrent builds never wait for each other.
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use anyhow::Context as _;
+use cargo_util::paths;
+use filetime::FileTime;
+use log::debug;
+
+use super::{CompileMode, Context, Unit};
+use crate::util::{self, CargoResult, Config, FileLock, Filesystem};
+
+/// The default of `build.cache-max-size`, in MiB.
+const DEFAULT_MAX_SIZE_MIB: u64 = 10 * 1024;
+
+/// Name of the lock file in the cache directory.
+const LOCK_FILE: &str = ".cache-lock";
+
+/// Name of the directory entries are written to before being renamed into
+/// place.
+const TMP_DIR: &str = ".tmp";
+
+/// A store of compiled artifacts shared across builds.
+///
+/// Both methods are called from the threads running the build jobs, and may
+/// be called concurrently.
+pub trait BuildCache: Send + Sync {
+    /// Copies the files stored for `key` to `files`, which are looked up by
+    /// their file names. Returns `false`, without touching `files`, when
+    /// there is no complete entry for `key`.
+    fn fetch(&self, key: &str, files: &[PathBuf]) -> CargoResult<bool>;
+
+    /// Stores `files` for `key`, unless there is already an entry for it.
+    fn store(&self, key: &str, files: &[PathBuf]) -> CargoResult<()>;
+}
+
+/// A `BuildCache` in a directory of the local filesystem.
+pub struct LocalBuildCache {
+    root: PathBuf,
+    /// Shared lock held for the whole build, which prevents eviction by other
+    /// builds while this one uses the cache.
+    _lock: FileLock,
+}
+
+impl LocalBuildCache {
+    /// Opens the cache if `-Z build-cache` is used, evicting old entries
+    /// first if it's over the size limit and no other build uses it.
+    pub fn open(config: &Config) -> CargoResult<Option<LocalBuildCache>> {
+        if !config.cli_unstable().build_cache {
+            return Ok(None);
+        }
+        let build_config = config.build_config()?;
+        let root = match &build_config.cache_dir {
+            Some(dir) => dir.resolve_path(config),
+            None => config.home().as_path_unlocked().join("build-cache"),
+        };
+        let max_size = build_config
+            .cache_max_size
+            .unwrap_or(DEFAULT_MAX_SIZE_MIB)
+            .saturating_mul(1024 * 1024);
+
+        let fs = Filesystem::new(root.clone());
+        // Other builds using the cache hold a shared lock until they finish,
+        // so rather than waiting for them, the cache is only cleaned up when
+        // no other build uses it.
+        match fs.try_open_rw(LOCK_FILE, config)? {
+            Some(_lock) => {
+                // Leftovers of builds that were interrupted while storing.
+                let tmp = root.join(TMP_DIR);
+                if tmp.exists() {
+                    paths::remove_dir_all(&tmp)?;
+                }
+                evict(&root, max_size).with_context(|| {
+                    format!("failed to clean the build cache {}", root.display())
+                })?;
+            }
+            None => debug!("build cache in use, skipping eviction"),
+        }
+        let lock = fs.open_ro(LOCK_FILE, config, "build cache")?;
+        Ok(Some(LocalBuildCache { root, _lock: lock }))
+    }
+}
+
+impl BuildCache for LocalBuildCache {
+    fn fetch(&self, key: &str, files: &[PathBuf]) -> CargoResult<bool> {
+        let entry = self.root.join(key);
+        let sources: Vec<_> = files
+            .iter()
+            .map(|file| entry.join(file_name(file)))
+            .collect();
+        if !sources.iter().all(|source| source.is_file()) {
+            return Ok(false);
+        }
+        for (source, file) in sources.iter().zip(files) {
+            // Copying gives the files a new mtime, like compiling them would.
+            paths::copy(source, file)?;
+        }
+        // The mtime of entries records when they were last used, for eviction.
+        paths::set_file_time_no_err(&entry, FileTime::from_system_time(SystemTime::now()));
+        Ok(true)
+    }
+
+    fn store(&self, key: &str, files: &[PathBuf]) -> CargoResult<()> {
+        let entry = self.root.join(key);
+        if entry.exists() {
+            return Ok(());
+        }
+        let tmp = self.root.join(TMP_DIR);
+        paths::create_dir_all(&tmp)?;
+        let staging = tempfile::Builder::new().prefix(key).tempdir_in(&tmp)?;
+        for file in files {
+            paths::copy(file, staging.path().join(file_name(file)))?;
+        }
+        // Another build may have stored the same entry in the meantime, in
+        // which case theirs is kept.
+        if let Err(e) = fs::rename(staging.path(), &entry) {
+            debug!(
+                "failed to rename {:?} to {:?}: {}",
+                staging.path(),
+                entry,
+                e
+            );
+        }
+        Ok(())
+    }
+}
+
+fn file_name(path: &Path) -> &std::ffi::OsStr {
+    path.file_name().expect("cached files have a file name")
+}
+
+/// Removes the least recently used entries of the cache in `root` until it
+/// isn't larger than `max_size` bytes.
+fn evict(root: &Path, max_size: u64) -> CargoResult<()> {
+    let mut entries = Vec::new();
+    let mut total = 0;
+    for entry in fs::read_dir(root)? {
+        let entry = entry?;
+        if !entry.file_type()?.is_dir() || entry.file_name() == TMP_DIR {
+            continue;
+        }
+        let mut size = 0;
+        for file in fs::read_dir(entry.path())? {
+            size += file?.metadata()?.len();
+        }
+        total += size;
+        entries.push((paths::mtime(&entry.path())?, size, entry.path()));
+    }
+    entries.sort();
+    for (_mtime, size, path) in entries {
+        if total <= max_size {
+            break;
+        }
+        debug!("evicting {:?} from the build cache", path);
+        paths::remove_dir_all(&path)?;
+        total -= size;
+    }
+    Ok(())
+}
+
+/// Returns whether the outputs of `unit` can be shared through the cache.
+pub fn is_cacheable(cx: &Context<'_, '_>, unit: &Unit) -> bool {
+    fn is_cacheable_dep<'a>(
+        cx: &'a Context<'_, '_>,
+        unit: &'a Unit,
+        visited: &mut HashSet<&'a Unit>,
+    ) -> bool {
+        if !visited.insert(unit) {
+            // Either being checked already, or found to be cacheable.
+            return true;
+        }
+        // Build scripts are not libraries, so this rules out units depending
+        // on the output of a build script, too.
+        !unit.is_local()
+            && unit.target.is_lib()
+            && matches!(
+                unit.mode,
+                CompileMode::Build | CompileMode::Check { test: false }
+            )
+            && cx
+                .unit_deps(unit)
+                .iter()
+                .all(|dep| is_cacheable_dep(cx, &dep.unit, visited))
+    }
+
+    // With `-Z binary-dep-depinfo`, the dep-info file lists the outputs of
+    // dependencies in the target directory it was compiled in.
+    !cx.bcx.config.cli_unstable().binary_dep_depinfo
+        && cx.files().use_extra_filename(unit)
+        && is_cacheable_dep(cx, unit, &mut HashSet::new())
+}
+
+/// Returns the key of `unit` in the cache.
+pub fn unit_key(cx: &Context<'_, '_>, unit: &Unit) -> String {
+    let metadata = cx.files().metadata(unit);
+    // The `Metadata` hash doesn't cover the locked revision of git sources,
+    // which are the same version of the package at every commit.
+    let hash = util::short_hash(&(
+        metadata.to_string(),
+        unit.pkg.package_id().source_id().precise(),
+        cx.bcx.rustflags_args(unit),
+        &cx.bcx.rustc().verbose_version,
+    ));
+    format!("{}-{}-{}", unit.target.crate_name(), metadata, hash)
+}
@@ -39,6 +39,8 @@ pub struct BuildConfig {
     pub export_dir: Option<PathBuf>,
     /// `true` to output a future incompatibility report at the end of the build
     pub future_incompat_report: bool,
+    /// `true` to print why each unit is rebuilt.
+    pub explain_rebuilds: bool,
 }
 
 impl BuildConfig {
@@ -86,6 +88,7 @@ impl BuildConfig {
             rustfix_diagnostic_server: RefCell::new(None),
             export_dir: None,
             future_incompat_report: false,
+            explain_rebuilds: false,
         })
     }
 
@@ -121,8 +121,14 @@ impl<'a, 'cfg> BuildContext<'a, 'cfg> {
         self.build_config.jobs
     }
 
-    pub fn rustflags_args(&self, unit: &Unit) -> &[String] {
-        &self.target_data.info(unit.kind).rustflags
+    /// Extra compiler flags to pass to `rustc` for the given unit.
+    ///
+    /// These are the flags from `RUSTFLAGS` and `build.rustflags`, followed
+    /// by the `rustflags` of the unit's profile.
+    pub fn rustflags_args(&self, unit: &Unit) -> Vec<String> {
+        let mut args = self.target_data.info(unit.kind).rustflags.clone();
+        args.extend(unit.profile.rustflags.iter().map(|flag| flag.to_string()));
+        args
     }
 
     pub fn rustdocflags_args(&self, unit: &Unit) -> &[String] {
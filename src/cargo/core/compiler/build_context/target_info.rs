@@ -747,12 +747,9 @@ impl<'cfg> RustcTargetData<'cfg> {
 
         // Get all kinds we currently know about.
         //
-        // For now, targets can only ever come from the root workspace
-        // units as artifact dependencies are not a thing yet, so this
-        // correctly represents all the kinds that can happen. When we
-        // have artifact dependencies or other ways for targets to
-        // appear at places that are not the root units, we may have
-        // to revisit this.
+        // Targets of artifact dependencies that force a particular target
+        // are only known after resolving, and are added with
+        // `merge_compile_kind` then.
         let all_kinds = requested_kinds
             .iter()
             .copied()
@@ -763,23 +760,29 @@ impl<'cfg> RustcTargetData<'cfg> {
                     .chain(p.manifest().forced_kind())
             }));
         for kind in all_kinds {
-            if let CompileKind::Target(target) = kind {
-                if !res.target_config.contains_key(&target) {
-                    res.target_config
-                        .insert(target, res.config.target_cfg_triple(target.short_name())?);
-                }
-                if !res.target_info.contains_key(&target) {
-                    res.target_info.insert(
-                        target,
-                        TargetInfo::new(res.config, &res.requested_kinds, &res.rustc, kind)?,
-                    );
-                }
-            }
+            res.merge_compile_kind(kind)?;
         }
 
         Ok(res)
     }
 
+    /// Loads the target info and config of `kind` if it wasn't known yet.
+    pub fn merge_compile_kind(&mut self, kind: CompileKind) -> CargoResult<()> {
+        if let CompileKind::Target(target) = kind {
+            if !self.target_config.contains_key(&target) {
+                self.target_config
+                    .insert(target, self.config.target_cfg_triple(target.short_name())?);
+            }
+            if !self.target_info.contains_key(&target) {
+                self.target_info.insert(
+                    target,
+                    TargetInfo::new(self.config, &self.requested_kinds, &self.rustc, kind)?,
+                );
+            }
+        }
+        Ok(())
+    }
+
     /// Returns a "short" name for the given kind, suitable for keying off
     /// configuration in Cargo or presenting to users.
     pub fn short_name<'a>(&'a self, kind: &'a CompileKind) -> &'a str {
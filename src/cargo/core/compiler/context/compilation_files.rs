@@ -122,6 +122,8 @@ pub struct OutputFile {
     pub export_path: Option<PathBuf>,
     /// Type of the file (library / debug symbol / else).
     pub flavor: FileFlavor,
+    /// The crate type that generates this file, if any.
+    pub crate_type: Option<CrateType>,
 }
 
 impl OutputFile {
@@ -230,6 +232,12 @@ impl<'a, 'cfg: 'a> CompilationFiles<'a, 'cfg> {
         self.host.dest()
     }
 
+    /// Returns the destination of intermediate artifacts for the host, which is
+    /// the same as `host_dest` unless there is a separate build directory.
+    pub fn host_build_dest(&self) -> &Path {
+        self.host.build_dest()
+    }
+
     /// Returns the root of the build output tree for the host (`/…/target`)
     pub fn host_root(&self) -> &Path {
         self.host.root()
@@ -371,6 +379,11 @@ impl<'a, 'cfg: 'a> CompilationFiles<'a, 'cfg> {
         {
             return None;
         }
+        // Binaries of other packages are only built for artifact
+        // dependencies, which use them from the `deps` directory.
+        if unit.target.is_bin() && !self.roots.contains(unit) && !self.ws.is_member(&unit.pkg) {
+            return None;
+        }
 
         let filename = file_type.uplift_filename(&unit.target);
         let uplift_path = if unit.target.is_example() {
@@ -406,6 +419,7 @@ impl<'a, 'cfg: 'a> CompilationFiles<'a, 'cfg> {
                     hardlink: None,
                     export_path: None,
                     flavor: FileFlavor::Normal,
+                    crate_type: None,
                 }]
             }
             CompileMode::RunCustomBuild => {
@@ -428,6 +442,7 @@ impl<'a, 'cfg: 'a> CompilationFiles<'a, 'cfg> {
                     hardlink: None,
                     export_path: None,
                     flavor: FileFlavor::Normal,
+                    crate_type: None,
                 }]
             }
             CompileMode::Test
@@ -498,6 +513,7 @@ impl<'a, 'cfg: 'a> CompilationFiles<'a, 'cfg> {
                 hardlink,
                 export_path,
                 flavor: file_type.flavor,
+                crate_type: file_type.crate_type,
             });
         }
         Ok(outputs)
@@ -19,7 +19,8 @@ use super::layout::Layout;
 use super::lto::Lto;
 use super::unit_graph::UnitDep;
 use super::{
-    BuildContext, Compilation, CompileKind, CompileMode, Executor, FileFlavor, RustDocFingerprint,
+    BuildCache, BuildContext, Compilation, CompileKind, CompileMode, Executor, FileFlavor,
+    RustDocFingerprint,
 };
 
 mod compilation_files;
@@ -84,6 +85,9 @@ pub struct Context<'a, 'cfg> {
     /// Map of Doc/Docscrape units to metadata for their -Cmetadata flag.
     /// See Context::find_metadata_units for more details.
     pub metadata_for_doc_units: HashMap<Unit, Metadata>,
+
+    /// The cache of compiled artifacts shared across builds, if any.
+    pub build_cache: Option<Arc<dyn BuildCache>>,
 }
 
 impl<'a, 'cfg> Context<'a, 'cfg> {
@@ -125,6 +129,7 @@ impl<'a, 'cfg> Context<'a, 'cfg> {
             pipelining,
             lto: HashMap::new(),
             metadata_for_doc_units: HashMap::new(),
+            build_cache: None,
         })
     }
 
@@ -152,6 +157,9 @@ impl<'a, 'cfg> Context<'a, 'cfg> {
         if self.bcx.build_config.mode.is_doc() {
             RustDocFingerprint::check_rustdoc_fingerprint(&self)?
         }
+        if !build_plan {
+            self.build_cache = exec.build_cache(self.bcx.config)?;
+        }
 
         for unit in &self.bcx.roots {
             // Build up a list of pending jobs, each of which represent
@@ -255,6 +263,10 @@ impl<'a, 'cfg> Context<'a, 'cfg> {
                         }
                     }
                 }
+                if unit.profile.trim_paths {
+                    args.extend(compiler::trim_paths_args(&self, unit));
+                    unstable_opts = true;
+                }
                 args.extend(self.bcx.rustdocflags_args(unit).iter().map(Into::into));
 
                 use super::MessageFormat;
@@ -579,7 +591,7 @@ impl<'a, 'cfg> Context<'a, 'cfg> {
     fn record_units_requiring_metadata(&mut self) {
         for (key, deps) in self.bcx.unit_graph.iter() {
             for dep in deps {
-                if self.only_requires_rmeta(key, &dep.unit) {
+                if dep.artifact.is_none() && self.only_requires_rmeta(key, &dep.unit) {
                     self.rmeta_required.insert(dep.unit.clone());
                 }
             }
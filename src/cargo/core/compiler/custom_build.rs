@@ -8,14 +8,51 @@ use crate::util::machine_message::{self, Message};
 use crate::util::{internal, profile};
 use anyhow::{bail, Context as _};
 use cargo_platform::Cfg;
-use cargo_util::paths;
+use cargo_util::{paths, ProcessBuilder};
+use filetime::FileTime;
 use std::collections::hash_map::{Entry, HashMap};
-use std::collections::{BTreeSet, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::env;
 use std::path::{Path, PathBuf};
 use std::str;
 use std::sync::{Arc, Mutex};
+use walkdir::WalkDir;
 
 const CARGO_WARNING: &str = "cargo:warning=";
+const CARGO_RERUN_IF_ENV_CHANGED: &str = "cargo:rerun-if-env-changed=";
+
+/// Environment variables that build scripts always inherit with
+/// `-Z build-script-sandbox`, as running programs like the compiler, a
+/// linker or `pkg-config` depends on them.
+const SANDBOX_ENV: &[&str] = &[
+    "PATH",
+    "HOME",
+    "USER",
+    "LANG",
+    "LC_ALL",
+    "TMPDIR",
+    "TMP",
+    "TEMP",
+    "CARGO_HOME",
+    "RUSTUP_HOME",
+    "RUSTUP_TOOLCHAIN",
+    "LD_LIBRARY_PATH",
+    // macOS
+    "DYLD_LIBRARY_PATH",
+    "DYLD_FALLBACK_LIBRARY_PATH",
+    "DYLD_FRAMEWORK_PATH",
+    // Windows
+    "SYSTEMROOT",
+    "WINDIR",
+    "PATHEXT",
+    "COMSPEC",
+    "USERNAME",
+    "USERPROFILE",
+];
+
+/// Version control directories, which build scripts don't write to, and
+/// that are too large to compare before and after every run.
+const VCS_DIRS: &[&str] = &[".git", ".hg", ".pijul", ".fossil", ".svn"];
 
 /// Contains the parsed output of a custom build script.
 #[derive(Clone, Debug, Hash, Default)]
@@ -28,6 +65,8 @@ pub struct BuildOutput {
     pub linker_args: Vec<(LinkType, String)>,
     /// Various `--cfg` flags to pass to the compiler.
     pub cfgs: Vec<String>,
+    /// Various `--check-cfg` flags to pass to the compiler, with `-Z check-cfg`.
+    pub check_cfgs: Vec<String>,
     /// Additional environment variables to run the compiler with.
     pub env: Vec<(String, String)>,
     /// Metadata to pass to the immediate dependencies.
@@ -37,6 +76,10 @@ pub struct BuildOutput {
     pub rerun_if_changed: Vec<PathBuf>,
     /// Environment variables which, when changed, will cause a rebuild.
     pub rerun_if_env_changed: Vec<String>,
+    /// Variables of `rerun_if_env_changed` that were removed from the
+    /// environment of the script by `-Z build-script-sandbox`, because they
+    /// weren't declared before it ran.
+    pub scrubbed_env: Vec<String>,
     /// Warnings generated by this build.
     ///
     /// These are only displayed if this is a "local" package, `-vv` is used,
@@ -104,6 +147,9 @@ pub struct BuildDeps {
     pub rerun_if_changed: Vec<PathBuf>,
     /// Environment variables that trigger a rebuild if they change.
     pub rerun_if_env_changed: Vec<String>,
+    /// Variables of `rerun_if_env_changed` the script ran without, which are
+    /// fingerprinted as unset so that it runs again with them.
+    pub scrubbed_env: Vec<String>,
 }
 
 /// Prepares a `Work` that executes the target as a custom build script.
@@ -220,6 +266,11 @@ fn build_work(cx: &mut Context<'_, '_>, unit: &Unit) -> CargoResult<Job> {
         cmd.env(&format!("CARGO_FEATURE_{}", super::envify(feat)), "1");
     }
 
+    // And the artifacts of its build dependencies.
+    for (var, value) in super::artifact::get_env(cx, dependencies)? {
+        cmd.env(&var, value);
+    }
+
     let mut cfg_map = HashMap::new();
     for cfg in bcx.target_data.cfg(unit.kind) {
         match *cfg {
@@ -297,7 +348,7 @@ fn build_work(cx: &mut Context<'_, '_>, unit: &Unit) -> CargoResult<Job> {
     let output_file = script_run_dir.join("output");
     let err_file = script_run_dir.join("stderr");
     let root_output_file = script_run_dir.join("root-output");
-    let host_target_root = cx.files().host_dest().to_path_buf();
+    let host_target_root = cx.files().host_build_dest().to_path_buf();
     let all = (
         id,
         library_name.clone(),
@@ -311,11 +362,19 @@ fn build_work(cx: &mut Context<'_, '_>, unit: &Unit) -> CargoResult<Job> {
     let extra_verbose = bcx.config.extra_verbose();
     let (prev_output, prev_script_out_dir) = prev_build_output(cx, unit);
     let metadata_hash = cx.get_run_build_script_metadata(unit);
+    let sandbox = cx
+        .bcx
+        .config
+        .cli_unstable()
+        .build_script_sandbox
+        .as_deref()
+        .map(|mode| Sandbox::new(cx, unit, mode == "deny", prev_output.as_ref()));
 
     paths::create_dir_all(&script_dir)?;
     paths::create_dir_all(&script_out_dir)?;
 
     let nightly_features_allowed = cx.bcx.config.nightly_features_allowed;
+    let extra_check_cfg = cx.bcx.config.cli_unstable().check_cfg;
     let targets: Vec<Target> = unit.pkg.targets().to_vec();
     // Need a separate copy for the fresh closure.
     let targets_fresh = targets.clone();
@@ -369,17 +428,29 @@ fn build_work(cx: &mut Context<'_, '_>, unit: &Unit) -> CargoResult<Job> {
             return Ok(());
         }
 
+        let mut scrubbed_env = BTreeSet::new();
+        let mut snapshot = None;
+        if let Some(sandbox) = &sandbox {
+            scrubbed_env = sandbox.scrub_env(&mut cmd);
+            snapshot = Some(sandbox.snapshot()?);
+        }
+        let mut sandbox_warnings = Vec::new();
+
         // And now finally, run the build command itself!
-        state.running(&cmd);
         let timestamp = paths::set_invocation_time(&script_run_dir)?;
         let prefix = format!("[{} {}] ", id.name(), id.version());
+        state.running(&cmd);
         let mut warnings_in_case_of_panic = Vec::new();
+        let mut declared_env = Vec::new();
         let output = cmd
             .exec_with_streaming(
                 &mut |stdout| {
                     if let Some(warning) = stdout.strip_prefix(CARGO_WARNING) {
                         warnings_in_case_of_panic.push(warning.to_owned());
                     }
+                    if let Some(var) = stdout.strip_prefix(CARGO_RERUN_IF_ENV_CHANGED) {
+                        declared_env.push(var.trim_end().to_owned());
+                    }
                     if extra_verbose {
                         state.stdout(format!("{}{}", prefix, stdout))?;
                     }
@@ -395,7 +466,28 @@ fn build_work(cx: &mut Context<'_, '_>, unit: &Unit) -> CargoResult<Job> {
             )
             .with_context(|| format!("failed to run custom build command for `{}`", pkg_descr));
 
+        // The script declared variables it wasn't given, as they weren't
+        // declared when it last ran. Rather than running it again, the next
+        // build runs it with them, even if this run fails.
+        let mut newly_declared_env = Vec::new();
+        if let Some(sandbox) = &sandbox {
+            sandbox.save_declared_env(&declared_env)?;
+            newly_declared_env = declared_env
+                .into_iter()
+                .filter(|var| scrubbed_env.contains(var))
+                .collect();
+            for var in &newly_declared_env {
+                sandbox_warnings.push(format!(
+                    "the build script declared the environment variable `{}`, \
+                     but ran without it since it wasn't declared before; \
+                     it will be rerun with it set by the next build",
+                    var
+                ));
+            }
+        }
+
         if let Err(error) = output {
+            warnings_in_case_of_panic.extend(sandbox_warnings);
             insert_warnings_in_build_outputs(
                 build_script_outputs,
                 id,
@@ -407,6 +499,28 @@ fn build_work(cx: &mut Context<'_, '_>, unit: &Unit) -> CargoResult<Job> {
 
         let output = output.unwrap();
 
+        if let (Some(sandbox), Some(snapshot)) = (&sandbox, snapshot) {
+            let written = sandbox.written_since(&snapshot)?;
+            if !written.is_empty() {
+                let written = written
+                    .iter()
+                    .map(|path| format!("  {}", path.display()))
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                if sandbox.deny {
+                    bail!(
+                        "build script of `{}` wrote outside of `OUT_DIR`:\n{}",
+                        pkg_descr,
+                        written
+                    );
+                }
+                sandbox_warnings.push(format!(
+                    "the build script wrote outside of `OUT_DIR`:\n{}",
+                    written
+                ));
+            }
+        }
+
         // After the build command has finished running, we need to be sure to
         // remember all of its output so we can later discover precisely what it
         // was, even if we don't run the build command again (due to freshness).
@@ -420,15 +534,20 @@ fn build_work(cx: &mut Context<'_, '_>, unit: &Unit) -> CargoResult<Job> {
         paths::set_file_time_no_err(output_file, timestamp);
         paths::write(&err_file, &output.stderr)?;
         paths::write(&root_output_file, paths::path2bytes(&script_out_dir)?)?;
-        let parsed_output = BuildOutput::parse(
+        let mut parsed_output = BuildOutput::parse(
             &output.stdout,
             library_name,
             &pkg_descr,
             &script_out_dir,
             &script_out_dir,
             nightly_features_allowed,
+            extra_check_cfg,
             &targets,
         )?;
+        // Fingerprinted as having seen them unset, so that the next build
+        // reruns it.
+        parsed_output.scrubbed_env = newly_declared_env;
+        parsed_output.warnings.extend(sandbox_warnings);
 
         if json_messages {
             emit_build_output(state, &parsed_output, script_out_dir.as_path(), id)?;
@@ -454,6 +573,7 @@ fn build_work(cx: &mut Context<'_, '_>, unit: &Unit) -> CargoResult<Job> {
                 &prev_script_out_dir,
                 &script_out_dir,
                 nightly_features_allowed,
+                extra_check_cfg,
                 &targets_fresh,
             )?,
         };
@@ -498,6 +618,143 @@ fn insert_warnings_in_build_outputs(
         .insert(id, metadata_hash, build_output_with_only_warnings);
 }
 
+/// State of `-Z build-script-sandbox` for running a build script.
+///
+/// The script only inherits the environment variables in `SANDBOX_ENV`, and
+/// those it declared with `cargo:rerun-if-env-changed` when it last ran,
+/// whether that run succeeded or not. Variables that are declared by a run
+/// without having been declared before are reported. Reads of variables
+/// that are never declared can't be detected, so they aren't reported; the
+/// variables are merely missing from the environment of the script.
+///
+/// Writes outside of `OUT_DIR` are found by comparing the files of the
+/// package before and after the script runs, except in version control
+/// directories.
+struct Sandbox {
+    /// Whether writes outside of `OUT_DIR` are an error, rather than a
+    /// warning.
+    deny: bool,
+    pkg_root: PathBuf,
+    /// Directories in the package that are not checked for writes, as Cargo
+    /// itself writes there, like the target directory.
+    skipped_dirs: Vec<PathBuf>,
+    /// Inherited environment variables that were declared by the last run.
+    declared_env: HashSet<String>,
+    /// File listing the variables declared by the last run, which is written
+    /// even if the script fails, unlike its output.
+    env_file: PathBuf,
+}
+
+/// The size and mtime of the files in a package.
+type Snapshot = BTreeMap<PathBuf, (u64, FileTime)>;
+
+impl Sandbox {
+    fn new(
+        cx: &Context<'_, '_>,
+        unit: &Unit,
+        deny: bool,
+        prev_output: Option<&BuildOutput>,
+    ) -> Sandbox {
+        let ws = cx.bcx.ws;
+        let env_file = cx.files().build_script_run_dir(unit).join("sandbox-env");
+        let mut declared_env: HashSet<String> = prev_output
+            .map(|output| output.rerun_if_env_changed.iter().cloned().collect())
+            .unwrap_or_default();
+        if let Ok(contents) = paths::read(&env_file) {
+            declared_env.extend(contents.lines().map(|var| var.to_string()));
+        }
+        Sandbox {
+            deny,
+            pkg_root: unit.pkg.root().to_path_buf(),
+            skipped_dirs: vec![
+                ws.target_dir().into_path_unlocked(),
+                ws.build_dir().into_path_unlocked(),
+            ],
+            declared_env,
+            env_file,
+        }
+    }
+
+    /// Records the variables declared by a run, for the next one.
+    fn save_declared_env(&self, declared_env: &[String]) -> CargoResult<()> {
+        let mut contents = String::new();
+        for var in declared_env {
+            contents.push_str(var);
+            contents.push('\n');
+        }
+        paths::write(&self.env_file, contents)
+    }
+
+    /// Removes the inherited environment variables the script may not read
+    /// from `cmd`, returning their names.
+    fn scrub_env(&self, cmd: &mut ProcessBuilder) -> BTreeSet<String> {
+        let mut scrubbed = BTreeSet::new();
+        for (key, _) in env::vars_os() {
+            let key = match key.into_string() {
+                Ok(key) => key,
+                Err(_) => continue,
+            };
+            if cmd.get_envs().contains_key(&key)
+                || SANDBOX_ENV.contains(&key.as_str())
+                || self.declared_env.contains(&key)
+            {
+                continue;
+            }
+            cmd.env_remove(&key);
+            scrubbed.insert(key);
+        }
+        scrubbed
+    }
+
+    fn snapshot(&self) -> CargoResult<Snapshot> {
+        let mut snapshot = Snapshot::new();
+        let walkdir = WalkDir::new(&self.pkg_root)
+            .into_iter()
+            .filter_entry(|entry| {
+                let path = entry.path();
+                // Sub-packages have their own build scripts, which may run
+                // at the same time.
+                entry.depth() == 0
+                    || !entry.file_type().is_dir()
+                    || !(self.skipped_dirs.iter().any(|dir| dir == path)
+                        || VCS_DIRS.iter().any(|dir| entry.file_name() == *dir)
+                        || path.join("Cargo.toml").exists())
+            });
+        for entry in walkdir {
+            let entry = entry.with_context(|| {
+                format!("failed to list the files of {}", self.pkg_root.display())
+            })?;
+            if entry.file_type().is_dir() {
+                continue;
+            }
+            let meta = entry.metadata()?;
+            snapshot.insert(
+                entry.path().to_path_buf(),
+                (meta.len(), FileTime::from_last_modification_time(&meta)),
+            );
+        }
+        Ok(snapshot)
+    }
+
+    /// Returns the files that were created, modified or removed since
+    /// `before` was taken, relative to the package root.
+    fn written_since(&self, before: &Snapshot) -> CargoResult<Vec<PathBuf>> {
+        let after = self.snapshot()?;
+        let written = after
+            .iter()
+            .filter(|(path, file)| before.get(*path) != Some(file))
+            .map(|(path, _)| path)
+            .chain(before.keys().filter(|path| !after.contains_key(*path)))
+            .map(|path| {
+                path.strip_prefix(&self.pkg_root)
+                    .unwrap_or(path)
+                    .to_path_buf()
+            })
+            .collect::<BTreeSet<_>>();
+        Ok(written.into_iter().collect())
+    }
+}
+
 impl BuildOutput {
     pub fn parse_file(
         path: &Path,
@@ -506,6 +763,7 @@ impl BuildOutput {
         script_out_dir_when_generated: &Path,
         script_out_dir: &Path,
         nightly_features_allowed: bool,
+        extra_check_cfg: bool,
         targets: &[Target],
     ) -> CargoResult<BuildOutput> {
         let contents = paths::read_bytes(path)?;
@@ -516,6 +774,7 @@ impl BuildOutput {
             script_out_dir_when_generated,
             script_out_dir,
             nightly_features_allowed,
+            extra_check_cfg,
             targets,
         )
     }
@@ -523,6 +782,7 @@ impl BuildOutput {
     // Parses the output of a script.
     // The `pkg_descr` is used for error messages.
     // The `library_name` is used for determining if RUSTC_BOOTSTRAP should be allowed.
+    // The `extra_check_cfg` is whether `cargo:rustc-check-cfg` is enabled with `-Z check-cfg`.
     pub fn parse(
         input: &[u8],
         // Takes String instead of InternedString so passing `unit.pkg.name()` will give a compile error.
@@ -531,12 +791,14 @@ impl BuildOutput {
         script_out_dir_when_generated: &Path,
         script_out_dir: &Path,
         nightly_features_allowed: bool,
+        extra_check_cfg: bool,
         targets: &[Target],
     ) -> CargoResult<BuildOutput> {
         let mut library_paths = Vec::new();
         let mut library_links = Vec::new();
         let mut linker_args = Vec::new();
         let mut cfgs = Vec::new();
+        let mut check_cfgs = Vec::new();
         let mut env = Vec::new();
         let mut metadata = Vec::new();
         let mut rerun_if_changed = Vec::new();
@@ -647,6 +909,13 @@ impl BuildOutput {
                     linker_args.push((LinkType::All, value));
                 }
                 "rustc-cfg" => cfgs.push(value.to_string()),
+                "rustc-check-cfg" => {
+                    if extra_check_cfg {
+                        check_cfgs.push(value.to_string());
+                    } else {
+                        warnings.push(format!("cargo:{} requires -Zcheck-cfg flag", key));
+                    }
+                }
                 "rustc-env" => {
                     let (key, val) = BuildOutput::parse_rustc_env(&value, &whence)?;
                     // Build scripts aren't allowed to set RUSTC_BOOTSTRAP.
@@ -706,10 +975,12 @@ impl BuildOutput {
             library_links,
             linker_args,
             cfgs,
+            check_cfgs,
             env,
             metadata,
             rerun_if_changed,
             rerun_if_env_changed,
+            scrubbed_env: Vec::new(),
             warnings,
         })
     }
@@ -792,7 +1063,7 @@ fn prepare_metabuild(cx: &Context<'_, '_>, unit: &Unit, deps: &[String]) -> Carg
     }
     output.push("}\n".to_string());
     let output = output.join("");
-    let path = unit.pkg.manifest().metabuild_path(cx.bcx.ws.target_dir());
+    let path = unit.pkg.manifest().metabuild_path(cx.bcx.ws.build_dir());
     paths::create_dir_all(path.parent().unwrap())?;
     paths::write_if_changed(path, &output)?;
     Ok(())
@@ -810,6 +1081,7 @@ impl BuildDeps {
                 .map(|p| &p.rerun_if_env_changed)
                 .cloned()
                 .unwrap_or_default(),
+            scrubbed_env: output.map(|p| &p.scrubbed_env).cloned().unwrap_or_default(),
         }
     }
 }
@@ -947,6 +1219,7 @@ fn prev_build_output(cx: &mut Context<'_, '_>, unit: &Unit) -> (Option<BuildOutp
             &prev_script_out_dir,
             &script_out_dir,
             cx.bcx.config.nightly_features_allowed,
+            cx.bcx.config.cli_unstable().check_cfg,
             unit.pkg.targets(),
         )
         .ok(),
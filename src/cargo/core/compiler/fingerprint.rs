@@ -207,6 +207,22 @@
 //! build, so it takes a conservative approach of assuming the file was *not*
 //! included, and it should be rebuilt during the next build.
 //!
+//! #### Checksum freshness
+//!
+//! With `-Zchecksum-freshness`, the size and a hash of the contents of every
+//! file in the dep-info are recorded as well, unless the file was modified
+//! after the build started. A file that is newer than the dep-info file,
+//! which happens when a target directory is restored from a cache along with
+//! a fresh checkout, is then only considered stale if its contents changed.
+//! The mtime of the dep-info file is then moved forward to that of the file,
+//! so that it's only hashed once.
+//! The outputs of the dependencies of a unit, and the output of the build
+//! scripts it depends on, are added to its dep-info with their checksums too.
+//! This replaces the comparison of the mtimes of the outputs of the
+//! dependencies with the mtime of the unit's own outputs for units that use
+//! `CheckDepInfo`, because a rebuilt dependency is then detected by the
+//! changed contents of its outputs.
+//!
 //! #### Rustdoc mtime handling
 //!
 //! Rustdoc does not emit a dep-info file, so Cargo currently has a relatively
@@ -315,6 +331,7 @@
 use std::collections::hash_map::{Entry, HashMap};
 use std::convert::TryInto;
 use std::env;
+use std::fmt;
 use std::hash::{self, Hash, Hasher};
 use std::io;
 use std::path::{Path, PathBuf};
@@ -335,6 +352,7 @@ use crate::core::Package;
 use crate::util;
 use crate::util::errors::CargoResult;
 use crate::util::interning::InternedString;
+use crate::util::machine_message::{self, Message as _};
 use crate::util::{internal, path_args, profile, StableHasher};
 use crate::CARGO_ENV;
 
@@ -374,6 +392,9 @@ pub fn prepare_target(cx: &mut Context<'_, '_>, unit: &Unit, force: bool) -> Car
     let mtime_on_use = cx.bcx.config.cli_unstable().mtime_on_use;
     let compare = compare_old_fingerprint(&loc, &*fingerprint, mtime_on_use);
     log_compare(unit, &compare);
+    if bcx.build_config.explain_rebuilds {
+        explain_rebuild(cx, unit, &compare)?;
+    }
 
     // If our comparison failed (e.g., we're going to trigger a rebuild of this
     // crate), then we also ensure the source of the crate passes all
@@ -551,6 +572,9 @@ pub struct Fingerprint {
     /// or should be considered stale.
     #[serde(skip)]
     fs_status: FsStatus,
+    /// Why `fs_status` is stale, once the filesystem has been checked.
+    #[serde(skip)]
+    stale_reason: Option<DirtyReason>,
     /// Files, relative to `target_root`, that are produced by the step that
     /// this `Fingerprint` represents. This is used to detect when the whole
     /// fingerprint is out of date if this is missing, or if previous
@@ -695,8 +719,52 @@ enum StaleItem {
         previous: Option<String>,
         current: Option<String>,
     },
+    /// The dep-info file was written without checksums, while
+    /// `-Zchecksum-freshness` is used.
+    MissingChecksums(PathBuf),
+}
+
+/// The reason a unit needs to be rebuilt, as reported by
+/// `--explain-rebuilds`.
+///
+/// Only the first reason found is reported, in the order `Fingerprint::compare`
+/// checks things.
+#[derive(Clone, Debug, Serialize)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+pub enum DirtyReason {
+    /// Something hashed into the fingerprint changed, such as the profile or
+    /// the features.
+    SettingChanged { setting: &'static str },
+    /// An env var read during compilation, or registered by a build script
+    /// with `rerun-if-env-changed`, has a new value.
+    EnvVarChanged { name: String },
+    /// A source file is newer than the outputs.
+    FileChanged { path: PathBuf },
+    /// A source file listed in the dep-info was removed.
+    FileMissing { path: PathBuf },
+    /// A dependency of the unit is rebuilt, or has been since this unit was.
+    DependencyRebuilt { name: InternedString },
+    /// Some outputs of the previous build were removed.
+    OutputsMissing,
+}
+
+impl fmt::Display for DirtyReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DirtyReason::SettingChanged { setting } => write!(f, "{} changed", setting),
+            DirtyReason::EnvVarChanged { name } => write!(f, "env var {} changed", name),
+            DirtyReason::FileChanged { path } => write!(f, "file {} changed", path.display()),
+            DirtyReason::FileMissing { path } => {
+                write!(f, "file {} is missing", path.display())
+            }
+            DirtyReason::DependencyRebuilt { name } => write!(f, "dependency {} rebuilt", name),
+            DirtyReason::OutputsMissing => write!(f, "outputs are missing"),
+        }
+    }
 }
 
+impl std::error::Error for DirtyReason {}
+
 impl LocalFingerprint {
     /// Checks dynamically at runtime if this `LocalFingerprint` has a stale
     /// item inside of it.
@@ -709,12 +777,16 @@ impl LocalFingerprint {
     ///
     /// * Another is the `rerun-if-changed` directive from build scripts. This
     ///   is where we'll find whether files have actually changed
+    ///
+    /// With `checksum_freshness`, files listed in the dep-info that are newer
+    /// than it are only stale if their contents changed.
     fn find_stale_item(
         &self,
         mtime_cache: &mut HashMap<PathBuf, FileTime>,
         pkg_root: &Path,
         target_root: &Path,
         cargo_exe: &Path,
+        checksum_freshness: bool,
     ) -> CargoResult<Option<StaleItem>> {
         match self {
             // We need to parse `dep_info`, learn about the crate's dependencies.
@@ -754,7 +826,23 @@ impl LocalFingerprint {
                         current,
                     }));
                 }
-                Ok(find_stale_file(mtime_cache, &dep_info, info.files.iter()))
+                if !checksum_freshness {
+                    return Ok(find_stale_file(
+                        mtime_cache,
+                        &dep_info,
+                        info.files.iter(),
+                        None,
+                    ));
+                }
+                match &info.checksums {
+                    Some(checksums) => Ok(find_stale_file(
+                        mtime_cache,
+                        &dep_info,
+                        info.files.iter(),
+                        Some(checksums),
+                    )),
+                    None => Ok(Some(StaleItem::MissingChecksums(dep_info))),
+                }
             }
 
             // We need to verify that no paths listed in `paths` are newer than
@@ -763,6 +851,7 @@ impl LocalFingerprint {
                 mtime_cache,
                 &target_root.join(output),
                 paths.iter().map(|p| pkg_root.join(p)),
+                None,
             )),
 
             // These have no dependencies on the filesystem, and their values
@@ -799,6 +888,7 @@ impl Fingerprint {
             config: 0,
             compile_kind: 0,
             fs_status: FsStatus::Stale,
+            stale_reason: None,
             outputs: Vec::new(),
         }
     }
@@ -827,57 +917,60 @@ impl Fingerprint {
     ///
     /// The purpose of this is exclusively to produce a diagnostic message
     /// indicating why we're recompiling something. This function always returns
-    /// an error, it will never return success.
-    fn compare(&self, old: &Fingerprint) -> CargoResult<()> {
+    /// an error, it will never return success. Details which don't fit in the
+    /// returned `DirtyReason` are logged.
+    fn compare(&self, old: &Fingerprint) -> Result<(), DirtyReason> {
+        let changed = |setting| Err(DirtyReason::SettingChanged { setting });
         if self.rustc != old.rustc {
-            bail!("rust compiler has changed")
+            return changed("rustc");
         }
         if self.features != old.features {
-            bail!(
+            info!(
                 "features have changed: previously {}, now {}",
-                old.features,
-                self.features
-            )
+                old.features, self.features
+            );
+            return changed("features");
         }
         if self.target != old.target {
-            bail!("target configuration has changed")
+            return changed("target configuration");
         }
         if self.path != old.path {
-            bail!("path to the source has changed")
+            return changed("source path");
         }
         if self.profile != old.profile {
-            bail!("profile configuration has changed")
+            return changed("profile");
         }
         if self.rustflags != old.rustflags {
-            bail!(
+            info!(
                 "RUSTFLAGS has changed: previously {:?}, now {:?}",
-                old.rustflags,
-                self.rustflags
-            )
+                old.rustflags, self.rustflags
+            );
+            return changed("rustflags");
         }
         if self.metadata != old.metadata {
-            bail!("metadata changed")
+            return changed("package metadata");
         }
         if self.config != old.config {
-            bail!("configuration settings have changed")
+            return changed("config settings");
         }
         if self.compile_kind != old.compile_kind {
-            bail!("compile kind (rustc target) changed")
+            return changed("compile kind");
         }
         let my_local = self.local.lock().unwrap();
         let old_local = old.local.lock().unwrap();
         if my_local.len() != old_local.len() {
-            bail!("local lens changed");
+            info!("local lens changed");
+            return changed("build script directives");
         }
         for (new, old) in my_local.iter().zip(old_local.iter()) {
             match (new, old) {
                 (LocalFingerprint::Precalculated(a), LocalFingerprint::Precalculated(b)) => {
                     if a != b {
-                        bail!(
+                        info!(
                             "precalculated components have changed: previously {}, now {}",
-                            b,
-                            a
-                        )
+                            b, a
+                        );
+                        return changed("package contents");
                     }
                 }
                 (
@@ -885,11 +978,11 @@ impl Fingerprint {
                     LocalFingerprint::CheckDepInfo { dep_info: bdep },
                 ) => {
                     if adep != bdep {
-                        bail!(
+                        info!(
                             "dep info output changed: previously {:?}, now {:?}",
-                            bdep,
-                            adep
-                        )
+                            bdep, adep
+                        );
+                        return changed("dep-info path");
                     }
                 }
                 (
@@ -903,18 +996,18 @@ impl Fingerprint {
                     },
                 ) => {
                     if aout != bout {
-                        bail!(
+                        info!(
                             "rerun-if-changed output changed: previously {:?}, now {:?}",
-                            bout,
-                            aout
-                        )
+                            bout, aout
+                        );
+                        return changed("build script directives");
                     }
                     if apaths != bpaths {
-                        bail!(
+                        info!(
                             "rerun-if-changed output changed: previously {:?}, now {:?}",
-                            bpaths,
-                            apaths,
-                        )
+                            bpaths, apaths,
+                        );
+                        return changed("build script directives");
                     }
                 }
                 (
@@ -928,57 +1021,62 @@ impl Fingerprint {
                     },
                 ) => {
                     if *akey != *bkey {
-                        bail!("env vars changed: previously {}, now {}", bkey, akey);
+                        info!("env vars changed: previously {}, now {}", bkey, akey);
+                        return changed("build script directives");
                     }
                     if *avalue != *bvalue {
-                        bail!(
+                        info!(
                             "env var `{}` changed: previously {:?}, now {:?}",
-                            akey,
-                            bvalue,
-                            avalue
-                        )
+                            akey, bvalue, avalue
+                        );
+                        return Err(DirtyReason::EnvVarChanged { name: akey.clone() });
                     }
                 }
-                (a, b) => bail!(
-                    "local fingerprint type has changed ({} => {})",
-                    b.kind(),
-                    a.kind()
-                ),
+                (a, b) => {
+                    info!(
+                        "local fingerprint type has changed ({} => {})",
+                        b.kind(),
+                        a.kind()
+                    );
+                    return changed("build script directives");
+                }
             }
         }
 
         if self.deps.len() != old.deps.len() {
-            bail!("number of dependencies has changed")
+            return changed("dependencies");
         }
         for (a, b) in self.deps.iter().zip(old.deps.iter()) {
             if a.name != b.name {
-                let e = format_err!("`{}` != `{}`", a.name, b.name)
-                    .context("unit dependency name changed");
-                return Err(e);
+                info!("unit dependency name changed: `{}` != `{}`", a.name, b.name);
+                return changed("dependencies");
             }
 
             if a.fingerprint.hash_u64() != b.fingerprint.hash_u64() {
-                let e = format_err!(
-                    "new ({}/{:x}) != old ({}/{:x})",
+                info!(
+                    "unit dependency information changed: new ({}/{:x}) != old ({}/{:x})",
                     a.name,
                     a.fingerprint.hash_u64(),
                     b.name,
                     b.fingerprint.hash_u64()
-                )
-                .context("unit dependency information changed");
-                return Err(e);
+                );
+                return Err(DirtyReason::DependencyRebuilt { name: a.name });
             }
         }
 
         if !self.fs_status.up_to_date() {
-            bail!("current filesystem status shows we're outdated");
+            info!("current filesystem status shows we're outdated");
+            if let Some(reason) = &self.stale_reason {
+                return Err(reason.clone());
+            }
         }
 
         // This typically means some filesystem modifications happened or
         // something transitive was odd. In general we should strive to provide
         // a better error message than this, so if you see this message a lot it
         // likely means this method needs to be updated!
-        bail!("two fingerprint comparison turned up nothing obvious");
+        info!("two fingerprint comparison turned up nothing obvious");
+        changed("fingerprint")
     }
 
     /// Dynamically inspect the local filesystem to update the `fs_status` field
@@ -995,9 +1093,20 @@ impl Fingerprint {
         pkg_root: &Path,
         target_root: &Path,
         cargo_exe: &Path,
+        checksum_freshness: bool,
     ) -> CargoResult<()> {
         assert!(!self.fs_status.up_to_date());
 
+        // With checksums, the outputs of dependencies are listed in the
+        // dep-info, so rebuilt dependencies are found by their contents.
+        let check_dep_mtimes = !checksum_freshness
+            || !self
+                .local
+                .get_mut()
+                .unwrap()
+                .iter()
+                .any(|local| matches!(local, LocalFingerprint::CheckDepInfo { .. }));
+
         let mut mtimes = HashMap::new();
 
         // Get the `mtime` of all outputs. Optionally update their mtime
@@ -1012,6 +1121,7 @@ impl Fingerprint {
                 // exists, so leave ourselves as stale and bail out.
                 Err(e) => {
                     debug!("failed to get mtime of {:?}: {}", output, e);
+                    self.stale_reason = Some(DirtyReason::OutputsMissing);
                     return Ok(());
                 }
             };
@@ -1039,8 +1149,14 @@ impl Fingerprint {
             let dep_mtimes = match &dep.fingerprint.fs_status {
                 FsStatus::UpToDate { mtimes } => mtimes,
                 // If our dependency is stale, so are we, so bail out.
-                FsStatus::Stale => return Ok(()),
+                FsStatus::Stale => {
+                    self.stale_reason = Some(DirtyReason::DependencyRebuilt { name: dep.name });
+                    return Ok(());
+                }
             };
+            if !check_dep_mtimes {
+                continue;
+            }
 
             // If our dependency edge only requires the rmeta file to be present
             // then we only need to look at that one output file, otherwise we
@@ -1077,6 +1193,7 @@ impl Fingerprint {
                     "dependency on `{}` is newer than we are {} > {} {:?}",
                     dep.name, dep_mtime, max_mtime, pkg_root
                 );
+                self.stale_reason = Some(DirtyReason::DependencyRebuilt { name: dep.name });
                 return Ok(());
             }
         }
@@ -1086,10 +1203,15 @@ impl Fingerprint {
         // files for this package itself. If we do find something log a helpful
         // message and bail out so we stay stale.
         for local in self.local.get_mut().unwrap().iter() {
-            if let Some(item) =
-                local.find_stale_item(mtime_cache, pkg_root, target_root, cargo_exe)?
-            {
+            if let Some(item) = local.find_stale_item(
+                mtime_cache,
+                pkg_root,
+                target_root,
+                cargo_exe,
+                checksum_freshness,
+            )? {
                 item.log();
+                self.stale_reason = Some(item.dirty_reason(pkg_root));
                 return Ok(());
             }
         }
@@ -1175,7 +1297,8 @@ impl DepFingerprint {
             name: dep.extern_crate_name,
             public: dep.public,
             fingerprint,
-            only_requires_rmeta: cx.only_requires_rmeta(parent, &dep.unit),
+            only_requires_rmeta: dep.artifact.is_none()
+                && cx.only_requires_rmeta(parent, &dep.unit),
         })
     }
 }
@@ -1209,6 +1332,27 @@ impl StaleItem {
                 info!("stale: changed env {:?}", var);
                 info!("       {:?} != {:?}", previous, current);
             }
+            StaleItem::MissingChecksums(path) => {
+                info!("stale: no checksums in {:?}", path);
+            }
+        }
+    }
+
+    /// Describes this item as the reason for rebuilding, with paths relative
+    /// to the package root when they're inside of it.
+    fn dirty_reason(&self, pkg_root: &Path) -> DirtyReason {
+        let relative = |path: &Path| path.strip_prefix(pkg_root).unwrap_or(path).to_path_buf();
+        match self {
+            StaleItem::MissingFile(path) => DirtyReason::FileMissing {
+                path: relative(path),
+            },
+            StaleItem::ChangedFile { stale, .. } => DirtyReason::FileChanged {
+                path: relative(stale),
+            },
+            StaleItem::ChangedEnv { var, .. } => DirtyReason::EnvVarChanged { name: var.clone() },
+            StaleItem::MissingChecksums(_) => DirtyReason::SettingChanged {
+                setting: "checksum-freshness",
+            },
         }
     }
 }
@@ -1248,6 +1392,7 @@ fn calculate(cx: &mut Context<'_, '_>, unit: &Unit) -> CargoResult<Arc<Fingerpri
         unit.pkg.root(),
         &target_root,
         cargo_exe,
+        cx.bcx.config.cli_unstable().checksum_freshness,
     )?;
 
     let fingerprint = Arc::new(fingerprint);
@@ -1304,11 +1449,10 @@ fn calculate_normal(cx: &mut Context<'_, '_>, unit: &Unit) -> CargoResult<Finger
     // hashed to take up less space on disk as we just need to know when things
     // change.
     let extra_flags = if unit.mode.is_doc() {
-        cx.bcx.rustdocflags_args(unit)
+        cx.bcx.rustdocflags_args(unit).to_vec()
     } else {
         cx.bcx.rustflags_args(unit)
-    }
-    .to_vec();
+    };
 
     let profile_hash = util::hash_u64((
         &unit.profile,
@@ -1331,6 +1475,13 @@ fn calculate_normal(cx: &mut Context<'_, '_>, unit: &Unit) -> CargoResult<Finger
     if let Some(allow_features) = &cx.bcx.config.cli_unstable().allow_features {
         allow_features.hash(&mut config);
     }
+    let lint_rustflags = unit.pkg.manifest().lint_rustflags();
+    if unit.is_local() && !lint_rustflags.is_empty() {
+        lint_rustflags.hash(&mut config);
+    }
+    if cx.bcx.config.cli_unstable().check_cfg {
+        super::check_cfg_args(cx, unit).hash(&mut config);
+    }
     let compile_kind = unit.kind.fingerprint_hash();
     Ok(Fingerprint {
         rustc: util::hash_u64(&cx.bcx.rustc().verbose_version),
@@ -1348,6 +1499,7 @@ fn calculate_normal(cx: &mut Context<'_, '_>, unit: &Unit) -> CargoResult<Finger
         compile_kind,
         rustflags: extra_flags,
         fs_status: FsStatus::Stale,
+        stale_reason: None,
         outputs,
     })
 }
@@ -1569,7 +1721,11 @@ fn local_fingerprints_deps(
     }
 
     for var in deps.rerun_if_env_changed.iter() {
-        let val = env::var(var).ok();
+        let val = if deps.scrubbed_env.contains(var) {
+            None
+        } else {
+            env::var(var).ok()
+        };
         local.push(LocalFingerprint::RerunIfEnvChanged {
             var: var.clone(),
             val,
@@ -1615,10 +1771,10 @@ pub fn dep_info_loc(cx: &mut Context<'_, '_>, unit: &Unit) -> PathBuf {
     cx.files().fingerprint_file_path(unit, "dep-")
 }
 
-/// Returns an absolute path that target directory.
-/// All paths are rewritten to be relative to this.
+/// Returns an absolute path that target directory, or the build directory if
+/// there is a separate one. All paths are rewritten to be relative to this.
 fn target_root(cx: &Context<'_, '_>) -> PathBuf {
-    cx.bcx.ws.target_dir().into_path_unlocked()
+    cx.bcx.ws.build_dir().into_path_unlocked()
 }
 
 fn compare_old_fingerprint(
@@ -1653,7 +1809,7 @@ fn compare_old_fingerprint(
     }
     let result = new_fingerprint.compare(&old_fingerprint);
     assert!(result.is_err());
-    result
+    result.map_err(|reason| reason.into())
 }
 
 fn log_compare(unit: &Unit, compare: &CargoResult<()>) {
@@ -1668,6 +1824,41 @@ fn log_compare(unit: &Unit, compare: &CargoResult<()>) {
     info!("    err: {:?}", ce);
 }
 
+/// Prints why `unit` is rebuilt with `--explain-rebuilds`, as a status line
+/// and as a JSON message.
+///
+/// Units which were never built before, or whose fingerprint is unreadable,
+/// are silently rebuilt.
+fn explain_rebuild(
+    cx: &Context<'_, '_>,
+    unit: &Unit,
+    compare: &CargoResult<()>,
+) -> CargoResult<()> {
+    let reason = match compare {
+        Err(e) => match e.downcast_ref::<DirtyReason>() {
+            Some(reason) => reason,
+            None => return Ok(()),
+        },
+        Ok(()) => return Ok(()),
+    };
+    let config = cx.bcx.config;
+    config
+        .shell()
+        .status("Dirty", format!("{}: {}", unit.pkg.name(), reason))?;
+    if cx.bcx.build_config.emit_json() {
+        let msg = machine_message::UnitDirty {
+            package_id: unit.pkg.package_id(),
+            manifest_path: unit.pkg.manifest_path(),
+            target: &unit.target,
+            message: reason.to_string(),
+            dirty_reason: reason,
+        }
+        .to_json_string();
+        writeln!(config.shell().out(), "{}", msg)?;
+    }
+    Ok(())
+}
+
 /// Parses Cargo's internal `EncodedDepInfo` structure that was previously
 /// serialized to disk.
 ///
@@ -1697,12 +1888,18 @@ pub fn parse_dep_info(
     };
     let mut ret = RustcDepInfo::default();
     ret.env = info.env;
-    for (ty, path) in info.files {
+    if info.checksums {
+        ret.checksums = Some(HashMap::new());
+    }
+    for (ty, path, checksum) in info.files {
         let path = match ty {
             DepInfoPathType::PackageRootRelative => pkg_root.join(path),
             // N.B. path might be absolute here in which case the join will have no effect
             DepInfoPathType::TargetRootRelative => target_root.join(path),
         };
+        if let (Some(checksums), Some(checksum)) = (&mut ret.checksums, checksum) {
+            checksums.insert(path.clone(), checksum);
+        }
         ret.files.push(path);
     }
     Ok(Some(ret))
@@ -1718,10 +1915,14 @@ fn pkg_fingerprint(bcx: &BuildContext<'_, '_>, pkg: &Package) -> CargoResult<Str
     source.fingerprint(pkg)
 }
 
+/// Finds the first of `paths` that is newer than `reference`, ignoring those
+/// whose size and hash still match the ones in `checksums`. If nothing is
+/// stale, the mtime of `reference` is moved forward to the newest of those.
 fn find_stale_file<I>(
     mtime_cache: &mut HashMap<PathBuf, FileTime>,
     reference: &Path,
     paths: I,
+    checksums: Option<&HashMap<PathBuf, (u64, u64)>>,
 ) -> Option<StaleItem>
 where
     I: IntoIterator,
@@ -1732,6 +1933,9 @@ where
         Err(..) => return Some(StaleItem::MissingFile(reference.to_path_buf())),
     };
 
+    // The newest of the files that are newer than `reference` but whose
+    // checksums still match.
+    let mut newest_unchanged = None;
     for path in paths {
         let path = path.as_ref();
         let path_mtime = match mtime_cache.entry(path.to_path_buf()) {
@@ -1766,6 +1970,13 @@ where
         if path_mtime <= reference_mtime {
             continue;
         }
+        if let Some(&checksum) = checksums.and_then(|checksums| checksums.get(path)) {
+            if file_checksum(path, Some(checksum.0)) == Some(checksum) {
+                debug!("{:?} is newer than {:?} but unchanged", path, reference);
+                newest_unchanged = newest_unchanged.max(Some(path_mtime));
+                continue;
+            }
+        }
 
         return Some(StaleItem::ChangedFile {
             reference: reference.to_path_buf(),
@@ -1779,9 +1990,28 @@ where
         "all paths up-to-date relative to {:?} mtime={}",
         reference, reference_mtime
     );
+    // Move the reference forward so that the unchanged files aren't hashed
+    // again by the next build. Any later modification makes them newer again.
+    if let Some(mtime) = newest_unchanged {
+        paths::set_file_time_no_err(reference, mtime);
+    }
     None
 }
 
+/// Returns the size and a hash of the contents of a file, or `None` if it
+/// can't be read. If `expected_size` is given and the file has a different
+/// size, `None` is returned without hashing its contents.
+fn file_checksum(path: &Path, expected_size: Option<u64>) -> Option<(u64, u64)> {
+    let size = std::fs::metadata(path).ok()?.len();
+    if expected_size.map_or(false, |expected| expected != size) {
+        return None;
+    }
+    let contents = paths::read_bytes(path).ok()?;
+    let mut hasher = StableHasher::new();
+    hasher.write(&contents);
+    Some((contents.len() as u64, hasher.finish()))
+}
+
 enum DepInfoPathType {
     // src/, e.g. src/lib.rs
     PackageRootRelative,
@@ -1813,6 +2043,12 @@ enum DepInfoPathType {
 ///
 /// The serialized Cargo format will contain a list of files, all of which are
 /// relative if they're under `root`. or absolute if they're elsewhere.
+///
+/// With `-Zchecksum-freshness`, `checksum_start` is the time the compilation
+/// started, and `extra_files` are the outputs of dependencies to record along
+/// with the files of the rustc dep-info. The checksum of each file is
+/// recorded, unless it was modified after the compilation started, in which
+/// case the contents rustc saw are unknown and only its mtime is checked.
 pub fn translate_dep_info(
     rustc_dep_info: &Path,
     cargo_dep_info: &Path,
@@ -1821,6 +2057,8 @@ pub fn translate_dep_info(
     target_root: &Path,
     rustc_cmd: &ProcessBuilder,
     allow_package: bool,
+    checksum_start: Option<FileTime>,
+    extra_files: &[PathBuf],
 ) -> CargoResult<()> {
     let depinfo = parse_rustc_dep_info(rustc_dep_info)?;
 
@@ -1828,6 +2066,7 @@ pub fn translate_dep_info(
     let pkg_root = pkg_root.canonicalize()?;
     let mut on_disk_info = EncodedDepInfo::default();
     on_disk_info.env = depinfo.env;
+    on_disk_info.checksums = checksum_start.is_some();
 
     // This is a bit of a tricky statement, but here we're *removing* the
     // dependency on environment variables that were defined specifically for
@@ -1861,7 +2100,9 @@ pub fn translate_dep_info(
         .env
         .retain(|(key, _)| !rustc_cmd.get_envs().contains_key(key) || key == CARGO_ENV);
 
-    for file in depinfo.files {
+    // The output of an overridden build script doesn't exist.
+    let extra_files = extra_files.iter().filter(|file| file.exists()).cloned();
+    for file in depinfo.files.into_iter().chain(extra_files) {
         // The path may be absolute or relative, canonical or not. Make sure
         // it is canonicalized so we are comparing the same kinds of paths.
         let abs_file = rustc_cwd.join(file);
@@ -1883,7 +2124,11 @@ pub fn translate_dep_info(
             // effect.
             (DepInfoPathType::TargetRootRelative, &*abs_file)
         };
-        on_disk_info.files.push((ty, path.to_owned()));
+        let checksum = checksum_start.and_then(|start| match paths::mtime(&canon_file) {
+            Ok(mtime) if mtime <= start => file_checksum(&canon_file, None),
+            _ => None,
+        });
+        on_disk_info.files.push((ty, path.to_owned(), checksum));
     }
     paths::write(cargo_dep_info, on_disk_info.serialize()?)?;
     Ok(())
@@ -1901,6 +2146,10 @@ pub struct RustcDepInfo {
     /// means that the env var wasn't actually set and the compilation depends
     /// on it not being set.
     pub env: Vec<(String, Option<String>)>,
+    /// The size and hash of the contents of files, for the files they were
+    /// recorded for with `-Zchecksum-freshness`. `None` if the dep-info was
+    /// written without checksums.
+    pub checksums: Option<HashMap<PathBuf, (u64, u64)>>,
 }
 
 // Same as `RustcDepInfo` except avoids absolute paths as much as possible to
@@ -1908,10 +2157,15 @@ pub struct RustcDepInfo {
 //
 // This is also stored in an optimized format to make parsing it fast because
 // Cargo will read it for crates on all future compilations.
+//
+// The checksums of files are stored at the end, so that older versions of
+// Cargo can still read the rest.
 #[derive(Default)]
 struct EncodedDepInfo {
-    files: Vec<(DepInfoPathType, PathBuf)>,
+    files: Vec<(DepInfoPathType, PathBuf, Option<(u64, u64)>)>,
     env: Vec<(String, Option<String>)>,
+    /// Whether checksums were recorded for the files.
+    checksums: bool,
 }
 
 impl EncodedDepInfo {
@@ -1926,7 +2180,7 @@ impl EncodedDepInfo {
                 _ => return None,
             };
             let bytes = read_bytes(bytes)?;
-            files.push((ty, paths::bytes2path(bytes).ok()?));
+            files.push((ty, paths::bytes2path(bytes).ok()?, None));
         }
 
         let nenv = read_usize(bytes)?;
@@ -1940,7 +2194,25 @@ impl EncodedDepInfo {
             };
             env.push((key, val));
         }
-        return Some(EncodedDepInfo { files, env });
+
+        let checksums = !bytes.is_empty();
+        if checksums {
+            if read_usize(bytes)? != nfiles {
+                return None;
+            }
+            for file in files.iter_mut() {
+                file.2 = match read_u8(bytes)? {
+                    0 => None,
+                    1 => Some((read_u64(bytes)?, read_u64(bytes)?)),
+                    _ => return None,
+                };
+            }
+        }
+        return Some(EncodedDepInfo {
+            files,
+            env,
+            checksums,
+        });
 
         fn read_usize(bytes: &mut &[u8]) -> Option<usize> {
             let ret = bytes.get(..4)?;
@@ -1948,6 +2220,12 @@ impl EncodedDepInfo {
             Some(u32::from_le_bytes(ret.try_into().unwrap()) as usize)
         }
 
+        fn read_u64(bytes: &mut &[u8]) -> Option<u64> {
+            let ret = bytes.get(..8)?;
+            *bytes = &bytes[8..];
+            Some(u64::from_le_bytes(ret.try_into().unwrap()))
+        }
+
         fn read_u8(bytes: &mut &[u8]) -> Option<u8> {
             let ret = *bytes.get(0)?;
             *bytes = &bytes[1..];
@@ -1966,7 +2244,7 @@ impl EncodedDepInfo {
         let mut ret = Vec::new();
         let dst = &mut ret;
         write_usize(dst, self.files.len());
-        for (ty, file) in self.files.iter() {
+        for (ty, file, _) in self.files.iter() {
             match ty {
                 DepInfoPathType::PackageRootRelative => dst.push(0),
                 DepInfoPathType::TargetRootRelative => dst.push(1),
@@ -1985,6 +2263,20 @@ impl EncodedDepInfo {
                 }
             }
         }
+
+        if self.checksums {
+            write_usize(dst, self.files.len());
+            for (_, _, checksum) in self.files.iter() {
+                match checksum {
+                    None => dst.push(0),
+                    Some((size, hash)) => {
+                        dst.push(1);
+                        dst.extend(&u64::to_le_bytes(*size));
+                        dst.extend(&u64::to_le_bytes(*hash));
+                    }
+                }
+            }
+        }
         return Ok(ret);
 
         fn write_bytes(dst: &mut Vec<u8>, val: impl AsRef<[u8]>) {
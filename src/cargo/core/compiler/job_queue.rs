@@ -50,7 +50,7 @@
 //! improved.
 
 use std::cell::{Cell, RefCell};
-use std::collections::{BTreeMap, HashMap, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::fmt::Write as _;
 use std::io;
 use std::marker;
@@ -137,6 +137,8 @@ struct DrainState<'cfg> {
     active: HashMap<JobId, Unit>,
     compiled: HashSet<PackageId>,
     documented: HashSet<PackageId>,
+    /// Packages that failed to build.
+    failed: BTreeSet<PackageId>,
     counts: HashMap<PackageId, usize>,
     progress: Progress<'cfg>,
     next_id: u32,
@@ -395,21 +397,28 @@ impl<'cfg> JobQueue<'cfg> {
             .filter(|dep| {
                 // Binaries aren't actually needed to *compile* tests, just to run
                 // them, so we don't include this dependency edge in the job graph.
-                !dep.unit.target.is_test() && !dep.unit.target.is_bin()
+                // Artifact dependencies on binaries are needed though.
+                dep.artifact.is_some() || (!dep.unit.target.is_test() && !dep.unit.target.is_bin())
             })
-            .map(|dep| {
+            .fold(HashMap::new(), |mut dependencies, dep| {
                 // Handle the case here where our `unit -> dep` dependency may
                 // only require the metadata, not the full compilation to
                 // finish. Use the tables in `cx` to figure out what kind
-                // of artifact is associated with this dependency.
-                let artifact = if cx.only_requires_rmeta(unit, &dep.unit) {
+                // of artifact is associated with this dependency. Artifact
+                // dependencies always need the full compilation, even if the
+                // same unit is also used as a library.
+                let artifact = if dep.artifact.is_none() && cx.only_requires_rmeta(unit, &dep.unit)
+                {
                     Artifact::Metadata
                 } else {
                     Artifact::All
                 };
-                (dep.unit.clone(), artifact)
-            })
-            .collect::<HashMap<_, _>>();
+                let entry = dependencies.entry(dep.unit.clone()).or_insert(artifact);
+                if artifact == Artifact::All {
+                    *entry = Artifact::All;
+                }
+                dependencies
+            });
 
         // This is somewhat tricky, but we may need to synthesize some
         // dependencies for this target if it requires full upstream
@@ -482,6 +491,7 @@ impl<'cfg> JobQueue<'cfg> {
             active: HashMap::new(),
             compiled: HashSet::new(),
             documented: HashSet::new(),
+            failed: BTreeSet::new(),
             counts: self.counts,
             progress,
             next_id: 0,
@@ -678,6 +688,7 @@ impl<'cfg> DrainState<'cfg> {
                 match result {
                     Ok(()) => self.finish(id, &unit, artifact, cx)?,
                     Err(e) => {
+                        self.failed.insert(unit.pkg.package_id());
                         let msg = "The following warnings were emitted during compilation:";
                         self.emit_warnings(Some(msg), &unit, cx)?;
                         self.back_compat_notice(cx, &unit)?;
@@ -862,6 +873,8 @@ impl<'cfg> DrainState<'cfg> {
         }
 
         if let Some(e) = error {
+            // It doesn't really matter if this fails.
+            drop(self.minimal_versions_report(cx));
             Some(e)
         } else if self.queue.is_empty() && self.pending_queue.is_empty() {
             let message = format!(
@@ -1190,6 +1203,89 @@ impl<'cfg> DrainState<'cfg> {
         Ok(())
     }
 
+    /// With `-Z minimal-versions` or `-Z direct-minimal-versions`, lists the
+    /// direct dependencies of the workspace members that failed to build, or
+    /// that depend on a package that failed to build, so their lower bounds
+    /// in `Cargo.toml` can be raised.
+    fn minimal_versions_report(&self, cx: &Context<'_, '_>) -> CargoResult<()> {
+        let unstable = cx.bcx.config.cli_unstable();
+        if !unstable.minimal_versions && !unstable.direct_minimal_versions {
+            return Ok(());
+        }
+        let members: HashSet<_> = cx.bcx.ws.members().map(|p| p.package_id()).collect();
+        let mut failed_below = HashMap::new();
+        let mut direct_deps = BTreeSet::new();
+        for (unit, deps) in cx.bcx.unit_graph.iter() {
+            if !members.contains(&unit.pkg.package_id()) {
+                continue;
+            }
+            for dep in deps {
+                let dep_id = dep.unit.pkg.package_id();
+                if members.contains(&dep_id) {
+                    continue;
+                }
+                let failed = self.failed_below(cx, &dep.unit, &mut failed_below);
+                if failed.is_empty() {
+                    continue;
+                }
+                let decl = match unit
+                    .pkg
+                    .dependencies()
+                    .iter()
+                    .find(|d| d.matches_id(dep_id))
+                {
+                    Some(decl) => decl,
+                    None => continue,
+                };
+                let mut line = format!(
+                    "  {} (`{} = \"{}\"` in {})",
+                    dep_id,
+                    decl.name_in_toml(),
+                    decl.version_req(),
+                    unit.pkg.package_id()
+                );
+                if !failed.contains(&dep_id) {
+                    let failed: Vec<_> = failed.iter().map(|id| id.to_string()).collect();
+                    write!(line, ", as its dependency {} failed", failed.join(", ")).unwrap();
+                }
+                direct_deps.insert(line);
+            }
+        }
+        if direct_deps.is_empty() {
+            return Ok(());
+        }
+        let mut msg =
+            String::from("the minimum versions of these direct dependencies failed to compile:\n");
+        for line in direct_deps {
+            msg.push_str(&line);
+            msg.push('\n');
+        }
+        msg.push_str("consider raising their lower bounds in `Cargo.toml`");
+        cx.bcx.config.shell().note(msg)
+    }
+
+    /// Returns the packages that failed to build among `unit` and the units
+    /// it depends on, memoized in `memo`.
+    fn failed_below<'a>(
+        &self,
+        cx: &'a Context<'_, '_>,
+        unit: &'a Unit,
+        memo: &mut HashMap<&'a Unit, BTreeSet<PackageId>>,
+    ) -> BTreeSet<PackageId> {
+        if let Some(failed) = memo.get(unit) {
+            return failed.clone();
+        }
+        let mut failed = BTreeSet::new();
+        if self.failed.contains(&unit.pkg.package_id()) {
+            failed.insert(unit.pkg.package_id());
+        }
+        for dep in cx.bcx.unit_graph[unit].iter() {
+            failed.extend(self.failed_below(cx, &dep.unit, memo));
+        }
+        memo.insert(unit, failed.clone());
+        failed
+    }
+
     fn back_compat_notice(&self, cx: &Context<'_, '_>, unit: &Unit) -> CargoResult<()> {
         if unit.pkg.name() != "diesel"
             || unit.pkg.version() >= &Version::new(1, 4, 8)
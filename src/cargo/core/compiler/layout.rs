@@ -97,6 +97,12 @@
 //!
 //! When cross-compiling, the layout is the same, except it appears in
 //! `target/$TRIPLE`.
+//!
+//! With `build.build-dir`, the intermediate artifacts in `.fingerprint`,
+//! `deps`, `incremental` and `build`, along with `.metabuild`, are placed in
+//! the same layout in the build directory instead. Final artifacts, examples,
+//! docs and everything else stay in the target directory, which is locked
+//! along with the build directory.
 
 use crate::core::compiler::CompileTarget;
 use crate::core::Workspace;
@@ -113,13 +119,16 @@ pub struct Layout {
     root: PathBuf,
     /// The final artifact destination: `$root/debug` (or `release`).
     dest: PathBuf,
-    /// The directory with rustc artifacts: `$dest/deps`
+    /// The destination of intermediate artifacts, which is `dest` unless
+    /// there is a separate build directory: `/path/to/build-dir/debug`.
+    build_dest: PathBuf,
+    /// The directory with rustc artifacts: `$build_dest/deps`
     deps: PathBuf,
-    /// The directory for build scripts: `$dest/build`
+    /// The directory for build scripts: `$build_dest/build`
     build: PathBuf,
-    /// The directory for incremental files: `$dest/incremental`
+    /// The directory for incremental files: `$build_dest/incremental`
     incremental: PathBuf,
-    /// The directory for fingerprints: `$dest/.fingerprint`
+    /// The directory for fingerprints: `$build_dest/.fingerprint`
     fingerprint: PathBuf,
     /// The directory for examples: `$dest/examples`
     examples: PathBuf,
@@ -130,6 +139,8 @@ pub struct Layout {
     /// The lockfile for a build (`.cargo-lock`). Will be unlocked when this
     /// struct is `drop`ped.
     _lock: FileLock,
+    /// The lockfile of the build directory, if separate.
+    _build_lock: Option<FileLock>,
 }
 
 impl Layout {
@@ -145,9 +156,13 @@ impl Layout {
         dest: &str,
     ) -> CargoResult<Layout> {
         let mut root = ws.target_dir();
+        let mut build_root = ws.build_dir();
         if let Some(target) = target {
             root.push(target.short_name());
+            build_root.push(target.short_name());
         }
+        let separate_build_dir = build_root.as_path_unlocked() != root.as_path_unlocked();
+        let build_dest = build_root.join(dest);
         let dest = root.join(dest);
         // If the root directory doesn't already exist go ahead and create it
         // here. Use this opportunity to exclude it from backups as well if the
@@ -162,20 +177,30 @@ impl Layout {
         // directory, so just lock the entire thing for the duration of this
         // compile.
         let lock = dest.open_rw(".cargo-lock", ws.config(), "build directory")?;
+        let build_lock = if separate_build_dir {
+            paths::create_dir_all_excluded_from_backups_atomic(build_root.as_path_unlocked())?;
+            paths::create_dir_all(build_dest.as_path_unlocked())?;
+            Some(build_dest.open_rw(".cargo-lock", ws.config(), "build directory")?)
+        } else {
+            None
+        };
         let root = root.into_path_unlocked();
         let dest = dest.into_path_unlocked();
+        let build_dest = build_dest.into_path_unlocked();
 
         Ok(Layout {
-            deps: dest.join("deps"),
-            build: dest.join("build"),
-            incremental: dest.join("incremental"),
-            fingerprint: dest.join(".fingerprint"),
+            deps: build_dest.join("deps"),
+            build: build_dest.join("build"),
+            incremental: build_dest.join("incremental"),
+            fingerprint: build_dest.join(".fingerprint"),
             examples: dest.join("examples"),
             doc: root.join("doc"),
             tmp: root.join("tmp"),
             root,
             dest,
+            build_dest,
             _lock: lock,
+            _build_lock: build_lock,
         })
     }
 
@@ -194,6 +219,11 @@ impl Layout {
     pub fn dest(&self) -> &Path {
         &self.dest
     }
+    /// Fetch the destination path for intermediate artifacts, the same as
+    /// `dest` unless there is a separate build directory.
+    pub fn build_dest(&self) -> &Path {
+        &self.build_dest
+    }
     /// Fetch the deps path.
     pub fn deps(&self) -> &Path {
         &self.deps
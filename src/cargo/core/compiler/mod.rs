@@ -1,3 +1,5 @@
+mod artifact;
+mod build_cache;
 mod build_config;
 mod build_context;
 mod build_plan;
@@ -14,6 +16,7 @@ mod layout;
 mod links;
 mod lto;
 mod output_depinfo;
+mod output_sbom;
 pub mod rustdoc;
 pub mod standard_lib;
 mod timings;
@@ -33,6 +36,7 @@ use anyhow::{Context as _, Error};
 use lazycell::LazyCell;
 use log::debug;
 
+pub use self::build_cache::{BuildCache, LocalBuildCache};
 pub use self::build_config::{BuildConfig, CompileMode, MessageFormat};
 pub use self::build_context::{
     BuildContext, FileFlavor, FileType, RustDocFingerprint, RustcTargetData, TargetInfo,
@@ -43,12 +47,14 @@ pub use self::compile_kind::{CompileKind, CompileTarget};
 pub use self::context::{Context, Metadata};
 pub use self::crate_type::CrateType;
 pub use self::custom_build::{BuildOutput, BuildScriptOutputs, BuildScripts};
+pub use self::fingerprint::DirtyReason;
 pub use self::job::Freshness;
 use self::job::{Job, Work};
 use self::job_queue::{JobQueue, JobState};
 pub(crate) use self::layout::Layout;
 pub use self::lto::Lto;
 use self::output_depinfo::output_depinfo;
+use self::output_sbom::build_sbom;
 use self::unit_graph::UnitDep;
 use crate::core::compiler::future_incompat::FutureIncompatReport;
 pub use crate::core::compiler::unit::{Unit, UnitInterner};
@@ -58,7 +64,7 @@ use crate::core::{Feature, PackageId, Target};
 use crate::util::errors::{CargoResult, VerboseError};
 use crate::util::interning::InternedString;
 use crate::util::machine_message::{self, Message};
-use crate::util::{add_path_args, internal, iter_join_onto, profile};
+use crate::util::{add_path_args, internal, iter_join_onto, profile, Config};
 use cargo_util::{paths, ProcessBuilder, ProcessError};
 
 const RUSTDOC_CRATE_VERSION_FLAG: &str = "--crate-version";
@@ -114,6 +120,13 @@ pub trait Executor: Send + Sync + 'static {
     fn force_rebuild(&self, _unit: &Unit) -> bool {
         false
     }
+
+    /// Called once before the build starts to get the cache of compiled
+    /// artifacts to use, if any. This is the local cache of `-Z build-cache`
+    /// by default.
+    fn build_cache(&self, config: &Config) -> CargoResult<Option<Arc<dyn BuildCache>>> {
+        Ok(LocalBuildCache::open(config)?.map(|cache| Arc::new(cache) as Arc<dyn BuildCache>))
+    }
 }
 
 /// A `DefaultExecutor` calls rustc without doing anything else. It is Cargo's
@@ -239,7 +252,7 @@ fn rustc(cx: &mut Context<'_, '_>, unit: &Unit, exec: &Arc<dyn Executor>) -> Car
     let rustc_dep_info_loc = root.join(dep_info_name);
     let dep_info_loc = fingerprint::dep_info_loc(cx, unit);
 
-    rustc.args(cx.bcx.rustflags_args(unit));
+    rustc.args(&cx.bcx.rustflags_args(unit));
     if cx.bcx.config.cli_unstable().binary_dep_depinfo {
         rustc.arg("-Z").arg("binary-dep-depinfo");
     }
@@ -251,8 +264,8 @@ fn rustc(cx: &mut Context<'_, '_>, unit: &Unit, exec: &Arc<dyn Executor>) -> Car
     exec.init(cx, unit);
     let exec = exec.clone();
 
-    let root_output = cx.files().host_dest().to_path_buf();
-    let target_dir = cx.bcx.ws.target_dir().into_path_unlocked();
+    let root_output = cx.files().host_build_dest().to_path_buf();
+    let target_dir = cx.bcx.ws.build_dir().into_path_unlocked();
     let pkg_root = unit.pkg.root().to_path_buf();
     let cwd = rustc
         .get_cwd()
@@ -261,6 +274,24 @@ fn rustc(cx: &mut Context<'_, '_>, unit: &Unit, exec: &Arc<dyn Executor>) -> Car
     let fingerprint_dir = cx.files().fingerprint_dir(unit);
     let script_metadata = cx.find_build_script_metadata(unit);
     let is_local = unit.is_local();
+    let checksum_freshness = cx.bcx.config.cli_unstable().checksum_freshness;
+    let dep_outputs = if checksum_freshness {
+        dependency_outputs(cx, unit)?
+    } else {
+        Vec::new()
+    };
+    let build_cache = match &cx.build_cache {
+        Some(cache) if build_cache::is_cacheable(cx, unit) => {
+            let mut files: Vec<_> = outputs
+                .iter()
+                .filter(|output| output.flavor != FileFlavor::DebugInfo)
+                .map(|output| output.path.clone())
+                .collect();
+            files.push(rustc_dep_info_loc.clone());
+            Some((Arc::clone(cache), build_cache::unit_key(cx, unit), files))
+        }
+        _ => None,
+    };
 
     return Ok(Work::new(move |state| {
         // Only at runtime have we discovered what the extra -L and -l
@@ -311,11 +342,22 @@ fn rustc(cx: &mut Context<'_, '_>, unit: &Unit, exec: &Arc<dyn Executor>) -> Car
             }
         }
 
-        state.running(&rustc);
         let timestamp = paths::set_invocation_time(&fingerprint_dir)?;
+        let cached = match &build_cache {
+            Some((cache, key, files)) => cache.fetch(key, files).unwrap_or_else(|e| {
+                log::warn!("failed to fetch `{}` from the build cache: {:?}", key, e);
+                false
+            }),
+            None => false,
+        };
+        if cached {
+            debug!("restored `{}` from the build cache", name);
+        } else {
+            state.running(&rustc);
+        }
         if build_plan {
             state.build_plan(buildkey, rustc.clone(), outputs.clone());
-        } else {
+        } else if !cached {
             exec.exec(
                 &rustc,
                 package_id,
@@ -350,6 +392,12 @@ fn rustc(cx: &mut Context<'_, '_>, unit: &Unit, exec: &Arc<dyn Executor>) -> Car
             })?;
             // Exec should never return with success *and* generate an error.
             debug_assert_eq!(output_options.errors_seen, 0);
+
+            if let Some((cache, key, files)) = &build_cache {
+                if let Err(e) = cache.store(key, files) {
+                    log::warn!("failed to store `{}` in the build cache: {:?}", key, e);
+                }
+            }
         }
 
         if rustc_dep_info_loc.exists() {
@@ -362,6 +410,12 @@ fn rustc(cx: &mut Context<'_, '_>, unit: &Unit, exec: &Arc<dyn Executor>) -> Car
                 &rustc,
                 // Do not track source files in the fingerprint for registry dependencies.
                 is_local,
+                if checksum_freshness {
+                    Some(timestamp)
+                } else {
+                    None
+                },
+                &dep_outputs,
             )
             .with_context(|| {
                 internal(format!(
@@ -402,6 +456,9 @@ fn rustc(cx: &mut Context<'_, '_>, unit: &Unit, exec: &Arc<dyn Executor>) -> Car
                 for cfg in &output.cfgs {
                     rustc.arg("--cfg").arg(cfg);
                 }
+                for check_cfg in &output.check_cfgs {
+                    rustc.arg("--check-cfg").arg(check_cfg);
+                }
                 if pass_l_flag {
                     for name in output.library_links.iter() {
                         rustc.arg("-l").arg(name);
@@ -440,6 +497,32 @@ fn rustc(cx: &mut Context<'_, '_>, unit: &Unit, exec: &Arc<dyn Executor>) -> Car
     }
 }
 
+/// Returns the files from dependencies that are used to compile `unit`, the
+/// outputs of library dependencies and of build scripts, which are recorded
+/// in its dep-info with `-Zchecksum-freshness`.
+fn dependency_outputs(cx: &mut Context<'_, '_>, unit: &Unit) -> CargoResult<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let deps = Vec::from(cx.unit_deps(unit)); // Create vec due to mutable borrow.
+    for dep in deps {
+        if dep.unit.mode.is_run_custom_build() {
+            files.push(cx.files().build_script_run_dir(&dep.unit).join("output"));
+        } else if dep.unit.target.is_linkable() {
+            let only_rmeta = cx.only_requires_rmeta(unit, &dep.unit);
+            for output in cx.outputs(&dep.unit)?.iter() {
+                let used = match output.flavor {
+                    FileFlavor::Rmeta => true,
+                    FileFlavor::Normal | FileFlavor::Linkable => !only_rmeta,
+                    FileFlavor::Auxiliary | FileFlavor::DebugInfo => false,
+                };
+                if used {
+                    files.push(output.path.clone());
+                }
+            }
+        }
+    }
+    Ok(files)
+}
+
 /// Link the compiled target (often of form `foo-{metadata_hash}`) to the
 /// final target. This must happen during both "Fresh" and "Compile".
 fn link_targets(cx: &mut Context<'_, '_>, unit: &Unit, fresh: bool) -> CargoResult<Work> {
@@ -448,7 +531,7 @@ fn link_targets(cx: &mut Context<'_, '_>, unit: &Unit, fresh: bool) -> CargoResu
     let export_dir = cx.files().export_dir();
     let package_id = unit.pkg.package_id();
     let manifest_path = PathBuf::from(unit.pkg.manifest_path());
-    let profile = unit.profile;
+    let profile = unit.profile.clone();
     let unit_mode = unit.mode;
     let features = unit.features.iter().map(|s| s.to_string()).collect();
     let json_messages = bcx.build_config.emit_json();
@@ -456,9 +539,17 @@ fn link_targets(cx: &mut Context<'_, '_>, unit: &Unit, fresh: bool) -> CargoResu
     let mut target = Target::clone(&unit.target);
     if let TargetSourcePath::Metabuild = target.src_path() {
         // Give it something to serialize.
-        let path = unit.pkg.manifest().metabuild_path(cx.bcx.ws.target_dir());
+        let path = unit.pkg.manifest().metabuild_path(cx.bcx.ws.build_dir());
         target.set_src_path(TargetSourcePath::Path(path));
     }
+    let sbom = if bcx.config.cli_unstable().sbom
+        && unit.mode.generates_executable()
+        && !unit.target.is_custom_build()
+    {
+        Some(build_sbom(cx, unit)?)
+    } else {
+        None
+    };
 
     Ok(Work::new(move |state| {
         // If we're a "root crate", e.g., the target of this compilation, then we
@@ -466,6 +557,7 @@ fn link_targets(cx: &mut Context<'_, '_>, unit: &Unit, fresh: bool) -> CargoResu
         // above. This means that `cargo build` will produce binaries in
         // `target/debug` which one probably expects.
         let mut destinations = vec![];
+        let mut sboms = vec![];
         for output in outputs.iter() {
             let src = &output.path;
             // This may have been a `cargo rustc` command which changes the
@@ -482,6 +574,15 @@ fn link_targets(cx: &mut Context<'_, '_>, unit: &Unit, fresh: bool) -> CargoResu
             };
             destinations.push(dst.clone());
             paths::link_or_copy(src, dst)?;
+            if let Some(sbom) = &sbom {
+                if matches!(output.flavor, FileFlavor::Normal | FileFlavor::Linkable) {
+                    let mut sbom_path = dst.clone().into_os_string();
+                    sbom_path.push(".cargo-sbom.json");
+                    let sbom_path = PathBuf::from(sbom_path);
+                    paths::write(&sbom_path, sbom)?;
+                    sboms.push(sbom_path);
+                }
+            }
             if let Some(ref path) = output.export_path {
                 let export_dir = export_dir.as_ref().unwrap();
                 paths::create_dir_all(export_dir)?;
@@ -507,6 +608,7 @@ fn link_targets(cx: &mut Context<'_, '_>, unit: &Unit, fresh: bool) -> CargoResu
                 features,
                 filenames: destinations,
                 executable,
+                sboms,
                 fresh,
             }
             .to_json_string();
@@ -597,6 +699,10 @@ fn prepare_rustc(
         base.env("CARGO_PRIMARY_PACKAGE", "1");
     }
 
+    for (var, value) in artifact::get_env(cx, cx.unit_deps(unit))? {
+        base.env(&var, value);
+    }
+
     if unit.target.is_test() || unit.target.is_bench() {
         let tmp = cx.files().layout(unit.kind).prepare_tmp()?;
         base.env("CARGO_TARGET_TMPDIR", tmp.display().to_string());
@@ -620,6 +726,9 @@ fn rustdoc(cx: &mut Context<'_, '_>, unit: &Unit) -> CargoResult<Work> {
     // script_metadata is not needed here, it is only for tests.
     let mut rustdoc = cx.compilation.rustdoc_process(unit, None)?;
     rustdoc.inherit_jobserver(&cx.jobserver);
+    for (var, value) in artifact::get_env(cx, cx.unit_deps(unit))? {
+        rustdoc.env(&var, value);
+    }
     let crate_name = unit.target.crate_name();
     rustdoc.arg("--crate-name").arg(&crate_name);
     add_path_args(bcx.ws, unit, &mut rustdoc);
@@ -640,9 +749,11 @@ fn rustdoc(cx: &mut Context<'_, '_>, unit: &Unit) -> CargoResult<Work> {
     for feat in &unit.features {
         rustdoc.arg("--cfg").arg(&format!("feature=\"{}\"", feat));
     }
+    rustdoc.args(&check_cfg_args(cx, unit));
 
     add_error_format_and_color(cx, &mut rustdoc, unit, false);
     add_allow_features(cx, &mut rustdoc);
+    add_lint_rustflags(unit, &mut rustdoc);
 
     if let Some(args) = cx.bcx.extra_args_for(unit) {
         rustdoc.args(args);
@@ -711,6 +822,9 @@ fn rustdoc(cx: &mut Context<'_, '_>, unit: &Unit) -> CargoResult<Work> {
                 for cfg in output.cfgs.iter() {
                     rustdoc.arg("--cfg").arg(cfg);
                 }
+                for check_cfg in &output.check_cfgs {
+                    rustdoc.arg("--check-cfg").arg(check_cfg);
+                }
                 for &(ref name, ref value) in output.env.iter() {
                     rustdoc.env(name, value);
                 }
@@ -773,6 +887,41 @@ fn add_cap_lints(bcx: &BuildContext<'_, '_>, unit: &Unit, cmd: &mut ProcessBuild
     }
 }
 
+/// Sets the lint levels from the package's `[lints]` table.
+///
+/// These are skipped for upstream deps, as their lints are capped anyway.
+fn add_lint_rustflags(unit: &Unit, cmd: &mut ProcessBuilder) {
+    if unit.is_local() {
+        cmd.args(unit.pkg.manifest().lint_rustflags());
+    }
+}
+
+/// Generates the `--check-cfg` arguments with all the features of the unit's
+/// package, so the compiler warns about `cfg(feature = "...")` with a feature
+/// that doesn't exist.
+///
+/// Returns nothing unless `-Z check-cfg` is enabled, or for upstream
+/// dependencies, which commonly check for features they don't declare (for
+/// example ones set with `--cfg` in `RUSTFLAGS`) and whose warnings are shown
+/// with `-vv`.
+fn check_cfg_args(cx: &Context<'_, '_>, unit: &Unit) -> Vec<String> {
+    if !cx.bcx.config.cli_unstable().check_cfg || !unit.is_local() {
+        return Vec::new();
+    }
+    let values = unit
+        .pkg
+        .summary()
+        .features()
+        .keys()
+        .map(|feature| format!("\"{}\"", feature))
+        .collect::<Vec<_>>()
+        .join(", ");
+    vec![
+        "--check-cfg".to_string(),
+        format!("cfg(feature, values({}))", values),
+    ]
+}
+
 /// Forward -Zallow-features if it is set for cargo.
 fn add_allow_features(cx: &Context<'_, '_>, cmd: &mut ProcessBuilder) {
     if let Some(allow) = &cx.bcx.config.cli_unstable().allow_features {
@@ -863,6 +1012,7 @@ fn build_base_args(
         ref panic,
         incremental,
         strip,
+        trim_paths,
         ..
     } = unit.profile;
     let test = unit.mode.is_any_test();
@@ -875,6 +1025,7 @@ fn build_base_args(
     add_path_args(bcx.ws, unit, cmd);
     add_error_format_and_color(cx, cmd, unit, cx.rmeta_required(unit));
     add_allow_features(cx, cmd);
+    add_lint_rustflags(unit, cmd);
 
     let mut contains_dy_lib = false;
     if !test {
@@ -986,6 +1137,7 @@ fn build_base_args(
     for feat in &unit.features {
         cmd.arg("--cfg").arg(&format!("feature=\"{}\"", feat));
     }
+    cmd.args(&check_cfg_args(cx, unit));
 
     let meta = cx.files().metadata(unit);
     cmd.arg("-C").arg(&format!("metadata={}", meta));
@@ -1026,6 +1178,10 @@ fn build_base_args(
         cmd.arg("-Z").arg(format!("strip={}", strip));
     }
 
+    if trim_paths {
+        cmd.args(&trim_paths_args(cx, unit));
+    }
+
     if unit.is_std {
         // -Zforce-unstable-if-unmarked prevents the accidental use of
         // unstable crates within the sysroot (such as "extern crate libc" or
@@ -1079,6 +1235,49 @@ fn lto_args(cx: &Context<'_, '_>, unit: &Unit) -> Vec<OsString> {
     result
 }
 
+/// Generates the `--remap-path-prefix` arguments of the `trim-paths` profile
+/// option, which keep the paths of the build machine out of the compiled
+/// output.
+///
+/// The sources of the standard library are remapped to `/rustc/<commit-hash>`,
+/// like in the pre-built standard library. The workspace root is remapped to
+/// an empty path, making the paths of workspace members relative to it, and
+/// the root of any other package (such as the ones in the registry and git
+/// source directories) to `<name>-<version>`.
+fn trim_paths_args(cx: &Context<'_, '_>, unit: &Unit) -> Vec<OsString> {
+    let remap = |from: &Path, to: &str| {
+        let mut arg = OsString::from("--remap-path-prefix=");
+        arg.push(from);
+        arg.push("=");
+        arg.push(to);
+        arg
+    };
+    let mut result = Vec::new();
+    let commit_hash = cx
+        .bcx
+        .rustc()
+        .verbose_version
+        .lines()
+        .find_map(|line| line.strip_prefix("commit-hash: "));
+    if let Some(commit_hash) = commit_hash {
+        let sysroot = &cx.bcx.target_data.info(unit.kind).sysroot;
+        result.push(remap(
+            &sysroot.join("lib/rustlib/src/rust"),
+            &format!("/rustc/{}", commit_hash),
+        ));
+    }
+    // Later arguments take precedence, so the package root comes last in case
+    // it is inside the workspace root without being a member.
+    result.push(remap(cx.bcx.ws.root(), ""));
+    if !cx.bcx.ws.is_member(&unit.pkg) {
+        result.push(remap(
+            unit.pkg.root(),
+            &format!("{}-{}", unit.pkg.name(), unit.pkg.version()),
+        ));
+    }
+    result
+}
+
 fn build_deps_args(
     cmd: &mut ProcessBuilder,
     cx: &mut Context<'_, '_>,
@@ -1211,7 +1410,9 @@ pub fn extern_args(
         };
 
     for dep in deps {
-        if dep.unit.target.is_linkable() && !dep.unit.mode.is_doc() {
+        // Artifact dependencies are passed through environment variables
+        // instead, see `artifact::get_env`.
+        if dep.unit.target.is_linkable() && !dep.unit.mode.is_doc() && dep.artifact.is_none() {
             link_to(dep, dep.extern_crate_name, dep.noprelude)?;
         }
     }
@@ -0,0 +1,170 @@
+//! Module for generating SBOM files with `-Z sbom`.
+//!
+//! A Software Bill of Materials is written next to every "uplifted" artifact,
+//! as `<artifact>.cargo-sbom.json`. It lists the crates that went into the
+//! artifact, which are the units reachable from the unit of the artifact in
+//! the `UnitGraph`, rather than all the packages of `Cargo.lock`. Example:
+//!
+//! ```json
+//! {
+//!   "version": 1,
+//!   "root": 0,
+//!   "crates": [
+//!     {
+//!       "id": "foo 0.1.0 (path+file:///path/to/foo)",
+//!       "target": "foo",
+//!       "kind": ["bin"],
+//!       "features": [],
+//!       "dependencies": [{ "index": 1, "kind": "normal" }]
+//!     },
+//!     {
+//!       "id": "bar 1.0.0 (registry+https://github.com/rust-lang/crates.io-index)",
+//!       "target": "bar",
+//!       "kind": ["lib"],
+//!       "features": ["default", "std"],
+//!       "dependencies": []
+//!     }
+//!   ],
+//!   "rustc": {
+//!     "version": "1.60.0",
+//!     "commit_hash": "7737e0b5c4103216d6fd8cf941b7ab9bdbaace7c",
+//!     "host": "x86_64-unknown-linux-gnu",
+//!     "verbose_version": "rustc 1.60.0 (7737e0b5c 2022-04-04)\n..."
+//!   },
+//!   "target": "x86_64-unknown-linux-gnu"
+//! }
+//! ```
+//!
+//! Build scripts are listed as crates of their own, which are dependencies of
+//! kind `build` of the crate they build, as are the build-dependencies of the
+//! package, which are dependencies of its build script. Procedural macros are
+//! dependencies of kind `proc-macro`. Neither is linked into the artifact, so
+//! the crates that are are those reachable from the root through
+//! dependencies of kind `normal` only.
+
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use super::{Context, Unit};
+use crate::core::manifest::TargetKind;
+use crate::core::PackageId;
+use crate::util::interning::InternedString;
+use crate::util::CargoResult;
+
+/// Version of the SBOM format, bumped on incompatible changes.
+const VERSION: u32 = 1;
+
+#[derive(Serialize)]
+struct Sbom<'a> {
+    version: u32,
+    root: usize,
+    crates: Vec<SbomCrate<'a>>,
+    rustc: SbomRustc<'a>,
+    target: &'a str,
+}
+
+#[derive(Serialize)]
+struct SbomCrate<'a> {
+    id: PackageId,
+    target: &'a str,
+    kind: &'a TargetKind,
+    features: &'a [InternedString],
+    dependencies: Vec<SbomDependency>,
+}
+
+#[derive(Serialize)]
+struct SbomDependency {
+    index: usize,
+    kind: &'static str,
+}
+
+#[derive(Serialize)]
+struct SbomRustc<'a> {
+    version: String,
+    commit_hash: Option<&'a str>,
+    host: InternedString,
+    verbose_version: &'a str,
+}
+
+/// Returns the SBOM of the artifacts of `root`, serialized to JSON.
+pub fn build_sbom(cx: &Context<'_, '_>, root: &Unit) -> CargoResult<String> {
+    let mut indices = HashMap::new();
+    let mut crates = Vec::new();
+    add_unit(cx, root, &mut indices, &mut crates);
+
+    let rustc = cx.bcx.rustc();
+    let sbom = Sbom {
+        version: VERSION,
+        root: 0,
+        crates,
+        rustc: SbomRustc {
+            version: rustc.version.to_string(),
+            commit_hash: rustc
+                .verbose_version
+                .lines()
+                .find_map(|line| line.strip_prefix("commit-hash: "))
+                .filter(|hash| *hash != "unknown"),
+            host: rustc.host,
+            verbose_version: &rustc.verbose_version,
+        },
+        target: cx.bcx.target_data.short_name(&root.kind),
+    };
+    Ok(serde_json::to_string_pretty(&sbom)?)
+}
+
+/// Adds `unit` and everything it depends on to `crates`, returning the index
+/// of `unit`.
+fn add_unit<'a>(
+    cx: &'a Context<'_, '_>,
+    unit: &'a Unit,
+    indices: &mut HashMap<&'a Unit, usize>,
+    crates: &mut Vec<SbomCrate<'a>>,
+) -> usize {
+    if let Some(&index) = indices.get(unit) {
+        return index;
+    }
+    let index = crates.len();
+    indices.insert(unit, index);
+    crates.push(SbomCrate {
+        id: unit.pkg.package_id(),
+        target: unit.target.name(),
+        kind: unit.target.kind(),
+        features: &unit.features,
+        dependencies: Vec::new(),
+    });
+
+    // The dependencies of a build script are only linked into the script.
+    let linked_kind = if unit.target.is_custom_build() {
+        "build"
+    } else {
+        "normal"
+    };
+    let mut dependencies = Vec::new();
+    for dep in cx.unit_deps(unit) {
+        if dep.unit.mode.is_run_custom_build() {
+            // Running the build script isn't a crate, but the build script
+            // it runs is. Other units it depends on only pass it metadata of
+            // `links` packages.
+            for script in cx.unit_deps(&dep.unit) {
+                if !script.unit.mode.is_run_custom_build() {
+                    dependencies.push(SbomDependency {
+                        index: add_unit(cx, &script.unit, indices, crates),
+                        kind: "build",
+                    });
+                }
+            }
+        } else {
+            dependencies.push(SbomDependency {
+                index: add_unit(cx, &dep.unit, indices, crates),
+                kind: if dep.unit.target.proc_macro() {
+                    "proc-macro"
+                } else {
+                    linked_kind
+                },
+            });
+        }
+    }
+    crates[index].dependencies = dependencies;
+    index
+}
@@ -18,7 +18,7 @@
 use crate::core::compiler::unit_graph::{UnitDep, UnitGraph};
 use crate::core::compiler::UnitInterner;
 use crate::core::compiler::{CompileKind, CompileMode, RustcTargetData, Unit};
-use crate::core::dependency::DepKind;
+use crate::core::dependency::{Artifact, ArtifactKind, ArtifactTarget, DepKind};
 use crate::core::profiles::{Profile, Profiles, UnitFor};
 use crate::core::resolver::features::{FeaturesFor, ResolvedFeatures};
 use crate::core::resolver::Resolve;
@@ -155,6 +155,7 @@ fn attach_std_deps(
                 // TODO: Does this `public` make sense?
                 public: true,
                 noprelude: true,
+                artifact: None,
             }));
             found = true;
         }
@@ -243,7 +244,7 @@ fn compute_deps(
     }
 
     let id = unit.pkg.package_id();
-    let filtered_deps = state.deps(unit, unit_for, &|dep| {
+    let dep_filter = |dep: &Dependency| {
         // If this target is a build command, then we only want build
         // dependencies, otherwise we want everything *other than* build
         // dependencies.
@@ -265,38 +266,71 @@ fn compute_deps(
         // If we've gotten past all that, then this dependency is
         // actually used!
         true
-    });
+    };
+    let filtered_deps = state.deps(unit, unit_for, &dep_filter);
 
     let mut ret = Vec::new();
     let mut dev_deps = Vec::new();
     for (id, deps) in filtered_deps {
         let pkg = state.get(id);
-        let lib = match pkg.targets().iter().find(|t| t.is_lib()) {
-            Some(t) => t,
-            None => continue,
-        };
-        let mode = check_or_build_mode(unit.mode, lib);
-        let dep_unit_for = unit_for.with_dependency(unit, lib);
-
         let start = ret.len();
-        if state.config.cli_unstable().dual_proc_macros && lib.proc_macro() && !unit.kind.is_host()
-        {
-            let unit_dep = new_unit_dep(state, unit, pkg, lib, dep_unit_for, unit.kind, mode)?;
-            ret.push(unit_dep);
-            let unit_dep =
-                new_unit_dep(state, unit, pkg, lib, dep_unit_for, CompileKind::Host, mode)?;
-            ret.push(unit_dep);
-        } else {
-            let unit_dep = new_unit_dep(
-                state,
-                unit,
-                pkg,
-                lib,
-                dep_unit_for,
-                unit.kind.for_target(lib),
-                mode,
-            )?;
-            ret.push(unit_dep);
+        let deps: Vec<&Dependency> = deps
+            .iter()
+            .filter(|dep| {
+                dep_filter(dep) && state.target_data.dep_platform_activated(dep, unit.kind)
+            })
+            .collect();
+
+        if let Some(lib) = pkg.targets().iter().find(|t| t.is_lib()) {
+            if deps.iter().any(|dep| dep.is_lib()) {
+                let mode = check_or_build_mode(unit.mode, lib);
+                let dep_unit_for = unit_for.with_dependency(unit, lib);
+
+                if state.config.cli_unstable().dual_proc_macros
+                    && lib.proc_macro()
+                    && !unit.kind.is_host()
+                {
+                    let unit_dep =
+                        new_unit_dep(state, unit, pkg, lib, dep_unit_for, unit.kind, mode)?;
+                    ret.push(unit_dep);
+                    let unit_dep =
+                        new_unit_dep(state, unit, pkg, lib, dep_unit_for, CompileKind::Host, mode)?;
+                    ret.push(unit_dep);
+                } else {
+                    let unit_dep = new_unit_dep(
+                        state,
+                        unit,
+                        pkg,
+                        lib,
+                        dep_unit_for,
+                        unit.kind.for_target(lib),
+                        mode,
+                    )?;
+                    ret.push(unit_dep);
+                }
+            }
+        }
+
+        // The artifacts of build dependencies are only needed to run the
+        // build script, see `compute_deps_custom_build`.
+        if !unit.target.is_custom_build() {
+            for dep in &deps {
+                if let Some(artifact) = dep.artifact() {
+                    let kind = match artifact.target() {
+                        Some(ArtifactTarget::Force(target)) => CompileKind::Target(target),
+                        _ => unit.kind,
+                    };
+                    ret.extend(artifact_unit_deps(
+                        state,
+                        unit,
+                        pkg,
+                        dep,
+                        artifact,
+                        kind,
+                        &|target| unit_for.with_dependency(unit, target),
+                    )?);
+                }
+            }
         }
 
         // If the unit added was a dev-dependency unit, then record that in the
@@ -414,7 +448,47 @@ fn compute_deps_custom_build(
         CompileKind::Host,
         CompileMode::Build,
     )?;
-    Ok(vec![unit_dep])
+    let script_unit = unit_dep.unit.clone();
+    let mut ret = vec![unit_dep];
+
+    // 3. The artifacts of build dependencies, which are built for the host
+    //    unless they ask for another target.
+    let deps = state.deps(&script_unit, script_unit_for, &|dep| {
+        dep.is_build() && dep.artifact().is_some()
+    });
+    for (id, deps) in deps {
+        let pkg = state.get(id);
+        for dep in deps {
+            let artifact = match dep.artifact() {
+                Some(artifact) if dep.is_build() => artifact,
+                _ => continue,
+            };
+            if !state
+                .target_data
+                .dep_platform_activated(dep, CompileKind::Host)
+            {
+                continue;
+            }
+            let kind = match artifact.target() {
+                None => None,
+                Some(ArtifactTarget::BuildDependencyAssumeTarget) => Some(unit.kind),
+                Some(ArtifactTarget::Force(target)) => Some(CompileKind::Target(target)),
+            };
+            ret.extend(artifact_unit_deps(
+                state,
+                unit,
+                pkg,
+                dep,
+                artifact,
+                kind.unwrap_or(CompileKind::Host),
+                &|target| match kind {
+                    Some(_) => UnitFor::new_host_artifact_target(),
+                    None => script_unit_for.with_dependency(&script_unit, target),
+                },
+            )?);
+        }
+    }
+    Ok(ret)
 }
 
 /// Returns the dependencies necessary to document a package.
@@ -429,8 +503,34 @@ fn compute_deps_doc(
     // built. If we're documenting *all* libraries, then we also depend on
     // the documentation of the library being built.
     let mut ret = Vec::new();
-    for (id, _deps) in deps {
+    for (id, deps) in deps {
         let dep = state.get(id);
+        for toml_dep in deps {
+            if let Some(artifact) = toml_dep.artifact() {
+                if toml_dep.kind() != DepKind::Normal {
+                    continue;
+                }
+                let kind = match artifact.target() {
+                    Some(ArtifactTarget::Force(target)) => CompileKind::Target(target),
+                    _ => unit.kind,
+                };
+                ret.extend(artifact_unit_deps(
+                    state,
+                    unit,
+                    dep,
+                    toml_dep,
+                    artifact,
+                    kind,
+                    &|target| unit_for.with_dependency(unit, target),
+                )?);
+            }
+        }
+        if !deps
+            .iter()
+            .any(|dep| dep.kind() == DepKind::Normal && dep.is_lib())
+        {
+            continue;
+        }
         let lib = match dep.targets().iter().find(|t| t.is_lib() && t.documented()) {
             Some(lib) => lib,
             None => continue,
@@ -598,6 +698,55 @@ fn dep_build_script(
         .transpose()
 }
 
+/// Returns the units for the artifacts that the artifact dependency `dep` of
+/// `parent` requests from `pkg`, built for `kind`.
+fn artifact_unit_deps(
+    state: &State<'_, '_>,
+    parent: &Unit,
+    pkg: &Package,
+    dep: &Dependency,
+    artifact: &Artifact,
+    kind: CompileKind,
+    unit_for: &dyn Fn(&Target) -> UnitFor,
+) -> CargoResult<Vec<UnitDep>> {
+    let mut ret = Vec::new();
+    for artifact_kind in artifact.kinds() {
+        let targets: Vec<&Target> = pkg
+            .targets()
+            .iter()
+            .filter(|t| match artifact_kind {
+                ArtifactKind::AllBinaries => t.is_bin(),
+                ArtifactKind::SelectedBinary(name) => t.is_bin() && t.name() == name.as_str(),
+                ArtifactKind::Cdylib => t.is_cdylib(),
+                ArtifactKind::Staticlib => t.is_staticlib(),
+            })
+            .collect();
+        if targets.is_empty() {
+            anyhow::bail!(
+                "dependency `{}` in package `{}` requires a `{}` artifact to be present",
+                dep.name_in_toml(),
+                parent.pkg.name(),
+                artifact_kind
+            );
+        }
+        for target in targets {
+            let mut unit_dep = new_unit_dep(
+                state,
+                parent,
+                pkg,
+                target,
+                unit_for(target),
+                kind,
+                CompileMode::Build,
+            )?;
+            unit_dep.extern_crate_name = dep.name_in_toml();
+            unit_dep.artifact = Some(artifact_kind.clone());
+            ret.push(unit_dep);
+        }
+    }
+    Ok(ret)
+}
+
 /// Choose the correct mode for dependencies.
 fn check_or_build_mode(mode: CompileMode, target: &Target) -> CompileMode {
     match mode {
@@ -668,6 +817,7 @@ fn new_unit_dep_with_profile(
         extern_crate_name,
         public,
         noprelude: false,
+        artifact: None,
     })
 }
 
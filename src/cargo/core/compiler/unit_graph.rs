@@ -1,5 +1,6 @@
 use crate::core::compiler::Unit;
 use crate::core::compiler::{CompileKind, CompileMode};
+use crate::core::dependency::ArtifactKind;
 use crate::core::profiles::{Profile, UnitFor};
 use crate::core::{PackageId, Target};
 use crate::util::interning::InternedString;
@@ -25,6 +26,10 @@ pub struct UnitDep {
     pub public: bool,
     /// If `true`, the dependency should not be added to Rust's prelude.
     pub noprelude: bool,
+    /// The kind of artifact requested if this is a unit of an artifact
+    /// dependency, which isn't passed to rustc with `--extern`, but made
+    /// available through `CARGO_*_FILE_*` environment variables instead.
+    pub artifact: Option<ArtifactKind>,
 }
 
 const VERSION: u32 = 1;
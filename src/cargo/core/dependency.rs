@@ -6,6 +6,7 @@ use serde::Serialize;
 use std::path::PathBuf;
 use std::rc::Rc;
 
+use crate::core::compiler::CompileTarget;
 use crate::core::{PackageId, SourceId, Summary};
 use crate::util::errors::CargoResult;
 use crate::util::interning::InternedString;
@@ -44,6 +45,10 @@ struct Inner {
     // This dependency should be used only for this platform.
     // `None` means *all platforms*.
     platform: Option<Platform>,
+
+    // The binaries or libraries built by the dependency that are made
+    // available to the dependent, if this is an artifact dependency.
+    artifact: Option<Artifact>,
 }
 
 #[derive(Serialize)]
@@ -65,6 +70,10 @@ struct SerializedDependency<'a> {
     /// The file system path for a local path dependency.
     #[serde(skip_serializing_if = "Option::is_none")]
     path: Option<PathBuf>,
+
+    /// The artifacts requested by an artifact dependency.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    artifact: Option<&'a Artifact>,
 }
 
 impl ser::Serialize for Dependency {
@@ -85,6 +94,7 @@ impl ser::Serialize for Dependency {
             rename: self.explicit_name_in_toml().map(|s| s.as_str()),
             registry: registry_id.as_ref().map(|sid| sid.url().as_str()),
             path: self.source_id().local_path(),
+            artifact: self.artifact(),
         }
         .serialize(s)
     }
@@ -159,6 +169,7 @@ impl Dependency {
                 specified_req: false,
                 platform: None,
                 explicit_name_in_toml: None,
+                artifact: None,
             }),
         }
     }
@@ -317,6 +328,25 @@ impl Dependency {
         self
     }
 
+    /// Sets the artifacts requested by an artifact dependency.
+    pub fn set_artifact(&mut self, artifact: Artifact) -> &mut Dependency {
+        Rc::make_mut(&mut self.inner).artifact = Some(artifact);
+        self
+    }
+
+    /// The artifacts requested from the package, if this is an artifact
+    /// dependency.
+    pub fn artifact(&self) -> Option<&Artifact> {
+        self.inner.artifact.as_ref()
+    }
+
+    /// Returns `true` if the library of the package is used by the
+    /// dependent, which is always the case unless this is an artifact
+    /// dependency without `lib = true`.
+    pub fn is_lib(&self) -> bool {
+        self.inner.artifact.as_ref().map_or(true, |a| a.is_lib())
+    }
+
     /// Locks this dependency to depending on the specified package ID.
     pub fn lock_to(&mut self, id: PackageId) -> &mut Dependency {
         assert_eq!(self.inner.source_id, id.source_id());
@@ -404,3 +434,156 @@ impl Dependency {
         self
     }
 }
+
+/// The artifacts of an artifact dependency, like
+/// `bar = { path = "bar", artifact = "bin", target = "wasm32-wasi" }`.
+#[derive(PartialEq, Eq, Hash, Clone, Debug)]
+pub struct Artifact {
+    kinds: Vec<ArtifactKind>,
+    is_lib: bool,
+    target: Option<ArtifactTarget>,
+}
+
+#[derive(Serialize)]
+struct SerializedArtifact<'a> {
+    kinds: Vec<String>,
+    lib: bool,
+    target: Option<&'a str>,
+}
+
+impl ser::Serialize for Artifact {
+    fn serialize<S>(&self, s: S) -> Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        SerializedArtifact {
+            kinds: self.kinds.iter().map(|k| k.to_string()).collect(),
+            lib: self.is_lib,
+            target: self.target.as_ref().map(|t| t.as_str()),
+        }
+        .serialize(s)
+    }
+}
+
+impl Artifact {
+    /// Creates an `Artifact` from the `artifact`, `lib` and `target` keys of
+    /// a dependency in `Cargo.toml`.
+    pub fn parse(
+        artifacts: &[impl AsRef<str>],
+        is_lib: bool,
+        target: Option<&str>,
+    ) -> CargoResult<Artifact> {
+        if artifacts.is_empty() {
+            anyhow::bail!("the `artifact` key must contain at least one artifact kind");
+        }
+        let kinds = artifacts
+            .iter()
+            .map(|kind| ArtifactKind::parse(kind.as_ref()))
+            .collect::<CargoResult<Vec<_>>>()?;
+        let target = target.map(ArtifactTarget::parse).transpose()?;
+        Ok(Artifact {
+            kinds,
+            is_lib,
+            target,
+        })
+    }
+
+    pub fn kinds(&self) -> &[ArtifactKind] {
+        &self.kinds
+    }
+
+    /// Returns `true` if the library of the package is also used as a
+    /// regular dependency.
+    pub fn is_lib(&self) -> bool {
+        self.is_lib
+    }
+
+    /// The target to build the artifacts for, if it's not the one the
+    /// dependent is built for.
+    pub fn target(&self) -> Option<ArtifactTarget> {
+        self.target
+    }
+}
+
+/// Where an artifact dependency is built for if `target` is given.
+#[derive(PartialEq, Eq, Hash, Copy, Clone, Debug)]
+pub enum ArtifactTarget {
+    /// `target = "target"`, which builds the artifact of a build-dependency
+    /// for the target the dependent is built for instead of the host.
+    BuildDependencyAssumeTarget,
+    /// `target = "<triple>"`, which always builds the artifact for the given
+    /// target.
+    Force(CompileTarget),
+}
+
+impl ArtifactTarget {
+    pub fn parse(target: &str) -> CargoResult<ArtifactTarget> {
+        Ok(match target {
+            "target" => ArtifactTarget::BuildDependencyAssumeTarget,
+            name => ArtifactTarget::Force(CompileTarget::new(name)?),
+        })
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            ArtifactTarget::BuildDependencyAssumeTarget => "target",
+            ArtifactTarget::Force(target) => target.rustc_target(),
+        }
+    }
+
+    /// The target this forces the artifact to be built for, if any.
+    pub fn to_compile_target(&self) -> Option<CompileTarget> {
+        match self {
+            ArtifactTarget::BuildDependencyAssumeTarget => None,
+            ArtifactTarget::Force(target) => Some(*target),
+        }
+    }
+}
+
+/// A kind of artifact an artifact dependency can request.
+#[derive(PartialEq, Eq, Hash, Clone, Debug, PartialOrd, Ord)]
+pub enum ArtifactKind {
+    /// `bin`, all binaries of the package.
+    AllBinaries,
+    /// `bin:<name>`, only the binary called `<name>`.
+    SelectedBinary(InternedString),
+    Cdylib,
+    Staticlib,
+}
+
+impl ArtifactKind {
+    pub fn parse(kind: &str) -> CargoResult<ArtifactKind> {
+        Ok(match kind {
+            "bin" => ArtifactKind::AllBinaries,
+            "cdylib" => ArtifactKind::Cdylib,
+            "staticlib" => ArtifactKind::Staticlib,
+            _ => match kind.strip_prefix("bin:") {
+                Some(name) if !name.is_empty() => ArtifactKind::SelectedBinary(name.into()),
+                _ => anyhow::bail!(
+                    "`artifact` must be one of `bin`, `bin:<name>`, `cdylib` or `staticlib`, \
+                     found `{}`",
+                    kind
+                ),
+            },
+        })
+    }
+
+    /// The name of the kind used in the `CARGO_<KIND>_FILE_*` environment
+    /// variables.
+    pub fn crate_type(&self) -> &'static str {
+        match self {
+            ArtifactKind::AllBinaries | ArtifactKind::SelectedBinary(_) => "bin",
+            ArtifactKind::Cdylib => "cdylib",
+            ArtifactKind::Staticlib => "staticlib",
+        }
+    }
+}
+
+impl std::fmt::Display for ArtifactKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ArtifactKind::SelectedBinary(name) => write!(f, "bin:{}", name),
+            kind => f.write_str(kind.crate_type()),
+        }
+    }
+}
@@ -409,6 +409,18 @@ features! {
 
     // Allow specifying different binary name apart from the crate name
     (unstable, different_binary_name, "", "reference/unstable.html#different-binary-name"),
+
+    // Allow inheriting package metadata and dependencies from the workspace root
+    (unstable, workspace_inheritance, "", "reference/unstable.html#workspace-inheritance"),
+
+    // Allow setting the lint levels of rustc, clippy and rustdoc with `[lints]`
+    (unstable, lints, "", "reference/unstable.html#lints"),
+
+    // Allow passing rustc flags in profiles
+    (unstable, profile_rustflags, "", "reference/unstable.html#profile-rustflags-option"),
+
+    // Allow remapping the paths of the build machine in the compiled output
+    (unstable, trim_paths, "", "reference/unstable.html#profile-trim-paths-option"),
 }
 
 pub struct Feature {
@@ -629,26 +641,39 @@ unstable_cli_options!(
     advanced_env: bool = (HIDDEN),
     avoid_dev_deps: bool = ("Avoid installing dev-dependencies if possible"),
     binary_dep_depinfo: bool = ("Track changes to dependency artifacts"),
+    bindeps: bool = ("Allow Cargo packages to depend on bin, cdylib, and staticlib crates, and use the artifacts built by those crates"),
     #[serde(deserialize_with = "deserialize_build_std")]
     build_std: Option<Vec<String>>  = ("Enable Cargo to compile the standard library itself as part of a crate graph compilation"),
     build_std_features: Option<Vec<String>>  = ("Configure features enabled for the standard library itself when building the standard library"),
+    build_cache: bool = ("Share compiled artifacts of dependencies across workspaces through a user-level cache"),
+    build_dir: bool = ("Allow moving intermediate build artifacts out of the target directory with `build.build-dir`"),
+    build_script_sandbox: Option<String> = ("Run build scripts with a scrubbed environment, and check that they only write to `OUT_DIR`"),
+    check_cfg: bool = ("Check the names of features and the cfgs declared by build scripts with `--check-cfg`"),
+    checksum_freshness: bool = ("Use the contents of files, rather than only their mtime, to decide whether to rebuild"),
     config_include: bool = ("Enable the `include` key in config files"),
     credential_process: bool = ("Add a config setting to fetch registry authentication tokens by calling an external process"),
+    direct_minimal_versions: bool = ("Resolve minimal dependency versions instead of maximum (direct dependencies only)"),
     doctest_in_workspace: bool = ("Compile doctests with paths relative to the workspace root"),
     doctest_xcompile: bool = ("Compile and run doctests for non-host target using runner config"),
     dual_proc_macros: bool = ("Build proc-macros for both the host and the target"),
     features: Option<Vec<String>>  = (HIDDEN),
+    gc: bool = ("Automatically clean up unused entries of the global cache in CARGO_HOME"),
     jobserver_per_rustc: bool = (HIDDEN),
     minimal_versions: bool = ("Resolve minimal dependency versions instead of maximum"),
+    msrv_policy: bool = ("Prefer dependency versions compatible with the workspace's `rust-version` when resolving"),
     mtime_on_use: bool = ("Configure Cargo to update the mtime of used files"),
     multitarget: bool = ("Allow passing multiple `--target` flags to the cargo subcommand selected"),
     namespaced_features: bool = ("Allow features with `dep:` prefix"),
+    next_lockfile_bump: bool = ("Use the next, unstable `Cargo.lock` format when writing lock files"),
     no_index_update: bool = ("Do not update the registry index even if the cache is outdated"),
+    package_workspace: bool = ("Package and publish several packages of a workspace at once, in dependency order"),
     panic_abort_tests: bool = ("Enable support to run tests with -Cpanic=abort"),
     host_config: bool = ("Enable the [host] section in the .cargo/config.toml file"),
     target_applies_to_host: bool = ("Enable the `target-applies-to-host` key in the .cargo/config.toml file"),
     rustdoc_map: bool = ("Allow passing external documentation mappings to rustdoc"),
+    sbom: bool = ("Write an SBOM of the crates linked into each artifact next to it"),
     separate_nightlies: bool = (HIDDEN),
+    sparse_registry: bool = ("Support plain-HTTP-based crate registries"),
     terminal_width: Option<Option<usize>>  = ("Provide a terminal width to rustc for error truncation"),
     timings: Option<Vec<String>>  = ("Display concurrency information"),
     unstable_options: bool = ("Allow the usage of unstable options"),
@@ -831,19 +856,38 @@ impl CliUnstable {
             "allow-features" => self.allow_features = Some(parse_features(v).into_iter().collect()),
             "unstable-options" => self.unstable_options = parse_empty(k, v)?,
             "no-index-update" => self.no_index_update = parse_empty(k, v)?,
+            "package-workspace" => self.package_workspace = parse_empty(k, v)?,
             "avoid-dev-deps" => self.avoid_dev_deps = parse_empty(k, v)?,
             "minimal-versions" => self.minimal_versions = parse_empty(k, v)?,
+            "direct-minimal-versions" => self.direct_minimal_versions = parse_empty(k, v)?,
+            "msrv-policy" => self.msrv_policy = parse_empty(k, v)?,
+            "next-lockfile-bump" => self.next_lockfile_bump = parse_empty(k, v)?,
             "advanced-env" => self.advanced_env = parse_empty(k, v)?,
+            "check-cfg" => self.check_cfg = parse_empty(k, v)?,
+            "checksum-freshness" => self.checksum_freshness = parse_empty(k, v)?,
             "config-include" => self.config_include = parse_empty(k, v)?,
             "dual-proc-macros" => self.dual_proc_macros = parse_empty(k, v)?,
             // can also be set in .cargo/config or with and ENV
             "mtime-on-use" => self.mtime_on_use = parse_empty(k, v)?,
             "named-profiles" => stabilized_warn(k, "1.57", STABILIZED_NAMED_PROFILES),
             "binary-dep-depinfo" => self.binary_dep_depinfo = parse_empty(k, v)?,
+            "bindeps" => self.bindeps = parse_empty(k, v)?,
             "build-std" => {
                 self.build_std = Some(crate::core::compiler::standard_lib::parse_unstable_flag(v))
             }
             "build-std-features" => self.build_std_features = Some(parse_features(v)),
+            "build-cache" => self.build_cache = parse_empty(k, v)?,
+            "build-dir" => self.build_dir = parse_empty(k, v)?,
+            "build-script-sandbox" => {
+                self.build_script_sandbox = match v {
+                    None | Some("warn") => Some("warn".to_string()),
+                    Some("deny") => Some("deny".to_string()),
+                    Some(s) => bail!(
+                        "-Z build-script-sandbox takes either `warn` or `deny` as an argument, found `{}`",
+                        s
+                    ),
+                }
+            }
             "timings" => self.timings = Some(parse_timings(v)),
             "doctest-xcompile" => self.doctest_xcompile = parse_empty(k, v)?,
             "doctest-in-workspace" => self.doctest_in_workspace = parse_empty(k, v)?,
@@ -876,6 +920,9 @@ impl CliUnstable {
             "namespaced-features" => self.namespaced_features = parse_empty(k, v)?,
             "weak-dep-features" => self.weak_dep_features = parse_empty(k, v)?,
             "credential-process" => self.credential_process = parse_empty(k, v)?,
+            "sparse-registry" => self.sparse_registry = parse_empty(k, v)?,
+            "sbom" => self.sbom = parse_empty(k, v)?,
+            "gc" => self.gc = parse_empty(k, v)?,
             "rustdoc-scrape-examples" => {
                 if let Some(s) = v {
                     self.rustdoc_scrape_examples = Some(s.to_string())
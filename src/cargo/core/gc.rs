@@ -0,0 +1,297 @@
+//! Garbage collection of the global cache in `CARGO_HOME`.
+//!
+//! Cargo never removes anything it downloads into `CARGO_HOME` on its own, so
+//! the `.crate` files in `registry/cache`, the sources extracted from them in
+//! `registry/src`, and the git databases and checkouts in `git/db` and
+//! `git/checkouts` pile up over time. This module removes the entries that
+//! haven't been used for a while, or the least recently used ones to bring
+//! the cache under a size limit.
+//!
+//! The last time an entry was used is tracked as the modification time of
+//! the entry itself (the `.crate` file, or the top-level directory of the
+//! others), which sources update with [`record_use`] whenever they use it.
+//! Entries are only recorded as used and removed while holding the package
+//! cache lock (see [`Config::acquire_package_cache_lock`]), so an entry isn't
+//! removed while another cargo process is downloading or extracting it.
+//! Builds release that lock before compiling, though, so entries used within
+//! the last day are never removed, not even to bring the cache under a size
+//! limit, since a concurrent build may still be reading them.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use anyhow::{bail, Context as _};
+use bytesize::ByteSize;
+use cargo_util::paths;
+use filetime::FileTime;
+use log::debug;
+use serde::Deserialize;
+
+use crate::util::errors::CargoResult;
+use crate::util::Config;
+
+/// The file whose modification time records when the cache was last
+/// automatically cleaned.
+const LAST_AUTO_GC_FILE: &str = ".last-gc";
+
+/// The default for `gc.auto.frequency`.
+const DEFAULT_AUTO_FREQUENCY: &str = "1 day";
+
+/// The default for `gc.auto.max-age`, which `cargo gc` also uses if neither
+/// `--max-age` nor `--max-size` are given.
+const DEFAULT_MAX_AGE: &str = "3 months";
+
+/// How long an entry is kept after it was last used, regardless of the size
+/// limit.
+const MIN_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Records that the cache entry at `path` was just used.
+///
+/// This only updates the modification time of `path`, and never fails since
+/// that's merely an optimization for `cargo gc`.
+pub fn record_use(path: &Path) {
+    paths::set_file_time_no_err(path, FileTime::now());
+}
+
+/// What `cargo gc` should remove.
+pub struct GcOptions {
+    /// Remove entries which haven't been used for this long.
+    pub max_age: Option<Duration>,
+    /// Remove the least recently used entries until the cache is at most this
+    /// many bytes, except those used within the last day.
+    pub max_size: Option<u64>,
+    /// Only report what would be removed.
+    pub dry_run: bool,
+}
+
+/// The `[gc.auto]` config table.
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct AutoGcConfig {
+    /// How often to clean the cache, or `"never"`.
+    frequency: Option<String>,
+    max_age: Option<String>,
+    max_size: Option<String>,
+}
+
+impl GcOptions {
+    /// The options configured in `[gc.auto]`.
+    pub fn from_config(config: &Config) -> CargoResult<GcOptions> {
+        let auto = auto_gc_config(config)?;
+        let max_age = auto.max_age.as_deref().unwrap_or(DEFAULT_MAX_AGE);
+        let max_age = parse_duration(max_age).context("invalid `gc.auto.max-age` config")?;
+        let max_size = auto
+            .max_size
+            .as_deref()
+            .map(parse_size)
+            .transpose()
+            .context("invalid `gc.auto.max-size` config")?;
+        Ok(GcOptions {
+            max_age: Some(max_age),
+            max_size,
+            dry_run: false,
+        })
+    }
+}
+
+/// A `.crate` file, extracted source, git database or checkout in the cache.
+struct CacheEntry {
+    path: PathBuf,
+    last_use: SystemTime,
+    size: u64,
+}
+
+/// Removes the cache entries selected by `opts`.
+pub fn gc(config: &Config, opts: &GcOptions) -> CargoResult<()> {
+    let _lock = config.acquire_package_cache_lock()?;
+    let mut entries = cache_entries(config)?;
+    // Least recently used first.
+    entries.sort_by_key(|e| e.last_use);
+
+    let now = SystemTime::now();
+    let mut cache_size: u64 = entries.iter().map(|e| e.size).sum();
+    let mut removed = 0;
+    let mut removed_size = 0;
+    for entry in &entries {
+        let unused = now.duration_since(entry.last_use).unwrap_or_default();
+        if unused <= MIN_AGE {
+            // Everything after this was used even more recently.
+            break;
+        }
+        let too_old = opts.max_age.map_or(false, |max_age| unused > max_age);
+        let too_big = opts
+            .max_size
+            .map_or(false, |max_size| cache_size > max_size);
+        if !too_old && !too_big {
+            continue;
+        }
+        config
+            .shell()
+            .verbose(|s| s.status("Removing", entry.path.display()))?;
+        if !opts.dry_run {
+            if entry.path.is_dir() {
+                paths::remove_dir_all(&entry.path)?;
+            } else {
+                paths::remove_file(&entry.path)?;
+            }
+        }
+        cache_size -= entry.size;
+        removed += 1;
+        removed_size += entry.size;
+    }
+
+    let summary = format!(
+        "{} cache {}, {}",
+        removed,
+        if removed == 1 { "entry" } else { "entries" },
+        ByteSize(removed_size).to_string_as(true)
+    );
+    if opts.dry_run {
+        config
+            .shell()
+            .status("Summary", format!("{} would be removed", summary))?;
+        config.shell().warn("no files deleted due to --dry-run")?;
+    } else {
+        config.shell().status("Removed", summary)?;
+    }
+    Ok(())
+}
+
+/// Cleans the cache with the `[gc.auto]` config if `-Z gc` is enabled and it
+/// hasn't been cleaned for `gc.auto.frequency`.
+///
+/// This is run after the builtin commands that download or extract packages,
+/// and only warns on errors.
+pub fn auto_gc(config: &Config) {
+    if !config.cli_unstable().gc {
+        return;
+    }
+    if let Err(e) = try_auto_gc(config) {
+        crate::display_warning_with_error(
+            "failed to automatically clean the global cache",
+            &e,
+            &mut config.shell(),
+        );
+    }
+}
+
+fn try_auto_gc(config: &Config) -> CargoResult<()> {
+    let auto = auto_gc_config(config)?;
+    let frequency = auto.frequency.as_deref().unwrap_or(DEFAULT_AUTO_FREQUENCY);
+    if frequency == "never" {
+        return Ok(());
+    }
+    let frequency = parse_duration(frequency).context("invalid `gc.auto.frequency` config")?;
+
+    let marker = config.home().join(LAST_AUTO_GC_FILE);
+    let marker = marker.as_path_unlocked();
+    if let Ok(last_gc) = fs::metadata(marker).and_then(|m| m.modified()) {
+        let since_last_gc = SystemTime::now()
+            .duration_since(last_gc)
+            .unwrap_or_default();
+        if since_last_gc < frequency {
+            debug!("skipping auto gc, last run {:?} ago", since_last_gc);
+            return Ok(());
+        }
+    }
+
+    gc(config, &GcOptions::from_config(config)?)?;
+    paths::write(marker, "")
+}
+
+fn auto_gc_config(config: &Config) -> CargoResult<AutoGcConfig> {
+    Ok(config
+        .get::<Option<AutoGcConfig>>("gc.auto")?
+        .unwrap_or_default())
+}
+
+/// Parses a duration like `30 days` or `6 months`.
+pub fn parse_duration(s: &str) -> CargoResult<Duration> {
+    humantime::parse_duration(s).with_context(|| {
+        format!(
+            "expected a duration like \"30 days\" or \"6 months\", found `{}`",
+            s
+        )
+    })
+}
+
+/// Parses a size in bytes like `500 MB` or `10GiB`.
+pub fn parse_size(s: &str) -> CargoResult<u64> {
+    match s.parse::<ByteSize>() {
+        Ok(size) => Ok(size.0),
+        Err(_) => bail!(
+            "expected a size like \"500 MB\" or \"10 GiB\", found `{}`",
+            s
+        ),
+    }
+}
+
+/// Lists everything in the cache that `cargo gc` may remove.
+fn cache_entries(config: &Config) -> CargoResult<Vec<CacheEntry>> {
+    let registry_cache = config.registry_cache_path();
+    let registry_src = config.registry_source_path();
+    let git = config.git_path();
+    let git = config.assert_package_cache_locked(&git);
+
+    let mut entries = Vec::new();
+    // `registry/cache/<registry>/<name>-<version>.crate`
+    for path in children(config.assert_package_cache_locked(&registry_cache), 2)? {
+        entries.push(cache_entry(path)?);
+    }
+    // `registry/src/<registry>/<name>-<version>`
+    for path in children(config.assert_package_cache_locked(&registry_src), 2)? {
+        entries.push(cache_entry(path)?);
+    }
+    // `git/checkouts/<name>-<hash>/<short rev>`
+    for path in children(&git.join("checkouts"), 2)? {
+        entries.push(cache_entry(path)?);
+    }
+    // `git/db/<name>-<hash>`
+    for path in children(&git.join("db"), 1)? {
+        entries.push(cache_entry(path)?);
+    }
+    Ok(entries)
+}
+
+/// Returns the paths `depth` levels below `dir`, if it exists.
+fn children(dir: &Path, depth: usize) -> CargoResult<Vec<PathBuf>> {
+    if depth == 0 {
+        return Ok(vec![dir.to_path_buf()]);
+    }
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+    let mut result = Vec::new();
+    for entry in fs::read_dir(dir).with_context(|| format!("failed to read `{}`", dir.display()))? {
+        let entry = entry.with_context(|| format!("failed to read `{}`", dir.display()))?;
+        let path = entry.path();
+        if depth > 1 && !path.is_dir() {
+            continue;
+        }
+        result.extend(children(&path, depth - 1)?);
+    }
+    Ok(result)
+}
+
+fn cache_entry(path: PathBuf) -> CargoResult<CacheEntry> {
+    let meta = fs::symlink_metadata(&path)
+        .with_context(|| format!("failed to stat `{}`", path.display()))?;
+    let last_use = meta.modified()?;
+    let size = if meta.is_dir() {
+        walkdir::WalkDir::new(&path)
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .filter_map(|e| e.metadata().ok())
+            .filter(|m| m.is_file())
+            .map(|m| m.len())
+            .sum()
+    } else {
+        meta.len()
+    };
+    Ok(CacheEntry {
+        path,
+        last_use,
+        size,
+    })
+}
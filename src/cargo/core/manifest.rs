@@ -53,6 +53,7 @@ pub struct Manifest {
     default_run: Option<String>,
     metabuild: Option<Vec<String>>,
     resolve_behavior: Option<ResolveBehavior>,
+    lint_rustflags: Vec<String>,
 }
 
 /// When parsing `Cargo.toml`, some warnings should silenced
@@ -392,6 +393,7 @@ impl Manifest {
         original: Rc<TomlManifest>,
         metabuild: Option<Vec<String>>,
         resolve_behavior: Option<ResolveBehavior>,
+        lint_rustflags: Vec<String>,
     ) -> Manifest {
         Manifest {
             summary,
@@ -417,6 +419,7 @@ impl Manifest {
             default_run,
             metabuild,
             resolve_behavior,
+            lint_rustflags,
         }
     }
 
@@ -555,6 +558,11 @@ impl Manifest {
         self.default_run.as_deref()
     }
 
+    /// The `-D`/`-W`/... flags for the lint levels of the `[lints]` table.
+    pub fn lint_rustflags(&self) -> &[String] {
+        &self.lint_rustflags
+    }
+
     pub fn metabuild(&self) -> Option<&Vec<String>> {
         self.metabuild.as_ref()
     }
@@ -828,6 +836,13 @@ impl Target {
         }
     }
 
+    pub fn is_staticlib(&self) -> bool {
+        match self.kind() {
+            TargetKind::Lib(libs) => libs.iter().any(|l| *l == CrateType::Staticlib),
+            _ => false,
+        }
+    }
+
     /// Returns whether this target produces an artifact which can be linked
     /// into a Rust crate.
     ///
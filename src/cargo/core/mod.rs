@@ -15,6 +15,7 @@ pub use self::workspace::{MaybePackage, Workspace, WorkspaceConfig, WorkspaceRoo
 pub mod compiler;
 pub mod dependency;
 pub mod features;
+pub mod gc;
 pub mod manifest;
 pub mod package;
 pub mod package_id;
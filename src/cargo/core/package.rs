@@ -581,6 +581,14 @@ impl<'cfg> PackageSet<'cfg> {
                     target_data,
                     force_all_targets,
                 )
+                .filter(|&package_id| {
+                    // Artifact dependencies don't need a lib unless they
+                    // ask for it with `lib = true`.
+                    resolve
+                        .deps(root_id)
+                        .filter(|&(id, _)| id == package_id)
+                        .any(|(_, deps)| deps.iter().any(|dep| dep.is_lib()))
+                })
                 .filter_map(|package_id| {
                     if let Ok(dep_pkg) = self.get_one(package_id) {
                         if !dep_pkg.targets().iter().any(|t| t.is_lib()) {
@@ -667,6 +675,8 @@ macro_rules! try_old_curl {
     };
 }
 
+pub(crate) use try_old_curl;
+
 impl<'a, 'cfg> Downloads<'a, 'cfg> {
     /// Starts to download the package for the `id` specified.
     ///
@@ -500,7 +500,7 @@ impl ProfileMaker {
         is_member: bool,
         unit_for: UnitFor,
     ) -> Profile {
-        let mut profile = self.default;
+        let mut profile = self.default.clone();
 
         // First apply profile-specific settings, things like
         // `[profile.release]`
@@ -626,6 +626,12 @@ fn merge_profile(profile: &mut Profile, toml: &TomlProfile) {
     if let Some(incremental) = toml.incremental {
         profile.incremental = incremental;
     }
+    if let Some(rustflags) = &toml.rustflags {
+        profile.rustflags = rustflags.clone().into_boxed_slice();
+    }
+    if let Some(trim_paths) = toml.trim_paths {
+        profile.trim_paths = trim_paths;
+    }
     profile.strip = match toml.strip {
         Some(StringOrBool::Bool(true)) => Strip::Named(InternedString::new("symbols")),
         None | Some(StringOrBool::Bool(false)) => Strip::None,
@@ -647,7 +653,7 @@ pub enum ProfileRoot {
 
 /// Profile settings used to determine which compiler flags to use for a
 /// target.
-#[derive(Clone, Copy, Eq, PartialOrd, Ord, serde::Serialize)]
+#[derive(Clone, Eq, PartialOrd, Ord, serde::Serialize)]
 pub struct Profile {
     pub name: InternedString,
     pub opt_level: InternedString,
@@ -666,6 +672,10 @@ pub struct Profile {
     pub incremental: bool,
     pub panic: PanicStrategy,
     pub strip: Strip,
+    #[serde(skip_serializing_if = "<[_]>::is_empty")] // remove when `rustflags` is stabilized
+    pub rustflags: Box<[InternedString]>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")] // remove when `trim-paths` is stabilized
+    pub trim_paths: bool,
 }
 
 impl Default for Profile {
@@ -685,6 +695,8 @@ impl Default for Profile {
             incremental: false,
             panic: PanicStrategy::Unwind,
             strip: Strip::None,
+            rustflags: Box::new([]),
+            trim_paths: false,
         }
     }
 }
@@ -712,6 +724,8 @@ compact_debug! {
                 incremental
                 panic
                 strip
+                rustflags
+                trim_paths
             )]
         }
     }
@@ -786,7 +800,7 @@ impl Profile {
     /// Compares all fields except `name`, which doesn't affect compilation.
     /// This is necessary for `Unit` deduplication for things like "test" and
     /// "dev" which are essentially the same.
-    fn comparable(&self) -> impl Hash + Eq {
+    fn comparable(&self) -> impl Hash + Eq + '_ {
         (
             self.opt_level,
             self.lto,
@@ -799,7 +813,7 @@ impl Profile {
             self.rpath,
             self.incremental,
             self.panic,
-            self.strip,
+            (self.strip, &self.rustflags, self.trim_paths),
         )
     }
 }
@@ -877,7 +891,9 @@ pub struct UnitFor {
     /// any of its dependencies. This enables `build-override` profiles for
     /// these targets.
     ///
-    /// An invariant is that if `host_features` is true, `host` must be true.
+    /// An invariant is that if `host_features` is true, `host` must be true,
+    /// except for the artifacts of build dependencies which are built for
+    /// the target (see `new_host_artifact_target`).
     ///
     /// Note that this is `true` for `RunCustomBuild` units, even though that
     /// unit should *not* use build-override profiles. This is a bit of a
@@ -1015,6 +1031,20 @@ impl UnitFor {
         unit_for
     }
 
+    /// A unit for an artifact of a build dependency, or any of its
+    /// dependencies, that is built for the target instead of the host with
+    /// `target = "target"` or `target = "<triple>"`.
+    ///
+    /// It isn't built with the `build-override` profile since it doesn't
+    /// run on the host, but uses the features of build dependencies.
+    pub fn new_host_artifact_target() -> UnitFor {
+        UnitFor {
+            host: false,
+            host_features: true,
+            panic_setting: PanicSetting::ReadProfile,
+        }
+    }
+
     /// Returns a new copy updated based on the target dependency.
     ///
     /// This is where the magic happens that the host/host_features settings
@@ -1087,8 +1117,14 @@ impl UnitFor {
                 host_features: false,
                 panic_setting: PanicSetting::Inherit,
             },
-            // host_features=true must always have host=true
+            // host_features=true must always have host=true, except for
+            // artifacts of build dependencies built for the target.
             // `Inherit` is not used in build dependencies.
+            UnitFor {
+                host: false,
+                host_features: true,
+                panic_setting: PanicSetting::ReadProfile,
+            },
             UnitFor {
                 host: true,
                 host_features: true,
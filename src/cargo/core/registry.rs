@@ -28,6 +28,11 @@ pub trait Registry {
         Ok(ret)
     }
 
+    /// Hints that `dep` is about to be queried, see [`Source::prefetch`].
+    fn prefetch(&mut self, _dep: &Dependency) -> CargoResult<()> {
+        Ok(())
+    }
+
     fn describe_source(&self, source: SourceId) -> String;
     fn is_replaced(&self, source: SourceId) -> bool;
 }
@@ -124,6 +129,13 @@ pub struct LockedPatchDependency {
 impl<'cfg> PackageRegistry<'cfg> {
     pub fn new(config: &'cfg Config) -> CargoResult<PackageRegistry<'cfg>> {
         let source_config = SourceConfigMap::new(config)?;
+        PackageRegistry::new_with_source_config(config, source_config)
+    }
+
+    pub fn new_with_source_config(
+        config: &'cfg Config,
+        source_config: SourceConfigMap<'cfg>,
+    ) -> CargoResult<PackageRegistry<'cfg>> {
         Ok(PackageRegistry {
             config,
             sources: SourceMap::new(),
@@ -666,6 +678,35 @@ impl<'cfg> Registry for PackageRegistry<'cfg> {
         Ok(())
     }
 
+    fn prefetch(&mut self, dep: &Dependency) -> CargoResult<()> {
+        assert!(self.patches_locked);
+        // Locked dependencies with a matching `[patch]` never query the
+        // source, see `query` above.
+        if dep.is_locked() {
+            if let Some(patches) = self.patches.get(dep.source_id().canonical_url()) {
+                let matching = patches
+                    .iter()
+                    .filter(|s| dep.matches_ignoring_source(s.package_id()))
+                    .count();
+                if matching == 1 {
+                    return Ok(());
+                }
+            }
+        }
+
+        self.ensure_loaded(dep.source_id(), Kind::Normal)
+            .with_context(|| {
+                format!(
+                    "failed to load source for dependency `{}`",
+                    dep.package_name()
+                )
+            })?;
+        match self.sources.get_mut(dep.source_id()) {
+            Some(source) => source.prefetch(dep),
+            None => Ok(()),
+        }
+    }
+
     fn describe_source(&self, id: SourceId) -> String {
         match self.sources.get(id) {
             Some(src) => src.describe(),
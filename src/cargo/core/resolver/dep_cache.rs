@@ -33,8 +33,13 @@ pub struct RegistryQueryer<'a> {
     /// versions first. That allows `cargo update -Z minimal-versions` which will
     /// specify minimum dependency versions to be used.
     minimal_versions: bool,
-    /// a cache of `Candidate`s that fulfil a `Dependency`
-    registry_cache: HashMap<Dependency, Rc<Vec<Summary>>>,
+    /// If set, only the dependencies of the packages being resolved, as
+    /// opposed to the ones of their dependencies, are sorted by minimal
+    /// versions first. That allows `-Z direct-minimal-versions`.
+    direct_minimal_versions: bool,
+    /// a cache of `Candidate`s that fulfil a `Dependency`, along with whether
+    /// they were sorted by minimal versions first
+    registry_cache: HashMap<(Dependency, bool), Rc<Vec<Summary>>>,
     /// a cache of `Dependency`s that are required for a `Summary`
     summary_cache: HashMap<
         (Option<PackageId>, Summary, ResolveOpts),
@@ -50,18 +55,31 @@ impl<'a> RegistryQueryer<'a> {
         replacements: &'a [(PackageIdSpec, Dependency)],
         version_prefs: &'a VersionPreferences,
         minimal_versions: bool,
+        direct_minimal_versions: bool,
     ) -> Self {
         RegistryQueryer {
             registry,
             replacements,
             version_prefs,
             minimal_versions,
+            direct_minimal_versions,
             registry_cache: HashMap::new(),
             summary_cache: HashMap::new(),
             used_replacements: HashMap::new(),
         }
     }
 
+    pub fn version_prefs(&self) -> &'a VersionPreferences {
+        self.version_prefs
+    }
+
+    /// Whether the candidates of dependencies which aren't direct
+    /// dependencies of the packages being resolved are sorted by minimal
+    /// versions first.
+    pub fn transitive_minimal_versions(&self) -> bool {
+        self.minimal_versions
+    }
+
     pub fn used_replacement_for(&self, p: PackageId) -> Option<(PackageId, PackageId)> {
         self.used_replacements.get(&p).map(|r| (p, r.package_id()))
     }
@@ -76,8 +94,19 @@ impl<'a> RegistryQueryer<'a> {
     /// any candidates are returned which match an override then the override is
     /// applied by performing a second query for what the override should
     /// return.
-    pub fn query(&mut self, dep: &Dependency) -> CargoResult<Rc<Vec<Summary>>> {
-        if let Some(out) = self.registry_cache.get(dep).cloned() {
+    ///
+    /// The candidates are sorted by minimal versions first if
+    /// `minimal_versions` is set.
+    pub fn query(
+        &mut self,
+        dep: &Dependency,
+        minimal_versions: bool,
+    ) -> CargoResult<Rc<Vec<Summary>>> {
+        if let Some(out) = self
+            .registry_cache
+            .get(&(dep.clone(), minimal_versions))
+            .cloned()
+        {
             return Ok(out);
         }
 
@@ -164,11 +193,15 @@ impl<'a> RegistryQueryer<'a> {
             }
         }
 
+        // Drop candidates that can't be built with the targeted Rust version, if
+        // requested. Otherwise they're only sorted after the compatible ones.
+        self.version_prefs.retain_compatible(&mut ret);
+
         // When we attempt versions for a package we'll want to do so in a sorted fashion to pick
         // the "best candidates" first. VersionPreferences implements this notion.
         self.version_prefs.sort_summaries(
             &mut ret,
-            if self.minimal_versions {
+            if minimal_versions {
                 VersionOrdering::MinimumVersionsFirst
             } else {
                 VersionOrdering::MaximumVersionsFirst
@@ -177,7 +210,8 @@ impl<'a> RegistryQueryer<'a> {
 
         let out = Rc::new(ret);
 
-        self.registry_cache.insert(dep.clone(), out.clone());
+        self.registry_cache
+            .insert((dep.clone(), minimal_versions), out.clone());
 
         Ok(out)
     }
@@ -207,12 +241,34 @@ impl<'a> RegistryQueryer<'a> {
         // for our own dependencies.
         let (used_features, deps) = resolve_features(parent, candidate, opts)?;
 
+        // Let the registry know about all of the dependencies we're about to
+        // query, so that sources which need a network round-trip per query can
+        // load them in parallel.
+        // The packages being resolved have no parent.
+        let minimal_versions =
+            self.minimal_versions || (self.direct_minimal_versions && parent.is_none());
+        for (dep, _) in deps.iter() {
+            if self
+                .registry_cache
+                .contains_key(&(dep.clone(), minimal_versions))
+            {
+                continue;
+            }
+            self.registry.prefetch(dep).with_context(|| {
+                format!(
+                    "failed to get `{}` as a dependency of {}",
+                    dep.package_name(),
+                    describe_path_in_context(cx, &candidate.package_id()),
+                )
+            })?;
+        }
+
         // Next, transform all dependencies into a list of possible candidates
         // which can satisfy that dependency.
         let mut deps = deps
             .into_iter()
             .map(|(dep, features)| {
-                let candidates = self.query(&dep).with_context(|| {
+                let candidates = self.query(&dep, minimal_versions).with_context(|| {
                     format!(
                         "failed to get `{}` as a dependency of {}",
                         dep.package_name(),
@@ -57,6 +57,13 @@
 //! Listed from most recent to oldest, these are some of the changes we've made
 //! to `Cargo.lock`'s serialization format:
 //!
+//! * Git sources in `[[package]]` entries no longer carry the locked commit
+//!   as a `#rev` URL fragment, instead the commit is listed in a separate
+//!   `rev` field. A package's `source` is now identical to the one used on
+//!   dependency edges and doesn't change when only the commit does. This is
+//!   `version = 4`, which is still unstable and only written with
+//!   `-Z next-lockfile-bump`.
+//!
 //! * A `version` marker is now at the top of the lock file which is a way for
 //!   super-old Cargos (at least since this was implemented) to give a formal
 //!   error if they see a lock file from a super-future Cargo. Additionally as
@@ -159,6 +166,7 @@ impl EncodableResolve {
 
         let mut version = match self.version {
             Some(3) => ResolveVersion::V3,
+            Some(4) => ResolveVersion::V4,
             Some(n) => bail!(
                 "lock file version `{}` was found, but this version of Cargo \
                  does not understand this lock file, perhaps Cargo needs \
@@ -201,7 +209,15 @@ impl EncodableResolve {
                         debug!("path dependency now missing {} v{}", pkg.name, pkg.version);
                         continue;
                     }
-                    Some(&source) => PackageId::new(&pkg.name, &pkg.version, source)?,
+                    Some(&source) => {
+                        // Since V4 the locked commit of git sources is kept
+                        // in its own field.
+                        let source = match &pkg.rev {
+                            Some(rev) => source.with_precise(Some(rev.clone())),
+                            None => source,
+                        };
+                        PackageId::new(&pkg.name, &pkg.version, source)?
+                    }
                 };
 
                 // If a package has a checksum listed directly on it then record
@@ -481,6 +497,7 @@ pub struct EncodableDependency {
     name: String,
     version: String,
     source: Option<SourceId>,
+    rev: Option<String>,
     checksum: Option<String>,
     dependencies: Option<Vec<EncodablePackageId>>,
     replace: Option<EncodablePackageId>,
@@ -592,17 +609,21 @@ impl<'a> ser::Serialize for Resolve {
             unused: self
                 .unused_patches()
                 .iter()
-                .map(|id| EncodableDependency {
-                    name: id.name().to_string(),
-                    version: id.version().to_string(),
-                    source: encode_source(id.source_id()),
-                    dependencies: None,
-                    replace: None,
-                    checksum: if self.version() >= ResolveVersion::V2 {
-                        self.checksums().get(id).and_then(|x| x.clone())
-                    } else {
-                        None
-                    },
+                .map(|id| {
+                    let (source, rev) = encode_source_rev(id.source_id(), self.version());
+                    EncodableDependency {
+                        name: id.name().to_string(),
+                        version: id.version().to_string(),
+                        source,
+                        rev,
+                        dependencies: None,
+                        replace: None,
+                        checksum: if self.version() >= ResolveVersion::V2 {
+                            self.checksums().get(id).and_then(|x| x.clone())
+                        } else {
+                            None
+                        },
+                    }
                 })
                 .collect(),
         };
@@ -613,6 +634,7 @@ impl<'a> ser::Serialize for Resolve {
             patch,
             version: match self.version() {
                 ResolveVersion::V3 => Some(3),
+                ResolveVersion::V4 => Some(4),
                 ResolveVersion::V2 | ResolveVersion::V1 => None,
             },
         }
@@ -664,10 +686,12 @@ fn encodable_resolve_node(
         }
     };
 
+    let (source, rev) = encode_source_rev(id.source_id(), resolve.version());
     EncodableDependency {
         name: id.name().to_string(),
         version: id.version().to_string(),
-        source: encode_source(id.source_id()),
+        source,
+        rev,
         dependencies: deps,
         replace,
         checksum: if resolve.version() >= ResolveVersion::V2 {
@@ -717,3 +741,15 @@ fn encode_source(id: SourceId) -> Option<SourceId> {
         Some(id)
     }
 }
+
+/// Encodes the source of a `[[package]]` entry along with its `rev`, which is
+/// only split out of git sources since `ResolveVersion::V4`.
+fn encode_source_rev(id: SourceId, version: ResolveVersion) -> (Option<SourceId>, Option<String>) {
+    match encode_source(id) {
+        Some(source) if source.is_git() && version >= ResolveVersion::V4 => (
+            Some(source.with_precise(None)),
+            source.precise().map(|rev| rev.to_string()),
+        ),
+        source => (source, None),
+    }
+}
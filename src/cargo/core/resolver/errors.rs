@@ -1,17 +1,21 @@
 use std::fmt;
 
 use crate::core::{Dependency, PackageId, Registry, Summary};
+use crate::util::interning::InternedString;
 use crate::util::lev_distance::lev_distance;
 use crate::util::{Config, VersionExt};
 use anyhow::Error;
+use serde::Serialize;
 
 use super::context::Context;
 use super::types::{ConflictMap, ConflictReason};
+use super::VersionPreferences;
 
 /// Error during resolution providing a path of `PackageId`s.
 pub struct ResolveError {
     cause: Error,
     package_path: Vec<PackageId>,
+    conflict: Option<Box<ResolveConflict>>,
 }
 
 impl ResolveError {
@@ -19,14 +23,52 @@ impl ResolveError {
         Self {
             cause: cause.into(),
             package_path,
+            conflict: None,
         }
     }
 
+    fn with_conflict(mut self, conflict: ResolveConflict) -> Self {
+        self.conflict = Some(Box::new(conflict));
+        self
+    }
+
     /// Returns a path of packages from the package whose requirements could not be resolved up to
     /// the root.
     pub fn package_path(&self) -> &[PackageId] {
         &self.package_path
     }
+
+    /// Returns the details of the dependency that could not be resolved, if
+    /// the error came from a failure to select a version for it.
+    pub fn conflict(&self) -> Option<&ResolveConflict> {
+        self.conflict.as_deref()
+    }
+}
+
+/// Machine-readable description of a dependency for which no version could
+/// be selected, emitted with `--message-format=json`.
+#[derive(Debug, Serialize)]
+pub struct ResolveConflict {
+    /// The name of the package that was required.
+    pub dependency: InternedString,
+    /// The version requirement of the dependency.
+    pub requirement: String,
+    /// The package that declared the dependency.
+    pub required_by: PackageId,
+    /// The versions which meet the requirement, that were all rejected
+    /// because of `conflicts`.
+    pub candidates: Vec<String>,
+    /// If no version meets the requirement, the versions that were found
+    /// instead, newest first.
+    pub available: Vec<String>,
+    /// The previously selected packages that the candidates conflict with.
+    pub conflicts: Vec<ConflictEntry>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ConflictEntry {
+    pub package_id: PackageId,
+    pub reason: ConflictReason,
 }
 
 impl std::error::Error for ResolveError {
@@ -70,6 +112,7 @@ impl From<(PackageId, ConflictReason)> for ActivateError {
 pub(super) fn activation_error(
     cx: &Context,
     registry: &mut dyn Registry,
+    version_prefs: &VersionPreferences,
     parent: &Summary,
     dep: &Dependency,
     conflicting_activations: &ConflictMap,
@@ -88,6 +131,23 @@ pub(super) fn activation_error(
         )
     };
 
+    let mut conflicts: Vec<_> = conflicting_activations.iter().collect();
+    conflicts.sort_unstable();
+    let conflict = |available: Vec<String>| ResolveConflict {
+        dependency: dep.package_name(),
+        requirement: dep.version_req().to_string(),
+        required_by: parent.package_id(),
+        candidates: candidates.iter().map(|c| c.version().to_string()).collect(),
+        available,
+        conflicts: conflicts
+            .iter()
+            .map(|(&package_id, reason)| ConflictEntry {
+                package_id,
+                reason: (*reason).clone(),
+            })
+            .collect(),
+    };
+
     if !candidates.is_empty() {
         let mut msg = format!("failed to select a version for `{}`.", dep.package_name());
         msg.push_str("\n    ... required by ");
@@ -205,7 +265,7 @@ pub(super) fn activation_error(
         msg.push_str(&*dep.package_name());
         msg.push_str("` which could resolve this conflict");
 
-        return to_resolve_err(anyhow::format_err!("{}", msg));
+        return to_resolve_err(anyhow::format_err!("{}", msg)).with_conflict(conflict(Vec::new()));
     }
 
     // We didn't actually find any candidates, so we need to
@@ -222,6 +282,7 @@ pub(super) fn activation_error(
         Err(e) => return to_resolve_err(e),
     };
     candidates.sort_unstable_by(|a, b| b.version().cmp(a.version()));
+    let available = candidates.iter().map(|c| c.version().to_string()).collect();
 
     let mut msg =
         if !candidates.is_empty() {
@@ -261,7 +322,18 @@ pub(super) fn activation_error(
                 );
             }
 
-            if registry.is_replaced(dep.source_id()) {
+            // Matching versions only go missing from the query if they were
+            // dropped for requiring a newer Rust version.
+            if version_prefs.is_rust_version_required()
+                && candidates
+                    .iter()
+                    .any(|c| dep.matches(c) && !version_prefs.is_rust_version_compatible(c))
+            {
+                msg.push_str(
+                    "\nthe matching versions require a newer Rust version, \
+                     which `resolver.rust-version-policy = \"required\"` doesn't allow",
+                );
+            } else if registry.is_replaced(dep.source_id()) {
                 msg.push_str("\nperhaps a crate was updated and forgotten to be re-vendored?");
             }
 
@@ -343,7 +415,7 @@ pub(super) fn activation_error(
         }
     }
 
-    to_resolve_err(anyhow::format_err!("{}", msg))
+    to_resolve_err(anyhow::format_err!("{}", msg)).with_conflict(conflict(available))
 }
 
 /// Returns String representation of dependency chain for a particular `pkgid`
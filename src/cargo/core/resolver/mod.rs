@@ -68,7 +68,9 @@ use self::types::{FeaturesSet, RcVecIter, RemainingDeps, ResolverProgress};
 
 pub use self::encode::Metadata;
 pub use self::encode::{EncodableDependency, EncodablePackageId, EncodableResolve};
-pub use self::errors::{ActivateError, ActivateResult, ResolveError};
+pub use self::errors::{
+    ActivateError, ActivateResult, ConflictEntry, ResolveConflict, ResolveError,
+};
 pub use self::features::{CliFeatures, ForceAllTargets, HasDevUnits};
 pub use self::resolve::{Resolve, ResolveVersion};
 pub use self::types::{ResolveBehavior, ResolveOpts};
@@ -129,12 +131,25 @@ pub fn resolve(
 ) -> CargoResult<Resolve> {
     let cx = Context::new(check_public_visible_dependencies);
     let _p = profile::start("resolving");
-    let minimal_versions = match config {
-        Some(config) => config.cli_unstable().minimal_versions,
-        None => false,
+    let (minimal_versions, direct_minimal_versions) = match config {
+        Some(config) => (
+            config.cli_unstable().minimal_versions,
+            config.cli_unstable().direct_minimal_versions,
+        ),
+        None => (false, false),
     };
-    let mut registry =
-        RegistryQueryer::new(registry, replacements, version_prefs, minimal_versions);
+    if minimal_versions && direct_minimal_versions {
+        anyhow::bail!(
+            "`-Z minimal-versions` and `-Z direct-minimal-versions` are mutually exclusive"
+        );
+    }
+    let mut registry = RegistryQueryer::new(
+        registry,
+        replacements,
+        version_prefs,
+        minimal_versions,
+        direct_minimal_versions,
+    );
     let cx = activate_deps_loop(cx, &mut registry, summaries, config)?;
 
     let mut cksums = HashMap::new();
@@ -332,9 +347,11 @@ fn activate_deps_loop(
                     }
                     None => {
                         debug!("no candidates found");
+                        let version_prefs = registry.version_prefs();
                         Err(errors::activation_error(
                             &cx,
                             registry.registry,
+                            version_prefs,
                             &parent,
                             &dep,
                             &conflicting_activations,
@@ -851,8 +868,12 @@ fn generalize_conflicting(
             // A dep is equivalent to one of the things it can resolve to.
             // Thus, if all the things it can resolve to have already ben determined
             // to be conflicting, then we can just say that we conflict with the parent.
+            //
+            // The order of the candidates doesn't matter here, and the ones
+            // of transitive dependencies are the most likely to be cached.
+            let minimal_versions = registry.transitive_minimal_versions();
             if let Some(others) = registry
-                .query(critical_parents_dep)
+                .query(critical_parents_dep, minimal_versions)
                 .expect("an already used dep now error!?")
                 .iter()
                 .rev() // the last one to be tried is the least likely to be in the cache, so start with that.
@@ -66,6 +66,11 @@ pub enum ResolveVersion {
     /// `branch = "master"` are no longer encoded the same way as those without
     /// branch specifiers.
     V3,
+    /// Git sources are encoded without their locked commit, which is instead
+    /// recorded in a separate `rev` field of each `[[package]]`. A package's
+    /// `source` now stays the same when only the commit changes. Unstable,
+    /// only written with `-Z next-lockfile-bump`.
+    V4,
 }
 
 impl Resolve {
@@ -222,6 +227,42 @@ unable to verify that `{0}` is the same as when the lockfile was generated
         Ok(())
     }
 
+    /// Adds everything recorded in `other` to this resolve.
+    ///
+    /// Both resolves are expected to come straight from `Cargo.lock`, namely
+    /// from the two sides of a merge conflict in it. The union of both is then
+    /// used as the previous resolve, so that versions locked on either side
+    /// are preferred when resolving again. Checksums must agree on both sides,
+    /// just like in `merge_from`.
+    pub fn merge_conflicting(&mut self, other: &Resolve) -> CargoResult<()> {
+        let version = self.version.max(other.version);
+        let mut metadata = other.metadata.clone();
+        metadata.extend(self.metadata.clone());
+        self.merge_from(other)?;
+        self.metadata = metadata;
+        self.version = version;
+
+        for id in other.iter() {
+            self.graph.add(id);
+            for (&dep, _) in other.graph.edges(&id) {
+                self.graph.link(id, dep);
+            }
+        }
+        for (&id, cksum) in other.checksums.iter() {
+            self.checksums.entry(id).or_insert_with(|| cksum.clone());
+        }
+        for (&id, &replace) in other.replacements.iter() {
+            self.replacements.entry(id).or_insert(replace);
+            self.reverse_replacements.entry(replace).or_insert(id);
+        }
+        for id in other.unused_patches.iter() {
+            if !self.unused_patches.contains(id) {
+                self.unused_patches.push(*id);
+            }
+        }
+        Ok(())
+    }
+
     pub fn contains<Q: ?Sized>(&self, k: &Q) -> bool
     where
         PackageId: Borrow<Q>,
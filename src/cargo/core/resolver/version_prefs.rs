@@ -4,7 +4,10 @@
 use std::cmp::Ordering;
 use std::collections::{HashMap, HashSet};
 
+use semver::{Version, VersionReq};
+
 use crate::core::{Dependency, PackageId, Summary};
+use crate::util::config::RustVersionPolicy;
 use crate::util::interning::InternedString;
 
 /// A collection of preferences for particular package versions.
@@ -14,10 +17,16 @@ use crate::util::interning::InternedString;
 ///
 /// As written, a version is either "preferred" or "not preferred".  Later extensions may
 /// introduce more granular preferences.
+///
+/// Registry packages whose `rust-version` is newer than the one set with
+/// [`Self::max_rust_version`] are sorted after the compatible ones, or removed entirely by
+/// [`Self::retain_compatible`] if the policy requires it.
 #[derive(Default)]
 pub struct VersionPreferences {
     try_to_use: HashSet<PackageId>,
     prefer_patch_deps: HashMap<InternedString, HashSet<Dependency>>,
+    max_rust_version: Option<Version>,
+    rust_version_required: bool,
 }
 
 pub enum VersionOrdering {
@@ -39,9 +48,59 @@ impl VersionPreferences {
             .insert(dep);
     }
 
+    /// Indicate the Rust version the resolved dependencies should be able to build with,
+    /// and how strictly that should be enforced.
+    pub fn max_rust_version(&mut self, version: Version, policy: RustVersionPolicy) {
+        match policy {
+            RustVersionPolicy::Off => {
+                self.max_rust_version = None;
+                self.rust_version_required = false;
+            }
+            RustVersionPolicy::Preferred | RustVersionPolicy::Required => {
+                self.max_rust_version = Some(version);
+                self.rust_version_required = policy == RustVersionPolicy::Required;
+            }
+        }
+    }
+
+    /// Whether the `rust-version` of the given summary is compatible with the one set with
+    /// [`Self::max_rust_version`].
+    ///
+    /// Only registry packages are considered, since path and git dependencies have no
+    /// alternative versions to fall back to.
+    pub fn is_rust_version_compatible(&self, summary: &Summary) -> bool {
+        let max = match &self.max_rust_version {
+            Some(max) => max,
+            None => return true,
+        };
+        if !summary.source_id().is_registry() {
+            return true;
+        }
+        match summary.rust_version() {
+            Some(rust_version) => VersionReq::parse(&rust_version)
+                .map(|req| req.matches(max))
+                .unwrap_or(true),
+            None => true,
+        }
+    }
+
+    /// Whether summaries with an incompatible `rust-version` are removed, rather than only
+    /// sorted last.
+    pub fn is_rust_version_required(&self) -> bool {
+        self.rust_version_required
+    }
+
+    /// Remove the summaries whose `rust-version` is incompatible, if the policy requires it.
+    pub fn retain_compatible(&self, summaries: &mut Vec<Summary>) {
+        if self.rust_version_required {
+            summaries.retain(|s| self.is_rust_version_compatible(s));
+        }
+    }
+
     /// Sort the given vector of summaries in-place, with all summaries presumed to be for
     /// the same package.  Preferred versions appear first in the result, sorted by
     /// `version_ordering`, followed by non-preferred versions sorted the same way.
+    /// Within each of those groups, versions with a compatible `rust-version` come first.
     pub fn sort_summaries(&self, summaries: &mut Vec<Summary>, version_ordering: VersionOrdering) {
         let should_prefer = |pkg_id: &PackageId| {
             self.try_to_use.contains(pkg_id)
@@ -54,7 +113,11 @@ impl VersionPreferences {
         summaries.sort_unstable_by(|a, b| {
             let prefer_a = should_prefer(&a.package_id());
             let prefer_b = should_prefer(&b.package_id());
-            let previous_cmp = prefer_a.cmp(&prefer_b).reverse();
+            let previous_cmp = prefer_a.cmp(&prefer_b).reverse().then_with(|| {
+                let compat_a = self.is_rust_version_compatible(a);
+                let compat_b = self.is_rust_version_compatible(b);
+                compat_a.cmp(&compat_b).reverse()
+            });
             match previous_cmp {
                 Ordering::Equal => {
                     let cmp = a.version().cmp(b.version());
@@ -95,6 +158,12 @@ mod test {
         Summary::new(&config, pkg_id, Vec::new(), &features, None::<&String>).unwrap()
     }
 
+    fn summ_rust_version(name: &str, version: &str, rust_version: &str) -> Summary {
+        let mut summary = summ(name, version);
+        summary.set_rust_version(Some(rust_version));
+        summary
+    }
+
     fn describe(summaries: &Vec<Summary>) -> String {
         let strs: Vec<String> = summaries
             .iter()
@@ -178,4 +247,41 @@ mod test {
             "foo/1.1.0, foo/1.2.3, foo/1.0.9, foo/1.2.4".to_string()
         );
     }
+
+    #[test]
+    fn test_max_rust_version() {
+        let mut vp = VersionPreferences::default();
+        vp.max_rust_version(Version::new(1, 60, 0), RustVersionPolicy::Preferred);
+
+        let mut summaries = vec![
+            summ_rust_version("foo", "1.2.4", "1.65"),
+            summ("foo", "1.2.3"),
+            summ_rust_version("foo", "1.1.0", "1.60"),
+            summ_rust_version("foo", "1.0.9", "1.70"),
+        ];
+
+        vp.sort_summaries(&mut summaries, VersionOrdering::MaximumVersionsFirst);
+        assert_eq!(
+            describe(&summaries),
+            "foo/1.2.3, foo/1.1.0, foo/1.2.4, foo/1.0.9".to_string()
+        );
+
+        assert!(!vp.is_rust_version_required());
+        vp.retain_compatible(&mut summaries);
+        assert_eq!(summaries.len(), 4);
+
+        vp.max_rust_version(Version::new(1, 60, 0), RustVersionPolicy::Required);
+        assert!(vp.is_rust_version_required());
+        vp.retain_compatible(&mut summaries);
+        assert_eq!(describe(&summaries), "foo/1.2.3, foo/1.1.0".to_string());
+
+        vp.max_rust_version(Version::new(1, 60, 0), RustVersionPolicy::Off);
+        assert!(!vp.is_rust_version_required());
+        let mut summaries = vec![
+            summ_rust_version("foo", "1.2.4", "1.65"),
+            summ("foo", "1.2.3"),
+        ];
+        vp.sort_summaries(&mut summaries, VersionOrdering::MaximumVersionsFirst);
+        assert_eq!(describe(&summaries), "foo/1.2.4, foo/1.2.3".to_string());
+    }
 }
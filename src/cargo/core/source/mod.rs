@@ -42,6 +42,16 @@ pub trait Source {
         Ok(ret)
     }
 
+    /// Hints that `dep` is about to be queried.
+    ///
+    /// This must not block. Sources for which each query may involve a
+    /// network round-trip can use this to start fetching the data for several
+    /// dependencies in parallel before the first one is queried. This is
+    /// purely an optimization, and the default implementation does nothing.
+    fn prefetch(&mut self, _dep: &Dependency) -> CargoResult<()> {
+        Ok(())
+    }
+
     /// Performs any network operations required to get the entire list of all names,
     /// versions and dependencies of packages managed by the `Source`.
     fn update(&mut self) -> CargoResult<()>;
@@ -139,6 +149,10 @@ impl<'a, T: Source + ?Sized + 'a> Source for Box<T> {
         (**self).fuzzy_query(dep, f)
     }
 
+    fn prefetch(&mut self, dep: &Dependency) -> CargoResult<()> {
+        (**self).prefetch(dep)
+    }
+
     /// Forwards to `Source::update`.
     fn update(&mut self) -> CargoResult<()> {
         (**self).update()
@@ -205,6 +219,10 @@ impl<'a, T: Source + ?Sized + 'a> Source for &'a mut T {
         (**self).fuzzy_query(dep, f)
     }
 
+    fn prefetch(&mut self, dep: &Dependency) -> CargoResult<()> {
+        (**self).prefetch(dep)
+    }
+
     fn update(&mut self) -> CargoResult<()> {
         (**self).update()
     }
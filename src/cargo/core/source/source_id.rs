@@ -276,6 +276,12 @@ impl SourceId {
         matches!(self.inner.kind, SourceKind::Registry)
     }
 
+    /// Returns `true` if this source is a remote registry whose index is
+    /// fetched file-by-file over HTTP rather than cloned with git.
+    pub fn is_sparse(self) -> bool {
+        self.is_remote_registry() && self.inner.url.scheme().starts_with("sparse+")
+    }
+
     /// Returns `true` if this source from a Git repository.
     pub fn is_git(self) -> bool {
         matches!(self.inner.kind, SourceKind::Git(_))
@@ -301,7 +307,7 @@ impl SourceId {
                 self,
                 yanked_whitelist,
                 config,
-            ))),
+            )?)),
             SourceKind::LocalRegistry => {
                 let path = match self.inner.url.to_file_path() {
                     Ok(p) => p,
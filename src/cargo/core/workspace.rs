@@ -17,12 +17,12 @@ use crate::core::resolver::ResolveBehavior;
 use crate::core::{Dependency, Edition, FeatureValue, PackageId, PackageIdSpec};
 use crate::core::{EitherManifest, Package, SourceId, VirtualManifest};
 use crate::ops;
-use crate::sources::{PathSource, CRATES_IO_INDEX, CRATES_IO_REGISTRY};
+use crate::sources::{PathSource, SourceConfigMap, CRATES_IO_INDEX, CRATES_IO_REGISTRY};
 use crate::util::errors::{CargoResult, ManifestError};
 use crate::util::interning::InternedString;
 use crate::util::lev_distance;
 use crate::util::toml::{read_manifest, TomlDependency, TomlProfiles};
-use crate::util::{config::ConfigRelativePath, Config, Filesystem, IntoUrl};
+use crate::util::{self, config::ConfigRelativePath, Config, Filesystem, IntoUrl};
 use cargo_util::paths;
 
 /// The core abstraction in Cargo for working with a workspace of crates.
@@ -53,6 +53,10 @@ pub struct Workspace<'cfg> {
     // `None` if the default path of `root/target` should be used.
     target_dir: Option<Filesystem>,
 
+    // Directory for the intermediate build artifacts, from `build.build-dir`.
+    // `None` if they go in the target directory.
+    build_dir: Option<Filesystem>,
+
     // List of members in this workspace with a listing of all their manifest
     // paths. The packages themselves can be looked up through the `packages`
     // set above.
@@ -94,6 +98,10 @@ pub struct Workspace<'cfg> {
 
     /// Workspace-level custom metadata
     custom_metadata: Option<toml::Value>,
+
+    /// Local registries whose packages are overlaid on top of a source when
+    /// resolving, see [`Workspace::set_local_overlays`].
+    local_overlays: HashMap<SourceId, PathBuf>,
 }
 
 // Separate structure for tracking loaded packages (to avoid loading anything
@@ -154,6 +162,7 @@ impl<'cfg> Workspace<'cfg> {
         } else {
             ws.root_manifest = ws.find_root(manifest_path)?;
         }
+        ws.build_dir = ws.config_build_dir()?;
 
         ws.custom_metadata = ws
             .load_workspace_config()?
@@ -174,6 +183,7 @@ impl<'cfg> Workspace<'cfg> {
             },
             root_manifest: None,
             target_dir: None,
+            build_dir: None,
             members: Vec::new(),
             member_ids: HashSet::new(),
             default_members: Vec::new(),
@@ -183,6 +193,7 @@ impl<'cfg> Workspace<'cfg> {
             ignore_lock: false,
             resolve_behavior: ResolveBehavior::V1,
             custom_metadata: None,
+            local_overlays: HashMap::new(),
         }
     }
 
@@ -195,6 +206,7 @@ impl<'cfg> Workspace<'cfg> {
         let mut ws = Workspace::new_default(current_manifest, config);
         ws.root_manifest = Some(root_path.join("Cargo.toml"));
         ws.target_dir = config.target_dir()?;
+        ws.build_dir = ws.config_build_dir()?;
         ws.packages
             .packages
             .insert(root_path, MaybePackage::Virtual(manifest));
@@ -232,6 +244,7 @@ impl<'cfg> Workspace<'cfg> {
         } else {
             ws.config.target_dir()?
         };
+        ws.build_dir = ws.config_build_dir()?;
         ws.members.push(ws.current_manifest.clone());
         ws.member_ids.insert(id);
         ws.default_members.push(ws.current_manifest.clone());
@@ -347,6 +360,62 @@ impl<'cfg> Workspace<'cfg> {
             .unwrap_or_else(|| Filesystem::new(self.root().join("target")))
     }
 
+    /// Returns the directory for intermediate build artifacts, like `deps`,
+    /// `build` and `.fingerprint`. This is the target directory unless
+    /// `build.build-dir` is set.
+    pub fn build_dir(&self) -> Filesystem {
+        self.build_dir.clone().unwrap_or_else(|| self.target_dir())
+    }
+
+    /// Reads `build.build-dir`, expanding the templates it may contain.
+    fn config_build_dir(&self) -> CargoResult<Option<Filesystem>> {
+        let val = match &self.config.build_config()?.build_dir {
+            Some(val) => val,
+            None => return Ok(None),
+        };
+        if !self.config.cli_unstable().build_dir {
+            self.config.warn_build_dir_ignored()?;
+            return Ok(None);
+        }
+        let raw = val.raw_value();
+        if raw.is_empty() {
+            bail!(
+                "the build directory is set to an empty string in {}",
+                val.value().definition
+            )
+        }
+        // The templates are expanded in a single pass, since the paths they
+        // expand to may contain braces themselves.
+        let root = self.root();
+        let mut dir = String::new();
+        let mut rest = raw;
+        while let Some(start) = rest.find('{') {
+            dir.push_str(&rest[..start]);
+            let template = match rest[start..].find('}') {
+                Some(end) => &rest[start..=start + end],
+                None => &rest[start..],
+            };
+            match template {
+                "{workspace-root}" => dir.push_str(&root.display().to_string()),
+                "{cargo-home}" => {
+                    dir.push_str(&self.config.home().as_path_unlocked().display().to_string())
+                }
+                "{workspace-path-hash}" => dir.push_str(&util::short_hash(&root)),
+                _ => bail!(
+                    "unknown template `{}` in the build directory set in {}, \
+                     expected one of `{{workspace-root}}`, `{{cargo-home}}` \
+                     or `{{workspace-path-hash}}`",
+                    template,
+                    val.value().definition
+                ),
+            }
+            rest = &rest[start + template.len()..];
+        }
+        dir.push_str(rest);
+        let dir = val.value().definition.root(self.config).join(dir);
+        Ok(Some(Filesystem::new(dir)))
+    }
+
     /// Returns the root `[replace]` section of this workspace.
     ///
     /// This may be from a virtual crate or an actual crate.
@@ -544,6 +613,31 @@ impl<'cfg> Workspace<'cfg> {
         self
     }
 
+    pub fn local_overlays(&self) -> &HashMap<SourceId, PathBuf> {
+        &self.local_overlays
+    }
+
+    /// Makes the packages of the local registry at each path available as if
+    /// they were in the source they are keyed by.
+    ///
+    /// This is used to verify packages that depend on siblings which haven't
+    /// been published yet.
+    pub fn set_local_overlays(
+        &mut self,
+        local_overlays: HashMap<SourceId, PathBuf>,
+    ) -> &mut Workspace<'cfg> {
+        self.local_overlays = local_overlays;
+        self
+    }
+
+    /// Creates a `PackageRegistry` for resolving this workspace, taking the
+    /// local overlays into account.
+    pub fn package_registry(&self) -> CargoResult<PackageRegistry<'cfg>> {
+        let source_config =
+            SourceConfigMap::new_with_overlays(self.config, self.local_overlays.clone())?;
+        PackageRegistry::new_with_source_config(self.config, source_config)
+    }
+
     pub fn custom_metadata(&self) -> Option<&toml::Value> {
         self.custom_metadata.as_ref()
     }
@@ -1580,7 +1674,7 @@ impl WorkspaceRootConfig {
     /// Checks the path against the `excluded` list.
     ///
     /// This method does **not** consider the `members` list.
-    fn is_excluded(&self, manifest_path: &Path) -> bool {
+    pub(crate) fn is_excluded(&self, manifest_path: &Path) -> bool {
         let excluded = self
             .exclude
             .iter()
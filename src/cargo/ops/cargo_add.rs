@@ -0,0 +1,466 @@
+//! Core of the `cargo add` command.
+
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+use anyhow::Context as _;
+use cargo_platform::Platform;
+
+use crate::core::registry::PackageRegistry;
+use crate::core::{
+    Dependency, FeatureValue, GitReference, Package, Registry, SourceId, Summary, Verbosity,
+    Workspace,
+};
+use crate::drop_eprintln;
+use crate::ops;
+use crate::util::errors::CargoResult;
+use crate::util::toml_mut::dependency::{
+    Dependency as TomlDependency, GitSource, PathSource, RegistrySource, Source,
+};
+use crate::util::toml_mut::manifest::{DepTable, LocalManifest};
+use crate::util::{validate_package_name, Config, IntoUrl};
+
+/// Information on what dependencies should be added.
+pub struct AddOptions<'a> {
+    /// Configuration information for cargo operations.
+    pub config: &'a Config,
+    /// Package to add dependencies to.
+    pub spec: &'a Package,
+    /// Dependencies to add or modify.
+    pub dependencies: Vec<DepOp>,
+    /// Which dependency section to add these to.
+    pub section: DepTable,
+    /// Act as if dependencies will be added.
+    pub dry_run: bool,
+}
+
+/// Dependency entry operation, as requested on the command-line.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DepOp {
+    /// Crate name, optionally with a version requirement (`name@req`).
+    pub crate_spec: Option<String>,
+    /// Dependency key, overriding the package name in `crate_spec`.
+    pub rename: Option<String>,
+    /// Features to activate.
+    pub features: Option<Vec<String>>,
+    /// Whether the default feature should be activated.
+    pub default_features: Option<bool>,
+    /// Whether dependency is optional.
+    pub optional: Option<bool>,
+    /// Registry for looking up dependency version.
+    pub registry: Option<String>,
+    /// Local path to the dependency.
+    pub path: Option<String>,
+    /// Git repository URL of the dependency.
+    pub git: Option<String>,
+    /// Git branch to use.
+    pub branch: Option<String>,
+    /// Git tag to use.
+    pub tag: Option<String>,
+    /// Git revision to use.
+    pub rev: Option<String>,
+}
+
+/// Executes `cargo add`.
+pub fn add(workspace: &Workspace<'_>, options: &AddOptions<'_>) -> CargoResult<()> {
+    let dep_table = options
+        .section
+        .to_table()
+        .into_iter()
+        .map(String::from)
+        .collect::<Vec<_>>();
+
+    let manifest_path = options.spec.manifest_path().to_path_buf();
+    let mut manifest = LocalManifest::try_new(&manifest_path)?;
+
+    if let Some(target) = options.section.target() {
+        target
+            .parse::<Platform>()
+            .with_context(|| format!("invalid target platform `{}`", target))?;
+    }
+
+    // Querying registries may update indices and frob caches, so synchronize
+    // with other Cargo processes for the whole operation.
+    let _lock = options.config.acquire_package_cache_lock()?;
+    let mut registry = PackageRegistry::new(options.config)?;
+    registry.lock_patches();
+
+    for raw in &options.dependencies {
+        let (dep, summary) = resolve_dependency(
+            &manifest,
+            raw,
+            workspace,
+            &options.section,
+            options.config,
+            &mut registry,
+        )?;
+
+        print_msg(options.config, &dep, summary.as_ref(), &options.section)?;
+        manifest.insert_into_table(&dep_table, &dep)?;
+    }
+
+    if options.dry_run {
+        options.config.shell().warn("aborting add due to dry run")?;
+    } else {
+        manifest.write()?;
+    }
+
+    Ok(())
+}
+
+/// Turns a command-line request into the manifest entry to write, along with
+/// the `Summary` of the package it resolved to, if one could be found.
+fn resolve_dependency(
+    manifest: &LocalManifest,
+    arg: &DepOp,
+    ws: &Workspace<'_>,
+    section: &DepTable,
+    config: &Config,
+    registry: &mut PackageRegistry<'_>,
+) -> CargoResult<(TomlDependency, Option<Summary>)> {
+    let (name, version_req) = match arg.crate_spec.as_deref() {
+        Some(spec) => {
+            let (name, req) = parse_crate_spec(spec)?;
+            (Some(name), req)
+        }
+        None => (None, None),
+    };
+
+    let (mut dep, summary) = if let Some(path) = &arg.path {
+        let path = config.cwd().join(path);
+        let pkg = read_path_package(&path, config)?;
+        if let Some(name) = &name {
+            if name.as_str() != pkg.name().as_str() {
+                anyhow::bail!(
+                    "the crate `{}` could not be found at `{}`, found `{}` instead",
+                    name,
+                    path.display(),
+                    pkg.name()
+                );
+            }
+        }
+        let mut src = PathSource::new(pkg.root());
+        if let Some(req) = &version_req {
+            src = src.set_version(req);
+        }
+        let dep = TomlDependency::new(&pkg.name()).set_source(src);
+        (dep, Some(pkg.summary().clone()))
+    } else if let Some(git) = &arg.git {
+        let name = name.ok_or_else(|| {
+            anyhow::format_err!(
+                "the name of the crate to add from `{}` must be specified, \
+                 for example `cargo add <name> --git {}`",
+                git,
+                git
+            )
+        })?;
+        let mut src = GitSource::new(git.as_str());
+        let reference = if let Some(branch) = &arg.branch {
+            src = src.set_branch(branch.as_str());
+            GitReference::Branch(branch.clone())
+        } else if let Some(tag) = &arg.tag {
+            src = src.set_tag(tag.as_str());
+            GitReference::Tag(tag.clone())
+        } else if let Some(rev) = &arg.rev {
+            src = src.set_rev(rev.as_str());
+            GitReference::Rev(rev.clone())
+        } else {
+            GitReference::DefaultBranch
+        };
+        if let Some(req) = &version_req {
+            src = src.set_version(req);
+        }
+        let source_id = SourceId::for_git(&git.as_str().into_url()?, reference)?;
+        let query = Dependency::parse(name.as_str(), version_req.as_deref(), source_id)?;
+        let summary = latest_summary(registry, &query)?.ok_or_else(|| {
+            anyhow::format_err!("the crate `{}` could not be found in `{}`", name, git)
+        })?;
+        let dep = TomlDependency::new(&name).set_source(src);
+        (dep, Some(summary))
+    } else {
+        let name = name.ok_or_else(|| {
+            anyhow::format_err!("a crate name, `--path` or `--git` must be specified")
+        })?;
+        resolve_registry_dependency(
+            manifest,
+            &name,
+            version_req.as_deref(),
+            arg,
+            ws,
+            section,
+            config,
+            registry,
+        )?
+    };
+
+    if let Some(rename) = &arg.rename {
+        dep = dep.set_rename(rename);
+    }
+
+    // Carry over anything the user did not ask to change from an existing
+    // entry for the same dependency.
+    let existing = manifest
+        .get_table(
+            &section
+                .to_table()
+                .into_iter()
+                .map(String::from)
+                .collect::<Vec<_>>(),
+        )
+        .ok()
+        .and_then(|table| table.get(dep.toml_key()))
+        .map(|item| {
+            TomlDependency::from_toml(manifest.path.parent().unwrap(), dep.toml_key(), item)
+        })
+        .transpose()?;
+    if let Some(existing) = &existing {
+        if existing.name != dep.name {
+            anyhow::bail!(
+                "cannot add `{}` as `{}`, that key is already used for the crate `{}`",
+                dep.name,
+                dep.toml_key(),
+                existing.name
+            );
+        }
+        dep.features = existing.features.clone();
+        dep.default_features = existing.default_features;
+        dep.optional = existing.optional;
+    }
+
+    if let Some(default_features) = arg.default_features {
+        dep.default_features = if default_features { None } else { Some(false) };
+    }
+    if let Some(optional) = arg.optional {
+        dep.optional = if optional { Some(true) } else { None };
+    }
+    if let Some(features) = &arg.features {
+        if let Some(summary) = &summary {
+            let unknown = features
+                .iter()
+                .filter(|f| !f.contains('/') && !summary.features().contains_key(f.as_str()))
+                .collect::<Vec<_>>();
+            if !unknown.is_empty() {
+                anyhow::bail!(
+                    "unrecognized feature{} for crate `{}`: {}",
+                    if unknown.len() == 1 { "" } else { "s" },
+                    dep.name,
+                    unknown
+                        .iter()
+                        .map(|f| format!("`{}`", f))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                );
+            }
+        }
+        let mut all = dep.features.take().unwrap_or_default();
+        for feature in features {
+            if !all.contains(feature) {
+                all.push(feature.clone());
+            }
+        }
+        if !all.is_empty() {
+            dep.features = Some(all);
+        }
+    }
+
+    Ok((dep, summary))
+}
+
+#[allow(clippy::too_many_arguments)]
+fn resolve_registry_dependency(
+    manifest: &LocalManifest,
+    name: &str,
+    version_req: Option<&str>,
+    arg: &DepOp,
+    ws: &Workspace<'_>,
+    section: &DepTable,
+    config: &Config,
+    registry: &mut PackageRegistry<'_>,
+) -> CargoResult<(TomlDependency, Option<Summary>)> {
+    // A sibling in the workspace is always added by path.
+    if arg.registry.is_none() {
+        if let Some(member) = ws.members().find(|p| p.name().as_str() == name) {
+            if member.manifest_path() == manifest.path.as_path() {
+                anyhow::bail!("cannot add `{}` as a dependency to itself", name);
+            }
+            let mut src = PathSource::new(member.root());
+            if let Some(req) = version_req {
+                src = src.set_version(req);
+            }
+            let dep = TomlDependency::new(name).set_source(src);
+            return Ok((dep, Some(member.summary().clone())));
+        }
+    }
+
+    let source_id = match &arg.registry {
+        Some(registry) => SourceId::alt_registry(config, registry)?,
+        None => SourceId::crates_io(config)?,
+    };
+
+    // If the dependency is already declared and no version was requested,
+    // keep whatever requirement is already in the manifest.
+    let existing_req = if version_req.is_none() {
+        manifest
+            .get_dependency_versions(arg.rename.as_deref().unwrap_or(name))
+            .filter_map(|(table, dep)| dep.ok().map(|dep| (table, dep)))
+            .find(|(table, dep)| table == section && dep.name == name)
+            .and_then(|(_, dep)| dep.source().and_then(|s| s.as_registry()).cloned())
+            .map(|src| src.version)
+    } else {
+        None
+    };
+
+    let query_req = version_req.or(existing_req.as_deref());
+    let query = Dependency::parse(name, query_req, source_id)?;
+    let summary = latest_summary(registry, &query)?;
+    let summary = match summary {
+        Some(summary) => summary,
+        None => match query_req {
+            Some(req) => anyhow::bail!(
+                "the crate `{}@{}` could not be found in registry index.",
+                name,
+                req
+            ),
+            None => anyhow::bail!("the crate `{}` could not be found in registry index.", name),
+        },
+    };
+
+    let version = match query_req {
+        Some(req) => req.to_string(),
+        None => summary.version().to_string(),
+    };
+    let mut dep = TomlDependency::new(name).set_source(RegistrySource::new(version));
+    if let Some(registry) = &arg.registry {
+        dep = dep.set_registry(registry);
+    }
+    Ok((dep, Some(summary)))
+}
+
+/// Picks the newest candidate for `dep`, preferring stable releases over
+/// pre-releases.
+fn latest_summary(
+    registry: &mut PackageRegistry<'_>,
+    dep: &Dependency,
+) -> CargoResult<Option<Summary>> {
+    let possibilities = registry.query_vec(dep, false)?;
+    let latest = possibilities
+        .iter()
+        .filter(|s| s.version().pre.is_empty())
+        .max_by_key(|s| s.version())
+        .or_else(|| possibilities.iter().max_by_key(|s| s.version()));
+    Ok(latest.cloned())
+}
+
+/// Splits `name@version-req` into its parts.
+fn parse_crate_spec(spec: &str) -> CargoResult<(String, Option<String>)> {
+    let (name, version) = match spec.split_once('@') {
+        Some((name, version)) => {
+            if version.is_empty() {
+                anyhow::bail!("missing version requirement after `@` in `{}`", spec);
+            }
+            semver::VersionReq::parse(version).with_context(|| {
+                format!(
+                    "invalid version requirement `{}` for crate `{}`",
+                    version, name
+                )
+            })?;
+            (name, Some(version.to_owned()))
+        }
+        None => (spec, None),
+    };
+    validate_package_name(name, "dependency name", "")?;
+    Ok((name.to_owned(), version))
+}
+
+fn read_path_package(path: &Path, config: &Config) -> CargoResult<Package> {
+    let manifest_path: PathBuf = if path.ends_with("Cargo.toml") {
+        path.to_path_buf()
+    } else {
+        path.join("Cargo.toml")
+    };
+    let source_id = SourceId::for_path(manifest_path.parent().unwrap())?;
+    let (pkg, _) = ops::read_package(&manifest_path, source_id, config)
+        .with_context(|| format!("failed to read package at `{}`", path.display()))?;
+    Ok(pkg)
+}
+
+fn print_msg(
+    config: &Config,
+    dep: &TomlDependency,
+    summary: Option<&Summary>,
+    section: &DepTable,
+) -> CargoResult<()> {
+    let mut message = String::new();
+    if let Some(rename) = dep.rename() {
+        message.push_str(&format!("{} (as {})", dep.name, rename));
+    } else {
+        message.push_str(&dep.name);
+    }
+    match dep.source() {
+        Some(Source::Registry(src)) => {
+            if src.version.chars().next().unwrap_or('0').is_ascii_digit() {
+                message.push_str(&format!(" v{}", src.version));
+            } else {
+                message.push_str(&format!(" {}", src.version));
+            }
+        }
+        Some(Source::Path(_)) | Some(Source::Git(_)) => {
+            if let Some(summary) = summary {
+                message.push_str(&format!(" (v{})", summary.version()));
+            }
+        }
+        None => {}
+    }
+    message.push_str(" to ");
+    if dep.optional().unwrap_or(false) {
+        message.push_str("optional ");
+    }
+    message.push_str(&section.to_string());
+    let mut shell = config.shell();
+    shell.status("Adding", message)?;
+
+    let summary = match summary {
+        Some(summary) => summary,
+        None => return Ok(()),
+    };
+    let activated: BTreeSet<&str> = dep
+        .features
+        .iter()
+        .flatten()
+        .map(|s| s.as_str())
+        .chain(
+            if dep.default_features().unwrap_or(true) {
+                summary.features().get("default")
+            } else {
+                None
+            }
+            .into_iter()
+            .flatten()
+            .filter_map(|fv| match fv {
+                FeatureValue::Feature(f) => Some(f.as_str()),
+                _ => None,
+            }),
+        )
+        .collect();
+    let available: BTreeSet<&str> = summary
+        .features()
+        .keys()
+        .map(|s| s.as_str())
+        .filter(|s| *s != "default")
+        .collect();
+    if available.is_empty() && activated.is_empty() {
+        return Ok(());
+    }
+    drop(shell);
+    if config.shell().verbosity() == Verbosity::Quiet {
+        return Ok(());
+    }
+    drop_eprintln!(config, "{:>13}Features:", " ");
+    for feature in &activated {
+        drop_eprintln!(config, "{:>13}+ {}", " ", feature);
+    }
+    for feature in available.difference(&activated) {
+        drop_eprintln!(config, "{:>13}- {}", " ", feature);
+    }
+    Ok(())
+}
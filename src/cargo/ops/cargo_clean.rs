@@ -29,6 +29,7 @@ pub struct CleanOptions<'a> {
 /// Cleans the package's build artifacts.
 pub fn clean(ws: &Workspace<'_>, opts: &CleanOptions<'_>) -> CargoResult<()> {
     let mut target_dir = ws.target_dir();
+    let mut build_dir = ws.build_dir();
     let config = ws.config();
 
     // If the doc option is set, we just want to delete the doc directory.
@@ -45,6 +46,7 @@ pub fn clean(ws: &Workspace<'_>, opts: &CleanOptions<'_>) -> CargoResult<()> {
         // that profile.
         let dir_name = profiles.get_dir_name();
         target_dir = target_dir.join(dir_name);
+        build_dir = build_dir.join(dir_name);
     }
 
     // If we have a spec, then we need to delete some packages, otherwise, just
@@ -53,7 +55,12 @@ pub fn clean(ws: &Workspace<'_>, opts: &CleanOptions<'_>) -> CargoResult<()> {
     // Note that we don't bother grabbing a lock here as we're just going to
     // blow it all away anyway.
     if opts.spec.is_empty() {
-        return clean_entire_folder(&target_dir.into_path_unlocked(), config);
+        let build_dir = build_dir.into_path_unlocked();
+        let target_dir = target_dir.into_path_unlocked();
+        if build_dir != target_dir {
+            clean_entire_folder(&build_dir, config)?;
+        }
+        return clean_entire_folder(&target_dir, config);
     }
 
     // Clean specific packages.
@@ -34,7 +34,7 @@ use crate::core::compiler::{CompileKind, CompileMode, CompileTarget, RustcTarget
 use crate::core::compiler::{DefaultExecutor, Executor, UnitInterner};
 use crate::core::profiles::{Profiles, UnitFor};
 use crate::core::resolver::features::{self, CliFeatures, FeaturesFor};
-use crate::core::resolver::{HasDevUnits, Resolve};
+use crate::core::resolver::{HasDevUnits, Resolve, ResolveError};
 use crate::core::{FeatureValue, Package, PackageSet, Shell, Summary, Target};
 use crate::core::{PackageId, PackageIdSpec, SourceId, TargetKind, Workspace};
 use crate::drop_println;
@@ -42,6 +42,7 @@ use crate::ops;
 use crate::ops::resolve::WorkspaceResolve;
 use crate::util::config::Config;
 use crate::util::interning::InternedString;
+use crate::util::machine_message::{self, Message};
 use crate::util::restricted_names::is_glob_pattern;
 use crate::util::{closest_msg, profile, CargoResult, StableHasher};
 
@@ -365,7 +366,7 @@ pub fn create_bcx<'a, 'cfg>(
     }
     config.validate_term_config()?;
 
-    let target_data = RustcTargetData::new(ws, &build_config.requested_kinds)?;
+    let mut target_data = RustcTargetData::new(ws, &build_config.requested_kinds)?;
 
     let all_packages = &Packages::All;
     let rustdoc_scrape_examples = &config.cli_unstable().rustdoc_scrape_examples;
@@ -384,13 +385,22 @@ pub fn create_bcx<'a, 'cfg>(
     };
     let resolve = ops::resolve_ws_with_opts(
         ws,
-        &target_data,
+        &mut target_data,
         &build_config.requested_kinds,
         cli_features,
         &resolve_specs,
         has_dev_units,
         crate::core::resolver::features::ForceAllTargets::No,
-    )?;
+    );
+    let resolve = match resolve {
+        Ok(resolve) => resolve,
+        Err(e) => {
+            if build_config.emit_json() {
+                emit_resolve_error(config, &e)?;
+            }
+            return Err(e);
+        }
+    };
     let WorkspaceResolve {
         mut pkg_set,
         workspace_resolve,
@@ -411,7 +421,7 @@ pub fn create_bcx<'a, 'cfg>(
             anyhow::bail!("-Zbuild-std requires --target");
         }
         let (std_package_set, std_resolve, std_features) =
-            standard_lib::resolve_std(ws, &target_data, &build_config.requested_kinds, crates)?;
+            standard_lib::resolve_std(ws, &mut target_data, &build_config.requested_kinds, crates)?;
         pkg_set.add_set(std_package_set);
         Some((std_resolve, std_features))
     } else {
@@ -716,6 +726,23 @@ pub fn create_bcx<'a, 'cfg>(
     Ok(bcx)
 }
 
+/// Prints a `resolve-error` JSON message if `err` is a failure to resolve the
+/// dependency graph, so tools don't have to parse the error message.
+fn emit_resolve_error(config: &Config, err: &anyhow::Error) -> CargoResult<()> {
+    let resolve_err = match err.chain().find_map(|e| e.downcast_ref::<ResolveError>()) {
+        Some(resolve_err) => resolve_err,
+        None => return Ok(()),
+    };
+    let msg = machine_message::ResolveFailed {
+        message: resolve_err.to_string(),
+        conflict: resolve_err.conflict(),
+        package_path: resolve_err.package_path(),
+    }
+    .to_json_string();
+    writeln!(config.shell().out(), "{}", msg)?;
+    Ok(())
+}
+
 impl FilterRule {
     pub fn new(targets: Vec<String>, all: bool) -> FilterRule {
         if all {
@@ -1606,7 +1633,7 @@ fn traverse_and_share(
     let new_unit = interner.intern(
         &unit.pkg,
         &unit.target,
-        unit.profile,
+        unit.profile.clone(),
         new_kind,
         unit.mode,
         unit.features.clone(),
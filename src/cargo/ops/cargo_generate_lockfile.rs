@@ -1,14 +1,15 @@
-use crate::core::registry::PackageRegistry;
+use crate::core::registry::{PackageRegistry, Registry};
 use crate::core::resolver::features::{CliFeatures, HasDevUnits};
-use crate::core::{PackageId, PackageIdSpec};
-use crate::core::{Resolve, SourceId, Workspace};
+use crate::core::{Dependency, PackageId, PackageIdSpec};
+use crate::core::{Resolve, SourceId, Verbosity, Workspace};
 use crate::ops;
 use crate::util::config::Config;
 use crate::util::CargoResult;
 use anyhow::Context;
 use log::debug;
+use semver::{Version, VersionReq};
 use std::collections::{BTreeMap, HashSet};
-use termcolor::Color::{self, Cyan, Green, Red};
+use termcolor::Color::{self, Cyan, Green, Red, Yellow};
 
 pub struct UpdateOptions<'a> {
     pub config: &'a Config,
@@ -142,7 +143,12 @@ pub fn update_lockfile(ws: &Workspace<'_>, opts: &UpdateOptions<'_>) -> CargoRes
             } else {
                 format!("{} -> v{}", removed[0], added[0].version())
             };
-            print_change("Updating", msg, Green)?;
+            // For example with `--precise`, a package may move to an older version.
+            if removed[0].source_id().is_registry() && added[0].version() < removed[0].version() {
+                print_change("Downgrading", msg, Yellow)?;
+            } else {
+                print_change("Updating", msg, Green)?;
+            }
         } else {
             for package in removed.iter() {
                 print_change("Removing", format!("{}", package), Red)?;
@@ -152,6 +158,7 @@ pub fn update_lockfile(ws: &Workspace<'_>, opts: &UpdateOptions<'_>) -> CargoRes
             }
         }
     }
+    report_held_back(&mut registry, &resolve, opts.config)?;
     if opts.dry_run {
         opts.config
             .shell()
@@ -176,6 +183,95 @@ pub fn update_lockfile(ws: &Workspace<'_>, opts: &UpdateOptions<'_>) -> CargoRes
         }
     }
 
+    /// Reports the registry packages of `resolve` that can't move to the
+    /// latest version of their registry. With `--verbose`, each of them is
+    /// listed along with the requirement that holds it back, otherwise only
+    /// the ones held back by a semver-incompatible release are counted.
+    fn report_held_back(
+        registry: &mut PackageRegistry<'_>,
+        resolve: &Resolve,
+        config: &Config,
+    ) -> CargoResult<()> {
+        let verbose = config.shell().verbosity() == Verbosity::Verbose;
+        let mut incompatible = 0;
+        for pkg_id in resolve.iter() {
+            if !pkg_id.source_id().is_registry() {
+                continue;
+            }
+            // A `*` requirement skips yanked and pre-release versions.
+            let query = Dependency::parse(pkg_id.name(), None, pkg_id.source_id())?;
+            let latest = match registry
+                .query_vec(&query, false)?
+                .iter()
+                .map(|s| s.version().clone())
+                .max()
+            {
+                Some(latest) if latest > *pkg_id.version() => latest,
+                _ => continue,
+            };
+            let (parent, dep) = match constraining_dep(resolve, pkg_id, &latest) {
+                Some(constraint) => constraint,
+                None => continue,
+            };
+            let compatible = VersionReq::parse(&format!("^{}", pkg_id.version()))?;
+            if !compatible.matches(&latest) {
+                incompatible += 1;
+            }
+            if verbose {
+                config.shell().status_with_color(
+                    "Unchanged",
+                    format!(
+                        "{} (latest: v{}), required by {} as `{} = \"{}\"`",
+                        pkg_id,
+                        latest,
+                        parent,
+                        dep.name_in_toml(),
+                        dep.version_req()
+                    ),
+                    Yellow,
+                )?;
+            }
+        }
+        if incompatible > 0 {
+            let mut msg = if incompatible == 1 {
+                "1 dependency is held back by a semver-incompatible newer release".to_string()
+            } else {
+                format!(
+                    "{} dependencies are held back by semver-incompatible newer releases",
+                    incompatible
+                )
+            };
+            if !verbose {
+                msg.push_str("\npass `--verbose` to see why");
+            }
+            config.shell().note(msg)?;
+        }
+        Ok(())
+    }
+
+    /// Finds a dependent of `pkg_id` whose requirement on it doesn't allow
+    /// `latest`. The path to the workspace from `Resolve::path_to_top` is
+    /// looked at first, since that is usually what the user cares about.
+    fn constraining_dep<'a>(
+        resolve: &'a Resolve,
+        pkg_id: PackageId,
+        latest: &Version,
+    ) -> Option<(PackageId, &'a Dependency)> {
+        let path = resolve.path_to_top(&pkg_id);
+        let first_parent = path.get(1).map(|(parent, _)| **parent);
+        first_parent
+            .into_iter()
+            .chain(resolve.iter().filter(|id| Some(*id) != first_parent))
+            .find_map(|parent| {
+                resolve
+                    .deps_not_replaced(parent)
+                    .filter(|(id, _)| *id == pkg_id)
+                    .flat_map(|(_, deps)| deps.iter())
+                    .find(|dep| !dep.version_req().matches(latest))
+                    .map(|dep| (parent, dep))
+            })
+    }
+
     fn compare_dependency_graphs(
         previous_resolve: &Resolve,
         resolve: &Resolve,
@@ -0,0 +1,212 @@
+//! Implementation of `cargo info`.
+
+use std::collections::HashSet;
+
+use anyhow::bail;
+
+use crate::core::dependency::DepKind;
+use crate::core::{Dependency, PackageSet, Source, SourceId, SourceMap, Summary, Workspace};
+use crate::drop_println;
+use crate::ops;
+use crate::sources::{RegistrySource, SourceConfigMap};
+use crate::util::interning::InternedString;
+use crate::util::{validate_package_name, CargoResult, Config, IntoUrl};
+
+/// Displays the metadata, features, dependencies and available versions of a
+/// package published in a registry.
+///
+/// `spec` is either a package name or `name@version`, where the version may be
+/// any version requirement. Without a version, the version locked in the
+/// `Cargo.lock` of `ws` is displayed if there is one, the latest version
+/// otherwise.
+pub fn info(
+    spec: &str,
+    ws: Option<&Workspace<'_>>,
+    config: &Config,
+    index: Option<String>,
+    reg: Option<String>,
+) -> CargoResult<()> {
+    let (name, version_req) = match spec.split_once('@') {
+        Some((name, req)) => (name, Some(req)),
+        None => (spec, None),
+    };
+    validate_package_name(name, "package name", "")?;
+
+    let source_id = match (reg, index) {
+        (Some(_), Some(_)) => {
+            bail!("both `--index` and `--registry` should not be set at the same time")
+        }
+        (Some(r), None) => SourceId::alt_registry(config, &r)?,
+        (None, Some(i)) => SourceId::for_registry(&i.into_url()?)?,
+        (None, None) => SourceId::crates_io(config)?,
+    };
+
+    let _lock = config.acquire_package_cache_lock()?;
+    let replaced_id = SourceConfigMap::new(config)?
+        .load(source_id, &HashSet::new())?
+        .replaced_source_id();
+    let mut source = if replaced_id.is_remote_registry() {
+        RegistrySource::remote(replaced_id, &HashSet::new(), config)?
+    } else if replaced_id.is_registry() {
+        let path = replaced_id.url().to_file_path().unwrap();
+        RegistrySource::local(replaced_id, &path, &HashSet::new(), config)
+    } else {
+        bail!(
+            "`cargo info` requires a registry, but `{}` is replaced with `{}`",
+            source_id,
+            replaced_id
+        );
+    };
+    source.update()?;
+
+    let mut versions = source.index_versions(InternedString::new(name))?;
+    versions.sort_by(|(a, _), (b, _)| a.version().cmp(b.version()));
+    let registry_name = source_id.display_registry_name();
+    if versions.is_empty() {
+        bail!("could not find `{}` in registry `{}`", name, registry_name);
+    }
+
+    let locked = match ws {
+        Some(ws) => ops::load_pkg_lockfile(ws)?.and_then(|resolve| {
+            resolve
+                .iter()
+                .find(|id| {
+                    id.name() == name
+                        && (id.source_id() == source_id || id.source_id() == replaced_id)
+                })
+                .map(|id| id.version().clone())
+        }),
+        None => None,
+    };
+
+    // Only packages whose `.crate` file is in the cache can be displayed when
+    // offline.
+    let dep = Dependency::parse(name, version_req, replaced_id)?;
+    let candidates: Vec<&(Summary, bool)> = versions
+        .iter()
+        .filter(|(s, _)| dep.matches(s))
+        .filter(|(s, _)| !config.offline() || source.is_crate_downloaded(s.package_id()))
+        .collect();
+    let latest = |pred: &dyn Fn(&Summary, bool) -> bool| {
+        candidates
+            .iter()
+            .rev()
+            .find(|(s, yanked)| pred(s, *yanked))
+            .map(|(s, _)| s)
+    };
+    let summary = match version_req {
+        Some(_) => latest(&|_, yanked| !yanked).or_else(|| latest(&|_, _| true)),
+        None => latest(&|s, _| Some(s.version()) == locked.as_ref())
+            .or_else(|| latest(&|s, yanked| !yanked && s.version().pre.is_empty()))
+            .or_else(|| latest(&|_, yanked| !yanked)),
+    };
+    let summary = match summary {
+        Some(summary) => summary.clone(),
+        None if config.offline() && versions.iter().any(|(s, _)| dep.matches(s)) => bail!(
+            "no matching version of `{}` has been downloaded from registry `{}`, \
+             which is required to display it in offline mode",
+            spec,
+            registry_name
+        ),
+        None => bail!(
+            "could not find `{}` in registry `{}` matching `{}`",
+            name,
+            registry_name,
+            dep.version_req()
+        ),
+    };
+
+    let pkg_id = summary.package_id();
+    let mut sources = SourceMap::new();
+    sources.insert(Box::new(source));
+    let pkg_set = PackageSet::new(&[pkg_id], sources, config)?;
+    let pkg = pkg_set.get_one(pkg_id)?;
+    let metadata = pkg.manifest().metadata();
+
+    drop_println!(config, "{} v{}", pkg.name(), pkg.version());
+    if let Some(description) = &metadata.description {
+        drop_println!(config, "{}", description.trim());
+    }
+    match (&metadata.license, &metadata.license_file) {
+        (Some(license), _) => drop_println!(config, "license: {}", license),
+        (None, Some(file)) => drop_println!(config, "license: see `{}`", file),
+        (None, None) => drop_println!(config, "license: unknown"),
+    }
+    if let Some(rust_version) = pkg.rust_version() {
+        drop_println!(config, "rust-version: {}", rust_version);
+    }
+    for (key, value) in [
+        ("documentation", &metadata.documentation),
+        ("homepage", &metadata.homepage),
+        ("repository", &metadata.repository),
+    ] {
+        if let Some(value) = value {
+            drop_println!(config, "{}: {}", key, value);
+        }
+    }
+
+    let features = pkg.summary().features();
+    if !features.is_empty() {
+        drop_println!(config, "features:");
+        for (feature, enables) in features {
+            let enables: Vec<_> = enables.iter().map(|fv| format!("\"{}\"", fv)).collect();
+            drop_println!(config, "  {} = [{}]", feature, enables.join(", "));
+        }
+    }
+
+    for (kind, title) in [
+        (DepKind::Normal, "dependencies"),
+        (DepKind::Build, "build-dependencies"),
+    ] {
+        let mut deps: Vec<_> = pkg
+            .dependencies()
+            .iter()
+            .filter(|d| d.kind() == kind)
+            .collect();
+        if deps.is_empty() {
+            continue;
+        }
+        deps.sort_by_key(|d| d.name_in_toml());
+        drop_println!(config, "{}:", title);
+        for dep in deps {
+            let mut notes = Vec::new();
+            if dep.name_in_toml() != dep.package_name() {
+                notes.push(format!("package `{}`", dep.package_name()));
+            }
+            if dep.is_optional() {
+                notes.push("optional".to_string());
+            }
+            if let Some(platform) = dep.platform() {
+                notes.push(platform.to_string());
+            }
+            let notes = if notes.is_empty() {
+                String::new()
+            } else {
+                format!(" ({})", notes.join(", "))
+            };
+            drop_println!(
+                config,
+                "  {} = \"{}\"{}",
+                dep.name_in_toml(),
+                dep.version_req(),
+                notes
+            );
+        }
+    }
+
+    let versions: Vec<_> = versions
+        .iter()
+        .map(|(s, yanked)| {
+            let mut version = s.version().to_string();
+            if Some(s.version()) == locked.as_ref() {
+                version.push_str(" (locked)");
+            }
+            if *yanked {
+                version.push_str(" (yanked)");
+            }
+            version
+        })
+        .collect();
+    drop_println!(config, "versions: {}", versions.join(", "));
+    Ok(())
+}
@@ -1,4 +1,4 @@
-use std::collections::{BTreeSet, HashMap};
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::fs::{self, File};
 use std::io::prelude::*;
 use std::io::SeekFrom;
@@ -7,16 +7,18 @@ use std::rc::Rc;
 use std::sync::Arc;
 
 use crate::core::compiler::{BuildConfig, CompileMode, DefaultExecutor, Executor};
+use crate::core::dependency::DepKind;
 use crate::core::resolver::CliFeatures;
 use crate::core::{Feature, Shell, Verbosity, Workspace};
 use crate::core::{Package, PackageId, PackageSet, Resolve, Source, SourceId};
-use crate::sources::PathSource;
+use crate::sources::{registry, PathSource};
 use crate::util::errors::CargoResult;
 use crate::util::toml::TomlManifest;
 use crate::util::{self, restricted_names, Config, FileLock};
 use crate::{drop_println, ops};
 use anyhow::Context as _;
-use cargo_util::paths;
+use cargo_util::registry::make_dep_path;
+use cargo_util::{paths, Sha256};
 use flate2::read::GzDecoder;
 use flate2::{Compression, GzBuilder};
 use log::debug;
@@ -79,6 +81,18 @@ pub fn package_one(
     ws: &Workspace<'_>,
     pkg: &Package,
     opts: &PackageOpts<'_>,
+) -> CargoResult<Option<FileLock>> {
+    package_one_with_overlays(ws, pkg, opts, &HashMap::new())
+}
+
+/// Like [`package_one`], but the packages of the local registries in
+/// `local_overlays` are available when generating the lock file and
+/// verifying the package, see [`Workspace::set_local_overlays`].
+fn package_one_with_overlays(
+    ws: &Workspace<'_>,
+    pkg: &Package,
+    opts: &PackageOpts<'_>,
+    local_overlays: &HashMap<SourceId, PathBuf>,
 ) -> CargoResult<Option<FileLock>> {
     let config = ws.config();
     let mut src = PathSource::new(pkg.root(), pkg.package_id().source_id(), config);
@@ -135,11 +149,12 @@ pub fn package_one(
         .shell()
         .status("Packaging", pkg.package_id().to_string())?;
     dst.file().set_len(0)?;
-    tar(ws, pkg, ar_files, dst.file(), &filename)
+    tar(ws, pkg, ar_files, dst.file(), &filename, local_overlays)
         .with_context(|| "failed to prepare local package for uploading")?;
     if opts.verify {
         dst.seek(SeekFrom::Start(0))?;
-        run_verify(ws, pkg, &dst, opts).with_context(|| "failed to verify package tarball")?
+        run_verify(ws, pkg, &dst, opts, local_overlays)
+            .with_context(|| "failed to verify package tarball")?
     }
 
     dst.seek(SeekFrom::Start(0))?;
@@ -166,22 +181,25 @@ pub fn package(ws: &Workspace<'_>, opts: &PackageOpts<'_>) -> CargoResult<Option
         // below, and will be validated during the verification step.
     }
 
+    let package_opts = |cli_features| PackageOpts {
+        config: opts.config,
+        list: opts.list,
+        check_metadata: opts.check_metadata,
+        allow_dirty: opts.allow_dirty,
+        verify: opts.verify,
+        jobs: opts.jobs,
+        to_package: ops::Packages::Default,
+        targets: opts.targets.clone(),
+        cli_features,
+    };
+
+    if ws.config().cli_unstable().package_workspace && !opts.list {
+        let dsts = package_with_dep_graph(ws, pkgs, package_opts)?;
+        return Ok(Some(dsts.into_iter().map(|(_, dst)| dst).collect()));
+    }
+
     for (pkg, cli_features) in pkgs {
-        let result = package_one(
-            ws,
-            pkg,
-            &PackageOpts {
-                config: opts.config,
-                list: opts.list,
-                check_metadata: opts.check_metadata,
-                allow_dirty: opts.allow_dirty,
-                verify: opts.verify,
-                jobs: opts.jobs,
-                to_package: ops::Packages::Default,
-                targets: opts.targets.clone(),
-                cli_features: cli_features,
-            },
-        )?;
+        let result = package_one(ws, pkg, &package_opts(cli_features))?;
 
         if !opts.list {
             dsts.push(result.unwrap());
@@ -196,6 +214,186 @@ pub fn package(ws: &Workspace<'_>, opts: &PackageOpts<'_>) -> CargoResult<Option
     }
 }
 
+/// Packages and verifies `pkgs` in dependency order, returning each package
+/// with its tarball in that order. The options for each package are made by
+/// `package_opts` from its features.
+///
+/// Every packaged tarball is added to a temporary local registry, which is
+/// overlaid on top of the registries the packages will be published to, so
+/// that a package depending on a sibling which hasn't been published yet can
+/// still be verified.
+pub(crate) fn package_with_dep_graph<'a, 'cfg>(
+    ws: &Workspace<'_>,
+    pkgs: Vec<(&'a Package, CliFeatures)>,
+    package_opts: impl Fn(CliFeatures) -> PackageOpts<'cfg>,
+) -> CargoResult<Vec<(&'a Package, FileLock)>> {
+    let config = ws.config();
+    let pkgs = sort_by_local_deps(pkgs)?;
+
+    // The registries the packages being published will be pulled from by
+    // their dependents.
+    let mut registry_ids = HashSet::new();
+    for (pkg, _) in pkgs.iter() {
+        for dep in pkg.dependencies() {
+            if pkgs.iter().any(|(p, _)| dep.matches_id(p.package_id())) {
+                let id = match dep.registry_id() {
+                    Some(id) => id,
+                    None => SourceId::crates_io(config)?,
+                };
+                registry_ids.insert(id);
+            }
+        }
+    }
+
+    let tmp_registry = TmpRegistry::new(ws)?;
+    let local_overlays = registry_ids
+        .into_iter()
+        .map(|id| (id, tmp_registry.root.clone()))
+        .collect();
+
+    let mut dsts = Vec::with_capacity(pkgs.len());
+    for (pkg, cli_features) in pkgs {
+        let dst = package_one_with_overlays(ws, pkg, &package_opts(cli_features), &local_overlays)?
+            .unwrap();
+        tmp_registry.add_package(ws, pkg, &dst)?;
+        dsts.push((pkg, dst));
+    }
+    Ok(dsts)
+}
+
+/// Sorts `pkgs` so that every package comes after the packages of the set
+/// that it depends on. Dev-dependencies are not taken into account, since
+/// they aren't needed to verify a package.
+fn sort_by_local_deps<'a>(
+    pkgs: Vec<(&'a Package, CliFeatures)>,
+) -> CargoResult<Vec<(&'a Package, CliFeatures)>> {
+    let ids: Vec<PackageId> = pkgs.iter().map(|(pkg, _)| pkg.package_id()).collect();
+    let local_deps = |pkg: &Package| -> Vec<PackageId> {
+        pkg.dependencies()
+            .iter()
+            .filter(|dep| dep.kind() != DepKind::Development)
+            .filter_map(|dep| ids.iter().copied().find(|id| dep.matches_id(*id)))
+            .collect()
+    };
+
+    let mut done = HashSet::new();
+    let mut sorted = Vec::with_capacity(pkgs.len());
+    let mut remaining = pkgs;
+    while !remaining.is_empty() {
+        let (ready, rest): (Vec<_>, Vec<_>) = remaining
+            .into_iter()
+            .partition(|(pkg, _)| local_deps(pkg).iter().all(|id| done.contains(id)));
+        if ready.is_empty() {
+            let names = rest
+                .iter()
+                .map(|(pkg, _)| format!("`{}`", pkg.name()))
+                .collect::<Vec<_>>();
+            anyhow::bail!(
+                "cyclic dependency between the packages {}",
+                names.join(", ")
+            );
+        }
+        done.extend(ready.iter().map(|(pkg, _)| pkg.package_id()));
+        sorted.extend(ready);
+        remaining = rest;
+    }
+    Ok(sorted)
+}
+
+/// A local registry holding the packages assembled so far by
+/// [`package_with_dep_graph`].
+struct TmpRegistry {
+    root: PathBuf,
+}
+
+impl TmpRegistry {
+    fn new(ws: &Workspace<'_>) -> CargoResult<TmpRegistry> {
+        let config = ws.config();
+        let root = ws
+            .target_dir()
+            .join("package")
+            .join("tmp-registry")
+            .into_path_unlocked();
+        if root.exists() {
+            paths::remove_dir_all(&root)?;
+        }
+        paths::create_dir_all(root.join("index"))?;
+
+        // The packages of a local registry are unpacked only once, so drop
+        // the ones unpacked from a previous run, their contents may have
+        // changed since.
+        let _lock = config.acquire_package_cache_lock()?;
+        let unpacked = registry::unpacked_path(config, SourceId::for_local_registry(&root)?)
+            .into_path_unlocked();
+        if unpacked.exists() {
+            paths::remove_dir_all(&unpacked)?;
+        }
+        Ok(TmpRegistry { root })
+    }
+
+    /// Adds the package `pkg`, assembled into `tarball`, to the registry.
+    fn add_package(
+        &self,
+        ws: &Workspace<'_>,
+        pkg: &Package,
+        tarball: &FileLock,
+    ) -> CargoResult<()> {
+        let filename = format!("{}-{}.crate", pkg.name(), pkg.version());
+        let dst = self.root.join(&filename);
+        // `tarball` was moved to its final location under `filename`.
+        paths::copy(tarball.parent().join(&filename), &dst)?;
+        let cksum = Sha256::new().update_path(&dst)?.finish_hex();
+
+        // Describe the package the way it will be published, with every
+        // dependency pointing at a registry. The registry is always spelled
+        // out, since this index is overlaid on several registries.
+        let new_pkg = published_package(ws, pkg)?;
+        let deps = new_pkg
+            .dependencies()
+            .iter()
+            .map(|dep| {
+                let (name, package) = match dep.explicit_name_in_toml() {
+                    Some(name) => (name, Some(dep.package_name())),
+                    None => (dep.package_name(), None),
+                };
+                serde_json::json!({
+                    "name": name,
+                    "req": dep.version_req().to_string(),
+                    "features": dep.features(),
+                    "optional": dep.is_optional(),
+                    "default_features": dep.uses_default_features(),
+                    "target": dep.platform().map(|p| p.to_string()),
+                    "kind": match dep.kind() {
+                        DepKind::Normal => "normal",
+                        DepKind::Build => "build",
+                        DepKind::Development => "dev",
+                    },
+                    "registry": dep.source_id().url().to_string(),
+                    "package": package,
+                })
+            })
+            .collect::<Vec<_>>();
+        let line = serde_json::json!({
+            "name": pkg.name(),
+            "vers": pkg.version().to_string(),
+            "deps": deps,
+            "cksum": cksum,
+            "features": new_pkg.manifest().original().features().cloned().unwrap_or_default(),
+            "yanked": false,
+            "links": pkg.manifest().links(),
+            "rust_version": pkg.rust_version(),
+        });
+
+        let index_file = self
+            .root
+            .join("index")
+            .join(make_dep_path(&pkg.name(), false));
+        paths::create_dir_all(index_file.parent().unwrap())?;
+        paths::append(&index_file, format!("{}\n", line).as_bytes())?;
+        Ok(())
+    }
+}
+
 /// Builds list of files to archive.
 fn build_ar_list(
     ws: &Workspace<'_>,
@@ -319,11 +517,9 @@ fn build_ar_list(
     Ok(result)
 }
 
-/// Construct `Cargo.lock` for the package to be published.
-fn build_lock(ws: &Workspace<'_>, orig_pkg: &Package) -> CargoResult<String> {
-    let config = ws.config();
-    let orig_resolve = ops::load_pkg_lockfile(ws)?;
-
+/// Converts `orig_pkg` into the package that will be published, where path
+/// dependencies have been replaced with registry dependencies.
+fn published_package(ws: &Workspace<'_>, orig_pkg: &Package) -> CargoResult<Package> {
     // Convert Package -> TomlManifest -> Manifest -> Package
     let toml_manifest = Rc::new(
         orig_pkg
@@ -334,11 +530,23 @@ fn build_lock(ws: &Workspace<'_>, orig_pkg: &Package) -> CargoResult<String> {
     let package_root = orig_pkg.root();
     let source_id = orig_pkg.package_id().source_id();
     let (manifest, _nested_paths) =
-        TomlManifest::to_real_manifest(&toml_manifest, source_id, package_root, config)?;
-    let new_pkg = Package::new(manifest, orig_pkg.manifest_path());
+        TomlManifest::to_real_manifest(&toml_manifest, source_id, package_root, ws.config())?;
+    Ok(Package::new(manifest, orig_pkg.manifest_path()))
+}
+
+/// Construct `Cargo.lock` for the package to be published.
+fn build_lock(
+    ws: &Workspace<'_>,
+    orig_pkg: &Package,
+    local_overlays: &HashMap<SourceId, PathBuf>,
+) -> CargoResult<String> {
+    let config = ws.config();
+    let orig_resolve = ops::load_pkg_lockfile(ws)?;
+    let new_pkg = published_package(ws, orig_pkg)?;
 
     // Regenerate Cargo.lock using the old one as a guide.
-    let tmp_ws = Workspace::ephemeral(new_pkg, ws.config(), None, true)?;
+    let mut tmp_ws = Workspace::ephemeral(new_pkg, ws.config(), None, true)?;
+    tmp_ws.set_local_overlays(local_overlays.clone());
     let (pkg_set, mut new_resolve) = ops::resolve_ws(&tmp_ws)?;
 
     if let Some(orig_resolve) = orig_resolve {
@@ -537,6 +745,7 @@ fn tar(
     ar_files: Vec<ArchiveFile>,
     dst: &File,
     filename: &str,
+    local_overlays: &HashMap<SourceId, PathBuf>,
 ) -> CargoResult<()> {
     // Prepare the encoder and its header.
     let filename = Path::new(filename);
@@ -579,7 +788,7 @@ fn tar(
             FileContents::Generated(generated_kind) => {
                 let contents = match generated_kind {
                     GeneratedFile::Manifest => pkg.to_registry_toml(ws)?,
-                    GeneratedFile::Lockfile => build_lock(ws, pkg)?,
+                    GeneratedFile::Lockfile => build_lock(ws, pkg, local_overlays)?,
                     GeneratedFile::VcsInfo(ref s) => serde_json::to_string_pretty(s)?,
                 };
                 header.set_entry_type(EntryType::file());
@@ -712,6 +921,7 @@ fn run_verify(
     pkg: &Package,
     tar: &FileLock,
     opts: &PackageOpts<'_>,
+    local_overlays: &HashMap<SourceId, PathBuf>,
 ) -> CargoResult<()> {
     let config = ws.config();
 
@@ -736,7 +946,8 @@ fn run_verify(
     let mut src = PathSource::new(&dst, id, ws.config());
     let new_pkg = src.root_package()?;
     let pkg_fingerprint = hash_all(&dst)?;
-    let ws = Workspace::ephemeral(new_pkg, config, None, true)?;
+    let mut ws = Workspace::ephemeral(new_pkg, config, None, true)?;
+    ws.set_local_overlays(local_overlays.clone());
 
     let rustc_args = if pkg
         .manifest()
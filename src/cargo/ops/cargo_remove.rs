@@ -0,0 +1,52 @@
+//! Core of the `cargo remove` command.
+
+use crate::core::Package;
+use crate::util::errors::CargoResult;
+use crate::util::toml_mut::manifest::{DepTable, LocalManifest};
+use crate::util::Config;
+
+/// Information on what dependencies should be removed.
+pub struct RemoveOptions<'a> {
+    /// Configuration information for cargo operations.
+    pub config: &'a Config,
+    /// Package to remove dependencies from.
+    pub spec: &'a Package,
+    /// Dependencies to remove.
+    pub dependencies: Vec<String>,
+    /// Which dependency section to remove these from.
+    pub section: DepTable,
+    /// Whether or not to actually write the manifest.
+    pub dry_run: bool,
+}
+
+/// Executes `cargo remove`.
+pub fn remove(options: &RemoveOptions<'_>) -> CargoResult<()> {
+    let dep_table = options
+        .section
+        .to_table()
+        .into_iter()
+        .map(String::from)
+        .collect::<Vec<_>>();
+
+    let manifest_path = options.spec.manifest_path().to_path_buf();
+    let mut manifest = LocalManifest::try_new(&manifest_path)?;
+
+    for dep in &options.dependencies {
+        options
+            .config
+            .shell()
+            .status("Removing", format!("{} from {}", dep, options.section))?;
+        manifest.remove_from_table(&dep_table, dep)?;
+    }
+
+    if options.dry_run {
+        options
+            .config
+            .shell()
+            .warn("aborting remove due to dry run")?;
+    } else {
+        manifest.write()?;
+    }
+
+    Ok(())
+}
@@ -1,4 +1,5 @@
 use std::io::prelude::*;
+use std::path::Path;
 
 use crate::core::{resolver, Resolve, ResolveVersion, Workspace};
 use crate::util::errors::CargoResult;
@@ -20,14 +21,80 @@ pub fn load_pkg_lockfile(ws: &Workspace<'_>) -> CargoResult<Option<Resolve>> {
         .with_context(|| format!("failed to read file: {}", f.path().display()))?;
 
     let resolve = (|| -> CargoResult<Option<Resolve>> {
-        let resolve: toml::Value = cargo_toml::parse(&s, f.path(), ws.config())?;
-        let v: resolver::EncodableResolve = resolve.try_into()?;
-        Ok(Some(v.into_resolve(&s, ws)?))
+        // A lock file with merge conflicts isn't valid TOML, but each side of
+        // the conflict is. Rather than failing, combine both sides so the
+        // resolver prefers whatever either of them locked, which then gets
+        // written back as a clean lock file.
+        if let Some((ours, theirs)) = split_merge_conflict(&s) {
+            let mut resolve = parse_lockfile(&ours, f.path(), ws)?;
+            resolve.merge_conflicting(&parse_lockfile(&theirs, f.path(), ws)?)?;
+            ws.config().shell().warn(format!(
+                "`{}` has merge conflicts, resolving them from both sides",
+                f.path().display()
+            ))?;
+            return Ok(Some(resolve));
+        }
+        Ok(Some(parse_lockfile(&s, f.path(), ws)?))
     })()
     .with_context(|| format!("failed to parse lock file at: {}", f.path().display()))?;
     Ok(resolve)
 }
 
+fn parse_lockfile(s: &str, path: &Path, ws: &Workspace<'_>) -> CargoResult<Resolve> {
+    let resolve: toml::Value = cargo_toml::parse(s, path, ws.config())?;
+    let v: resolver::EncodableResolve = resolve.try_into()?;
+    v.into_resolve(s, ws)
+}
+
+/// Splits a lock file containing git merge conflict markers into "our" and
+/// "their" side of the conflicts. The common ancestor section of the `diff3`
+/// conflict style is dropped.
+///
+/// Returns `None` if there are no conflict markers, or if they are unbalanced.
+fn split_merge_conflict(s: &str) -> Option<(String, String)> {
+    #[derive(PartialEq)]
+    enum Section {
+        Both,
+        Ours,
+        Base,
+        Theirs,
+    }
+
+    let mut section = Section::Both;
+    let mut found = false;
+    let mut ours = String::new();
+    let mut theirs = String::new();
+    for line in s.lines() {
+        section = if line.starts_with("<<<<<<<") {
+            if section != Section::Both {
+                return None;
+            }
+            found = true;
+            Section::Ours
+        } else if line.starts_with("|||||||") && section == Section::Ours {
+            Section::Base
+        } else if line.starts_with("=======") && section != Section::Both {
+            Section::Theirs
+        } else if line.starts_with(">>>>>>>") && section == Section::Theirs {
+            Section::Both
+        } else {
+            if matches!(section, Section::Both | Section::Ours) {
+                ours.push_str(line);
+                ours.push('\n');
+            }
+            if matches!(section, Section::Both | Section::Theirs) {
+                theirs.push_str(line);
+                theirs.push('\n');
+            }
+            continue;
+        };
+    }
+    if !found || section != Section::Both {
+        return None;
+    }
+    Some((ours, theirs))
+}
+
 /// Generate a toml String of Cargo.lock from a Resolve.
 pub fn resolve_to_string(ws: &Workspace<'_>, resolve: &mut Resolve) -> CargoResult<String> {
     let (_orig, out, _ws_root) = resolve_to_string_orig(ws, resolve);
@@ -35,6 +102,12 @@ pub fn resolve_to_string(ws: &Workspace<'_>, resolve: &mut Resolve) -> CargoResu
 }
 
 pub fn write_pkg_lockfile(ws: &Workspace<'_>, resolve: &mut Resolve) -> CargoResult<()> {
+    // Opting into the next lock file format is an explicit request to use
+    // it, so the lock file is rewritten even if nothing else changed.
+    if ws.config().cli_unstable().next_lockfile_bump && resolve.version() < ResolveVersion::V4 {
+        resolve.set_version(ResolveVersion::V4);
+    }
+
     let (orig, mut out, ws_root) = resolve_to_string_orig(ws, resolve);
 
     // If the lock file contents haven't changed so don't rewrite it. This is
@@ -197,6 +270,9 @@ fn emit_package(dep: &toml::value::Table, out: &mut String) {
     if dep.contains_key("source") {
         out.push_str(&format!("source = {}\n", &dep["source"]));
     }
+    if dep.contains_key("rev") {
+        out.push_str(&format!("rev = {}\n", &dep["rev"]));
+    }
     if dep.contains_key("checksum") {
         out.push_str(&format!("checksum = {}\n", &dep["checksum"]));
     }
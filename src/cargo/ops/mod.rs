@@ -1,5 +1,6 @@
 use crate::sources::CRATES_IO_DOMAIN;
 
+pub use self::cargo_add::{add, AddOptions, DepOp};
 pub use self::cargo_clean::{clean, CleanOptions};
 pub use self::cargo_compile::{
     compile, compile_with_exec, compile_ws, create_bcx, print, resolve_all_features, CompileOptions,
@@ -10,12 +11,14 @@ pub use self::cargo_fetch::{fetch, FetchOptions};
 pub use self::cargo_generate_lockfile::generate_lockfile;
 pub use self::cargo_generate_lockfile::update_lockfile;
 pub use self::cargo_generate_lockfile::UpdateOptions;
+pub use self::cargo_info::info;
 pub use self::cargo_install::{install, install_list};
 pub use self::cargo_new::{init, new, NewOptions, VersionControl};
 pub use self::cargo_output_metadata::{output_metadata, ExportInfo, OutputMetadataOptions};
 pub use self::cargo_package::{package, package_one, PackageOpts};
 pub use self::cargo_pkgid::pkgid;
 pub use self::cargo_read_manifest::{read_package, read_packages};
+pub use self::cargo_remove::{remove, RemoveOptions};
 pub use self::cargo_run::run;
 pub use self::cargo_test::{run_benches, run_tests, TestOptions};
 pub use self::cargo_uninstall::uninstall;
@@ -32,18 +35,21 @@ pub use self::resolve::{
 };
 pub use self::vendor::{vendor, VendorOptions};
 
+mod cargo_add;
 mod cargo_clean;
 mod cargo_compile;
 pub mod cargo_config;
 mod cargo_doc;
 mod cargo_fetch;
 mod cargo_generate_lockfile;
+mod cargo_info;
 mod cargo_install;
 mod cargo_new;
 mod cargo_output_metadata;
 mod cargo_package;
 mod cargo_pkgid;
 mod cargo_read_manifest;
+mod cargo_remove;
 mod cargo_run;
 mod cargo_test;
 mod cargo_uninstall;
@@ -1,10 +1,10 @@
-use std::collections::{BTreeMap, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashSet};
 use std::fs::File;
 use std::io::{self, BufRead};
 use std::iter::repeat;
 use std::path::PathBuf;
 use std::str;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use std::{cmp, env};
 
 use anyhow::{bail, format_err, Context as _};
@@ -18,7 +18,7 @@ use crate::core::dependency::DepKind;
 use crate::core::manifest::ManifestMetadata;
 use crate::core::resolver::CliFeatures;
 use crate::core::source::Source;
-use crate::core::{Package, SourceId, Workspace};
+use crate::core::{Dependency, Package, SourceId, Workspace};
 use crate::ops;
 use crate::sources::{RegistrySource, SourceConfigMap, CRATES_IO_DOMAIN, CRATES_IO_REGISTRY};
 use crate::util::config::{self, Config, SslVersionConfig, SslVersionConfigRange};
@@ -60,15 +60,45 @@ pub struct PublishOpts<'cfg> {
 pub fn publish(ws: &Workspace<'_>, opts: &PublishOpts<'_>) -> CargoResult<()> {
     let specs = opts.to_publish.to_package_id_specs(ws)?;
     let mut pkgs = ws.members_with_features(&specs, &opts.cli_features)?;
-
-    let (pkg, cli_features) = pkgs.pop().unwrap();
+    if let ops::Packages::All | ops::Packages::OptOut(_) = opts.to_publish {
+        // Packages that can't be published at all are skipped when
+        // publishing the whole workspace.
+        pkgs.retain(|(pkg, _)| !matches!(pkg.publish(), Some(registries) if registries.is_empty()));
+    }
+    if pkgs.is_empty() {
+        bail!("no packages to publish in the workspace");
+    }
+    if pkgs.len() > 1 && !opts.config.cli_unstable().package_workspace {
+        bail!(
+            "publishing multiple packages requires `-Z package-workspace`\n\
+             select a single package to publish with `-p`"
+        );
+    }
 
     let mut publish_registry = opts.registry.clone();
-    if let Some(ref allowed_registries) = *pkg.publish() {
-        if publish_registry.is_none() && allowed_registries.len() == 1 {
-            // If there is only one allowed registry, push to that one directly,
-            // even though there is no registry specified in the command.
-            let default_registry = &allowed_registries[0];
+    if publish_registry.is_none() {
+        // If there is only one allowed registry, push to that one directly,
+        // even though there is no registry specified in the command.
+        let default_registries: BTreeSet<_> = pkgs
+            .iter()
+            .filter_map(|(pkg, _)| match pkg.publish() {
+                Some(registries) if registries.len() == 1 => Some(&registries[0]),
+                _ => None,
+            })
+            .collect();
+        if default_registries.len() > 1 {
+            bail!(
+                "the packages to publish have different registries in their `publish` \
+                 values: `{}`\n\
+                 select a registry to publish to with `--registry`",
+                default_registries
+                    .iter()
+                    .map(|r| r.as_str())
+                    .collect::<Vec<_>>()
+                    .join("`, `")
+            );
+        }
+        if let Some(default_registry) = default_registries.into_iter().next() {
             if default_registry != CRATES_IO_REGISTRY {
                 // Don't change the registry for crates.io and don't warn the user.
                 // crates.io will be defaulted even without this.
@@ -79,17 +109,20 @@ pub fn publish(ws: &Workspace<'_>, opts: &PublishOpts<'_>) -> CargoResult<()> {
                 publish_registry = Some(default_registry.clone());
             }
         }
-
-        let reg_name = publish_registry
-            .clone()
-            .unwrap_or_else(|| CRATES_IO_REGISTRY.to_string());
-        if !allowed_registries.contains(&reg_name) {
-            bail!(
-                "`{}` cannot be published.\n\
-                 The registry `{}` is not listed in the `publish` value in Cargo.toml.",
-                pkg.name(),
-                reg_name
-            );
+    }
+    let reg_name = publish_registry
+        .clone()
+        .unwrap_or_else(|| CRATES_IO_REGISTRY.to_string());
+    for (pkg, _) in pkgs.iter() {
+        if let Some(ref allowed_registries) = *pkg.publish() {
+            if !allowed_registries.contains(&reg_name) {
+                bail!(
+                    "`{}` cannot be published.\n\
+                     The registry `{}` is not listed in the `publish` value in Cargo.toml.",
+                    pkg.name(),
+                    reg_name
+                );
+            }
         }
     }
 
@@ -101,48 +134,115 @@ pub fn publish(ws: &Workspace<'_>, opts: &PublishOpts<'_>) -> CargoResult<()> {
         true,
         !opts.dry_run,
     )?;
-    verify_dependencies(pkg, &registry, reg_id)?;
+    for (pkg, _) in pkgs.iter() {
+        verify_dependencies(pkg, &registry, reg_id)?;
+    }
 
-    // Prepare a tarball, with a non-suppressible warning if metadata
+    // Prepare the tarballs, with a non-suppressible warning if metadata
     // is missing since this is being put online.
-    let tarball = ops::package_one(
-        ws,
-        pkg,
-        &ops::PackageOpts {
-            config: opts.config,
-            verify: opts.verify,
-            list: false,
-            check_metadata: true,
-            allow_dirty: opts.allow_dirty,
-            to_package: ops::Packages::Default,
-            targets: opts.targets.clone(),
-            jobs: opts.jobs,
-            cli_features: cli_features,
-        },
-    )?
-    .unwrap();
-
-    opts.config
-        .shell()
-        .status("Uploading", pkg.package_id().to_string())?;
-    transmit(
-        opts.config,
-        pkg,
-        tarball.file(),
-        &mut registry,
-        reg_id,
-        opts.dry_run,
-    )?;
+    let package_opts = |cli_features| ops::PackageOpts {
+        config: opts.config,
+        verify: opts.verify,
+        list: false,
+        check_metadata: true,
+        allow_dirty: opts.allow_dirty,
+        to_package: ops::Packages::Default,
+        targets: opts.targets.clone(),
+        jobs: opts.jobs,
+        cli_features,
+    };
+    let multiple = pkgs.len() > 1;
+    let tarballs = if multiple {
+        // All packages are assembled and verified before anything is
+        // uploaded, so a failure doesn't leave a partially published set.
+        super::cargo_package::package_with_dep_graph(ws, pkgs, package_opts)?
+    } else {
+        let (pkg, cli_features) = pkgs.pop().unwrap();
+        let tarball = ops::package_one(ws, pkg, &package_opts(cli_features))?.unwrap();
+        vec![(pkg, tarball)]
+    };
+
+    for (pkg, tarball) in tarballs.iter() {
+        opts.config
+            .shell()
+            .status("Uploading", pkg.package_id().to_string())?;
+        transmit(
+            opts.config,
+            pkg,
+            tarball.file(),
+            &mut registry,
+            reg_id,
+            opts.dry_run,
+        )?;
+        // The packages published next may depend on this one, so it has to
+        // be in the index before they can be uploaded.
+        if multiple && !opts.dry_run {
+            wait_for_publish(opts.config, reg_id, pkg)?;
+        }
+    }
 
     Ok(())
 }
 
+/// Polls the index of the registry `registry_src` until `pkg` shows up in
+/// it, or until `publish.timeout` seconds have passed.
+fn wait_for_publish(config: &Config, registry_src: SourceId, pkg: &Package) -> CargoResult<()> {
+    let timeout = Duration::from_secs(config.get::<Option<u64>>("publish.timeout")?.unwrap_or(60));
+    let query = Dependency::parse(
+        pkg.name(),
+        Some(&format!("={}", pkg.version())),
+        registry_src,
+    )?;
+    config.shell().status(
+        "Waiting",
+        format!(
+            "for `{} v{}` to be available in registry `{}`",
+            pkg.name(),
+            pkg.version(),
+            registry_src.display_registry_name()
+        ),
+    )?;
+
+    let start = Instant::now();
+    loop {
+        {
+            let _lock = config.acquire_package_cache_lock()?;
+            // Force the index to be fetched again.
+            config.updated_sources().remove(&registry_src);
+            let mut src = RegistrySource::remote(registry_src, &HashSet::new(), config)?;
+            src.update()?;
+            if !src.query_vec(&query)?.is_empty() {
+                return Ok(());
+            }
+        }
+        if start.elapsed() >= timeout {
+            config.shell().warn(format!(
+                "timed out waiting for `{} v{}` to be available in registry `{}`\n\
+                 The packages that depend on it may fail to be published until it is.",
+                pkg.name(),
+                pkg.version(),
+                registry_src.display_registry_name()
+            ))?;
+            return Ok(());
+        }
+        std::thread::sleep(Duration::from_secs(1));
+    }
+}
+
 fn verify_dependencies(
     pkg: &Package,
     registry: &Registry,
     registry_src: SourceId,
 ) -> CargoResult<()> {
     for dep in pkg.dependencies().iter() {
+        // The registry index can't describe artifact dependencies yet.
+        if dep.artifact().is_some() {
+            bail!(
+                "artifact dependencies cannot be published yet, \
+                 `{}` needs to be a regular dependency",
+                dep.name_in_toml()
+            );
+        }
         if super::check_dep_has_version(dep, true)? {
             continue;
         }
@@ -287,6 +387,7 @@ fn transmit(
                 license_file: license_file.clone(),
                 badges: badges.clone(),
                 links: links.clone(),
+                rust_version: pkg.rust_version().map(|v| v.to_owned()),
                 v: None,
             },
             tarball,
@@ -430,7 +531,7 @@ fn registry(
     }
     let api_host = {
         let _lock = config.acquire_package_cache_lock()?;
-        let mut src = RegistrySource::remote(sid, &HashSet::new(), config);
+        let mut src = RegistrySource::remote(sid, &HashSet::new(), config)?;
         // Only update the index if the config is not available or `force` is set.
         let cfg = src.config();
         let mut updated_cfg = || {
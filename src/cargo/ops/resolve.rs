@@ -25,6 +25,7 @@ use crate::core::{
 };
 use crate::ops;
 use crate::sources::PathSource;
+use crate::util::config::RustVersionPolicy;
 use crate::util::errors::CargoResult;
 use crate::util::{profile, CanonicalUrl};
 use anyhow::Context as _;
@@ -63,7 +64,7 @@ version. This may also occur with an optional dependency that is not enabled.";
 /// This is a simple interface used by commands like `clean`, `fetch`, and
 /// `package`, which don't specify any options or features.
 pub fn resolve_ws<'a>(ws: &Workspace<'a>) -> CargoResult<(PackageSet<'a>, Resolve)> {
-    let mut registry = PackageRegistry::new(ws.config())?;
+    let mut registry = ws.package_registry()?;
     let resolve = resolve_with_registry(ws, &mut registry)?;
     let packages = get_resolved_packages(&resolve, registry)?;
     Ok((packages, resolve))
@@ -81,14 +82,14 @@ pub fn resolve_ws<'a>(ws: &Workspace<'a>) -> CargoResult<(PackageSet<'a>, Resolv
 /// members. In this case, `opts.all_features` must be `true`.
 pub fn resolve_ws_with_opts<'cfg>(
     ws: &Workspace<'cfg>,
-    target_data: &RustcTargetData<'cfg>,
+    target_data: &mut RustcTargetData<'cfg>,
     requested_targets: &[CompileKind],
     cli_features: &CliFeatures,
     specs: &[PackageIdSpec],
     has_dev_units: HasDevUnits,
     force_all_targets: ForceAllTargets,
 ) -> CargoResult<WorkspaceResolve<'cfg>> {
-    let mut registry = PackageRegistry::new(ws.config())?;
+    let mut registry = ws.package_registry()?;
     let mut add_patches = true;
     let resolve = if ws.ignore_lock() {
         None
@@ -145,6 +146,23 @@ pub fn resolve_ws_with_opts<'cfg>(
 
     let pkg_set = get_resolved_packages(&resolved_with_overrides, registry)?;
 
+    // Artifact dependencies may be built for targets that weren't requested,
+    // so those are downloaded and have their features resolved as well.
+    let mut requested_targets = requested_targets.to_vec();
+    let artifact_kinds = resolved_with_overrides
+        .iter()
+        .flat_map(|pkg_id| resolved_with_overrides.deps(pkg_id))
+        .flat_map(|(_, deps)| deps)
+        .filter_map(|dep| dep.artifact()?.target()?.to_compile_target())
+        .map(CompileKind::Target)
+        .collect::<Vec<_>>();
+    for kind in artifact_kinds {
+        if !requested_targets.contains(&kind) {
+            target_data.merge_compile_kind(kind)?;
+            requested_targets.push(kind);
+        }
+    }
+
     let member_ids = ws
         .members_with_features(specs, cli_features)?
         .into_iter()
@@ -154,7 +172,7 @@ pub fn resolve_ws_with_opts<'cfg>(
         &resolved_with_overrides,
         &member_ids,
         has_dev_units,
-        requested_targets,
+        &requested_targets,
         target_data,
         force_all_targets,
     )?;
@@ -167,7 +185,7 @@ pub fn resolve_ws_with_opts<'cfg>(
         &pkg_set,
         cli_features,
         specs,
-        requested_targets,
+        &requested_targets,
         feature_opts,
     )?;
 
@@ -175,7 +193,7 @@ pub fn resolve_ws_with_opts<'cfg>(
         &resolved_with_overrides,
         &member_ids,
         has_dev_units,
-        requested_targets,
+        &requested_targets,
         target_data,
         force_all_targets,
     );
@@ -276,6 +294,17 @@ pub fn resolve_with_previous<'cfg>(
     // of various packages.
     let mut version_prefs = VersionPreferences::default();
 
+    if ws.config().cli_unstable().msrv_policy {
+        let policy = ws
+            .config()
+            .resolver_config()?
+            .rust_version_policy
+            .unwrap_or(RustVersionPolicy::Preferred);
+        if policy != RustVersionPolicy::Off {
+            version_prefs.max_rust_version(target_rust_version(ws)?, policy);
+        }
+    }
+
     // This is a set of PackageIds of `[patch]` entries, and some related locked PackageIds, for
     // which locking should be avoided (but which will be preferred when searching dependencies,
     // via prefer_patch_deps below)
@@ -825,3 +854,37 @@ fn emit_warnings_of_unused_patches(
 
     return Ok(());
 }
+
+/// The Rust version dependencies are resolved for with `-Z msrv-policy`.
+///
+/// This is the oldest `rust-version` declared by a workspace member, or the
+/// version of the active rustc if none of them declare one.
+fn target_rust_version(ws: &Workspace<'_>) -> CargoResult<semver::Version> {
+    let oldest = ws
+        .members()
+        .filter_map(|pkg| pkg.rust_version())
+        .map(|rust_version| {
+            // `rust-version` is validated to be a bare `major[.minor[.patch]]`.
+            let full = match rust_version.matches('.').count() {
+                0 => format!("{}.0.0", rust_version),
+                1 => format!("{}.0", rust_version),
+                _ => rust_version.to_string(),
+            };
+            semver::Version::parse(&full)
+                .with_context(|| format!("invalid `rust-version` `{}`", rust_version))
+        })
+        .collect::<CargoResult<Vec<_>>>()?
+        .into_iter()
+        .min();
+    if let Some(oldest) = oldest {
+        return Ok(oldest);
+    }
+    // Remove any pre-release identifiers, so nightly toolchains are treated
+    // as the release they will become.
+    let rustc = ws.config().load_global_rustc(Some(ws))?;
+    Ok(semver::Version::new(
+        rustc.version.major,
+        rustc.version.minor,
+        rustc.version.patch,
+    ))
+}
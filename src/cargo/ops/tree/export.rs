@@ -0,0 +1,233 @@
+//! Code for printing the graph of `cargo tree` as JSON or DOT, with
+//! `--format json` and `--format dot`.
+//!
+//! Unlike the tree, where a package is displayed once for every path to it,
+//! these list every node of the graph once, along with the edges between
+//! them. The same nodes and edges the tree would display are included, so
+//! `--invert`, `--prune`, `--depth` and `-e` apply. With `--invert`, edges
+//! point from a package to the packages depending on it.
+
+use super::format::Pattern;
+use super::graph::{EdgeKind, Graph, Node};
+use super::{is_filtered_out, TreeOptions};
+use crate::core::compiler::CompileKind;
+use crate::core::dependency::DepKind;
+use crate::core::{PackageId, PackageIdSpec};
+use crate::drop_println;
+use crate::util::interning::InternedString;
+use crate::util::{CargoResult, Config};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet, VecDeque};
+
+/// Version of the JSON output, bumped on incompatible changes.
+const VERSION: u32 = 1;
+
+#[derive(Serialize)]
+struct SerializedGraph<'a> {
+    version: u32,
+    roots: Vec<usize>,
+    nodes: Vec<SerializedNode<'a>>,
+    edges: Vec<SerializedEdge>,
+}
+
+#[derive(Serialize)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+enum SerializedNode<'a> {
+    Package {
+        id: PackageId,
+        name: InternedString,
+        version: String,
+        features: &'a [InternedString],
+        platform: CompileKind,
+        proc_macro: bool,
+        /// Whether other versions of the package are in the graph too, like
+        /// `--duplicates` would report.
+        duplicate: bool,
+    },
+    Feature {
+        package: PackageId,
+        name: InternedString,
+        /// Whether the feature was enabled on the command-line.
+        cli: bool,
+    },
+}
+
+#[derive(Serialize)]
+struct SerializedEdge {
+    from: usize,
+    to: usize,
+    kind: &'static str,
+}
+
+/// The nodes and edges to print, where nodes are indexes into the graph, and
+/// edges are indexes into `nodes`.
+struct Subgraph {
+    nodes: Vec<usize>,
+    edges: Vec<(usize, usize, EdgeKind)>,
+}
+
+/// Collects the nodes reachable from the roots, along with the edges between
+/// them, with the same filters as the tree.
+fn collect(
+    opts: &TreeOptions,
+    roots: &[usize],
+    pkgs_to_prune: &[PackageIdSpec],
+    graph: &Graph<'_>,
+) -> Subgraph {
+    let mut nodes = Vec::new();
+    let mut edges = Vec::new();
+    // Maps a graph index to its index in `nodes`.
+    let mut remap = HashMap::new();
+    // Nodes are visited breadth-first so that `--depth` counts the shortest
+    // path from a root.
+    let mut queue = VecDeque::new();
+    for &root in roots {
+        if !remap.contains_key(&root) {
+            remap.insert(root, nodes.len());
+            nodes.push(root);
+            queue.push_back((root, 0));
+        }
+    }
+    while let Some((index, depth)) = queue.pop_front() {
+        if depth >= opts.max_display_depth {
+            continue;
+        }
+        for kind in &[
+            EdgeKind::Dep(DepKind::Normal),
+            EdgeKind::Dep(DepKind::Build),
+            EdgeKind::Dep(DepKind::Development),
+            EdgeKind::Feature,
+        ] {
+            for dep in graph.connected_nodes(index, kind) {
+                if is_filtered_out(graph, dep, pkgs_to_prune, opts.no_proc_macro) {
+                    continue;
+                }
+                let to = *remap.entry(dep).or_insert_with(|| {
+                    nodes.push(dep);
+                    queue.push_back((dep, depth + 1));
+                    nodes.len() - 1
+                });
+                edges.push((remap[&index], to, *kind));
+            }
+        }
+    }
+    Subgraph { nodes, edges }
+}
+
+fn edge_kind_name(kind: EdgeKind) -> &'static str {
+    match kind {
+        EdgeKind::Dep(DepKind::Normal) => "normal",
+        EdgeKind::Dep(DepKind::Build) => "build",
+        EdgeKind::Dep(DepKind::Development) => "dev",
+        EdgeKind::Feature => "feature",
+    }
+}
+
+/// Prints the graph reachable from the roots as JSON.
+pub fn print_json(
+    config: &Config,
+    opts: &TreeOptions,
+    roots: &[usize],
+    pkgs_to_prune: &[PackageIdSpec],
+    graph: &Graph<'_>,
+) -> CargoResult<()> {
+    let subgraph = collect(opts, roots, pkgs_to_prune, graph);
+
+    let mut versions: HashMap<InternedString, HashSet<PackageId>> = HashMap::new();
+    for &index in &subgraph.nodes {
+        if let Node::Package { package_id, .. } = graph.node(index) {
+            versions
+                .entry(package_id.name())
+                .or_default()
+                .insert(*package_id);
+        }
+    }
+
+    let nodes = subgraph
+        .nodes
+        .iter()
+        .map(|&index| match graph.node(index) {
+            Node::Package {
+                package_id,
+                features,
+                kind,
+            } => SerializedNode::Package {
+                id: *package_id,
+                name: package_id.name(),
+                version: package_id.version().to_string(),
+                features,
+                platform: *kind,
+                proc_macro: graph.package_for_id(*package_id).proc_macro(),
+                duplicate: versions[&package_id.name()].len() > 1,
+            },
+            Node::Feature { node_index, name } => SerializedNode::Feature {
+                package: match graph.node(*node_index) {
+                    Node::Package { package_id, .. } => *package_id,
+                    Node::Feature { .. } => panic!("feature node for a feature node"),
+                },
+                name: *name,
+                cli: graph.is_cli_feature(index),
+            },
+        })
+        .collect();
+    let edges = subgraph
+        .edges
+        .iter()
+        .map(|&(from, to, kind)| SerializedEdge {
+            from,
+            to,
+            kind: edge_kind_name(kind),
+        })
+        .collect();
+
+    config.shell().print_json(&SerializedGraph {
+        version: VERSION,
+        roots: (0..roots.len()).collect(),
+        nodes,
+        edges,
+    })
+}
+
+/// Prints the graph reachable from the roots in the DOT language of graphviz.
+pub fn print_dot(
+    config: &Config,
+    opts: &TreeOptions,
+    roots: &[usize],
+    pkgs_to_prune: &[PackageIdSpec],
+    graph: &Graph<'_>,
+) -> CargoResult<()> {
+    let subgraph = collect(opts, roots, pkgs_to_prune, graph);
+    let label = Pattern::new("{p}")?;
+
+    drop_println!(config, "digraph {{");
+    for (i, &index) in subgraph.nodes.iter().enumerate() {
+        let text = escape(&label.display(graph, index).to_string());
+        match graph.node(index) {
+            Node::Package { .. } => drop_println!(config, "    {} [label=\"{}\"];", i, text),
+            Node::Feature { .. } => {
+                drop_println!(config, "    {} [label=\"{}\", shape=box];", i, text)
+            }
+        }
+    }
+    for &(from, to, kind) in &subgraph.edges {
+        match kind {
+            EdgeKind::Dep(DepKind::Build) | EdgeKind::Dep(DepKind::Development) => drop_println!(
+                config,
+                "    {} -> {} [label=\"{}\", style=dashed];",
+                from,
+                to,
+                edge_kind_name(kind)
+            ),
+            EdgeKind::Dep(DepKind::Normal) | EdgeKind::Feature => {
+                drop_println!(config, "    {} -> {};", from, to)
+            }
+        }
+    }
+    drop_println!(config, "}}");
+    Ok(())
+}
+
+/// Escapes a string for a quoted DOT ID.
+fn escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
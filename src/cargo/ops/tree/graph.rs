@@ -126,6 +126,14 @@ impl<'a> Graph<'a> {
         &self.nodes[index]
     }
 
+    /// Returns the IDs of all packages in the graph.
+    pub fn package_ids(&self) -> impl Iterator<Item = PackageId> + '_ {
+        self.nodes.iter().filter_map(|node| match node {
+            Node::Package { package_id, .. } => Some(*package_id),
+            Node::Feature { .. } => None,
+        })
+    }
+
     /// Given a slice of PackageIds, returns the indexes of all nodes that match.
     pub fn indexes_from_ids(&self, package_ids: &[PackageId]) -> Vec<usize> {
         let mut result: Vec<(&Node, usize)> = self
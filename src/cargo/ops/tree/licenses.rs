@@ -0,0 +1,339 @@
+//! Code for the license report of `cargo report licenses`.
+//!
+//! The report covers the same packages `cargo tree` would display with the
+//! same options, so the target, feature and dependency kind filters apply.
+
+use super::{with_graph, TreeOptions};
+use crate::core::{PackageId, Workspace};
+use crate::drop_println;
+use crate::util::interning::InternedString;
+use crate::util::{self, CargoResult};
+use anyhow::{bail, format_err};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::str::FromStr;
+use std::time::SystemTime;
+
+pub enum LicenseFormat {
+    Text,
+    Json,
+    /// An SPDX 2.3 document in the tag-value format.
+    Spdx,
+}
+
+impl FromStr for LicenseFormat {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<LicenseFormat, &'static str> {
+        match s {
+            "text" => Ok(LicenseFormat::Text),
+            "json" => Ok(LicenseFormat::Json),
+            "spdx" => Ok(LicenseFormat::Spdx),
+            _ => Err("invalid license report format"),
+        }
+    }
+}
+
+/// The licenses allowed and denied by `[workspace.metadata.licenses]`.
+#[derive(Default, Deserialize)]
+struct LicensePolicy {
+    #[serde(default)]
+    allow: Vec<String>,
+    #[serde(default)]
+    deny: Vec<String>,
+}
+
+impl LicensePolicy {
+    fn from_workspace(ws: &Workspace<'_>) -> CargoResult<LicensePolicy> {
+        match ws.custom_metadata().and_then(|m| m.get("licenses")) {
+            Some(policy) => policy
+                .clone()
+                .try_into()
+                .map_err(|e| format_err!("invalid `workspace.metadata.licenses`: {}", e)),
+            None => Ok(LicensePolicy::default()),
+        }
+    }
+
+    fn allows(&self, id: &str) -> bool {
+        let listed = |list: &[String]| list.iter().any(|l| l.eq_ignore_ascii_case(id));
+        !listed(&self.deny) && (self.allow.is_empty() || listed(&self.allow))
+    }
+
+    /// Checks the license expression of a package, returning why it doesn't
+    /// satisfy the policy if it doesn't.
+    fn check(&self, license: Option<&str>) -> Result<(), String> {
+        let license = match license {
+            Some(license) => license,
+            // Without an expression, nothing can be said to be allowed.
+            None if self.allow.is_empty() => return Ok(()),
+            None => return Err("no license expression is declared".to_string()),
+        };
+        match eval_expression(license, &|id| self.allows(id)) {
+            Ok(true) => Ok(()),
+            Ok(false) => Err(format!("license `{}` is not allowed", license)),
+            Err(e) => Err(format!("license `{}` is invalid: {}", license, e)),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct LicenseReport<'a> {
+    packages: Vec<PackageLicense<'a>>,
+}
+
+#[derive(Serialize)]
+struct PackageLicense<'a> {
+    name: InternedString,
+    version: String,
+    id: PackageId,
+    source: String,
+    license: Option<String>,
+    license_file: Option<&'a str>,
+    checksum: Option<&'a str>,
+    repository: Option<&'a str>,
+    #[serde(skip)]
+    member: bool,
+}
+
+/// Entry point for the `cargo report licenses` command.
+///
+/// Fails after printing the report if a package that isn't a workspace member
+/// doesn't satisfy the license policy of the workspace.
+pub fn report_licenses(
+    ws: &Workspace<'_>,
+    opts: &TreeOptions,
+    format: LicenseFormat,
+) -> CargoResult<()> {
+    let policy = LicensePolicy::from_workspace(ws)?;
+    let config = ws.config();
+    with_graph(ws, opts, |ws_resolve, _specs, graph| {
+        let checksums = ws_resolve.targeted_resolve.checksums();
+        let ids: BTreeSet<PackageId> = graph.package_ids().collect();
+        let mut packages = Vec::new();
+        let mut violations = Vec::new();
+        for id in ids {
+            let pkg = graph.package_for_id(id);
+            let metadata = pkg.manifest().metadata();
+            let license = metadata.license.as_deref().map(normalize_license);
+            let member = ws.is_member(pkg);
+            if !member {
+                if let Err(reason) = policy.check(license.as_deref()) {
+                    violations.push(format!("{}: {}", id, reason));
+                }
+            }
+            packages.push(PackageLicense {
+                name: id.name(),
+                version: id.version().to_string(),
+                id,
+                source: id.source_id().as_url().to_string(),
+                license,
+                license_file: metadata.license_file.as_deref(),
+                checksum: checksums.get(&id).and_then(|c| c.as_deref()),
+                repository: metadata.repository.as_deref(),
+                member,
+            });
+        }
+
+        match format {
+            LicenseFormat::Json => config.shell().print_json(&LicenseReport { packages })?,
+            LicenseFormat::Text => {
+                for (i, p) in packages.iter().enumerate() {
+                    if i != 0 {
+                        drop_println!(config);
+                    }
+                    drop_println!(config, "{} v{}", p.name, p.version);
+                    drop_println!(
+                        config,
+                        "    license: {}",
+                        p.license.as_deref().unwrap_or("NOASSERTION")
+                    );
+                    if let Some(file) = p.license_file {
+                        drop_println!(config, "    license-file: {}", file);
+                    }
+                    drop_println!(config, "    source: {}", p.source);
+                    if let Some(checksum) = p.checksum {
+                        drop_println!(config, "    checksum: {}", checksum);
+                    }
+                    if let Some(repository) = p.repository {
+                        drop_println!(config, "    repository: {}", repository);
+                    }
+                }
+            }
+            LicenseFormat::Spdx => print_spdx(ws, &packages),
+        }
+
+        if !violations.is_empty() {
+            bail!(
+                "{} {} not satisfy the license policy of `workspace.metadata.licenses`:\n  {}",
+                violations.len(),
+                if violations.len() == 1 {
+                    "package does"
+                } else {
+                    "packages do"
+                },
+                violations.join("\n  ")
+            );
+        }
+        Ok(())
+    })
+}
+
+/// Prints the report as an SPDX document in the tag-value format, describing
+/// the workspace members.
+fn print_spdx(ws: &Workspace<'_>, packages: &[PackageLicense<'_>]) {
+    let config = ws.config();
+    let name = ws
+        .root()
+        .file_name()
+        .map_or("workspace".into(), |name| name.to_string_lossy());
+    let created = humantime::format_rfc3339_seconds(SystemTime::now()).to_string();
+    drop_println!(config, "SPDXVersion: SPDX-2.3");
+    drop_println!(config, "DataLicense: CC0-1.0");
+    drop_println!(config, "SPDXID: SPDXRef-DOCUMENT");
+    drop_println!(config, "DocumentName: {}", name);
+    drop_println!(
+        config,
+        "DocumentNamespace: https://spdx.org/spdxdocs/{}-{}",
+        name,
+        util::short_hash(&(ws.root(), &created))
+    );
+    drop_println!(config, "Creator: Tool: cargo-{}", crate::version().version);
+    drop_println!(config, "Created: {}", created);
+    for (i, p) in packages.iter().enumerate() {
+        let spdx_id = format!("SPDXRef-Package-{}", i);
+        drop_println!(config);
+        drop_println!(config, "PackageName: {}", p.name);
+        drop_println!(config, "SPDXID: {}", spdx_id);
+        drop_println!(config, "PackageVersion: {}", p.version);
+        drop_println!(
+            config,
+            "PackageDownloadLocation: {}",
+            spdx_download_location(p.id)
+        );
+        drop_println!(config, "FilesAnalyzed: false");
+        if let Some(checksum) = p.checksum {
+            drop_println!(config, "PackageChecksum: SHA256: {}", checksum);
+        }
+        if let Some(repository) = p.repository {
+            drop_println!(config, "PackageHomePage: {}", repository);
+        }
+        drop_println!(config, "PackageLicenseConcluded: NOASSERTION");
+        drop_println!(
+            config,
+            "PackageLicenseDeclared: {}",
+            p.license.as_deref().unwrap_or("NOASSERTION")
+        );
+        drop_println!(config, "PackageCopyrightText: NOASSERTION");
+        if p.member {
+            drop_println!(
+                config,
+                "Relationship: SPDXRef-DOCUMENT DESCRIBES {}",
+                spdx_id
+            );
+        }
+    }
+}
+
+/// Where the source of a package can be downloaded from, in the syntax of
+/// the SPDX `PackageDownloadLocation` field.
+fn spdx_download_location(id: PackageId) -> String {
+    let source_id = id.source_id();
+    if source_id.is_default_registry() {
+        format!(
+            "https://crates.io/api/v1/crates/{}/{}/download",
+            id.name(),
+            id.version()
+        )
+    } else if source_id.is_git() {
+        match source_id.precise() {
+            Some(rev) => format!("git+{}@{}", source_id.url(), rev),
+            None => format!("git+{}", source_id.url()),
+        }
+    } else if source_id.is_path() {
+        "NONE".to_string()
+    } else {
+        "NOASSERTION".to_string()
+    }
+}
+
+/// Turns the deprecated `MIT/Apache-2.0` syntax into an SPDX expression.
+fn normalize_license(license: &str) -> String {
+    if license.contains('/') {
+        license
+            .split('/')
+            .map(str::trim)
+            .collect::<Vec<_>>()
+            .join(" OR ")
+    } else {
+        license.to_string()
+    }
+}
+
+/// Evaluates an SPDX license expression, where each license identifier
+/// evaluates to `allowed(id)`. Exceptions added with `WITH` are ignored.
+fn eval_expression(expr: &str, allowed: &dyn Fn(&str) -> bool) -> Result<bool, String> {
+    let spaced = expr.replace('(', " ( ").replace(')', " ) ");
+    let tokens: Vec<&str> = spaced.split_whitespace().collect();
+    let mut pos = 0;
+    let value = eval_or(&tokens, &mut pos, allowed)?;
+    match tokens.get(pos) {
+        Some(token) => Err(format!("unexpected `{}`", token)),
+        None => Ok(value),
+    }
+}
+
+fn eval_or(
+    tokens: &[&str],
+    pos: &mut usize,
+    allowed: &dyn Fn(&str) -> bool,
+) -> Result<bool, String> {
+    let mut value = eval_and(tokens, pos, allowed)?;
+    while tokens.get(*pos) == Some(&"OR") {
+        *pos += 1;
+        value |= eval_and(tokens, pos, allowed)?;
+    }
+    Ok(value)
+}
+
+fn eval_and(
+    tokens: &[&str],
+    pos: &mut usize,
+    allowed: &dyn Fn(&str) -> bool,
+) -> Result<bool, String> {
+    let mut value = eval_license(tokens, pos, allowed)?;
+    while tokens.get(*pos) == Some(&"AND") {
+        *pos += 1;
+        value &= eval_license(tokens, pos, allowed)?;
+    }
+    Ok(value)
+}
+
+fn eval_license(
+    tokens: &[&str],
+    pos: &mut usize,
+    allowed: &dyn Fn(&str) -> bool,
+) -> Result<bool, String> {
+    match tokens.get(*pos) {
+        Some(&"(") => {
+            *pos += 1;
+            let value = eval_or(tokens, pos, allowed)?;
+            if tokens.get(*pos) != Some(&")") {
+                return Err("missing `)`".to_string());
+            }
+            *pos += 1;
+            Ok(value)
+        }
+        Some(&id) if !["AND", "OR", "WITH", ")"].contains(&id) => {
+            *pos += 1;
+            if tokens.get(*pos) == Some(&"WITH") {
+                if tokens.get(*pos + 1).is_none() {
+                    return Err("missing exception after `WITH`".to_string());
+                }
+                *pos += 2;
+            }
+            Ok(allowed(id))
+        }
+        Some(token) => Err(format!("unexpected `{}`", token)),
+        None => Err("unexpected end of expression".to_string()),
+    }
+}
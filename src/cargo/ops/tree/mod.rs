@@ -5,7 +5,7 @@ use crate::core::compiler::{CompileKind, RustcTargetData};
 use crate::core::dependency::DepKind;
 use crate::core::resolver::{features::CliFeatures, ForceAllTargets, HasDevUnits};
 use crate::core::{Package, PackageId, PackageIdSpec, Workspace};
-use crate::ops::{self, Packages};
+use crate::ops::{self, Packages, WorkspaceResolve};
 use crate::util::{CargoResult, Config};
 use crate::{drop_print, drop_println};
 use anyhow::Context;
@@ -13,9 +13,12 @@ use graph::Graph;
 use std::collections::{HashMap, HashSet};
 use std::str::FromStr;
 
+mod export;
 mod format;
 mod graph;
+mod licenses;
 
+pub use licenses::{report_licenses, LicenseFormat};
 pub use {graph::EdgeKind, graph::Node};
 
 pub struct TreeOptions {
@@ -43,6 +46,8 @@ pub struct TreeOptions {
     pub charset: Charset,
     /// A format string indicating how each package should be displayed.
     pub format: String,
+    /// Whether to print a tree, or the graph as JSON or DOT.
+    pub output_format: OutputFormat,
     /// Includes features in the tree as separate nodes.
     pub graph_features: bool,
     /// Maximum display depth of the dependency tree.
@@ -68,6 +73,12 @@ impl Target {
     }
 }
 
+pub enum OutputFormat {
+    Text,
+    Json,
+    Dot,
+}
+
 pub enum Charset {
     Utf8,
     Ascii,
@@ -128,6 +139,19 @@ static ASCII_SYMBOLS: Symbols = Symbols {
 
 /// Entry point for the `cargo tree` command.
 pub fn build_and_print(ws: &Workspace<'_>, opts: &TreeOptions) -> CargoResult<()> {
+    with_graph(ws, opts, |ws_resolve, specs, graph| {
+        print_graph(ws, opts, ws_resolve, specs, graph)
+    })
+}
+
+/// Resolves the workspace for the packages, features, targets and dependency
+/// kinds selected in `opts`, and builds their dependency graph. `f` is then
+/// called with the resolve, the selected package specs and the graph.
+fn with_graph<T>(
+    ws: &Workspace<'_>,
+    opts: &TreeOptions,
+    f: impl FnOnce(&WorkspaceResolve<'_>, Vec<PackageIdSpec>, Graph<'_>) -> CargoResult<T>,
+) -> CargoResult<T> {
     let requested_targets = match &opts.target {
         Target::All | Target::Host => Vec::new(),
         Target::Specific(t) => t.clone(),
@@ -135,7 +159,7 @@ pub fn build_and_print(ws: &Workspace<'_>, opts: &TreeOptions) -> CargoResult<()
     // TODO: Target::All is broken with -Zfeatures=itarget. To handle that properly,
     // `FeatureResolver` will need to be taught what "all" means.
     let requested_kinds = CompileKind::from_requested_targets(ws.config(), &requested_targets)?;
-    let target_data = RustcTargetData::new(ws, &requested_kinds)?;
+    let mut target_data = RustcTargetData::new(ws, &requested_kinds)?;
     let specs = opts.packages.to_package_id_specs(ws)?;
     let has_dev = if opts
         .edge_kinds
@@ -152,7 +176,7 @@ pub fn build_and_print(ws: &Workspace<'_>, opts: &TreeOptions) -> CargoResult<()
     };
     let ws_resolve = ops::resolve_ws_with_opts(
         ws,
-        &target_data,
+        &mut target_data,
         &requested_kinds,
         &opts.cli_features,
         &specs,
@@ -166,7 +190,7 @@ pub fn build_and_print(ws: &Workspace<'_>, opts: &TreeOptions) -> CargoResult<()
         .map(|pkg| (pkg.package_id(), pkg))
         .collect();
 
-    let mut graph = graph::build(
+    let graph = graph::build(
         ws,
         &ws_resolve.targeted_resolve,
         &ws_resolve.resolved_features,
@@ -177,7 +201,16 @@ pub fn build_and_print(ws: &Workspace<'_>, opts: &TreeOptions) -> CargoResult<()
         package_map,
         opts,
     )?;
+    f(&ws_resolve, specs, graph)
+}
 
+fn print_graph(
+    ws: &Workspace<'_>,
+    opts: &TreeOptions,
+    ws_resolve: &WorkspaceResolve<'_>,
+    specs: Vec<PackageIdSpec>,
+    mut graph: Graph<'_>,
+) -> CargoResult<()> {
     let root_specs = if opts.invert.is_empty() {
         specs
     } else {
@@ -213,7 +246,15 @@ pub fn build_and_print(ws: &Workspace<'_>, opts: &TreeOptions) -> CargoResult<()
         })
         .collect::<CargoResult<Vec<PackageIdSpec>>>()?;
 
-    print(ws.config(), opts, root_indexes, &pkgs_to_prune, &graph)?;
+    match opts.output_format {
+        OutputFormat::Text => print(ws.config(), opts, root_indexes, &pkgs_to_prune, &graph)?,
+        OutputFormat::Json => {
+            export::print_json(ws.config(), opts, &root_indexes, &pkgs_to_prune, &graph)?
+        }
+        OutputFormat::Dot => {
+            export::print_dot(ws.config(), opts, &root_indexes, &pkgs_to_prune, &graph)?
+        }
+    }
     Ok(())
 }
 
@@ -397,28 +438,7 @@ fn print_dependencies<'a>(
 
     let mut it = deps
         .iter()
-        .filter(|dep| {
-            // Filter out proc-macro dependencies.
-            if no_proc_macro {
-                match graph.node(**dep) {
-                    &Node::Package { package_id, .. } => {
-                        !graph.package_for_id(package_id).proc_macro()
-                    }
-                    _ => true,
-                }
-            } else {
-                true
-            }
-        })
-        .filter(|dep| {
-            // Filter out packages to prune.
-            match graph.node(**dep) {
-                Node::Package { package_id, .. } => {
-                    !pkgs_to_prune.iter().any(|spec| spec.matches(*package_id))
-                }
-                _ => true,
-            }
-        })
+        .filter(|dep| !is_filtered_out(graph, **dep, pkgs_to_prune, no_proc_macro))
         .peekable();
 
     while let Some(dependency) = it.next() {
@@ -441,3 +461,20 @@ fn print_dependencies<'a>(
         levels_continue.pop();
     }
 }
+
+/// Returns `true` if the given dependency node is not displayed, because it is
+/// a proc-macro with `-e no-proc-macro` or a package to prune.
+fn is_filtered_out(
+    graph: &Graph<'_>,
+    index: usize,
+    pkgs_to_prune: &[PackageIdSpec],
+    no_proc_macro: bool,
+) -> bool {
+    match graph.node(index) {
+        &Node::Package { package_id, .. } => {
+            (no_proc_macro && graph.package_for_id(package_id).proc_macro())
+                || pkgs_to_prune.iter().any(|spec| spec.matches(package_id))
+        }
+        Node::Feature { .. } => false,
+    }
+}
@@ -5,6 +5,7 @@
 //! sources to one another via the `replace-with` key in `.cargo/config`.
 
 use crate::core::{GitReference, PackageId, Source, SourceId};
+use crate::sources::overlay::OverlaySource;
 use crate::sources::{ReplacedSource, CRATES_IO_REGISTRY};
 use crate::util::config::{self, ConfigRelativePath, OptValue};
 use crate::util::errors::CargoResult;
@@ -12,6 +13,7 @@ use crate::util::{Config, IntoUrl};
 use anyhow::{bail, Context as _};
 use log::debug;
 use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
 use url::Url;
 
 #[derive(Clone)]
@@ -20,6 +22,9 @@ pub struct SourceConfigMap<'cfg> {
     cfgs: HashMap<String, SourceConfig>,
     /// Mapping of `SourceId` to the source name.
     id2name: HashMap<SourceId, String>,
+    /// Mapping of `SourceId` to the path of a local registry whose packages
+    /// are overlaid on top of that source.
+    overlays: HashMap<SourceId, PathBuf>,
     config: &'cfg Config,
 }
 
@@ -78,10 +83,22 @@ impl<'cfg> SourceConfigMap<'cfg> {
         Ok(base)
     }
 
+    /// Like [`SourceConfigMap::new`], but the packages of the local registry
+    /// at each path are added on top of the source it is keyed by.
+    pub fn new_with_overlays(
+        config: &'cfg Config,
+        overlays: HashMap<SourceId, PathBuf>,
+    ) -> CargoResult<SourceConfigMap<'cfg>> {
+        let mut base = SourceConfigMap::new(config)?;
+        base.overlays = overlays;
+        Ok(base)
+    }
+
     pub fn empty(config: &'cfg Config) -> CargoResult<SourceConfigMap<'cfg>> {
         let mut base = SourceConfigMap {
             cfgs: HashMap::new(),
             id2name: HashMap::new(),
+            overlays: HashMap::new(),
             config,
         };
         base.add(
@@ -103,6 +120,22 @@ impl<'cfg> SourceConfigMap<'cfg> {
         &self,
         id: SourceId,
         yanked_whitelist: &HashSet<PackageId>,
+    ) -> CargoResult<Box<dyn Source + 'cfg>> {
+        let src = self.load_source(id, yanked_whitelist)?;
+        match self.overlays.get(&id) {
+            Some(path) => {
+                let local_id = SourceId::for_local_registry(path)?;
+                let local = local_id.load(self.config, yanked_whitelist)?;
+                Ok(Box::new(OverlaySource::new(src, local_id, local)))
+            }
+            None => Ok(src),
+        }
+    }
+
+    fn load_source(
+        &self,
+        id: SourceId,
+        yanked_whitelist: &HashSet<PackageId>,
     ) -> CargoResult<Box<dyn Source + 'cfg>> {
         debug!("loading: {}", id);
 
@@ -1,3 +1,4 @@
+use crate::core::gc;
 use crate::core::source::{MaybePackage, Source, SourceId};
 use crate::core::GitReference;
 use crate::core::{Dependency, Package, PackageId, Summary};
@@ -174,6 +175,8 @@ impl<'cfg> Source for GitSource<'cfg> {
             .join(&self.ident)
             .join(short_id.as_str());
         db.copy_to(actual_rev, &checkout_path, self.config)?;
+        gc::record_use(&db_path);
+        gc::record_use(&checkout_path);
 
         let source_id = self.source_id.with_precise(Some(actual_rev.to_string()));
         let path_source = PathSource::new_recursive(&checkout_path, source_id, self.config);
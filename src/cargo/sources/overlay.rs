@@ -0,0 +1,141 @@
+//! A source that overlays locally packaged crates on top of a registry.
+//!
+//! This is used by `cargo package` and `cargo publish` when several packages
+//! of a workspace are handled at once: the `.crate` files of the packages
+//! that were just assembled are put into a local registry, which is then
+//! layered over the registry they will eventually be published to. That way
+//! a package depending on a sibling that hasn't been published yet can still
+//! be verified.
+
+use std::collections::HashSet;
+
+use crate::core::source::MaybePackage;
+use crate::core::{Dependency, Package, PackageId, Source, SourceId, Summary};
+use crate::sources::ReplacedSource;
+use crate::util::errors::CargoResult;
+
+pub struct OverlaySource<'cfg> {
+    /// The local registry, already mapped to the `SourceId` of `remote`.
+    local: ReplacedSource<'cfg>,
+    remote: Box<dyn Source + 'cfg>,
+    /// Packages that were found in `local`, and thus must be downloaded from
+    /// there.
+    local_packages: HashSet<PackageId>,
+}
+
+impl<'cfg> OverlaySource<'cfg> {
+    pub fn new(
+        remote: Box<dyn Source + 'cfg>,
+        local_id: SourceId,
+        local: Box<dyn Source + 'cfg>,
+    ) -> OverlaySource<'cfg> {
+        OverlaySource {
+            local: ReplacedSource::new(remote.source_id(), local_id, local),
+            remote,
+            local_packages: HashSet::new(),
+        }
+    }
+}
+
+impl<'cfg> Source for OverlaySource<'cfg> {
+    fn source_id(&self) -> SourceId {
+        self.remote.source_id()
+    }
+
+    fn replaced_source_id(&self) -> SourceId {
+        self.remote.replaced_source_id()
+    }
+
+    fn supports_checksums(&self) -> bool {
+        self.remote.supports_checksums()
+    }
+
+    fn requires_precise(&self) -> bool {
+        self.remote.requires_precise()
+    }
+
+    fn query(&mut self, dep: &Dependency, f: &mut dyn FnMut(Summary)) -> CargoResult<()> {
+        let mut found = HashSet::new();
+        let local_packages = &mut self.local_packages;
+        self.local.query(dep, &mut |summary| {
+            found.insert(summary.package_id());
+            local_packages.insert(summary.package_id());
+            f(summary)
+        })?;
+        // Locally packaged versions take precedence over the ones that may
+        // already be in the registry.
+        self.remote.query(dep, &mut |summary| {
+            if !found.contains(&summary.package_id()) {
+                f(summary)
+            }
+        })
+    }
+
+    fn fuzzy_query(&mut self, dep: &Dependency, f: &mut dyn FnMut(Summary)) -> CargoResult<()> {
+        self.local.fuzzy_query(dep, f)?;
+        self.remote.fuzzy_query(dep, f)
+    }
+
+    fn prefetch(&mut self, dep: &Dependency) -> CargoResult<()> {
+        self.local.prefetch(dep)?;
+        self.remote.prefetch(dep)
+    }
+
+    fn update(&mut self) -> CargoResult<()> {
+        self.local.update()?;
+        self.remote.update()
+    }
+
+    fn download(&mut self, id: PackageId) -> CargoResult<MaybePackage> {
+        if self.local_packages.contains(&id) {
+            self.local.download(id)
+        } else {
+            self.remote.download(id)
+        }
+    }
+
+    fn finish_download(&mut self, id: PackageId, data: Vec<u8>) -> CargoResult<Package> {
+        if self.local_packages.contains(&id) {
+            self.local.finish_download(id, data)
+        } else {
+            self.remote.finish_download(id, data)
+        }
+    }
+
+    fn fingerprint(&self, pkg: &Package) -> CargoResult<String> {
+        if self.local_packages.contains(&pkg.package_id()) {
+            self.local.fingerprint(pkg)
+        } else {
+            self.remote.fingerprint(pkg)
+        }
+    }
+
+    fn verify(&self, id: PackageId) -> CargoResult<()> {
+        if self.local_packages.contains(&id) {
+            self.local.verify(id)
+        } else {
+            self.remote.verify(id)
+        }
+    }
+
+    fn describe(&self) -> String {
+        self.remote.describe()
+    }
+
+    fn is_replaced(&self) -> bool {
+        self.remote.is_replaced()
+    }
+
+    fn add_to_yanked_whitelist(&mut self, pkgs: &[PackageId]) {
+        self.local.add_to_yanked_whitelist(pkgs);
+        self.remote.add_to_yanked_whitelist(pkgs);
+    }
+
+    fn is_yanked(&mut self, pkg: PackageId) -> CargoResult<bool> {
+        if self.local_packages.contains(&pkg) {
+            Ok(false)
+        } else {
+            self.remote.is_yanked(pkg)
+        }
+    }
+}
@@ -0,0 +1,579 @@
+//! Access to a HTTP-based crate registry.
+//!
+//! See [`HttpRegistry`] for details.
+
+use crate::core::gc;
+use crate::core::package::try_old_curl;
+use crate::core::{PackageId, SourceId};
+use crate::ops;
+use crate::sources::registry::MaybeLock;
+use crate::sources::registry::{
+    LoadResponse, RegistryConfig, RegistryData, CHECKSUM_TEMPLATE, CRATE_TEMPLATE,
+    LOWER_PREFIX_TEMPLATE, PREFIX_TEMPLATE, VERSION_TEMPLATE,
+};
+use crate::util::errors::{CargoResult, HttpNot200};
+use crate::util::network::Retry;
+use crate::util::{Config, Filesystem};
+use anyhow::Context as _;
+use cargo_util::{paths, registry::make_dep_path, Sha256};
+use curl::easy::{Easy, HttpVersion, List};
+use curl::multi::{EasyHandle, Multi};
+use log::{debug, trace, warn};
+use std::collections::HashMap;
+use std::fmt::Write as FmtWrite;
+use std::fs::{self, File, OpenOptions};
+use std::io::prelude::*;
+use std::io::SeekFrom;
+use std::mem;
+use std::path::{Path, PathBuf};
+use std::str;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+const ETAG: &str = "etag";
+const LAST_MODIFIED: &str = "last-modified";
+
+/// A registry served over plain HTTP (a "sparse" registry).
+///
+/// The index has the same layout as a git-based index, but instead of
+/// cloning the whole index, each index file is fetched individually the
+/// first time it is needed. Files are cached in the `.cache` directory of
+/// the index along with the `ETag` or `Last-Modified` header of the
+/// response, which are used to make a conditional request the next time the
+/// index is updated, so that unchanged files are not downloaded again.
+///
+/// Since the dependencies of a crate are only known once its index file has
+/// been fetched, the resolver tells the registry about upcoming queries with
+/// [`RegistryData::prefetch`], which starts transfers for all of them in
+/// parallel on a single curl [`Multi`] handle. [`RegistryData::load`] then
+/// drives the transfers until the requested file is available.
+pub struct HttpRegistry<'cfg> {
+    index_path: Filesystem,
+    /// Path to the cache of `.crate` files (`$CARGO_HOME/registry/path/$REG-HASH`).
+    cache_path: Filesystem,
+    source_id: SourceId,
+    config: &'cfg Config,
+    /// The URL of the root of the index, without the `sparse+` prefix.
+    url: String,
+    multi: Multi,
+    /// Whether or not to use curl HTTP/2 multiplexing.
+    multiplexing: bool,
+    /// Whether the index should be checked for changes in this session.
+    ///
+    /// Until [`RegistryData::update_index`] is called, cached index files are
+    /// assumed to be fresh and only missing files are fetched.
+    requested_update: bool,
+    /// Responses for the index files fetched in this session.
+    fetched: HashMap<PathBuf, Fetched>,
+    /// Transfers which are currently in progress, keyed by token.
+    pending: HashMap<usize, (Transfer<'cfg>, EasyHandle)>,
+    /// The token for the next transfer.
+    next: usize,
+    /// The parsed `config.json` of the registry, once loaded.
+    registry_config: Option<RegistryConfig>,
+}
+
+/// The outcome of fetching an index file.
+enum Fetched {
+    /// The file was downloaded, along with the `index_version` to cache it
+    /// with, if the server provided one.
+    Data {
+        raw_data: Vec<u8>,
+        index_version: Option<String>,
+    },
+    /// The server reported that the cached file is still up to date.
+    NotModified,
+    /// The file doesn't exist in the index.
+    NotFound,
+}
+
+/// An in-flight request for an index file.
+struct Transfer<'cfg> {
+    path: PathBuf,
+    url: String,
+    received: Arc<Mutex<Received>>,
+    retry: Retry<'cfg>,
+}
+
+/// The parts of a response collected by the curl callbacks.
+#[derive(Default)]
+struct Received {
+    data: Vec<u8>,
+    etag: Option<String>,
+    last_modified: Option<String>,
+}
+
+impl<'cfg> HttpRegistry<'cfg> {
+    pub fn new(
+        source_id: SourceId,
+        config: &'cfg Config,
+        name: &str,
+    ) -> CargoResult<HttpRegistry<'cfg>> {
+        let url = source_id
+            .url()
+            .as_str()
+            .strip_prefix("sparse+")
+            .expect("sparse registry needs sparse+ prefix")
+            .trim_end_matches('/')
+            .to_string();
+
+        // See `PackageSet::new` for why pipelining is disabled.
+        let mut multi = Multi::new();
+        let multiplexing = config.http_config()?.multiplexing.unwrap_or(true);
+        multi
+            .pipelining(false, multiplexing)
+            .with_context(|| "failed to enable multiplexing/pipelining in curl")?;
+        multi.set_max_host_connections(2)?;
+
+        Ok(HttpRegistry {
+            index_path: config.registry_index_path().join(name),
+            cache_path: config.registry_cache_path().join(name),
+            source_id,
+            config,
+            url,
+            multi,
+            multiplexing,
+            requested_update: false,
+            fetched: HashMap::new(),
+            pending: HashMap::new(),
+            next: 0,
+            registry_config: None,
+        })
+    }
+
+    fn filename(&self, pkg: PackageId) -> String {
+        format!("{}-{}.crate", pkg.name(), pkg.version())
+    }
+
+    fn full_url(&self, path: &Path) -> String {
+        format!("{}/{}", self.url, path.display())
+    }
+
+    /// Whether `path` has to be requested from the server, rather than
+    /// trusting the cached copy (if any) described by `index_version`.
+    fn needs_fetch(&self, index_version: Option<&str>) -> bool {
+        if !self.config.network_allowed() {
+            return false;
+        }
+        index_version.is_none() || self.requested_update
+    }
+
+    fn is_pending(&self, path: &Path) -> bool {
+        self.pending.values().any(|(t, _)| t.path == path)
+    }
+
+    /// Starts fetching the index file at `path`, unless it's already been
+    /// fetched or is in progress.
+    fn start_fetch(&mut self, path: &Path, index_version: Option<&str>) -> CargoResult<()> {
+        if self.fetched.contains_key(path) || self.is_pending(path) {
+            return Ok(());
+        }
+        let url = self.full_url(path);
+        let token = self.next;
+        self.next += 1;
+        debug!("fetching {} as {}", url, token);
+
+        let mut handle = ops::http_handle(self.config)?;
+        handle.get(true)?;
+        handle.url(&url)?;
+        handle.follow_location(true)?;
+
+        // See `Downloads::start_inner` for why HTTP/2 and pipewait are set up
+        // this way.
+        if self.multiplexing {
+            try_old_curl!(handle.http_version(HttpVersion::V2), "HTTP2");
+        } else {
+            handle.http_version(HttpVersion::V11)?;
+        }
+        try_old_curl!(handle.pipewait(true), "pipewait");
+
+        // Ask the server to only send the file if it has changed since we
+        // cached it.
+        let mut headers = List::new();
+        if let Some(index_version) = index_version {
+            if let Some((key, value)) = index_version.split_once(':') {
+                match key {
+                    ETAG => headers.append(&format!("If-None-Match: {}", value.trim()))?,
+                    LAST_MODIFIED => {
+                        headers.append(&format!("If-Modified-Since: {}", value.trim()))?
+                    }
+                    _ => debug!("unexpected index version: {}", index_version),
+                }
+            }
+        }
+        handle.http_headers(headers)?;
+
+        let received = Arc::new(Mutex::new(Received::default()));
+        let data = Arc::clone(&received);
+        handle.write_function(move |buf| {
+            trace!("{} - {} bytes of data", token, buf.len());
+            data.lock().unwrap().data.extend_from_slice(buf);
+            Ok(buf.len())
+        })?;
+        let header = Arc::clone(&received);
+        handle.header_function(move |buf| {
+            let line = match str::from_utf8(buf) {
+                Ok(line) => line.trim(),
+                Err(_) => return true,
+            };
+            let mut received = header.lock().unwrap();
+            // A new status line means that a redirect was followed, so only
+            // the headers of the final response are kept.
+            if line.starts_with("HTTP/") {
+                received.etag = None;
+                received.last_modified = None;
+            } else if let Some((name, value)) = line.split_once(':') {
+                let value = value.trim().to_string();
+                if name.eq_ignore_ascii_case(ETAG) {
+                    received.etag = Some(value);
+                } else if name.eq_ignore_ascii_case(LAST_MODIFIED) {
+                    received.last_modified = Some(value);
+                }
+            }
+            true
+        })?;
+
+        let transfer = Transfer {
+            path: path.to_path_buf(),
+            url,
+            received,
+            retry: Retry::new(self.config)?,
+        };
+        self.enqueue(token, transfer, handle)
+    }
+
+    fn enqueue(&mut self, token: usize, transfer: Transfer<'cfg>, handle: Easy) -> CargoResult<()> {
+        let mut handle = self.multi.add(handle)?;
+        handle.set_token(token)?;
+        self.pending.insert(token, (transfer, handle));
+        Ok(())
+    }
+
+    /// Drives the pending transfers until the index file at `path` has been
+    /// fetched.
+    fn wait_for(&mut self, path: &Path) -> CargoResult<()> {
+        while !self.fetched.contains_key(path) {
+            assert!(!self.pending.is_empty());
+            self.multi
+                .perform()
+                .with_context(|| "failed to perform http requests")?;
+
+            let mut results = Vec::new();
+            let pending = &self.pending;
+            self.multi.messages(|msg| {
+                let token = msg.token().expect("failed to read token");
+                let handle = &pending[&token].1;
+                if let Some(result) = msg.result_for(handle) {
+                    results.push((token, result));
+                } else {
+                    debug!("message without a result (?)");
+                }
+            });
+
+            if results.is_empty() {
+                let timeout = self
+                    .multi
+                    .get_timeout()?
+                    .unwrap_or_else(|| Duration::new(5, 0));
+                self.multi
+                    .wait(&mut [], timeout)
+                    .with_context(|| "failed to wait on curl `Multi`")?;
+            }
+            for (token, result) in results {
+                self.finish_transfer(token, result)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Records the response of a completed transfer, or restarts it if it
+    /// failed with a spurious error.
+    fn finish_transfer(
+        &mut self,
+        token: usize,
+        result: Result<(), curl::Error>,
+    ) -> CargoResult<()> {
+        let (mut transfer, handle) = self
+            .pending
+            .remove(&token)
+            .expect("got a token for a non-in-progress transfer");
+        let handle = self.multi.remove(handle)?;
+        debug!("{} finished with {:?}", transfer.url, result);
+
+        let code = {
+            let url = &transfer.url;
+            transfer
+                .retry
+                .r#try(|| {
+                    result?;
+                    match handle.response_code()? {
+                        code @ (200 | 304 | 404 | 410) => Ok(code),
+                        code => {
+                            let url = handle.effective_url()?.unwrap_or(url);
+                            Err(HttpNot200 {
+                                code,
+                                url: url.to_string(),
+                            }
+                            .into())
+                        }
+                    }
+                })
+                .with_context(|| format!("failed to fetch `{}`", url))?
+        };
+        let code = match code {
+            Some(code) => code,
+            None => {
+                *transfer.received.lock().unwrap() = Received::default();
+                return self.enqueue(token, transfer, handle);
+            }
+        };
+
+        let received = mem::take(&mut *transfer.received.lock().unwrap());
+        let fetched = match code {
+            200 => Fetched::Data {
+                raw_data: received.data,
+                index_version: received
+                    .etag
+                    .map(|etag| format!("{}: {}", ETAG, etag))
+                    .or_else(|| {
+                        received
+                            .last_modified
+                            .map(|lm| format!("{}: {}", LAST_MODIFIED, lm))
+                    }),
+            },
+            304 => Fetched::NotModified,
+            _ => Fetched::NotFound,
+        };
+        self.fetched.insert(transfer.path, fetched);
+        Ok(())
+    }
+}
+
+const LAST_UPDATED_FILE: &str = ".last-updated";
+
+impl<'cfg> RegistryData for HttpRegistry<'cfg> {
+    fn prepare(&self) -> CargoResult<()> {
+        self.index_path.create_dir()?;
+        Ok(())
+    }
+
+    fn index_path(&self) -> &Filesystem {
+        &self.index_path
+    }
+
+    fn assert_index_locked<'a>(&self, path: &'a Filesystem) -> &'a Path {
+        self.config.assert_package_cache_locked(path)
+    }
+
+    fn load(
+        &mut self,
+        root: &Path,
+        path: &Path,
+        index_version: Option<&str>,
+    ) -> CargoResult<LoadResponse> {
+        match self.fetched.get(path) {
+            Some(Fetched::Data {
+                raw_data,
+                index_version: fetched_version,
+            }) => {
+                // The data may have already been written to the cache by a
+                // previous load.
+                if fetched_version.is_some() && fetched_version.as_deref() == index_version {
+                    return Ok(LoadResponse::CacheValid);
+                }
+                return Ok(LoadResponse::Data {
+                    raw_data: raw_data.clone(),
+                    index_version: fetched_version.clone(),
+                });
+            }
+            Some(Fetched::NotModified) if index_version.is_some() => {
+                return Ok(LoadResponse::CacheValid);
+            }
+            Some(Fetched::NotModified) => {
+                // The cache went missing since the request was made, so ask
+                // for the whole file again.
+                self.fetched.remove(path);
+            }
+            Some(Fetched::NotFound) => return Ok(LoadResponse::NotFound),
+            None => {}
+        }
+
+        if !self.needs_fetch(index_version) {
+            return Ok(if index_version.is_some() {
+                LoadResponse::CacheValid
+            } else {
+                LoadResponse::NotFound
+            });
+        }
+
+        self.start_fetch(path, index_version)?;
+        self.wait_for(path)?;
+        self.load(root, path, index_version)
+    }
+
+    fn supports_prefetch(&self) -> bool {
+        true
+    }
+
+    fn prefetch(
+        &mut self,
+        _root: &Path,
+        path: &Path,
+        index_version: Option<&str>,
+    ) -> CargoResult<()> {
+        if !self.needs_fetch(index_version) {
+            return Ok(());
+        }
+        self.start_fetch(path, index_version)
+    }
+
+    fn config(&mut self) -> CargoResult<Option<RegistryConfig>> {
+        if let Some(config) = &self.registry_config {
+            return Ok(Some(config.clone()));
+        }
+        debug!("loading config");
+        self.prepare()?;
+        let root = self
+            .config
+            .assert_package_cache_locked(&self.index_path)
+            .to_path_buf();
+        let config_path = root.join("config.json");
+
+        // The config is stored next to the cached index files, so that it's
+        // available offline.
+        let json = match paths::read_bytes(&config_path) {
+            Ok(json) if !self.requested_update || !self.config.network_allowed() => json,
+            _ => match self.load(&root, Path::new("config.json"), None)? {
+                LoadResponse::Data { raw_data, .. } => {
+                    paths::write(&config_path, &raw_data)?;
+                    raw_data
+                }
+                _ => anyhow::bail!(
+                    "failed to find `config.json` in the {}",
+                    self.source_id.display_index()
+                ),
+            },
+        };
+        let config: RegistryConfig = serde_json::from_slice(&json)
+            .with_context(|| format!("failed to parse `{}`", config_path.display()))?;
+        trace!("config loaded");
+        self.registry_config = Some(config.clone());
+        Ok(Some(config))
+    }
+
+    fn update_index(&mut self) -> CargoResult<()> {
+        if self.config.offline() {
+            return Ok(());
+        }
+        if self.config.cli_unstable().no_index_update {
+            return Ok(());
+        }
+        // There's nothing to download up front, this just makes the next
+        // loads check whether their cached files are still fresh. Only print
+        // the status once per session, like for git indexes.
+        self.requested_update = true;
+        self.registry_config = None;
+        if self.config.updated_sources().contains(&self.source_id) {
+            return Ok(());
+        }
+
+        debug!("updating the index");
+
+        // Ensure that we'll actually be able to acquire an HTTP handle later
+        // on, see `RemoteRegistry::update_index`.
+        self.config.http()?;
+
+        self.prepare()?;
+        let path = self.config.assert_package_cache_locked(&self.index_path);
+        self.config
+            .shell()
+            .status("Updating", self.source_id.display_index())?;
+        self.config.updated_sources().insert(self.source_id);
+
+        // Create a dummy file to record the mtime for when we updated the
+        // index.
+        paths::create(&path.join(LAST_UPDATED_FILE))?;
+
+        Ok(())
+    }
+
+    fn download(&mut self, pkg: PackageId, checksum: &str) -> CargoResult<MaybeLock> {
+        let filename = self.filename(pkg);
+
+        // See `RemoteRegistry::download` for why a read-only copy is tried
+        // first.
+        let path = self.cache_path.join(&filename);
+        let path = self.config.assert_package_cache_locked(&path);
+        if let Ok(dst) = File::open(&path) {
+            let meta = dst.metadata()?;
+            if meta.len() > 0 {
+                gc::record_use(path);
+                return Ok(MaybeLock::Ready(dst));
+            }
+        }
+
+        let config = self.config()?.unwrap();
+        let mut url = config.dl;
+        if !url.contains(CRATE_TEMPLATE)
+            && !url.contains(VERSION_TEMPLATE)
+            && !url.contains(PREFIX_TEMPLATE)
+            && !url.contains(LOWER_PREFIX_TEMPLATE)
+            && !url.contains(CHECKSUM_TEMPLATE)
+        {
+            write!(url, "/{}/{}/download", CRATE_TEMPLATE, VERSION_TEMPLATE).unwrap();
+        }
+        let prefix = make_dep_path(&*pkg.name(), true);
+        let url = url
+            .replace(CRATE_TEMPLATE, &*pkg.name())
+            .replace(VERSION_TEMPLATE, &pkg.version().to_string())
+            .replace(PREFIX_TEMPLATE, &prefix)
+            .replace(LOWER_PREFIX_TEMPLATE, &prefix.to_lowercase())
+            .replace(CHECKSUM_TEMPLATE, checksum);
+
+        Ok(MaybeLock::Download {
+            url,
+            descriptor: pkg.to_string(),
+        })
+    }
+
+    fn finish_download(
+        &mut self,
+        pkg: PackageId,
+        checksum: &str,
+        data: &[u8],
+    ) -> CargoResult<File> {
+        // Verify what we just downloaded
+        let actual = Sha256::new().update(data).finish_hex();
+        if actual != checksum {
+            anyhow::bail!("failed to verify the checksum of `{}`", pkg)
+        }
+
+        let filename = self.filename(pkg);
+        self.cache_path.create_dir()?;
+        let path = self.cache_path.join(&filename);
+        let path = self.config.assert_package_cache_locked(&path);
+        let mut dst = OpenOptions::new()
+            .create(true)
+            .read(true)
+            .write(true)
+            .open(&path)
+            .with_context(|| format!("failed to open `{}`", path.display()))?;
+        let meta = dst.metadata()?;
+        if meta.len() > 0 {
+            return Ok(dst);
+        }
+
+        dst.write_all(data)?;
+        dst.seek(SeekFrom::Start(0))?;
+        Ok(dst)
+    }
+
+    fn is_crate_downloaded(&self, pkg: PackageId) -> bool {
+        let path = self.cache_path.join(self.filename(pkg));
+        let path = self.config.assert_package_cache_locked(&path);
+        if let Ok(meta) = fs::metadata(path) {
+            return meta.len() > 0;
+        }
+        false
+    }
+}
@@ -2,8 +2,9 @@
 //!
 //! This module contains management of the index and various operations, such as
 //! actually parsing the index, looking for crates, etc. This is intended to be
-//! abstract over remote indices (downloaded via git) and local registry indices
-//! (which are all just present on the filesystem).
+//! abstract over remote indices (downloaded via git or fetched file-by-file
+//! over HTTP) and local registry indices (which are all just present on the
+//! filesystem).
 //!
 //! ## Index Performance
 //!
@@ -68,7 +69,7 @@
 
 use crate::core::dependency::Dependency;
 use crate::core::{PackageId, SourceId, Summary};
-use crate::sources::registry::{RegistryData, RegistryPackage, INDEX_V_MAX};
+use crate::sources::registry::{LoadResponse, RegistryData, RegistryPackage, INDEX_V_MAX};
 use crate::util::interning::InternedString;
 use crate::util::{internal, CargoResult, Config, Filesystem, OptVersionReq, ToSemver};
 use anyhow::bail;
@@ -78,7 +79,7 @@ use semver::Version;
 use std::collections::{HashMap, HashSet};
 use std::convert::TryInto;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::str;
 
 /// Crates.io treats hyphen and underscores as interchangeable, but the index and old Cargo do not.
@@ -166,12 +167,23 @@ fn overflow_hyphen() {
     )
 }
 
+/// Returns the path of the index file for the package `name`, relative to
+/// the root of the index.
+fn index_file_path(name: InternedString) -> String {
+    let fs_name = name
+        .chars()
+        .flat_map(|c| c.to_lowercase())
+        .collect::<String>();
+    make_dep_path(&fs_name, false)
+}
+
 /// Manager for handling the on-disk index.
 ///
 /// Note that local and remote registries store the index differently. Local
 /// is a simple on-disk tree of files of the raw index. Remote registries are
-/// stored as a raw git repository. The different means of access are handled
-/// via the [`RegistryData`] trait abstraction.
+/// stored as a raw git repository, or fetched one file at a time for HTTP
+/// ("sparse") registries. The different means of access are handled via the
+/// [`RegistryData`] trait abstraction.
 ///
 /// This transparently handles caching of the index in a more efficient format.
 pub struct RegistryIndex<'cfg> {
@@ -365,15 +377,10 @@ impl<'cfg> RegistryIndex<'cfg> {
         // let root = self.config.assert_package_cache_locked(&self.path);
         let root = load.assert_index_locked(&self.path);
         let cache_root = root.join(".cache");
-        let index_version = load.current_version();
 
         // See module comment in `registry/mod.rs` for why this is structured
         // the way it is.
-        let fs_name = name
-            .chars()
-            .flat_map(|c| c.to_lowercase())
-            .collect::<String>();
-        let raw_path = make_dep_path(&fs_name, false);
+        let raw_path = index_file_path(name);
 
         // Attempt to handle misspellings by searching for a chain of related
         // names to the original `raw_path` name. Only return summaries
@@ -382,7 +389,6 @@ impl<'cfg> RegistryIndex<'cfg> {
         // along the way produce helpful "did you mean?" suggestions.
         for path in UncanonicalizedIter::new(&raw_path).take(1024) {
             let summaries = Summaries::parse(
-                index_version.as_deref(),
                 root,
                 &cache_root,
                 path.as_ref(),
@@ -402,6 +408,30 @@ impl<'cfg> RegistryIndex<'cfg> {
         Ok(self.summaries_cache.get_mut(&name).unwrap())
     }
 
+    /// Lets the `RegistryData` know that the index entry for `name` is about
+    /// to be queried, so that it can start loading it ahead of time.
+    pub fn prefetch(
+        &mut self,
+        name: InternedString,
+        load: &mut dyn RegistryData,
+    ) -> CargoResult<()> {
+        if !load.supports_prefetch() || self.summaries_cache.contains_key(&name) {
+            return Ok(());
+        }
+        load.prepare()?;
+        let root = load.assert_index_locked(&self.path);
+        let relative = PathBuf::from(index_file_path(name));
+        let index_version =
+            fs::read(root.join(".cache").join(&relative))
+                .ok()
+                .and_then(|contents| {
+                    SummariesCache::parse(&contents)
+                        .ok()
+                        .map(|(_, index_version)| index_version.to_string())
+                });
+        load.prefetch(root, &relative, index_version.as_deref())
+    }
+
     pub fn query_inner(
         &mut self,
         dep: &Dependency,
@@ -516,16 +546,16 @@ impl<'cfg> RegistryIndex<'cfg> {
 impl Summaries {
     /// Parse out a `Summaries` instances from on-disk state.
     ///
-    /// This will attempt to prefer parsing a previous cache file that already
-    /// exists from a previous invocation of Cargo (aka you're typing `cargo
-    /// build` again after typing it previously). If parsing fails or the cache
-    /// isn't found, then we take a slower path which loads the full descriptor
-    /// for `relative` from the underlying index (aka typically libgit2 with
-    /// crates.io) and then parse everything in there.
+    /// This will do the followings in order:
+    ///
+    /// 1. Attempt to prefer parsing a previous cache file that already exists
+    ///    from a previous invocation of Cargo (aka you're typing `cargo build`
+    ///    again after typing it previously).
+    /// 2. If parsing fails, or the cache isn't found or is invalid, we then
+    ///    take a slower path which loads the full descriptor for `relative`
+    ///    from the underlying index (aka libgit2 with crates.io, or from a
+    ///    remote HTTP index) and then parse everything in there.
     ///
-    /// * `index_version` - a version string to describe the current state of
-    ///   the index which for remote registries is the current git sha and
-    ///   for local registries is not available.
     /// * `root` - this is the root argument passed to `load`
     /// * `cache_root` - this is the root on the filesystem itself of where to
     ///   store cache files.
@@ -536,7 +566,6 @@ impl Summaries {
     /// * `load` - the actual index implementation which may be very slow to
     ///   call. We avoid this if we can.
     pub fn parse(
-        index_version: Option<&str>,
         root: &Path,
         cache_root: &Path,
         relative: &Path,
@@ -548,89 +577,79 @@ impl Summaries {
         // of reasons, but consider all of them non-fatal and just log their
         // occurrence in case anyone is debugging anything.
         let cache_path = cache_root.join(relative);
-        let mut cache_contents = None;
-        if let Some(index_version) = index_version {
-            match fs::read(&cache_path) {
-                Ok(contents) => match Summaries::parse_cache(contents, index_version) {
-                    Ok(s) => {
-                        log::debug!("fast path for registry cache of {:?}", relative);
-                        if cfg!(debug_assertions) {
-                            cache_contents = Some(s.raw_data);
-                        } else {
-                            return Ok(Some(s));
-                        }
-                    }
-                    Err(e) => {
-                        log::debug!("failed to parse {:?} cache: {}", relative, e);
-                    }
-                },
-                Err(e) => log::debug!("cache missing for {:?} error: {}", relative, e),
-            }
+        let mut cached_summaries = None;
+        let mut index_version = None;
+        match fs::read(&cache_path) {
+            Ok(contents) => match Summaries::parse_cache(contents) {
+                Ok((s, v)) => {
+                    cached_summaries = Some(s);
+                    index_version = Some(v);
+                }
+                Err(e) => {
+                    log::debug!("failed to parse {:?} cache: {}", relative, e);
+                }
+            },
+            Err(e) => log::debug!("cache missing for {:?} error: {}", relative, e),
         }
 
-        // This is the fallback path where we actually talk to libgit2 to load
-        // information. Here we parse every single line in the index (as we need
-        // to find the versions)
-        log::debug!("slow path for {:?}", relative);
-        let mut ret = Summaries::default();
-        let mut hit_closure = false;
-        let mut cache_bytes = None;
-        let err = load.load(root, relative, &mut |contents| {
-            ret.raw_data = contents.to_vec();
-            let mut cache = SummariesCache::default();
-            hit_closure = true;
-            for line in split(contents, b'\n') {
-                // Attempt forwards-compatibility on the index by ignoring
-                // everything that we ourselves don't understand, that should
-                // allow future cargo implementations to break the
-                // interpretation of each line here and older cargo will simply
-                // ignore the new lines.
-                let summary = match IndexSummary::parse(config, line, source_id) {
-                    Ok(summary) => summary,
-                    Err(e) => {
-                        // This should only happen when there is an index
-                        // entry from a future version of cargo that this
-                        // version doesn't understand. Hopefully, those future
-                        // versions of cargo correctly set INDEX_V_MAX and
-                        // CURRENT_CACHE_VERSION, otherwise this will skip
-                        // entries in the cache preventing those newer
-                        // versions from reading them (that is, until the
-                        // cache is rebuilt).
-                        log::info!("failed to parse {:?} registry package: {}", relative, e);
-                        continue;
-                    }
-                };
-                let version = summary.summary.package_id().version().clone();
-                cache.versions.push((version.clone(), line));
-                ret.versions.insert(version, summary.into());
+        let response = load.load(root, relative, index_version.as_deref())?;
+
+        let (raw_data, index_version) = match response {
+            LoadResponse::CacheValid => {
+                log::debug!("fast path for registry cache of {:?}", relative);
+                return Ok(cached_summaries);
             }
-            if let Some(index_version) = index_version {
-                cache_bytes = Some(cache.serialize(index_version));
+            LoadResponse::NotFound => {
+                // The crate doesn't exist (anymore), so don't leave a stale
+                // cache behind for it.
+                if cached_summaries.is_some() {
+                    if let Err(e) = fs::remove_file(&cache_path) {
+                        log::info!("failed to remove stale cache: {}", e);
+                    }
+                }
+                return Ok(None);
             }
-            Ok(())
-        });
+            LoadResponse::Data {
+                raw_data,
+                index_version,
+            } => (raw_data, index_version),
+        };
 
-        // We ignore lookup failures as those are just crates which don't exist
-        // or we haven't updated the registry yet. If we actually ran the
-        // closure though then we care about those errors.
-        if !hit_closure {
-            debug_assert!(cache_contents.is_none());
-            return Ok(None);
-        }
-        err?;
-
-        // If we've got debug assertions enabled and the cache was previously
-        // present and considered fresh this is where the debug assertions
-        // actually happens to verify that our cache is indeed fresh and
-        // computes exactly the same value as before.
-        if cfg!(debug_assertions) && cache_contents.is_some() && cache_bytes != cache_contents {
-            panic!(
-                "original cache contents:\n{:?}\n\
-                 does not equal new cache contents:\n{:?}\n",
-                cache_contents.as_ref().map(|s| String::from_utf8_lossy(s)),
-                cache_bytes.as_ref().map(|s| String::from_utf8_lossy(s)),
-            );
+        // This is the fallback path where we actually talk to the registry
+        // backend to load information. Here we parse every single line in the
+        // index (as we need to find the versions)
+        log::debug!("slow path for {:?}", relative);
+        let mut ret = Summaries::default();
+        let mut cache = SummariesCache::default();
+        for line in split(&raw_data, b'\n') {
+            // Attempt forwards-compatibility on the index by ignoring
+            // everything that we ourselves don't understand, that should
+            // allow future cargo implementations to break the
+            // interpretation of each line here and older cargo will simply
+            // ignore the new lines.
+            let summary = match IndexSummary::parse(config, line, source_id) {
+                Ok(summary) => summary,
+                Err(e) => {
+                    // This should only happen when there is an index
+                    // entry from a future version of cargo that this
+                    // version doesn't understand. Hopefully, those future
+                    // versions of cargo correctly set INDEX_V_MAX and
+                    // CURRENT_CACHE_VERSION, otherwise this will skip
+                    // entries in the cache preventing those newer
+                    // versions from reading them (that is, until the
+                    // cache is rebuilt).
+                    log::info!("failed to parse {:?} registry package: {}", relative, e);
+                    continue;
+                }
+            };
+            let version = summary.summary.package_id().version().clone();
+            cache.versions.push((version.clone(), line));
+            ret.versions.insert(version, summary.into());
         }
+        let cache_bytes = index_version
+            .as_deref()
+            .map(|index_version| cache.serialize(index_version));
+        ret.raw_data = raw_data;
 
         // Once we have our `cache_bytes` which represents the `Summaries` we're
         // about to return, write that back out to disk so future Cargo
@@ -653,8 +672,12 @@ impl Summaries {
 
     /// Parses an open `File` which represents information previously cached by
     /// Cargo.
-    pub fn parse_cache(contents: Vec<u8>, last_index_update: &str) -> CargoResult<Summaries> {
-        let cache = SummariesCache::parse(&contents, last_index_update)?;
+    ///
+    /// Returns the parsed summaries along with the version of the index data
+    /// they were generated from.
+    pub fn parse_cache(contents: Vec<u8>) -> CargoResult<(Summaries, InternedString)> {
+        let (cache, index_version) = SummariesCache::parse(&contents)?;
+        let index_version = InternedString::new(index_version);
         let mut ret = Summaries::default();
         for (version, summary) in cache.versions {
             let (start, end) = subslice_bounds(&contents, summary);
@@ -662,7 +685,7 @@ impl Summaries {
                 .insert(version, MaybeIndexSummary::Unparsed { start, end });
         }
         ret.raw_data = contents;
-        return Ok(ret);
+        return Ok((ret, index_version));
 
         // Returns the start/end offsets of `inner` with `outer`. Asserts that
         // `inner` is a subslice of `outer`.
@@ -681,9 +704,9 @@ impl Summaries {
 // Implementation of serializing/deserializing the cache of summaries on disk.
 // Currently the format looks like:
 //
-// +--------------------+----------------------+-------------+---+
-// | cache version byte | index format version | git sha rev | 0 |
-// +--------------------+----------------------+-------------+---+
+// +--------------------+----------------------+---------------+---+
+// | cache version byte | index format version | index version | 0 |
+// +--------------------+----------------------+---------------+---+
 //
 // followed by...
 //
@@ -697,9 +720,12 @@ impl Summaries {
 //
 // The leading version byte is intended to ensure that there's some level of
 // future compatibility against changes to this cache format so if different
-// versions of Cargo share the same cache they don't get too confused. The git
-// sha lets us know when the file needs to be regenerated (it needs regeneration
-// whenever the index itself updates).
+// versions of Cargo share the same cache they don't get too confused. The
+// index version lets us know when the file needs to be regenerated. For git
+// indexes it is the git sha of the index (it needs regeneration whenever the
+// index itself updates), and for HTTP indexes it is the `ETag` or
+// `Last-Modified` header of the index file, which is passed back to the server
+// to check whether the file has changed.
 //
 // Cache versions:
 // * `1`: The original version.
@@ -718,7 +744,9 @@ impl Summaries {
 const CURRENT_CACHE_VERSION: u8 = 3;
 
 impl<'a> SummariesCache<'a> {
-    fn parse(data: &'a [u8], last_index_update: &str) -> CargoResult<SummariesCache<'a>> {
+    /// Parses the cache, returning it along with the index version it was
+    /// generated from.
+    fn parse(data: &'a [u8]) -> CargoResult<(SummariesCache<'a>, &'a str)> {
         // NB: keep this method in sync with `serialize` below
         let (first_byte, rest) = data
             .split_first()
@@ -740,17 +768,11 @@ impl<'a> SummariesCache<'a> {
         let rest = &rest[4..];
 
         let mut iter = split(rest, 0);
-        if let Some(update) = iter.next() {
-            if update != last_index_update.as_bytes() {
-                bail!(
-                    "cache out of date: current index ({}) != cache ({})",
-                    last_index_update,
-                    str::from_utf8(update)?,
-                )
-            }
+        let last_index_update = if let Some(update) = iter.next() {
+            str::from_utf8(update)?
         } else {
             bail!("malformed file");
-        }
+        };
         let mut ret = SummariesCache::default();
         while let Some(version) = iter.next() {
             let version = str::from_utf8(version)?;
@@ -758,7 +780,7 @@ impl<'a> SummariesCache<'a> {
             let summary = iter.next().unwrap();
             ret.versions.push((version, summary));
         }
-        Ok(ret)
+        Ok((ret, last_index_update))
     }
 
     fn serialize(&self, index_version: &str) -> Vec<u8> {
@@ -835,6 +857,7 @@ impl IndexSummary {
             features2,
             yanked,
             links,
+            rust_version,
             v,
         } = serde_json::from_slice(line)?;
         let v = v.unwrap_or(1);
@@ -851,6 +874,7 @@ impl IndexSummary {
         }
         let mut summary = Summary::new(config, pkgid, deps, &features, links)?;
         summary.set_checksum(cksum);
+        summary.set_rust_version(rust_version);
         Ok(IndexSummary {
             summary,
             yanked: yanked.unwrap_or(false),
@@ -1,7 +1,6 @@
 use crate::core::PackageId;
-use crate::sources::registry::{MaybeLock, RegistryConfig, RegistryData};
+use crate::sources::registry::{LoadResponse, MaybeLock, RegistryConfig, RegistryData};
 use crate::util::errors::CargoResult;
-use crate::util::interning::InternedString;
 use crate::util::{Config, Filesystem};
 use cargo_util::{paths, Sha256};
 use std::fs::File;
@@ -45,17 +44,21 @@ impl<'cfg> RegistryData for LocalRegistry<'cfg> {
         path.as_path_unlocked()
     }
 
-    fn current_version(&self) -> Option<InternedString> {
-        None
-    }
-
     fn load(
-        &self,
+        &mut self,
         root: &Path,
         path: &Path,
-        data: &mut dyn FnMut(&[u8]) -> CargoResult<()>,
-    ) -> CargoResult<()> {
-        data(&paths::read_bytes(&root.join(path))?)
+        _index_version: Option<&str>,
+    ) -> CargoResult<LoadResponse> {
+        // Local registries have no versioning, so the cache (if any) is never
+        // trusted and the file is simply read from disk every time.
+        match paths::read_bytes(&root.join(path)) {
+            Ok(raw_data) => Ok(LoadResponse::Data {
+                raw_data,
+                index_version: None,
+            }),
+            Err(_) => Ok(LoadResponse::NotFound),
+        }
     }
 
     fn config(&mut self) -> CargoResult<Option<RegistryConfig>> {
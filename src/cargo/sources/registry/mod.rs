@@ -173,6 +173,7 @@ use serde::Deserialize;
 use tar::Archive;
 
 use crate::core::dependency::{DepKind, Dependency};
+use crate::core::gc;
 use crate::core::source::MaybePackage;
 use crate::core::{Package, PackageId, Source, SourceId, Summary};
 use crate::sources::PathSource;
@@ -192,7 +193,7 @@ const LOWER_PREFIX_TEMPLATE: &str = "{lowerprefix}";
 const CHECKSUM_TEMPLATE: &str = "{sha256-checksum}";
 
 /// A "source" for a local (see `local::LocalRegistry`) or remote (see
-/// `remote::RemoteRegistry`) registry.
+/// `remote::RemoteRegistry` and `http_remote::HttpRegistry`) registry.
 ///
 /// This contains common functionality that is shared between the two registry
 /// kinds, with the registry-specific logic implemented as part of the
@@ -227,7 +228,7 @@ pub struct RegistrySource<'cfg> {
 }
 
 /// The `config.json` file stored in the index.
-#[derive(Deserialize)]
+#[derive(Clone, Deserialize)]
 pub struct RegistryConfig {
     /// Download endpoint for all crates.
     ///
@@ -283,6 +284,11 @@ pub struct RegistryPackage<'a> {
     /// Added early 2018 (see <https://github.com/rust-lang/cargo/pull/4978>),
     /// can be `None` if published before then.
     links: Option<InternedString>,
+    /// The minimum supported Rust version of this package.
+    ///
+    /// Added in 2022, can be `None` if published before then or if the
+    /// package doesn't declare a `rust-version`.
+    rust_version: Option<InternedString>,
     /// The schema version for this entry.
     ///
     /// If this is None, it defaults to version 1. Entries with unknown
@@ -439,13 +445,38 @@ pub trait RegistryData {
     ///
     /// * `root` is the root path to the index.
     /// * `path` is the relative path to the package to load (like `ca/rg/cargo`).
-    /// * `data` is a callback that will receive the raw bytes of the index JSON file.
+    /// * `index_version` is the version of the requested crate data currently
+    ///   in cache. This is the value previously returned in
+    ///   [`LoadResponse::Data`], and is `None` if nothing is cached.
     fn load(
-        &self,
+        &mut self,
         root: &Path,
         path: &Path,
-        data: &mut dyn FnMut(&[u8]) -> CargoResult<()>,
-    ) -> CargoResult<()>;
+        index_version: Option<&str>,
+    ) -> CargoResult<LoadResponse>;
+
+    /// Whether [`RegistryData::prefetch`] does anything for this registry.
+    ///
+    /// This lets the index skip the work of looking up the cached
+    /// `index_version` for registries which don't prefetch.
+    fn supports_prefetch(&self) -> bool {
+        false
+    }
+
+    /// Hints that the index file at `path` is likely to be loaded soon.
+    ///
+    /// This must not block. Registries which have to fetch index files one
+    /// at a time (see `http_remote::HttpRegistry`) use this to start fetching
+    /// several of them in parallel. The arguments are the same as for
+    /// [`RegistryData::load`].
+    fn prefetch(
+        &mut self,
+        _root: &Path,
+        _path: &Path,
+        _index_version: Option<&str>,
+    ) -> CargoResult<()> {
+        Ok(())
+    }
 
     /// Loads the `config.json` file and returns it.
     ///
@@ -499,15 +530,24 @@ pub trait RegistryData {
     ///
     /// Returns the [`Path`] to the [`Filesystem`].
     fn assert_index_locked<'a>(&self, path: &'a Filesystem) -> &'a Path;
+}
 
-    /// Returns the current "version" of the index.
-    ///
-    /// For local registries, this returns `None` because there is no
-    /// versioning. For remote registries, this returns the SHA hash of the
-    /// git index on disk (or None if the index hasn't been downloaded yet).
-    ///
-    /// This is used by index caching to check if the cache is out of date.
-    fn current_version(&self) -> Option<InternedString>;
+/// The result of [`RegistryData::load`] for a single index file.
+pub enum LoadResponse {
+    /// The cache is valid. The cached data should be used.
+    CacheValid,
+
+    /// The cache is out of date. Returned data should be used.
+    Data {
+        raw_data: Vec<u8>,
+        /// Version of this data to store in the cache, and to pass back to
+        /// [`RegistryData::load`] on subsequent calls. `None` means the data
+        /// should not be cached.
+        index_version: Option<String>,
+    },
+
+    /// The requested crate was not found.
+    NotFound,
 }
 
 /// The status of [`RegistryData::download`] which indicates if a `.crate`
@@ -523,6 +563,7 @@ pub enum MaybeLock {
     Download { url: String, descriptor: String },
 }
 
+mod http_remote;
 mod index;
 mod local;
 mod remote;
@@ -533,15 +574,36 @@ fn short_name(id: SourceId) -> String {
     format!("{}-{}", ident, hash)
 }
 
+/// The directory where the packages of the registry `source_id` are unpacked.
+pub(crate) fn unpacked_path(config: &Config, source_id: SourceId) -> Filesystem {
+    config.registry_source_path().join(short_name(source_id))
+}
+
 impl<'cfg> RegistrySource<'cfg> {
     pub fn remote(
         source_id: SourceId,
         yanked_whitelist: &HashSet<PackageId>,
         config: &'cfg Config,
-    ) -> RegistrySource<'cfg> {
+    ) -> CargoResult<RegistrySource<'cfg>> {
         let name = short_name(source_id);
-        let ops = remote::RemoteRegistry::new(source_id, config, &name);
-        RegistrySource::new(source_id, config, &name, Box::new(ops), yanked_whitelist)
+        let ops: Box<dyn RegistryData + 'cfg> = if source_id.is_sparse() {
+            if !config.cli_unstable().sparse_registry {
+                anyhow::bail!(
+                    "usage of sparse registry `{}` requires `-Z sparse-registry`",
+                    source_id.url()
+                );
+            }
+            Box::new(http_remote::HttpRegistry::new(source_id, config, &name)?)
+        } else {
+            Box::new(remote::RemoteRegistry::new(source_id, config, &name))
+        };
+        Ok(RegistrySource::new(
+            source_id,
+            config,
+            &name,
+            ops,
+            yanked_whitelist,
+        ))
     }
 
     pub fn local(
@@ -580,6 +642,21 @@ impl<'cfg> RegistrySource<'cfg> {
         self.ops.config()
     }
 
+    /// Returns every version of `name` listed in the index, including yanked
+    /// ones, along with whether it is yanked.
+    pub fn index_versions(&mut self, name: InternedString) -> CargoResult<Vec<(Summary, bool)>> {
+        Ok(self
+            .index
+            .summaries(name, &OptVersionReq::Any, &mut *self.ops)?
+            .map(|s| (s.summary.clone(), s.yanked))
+            .collect())
+    }
+
+    /// Returns `true` if the `.crate` file of `pkg` is available locally.
+    pub fn is_crate_downloaded(&self, pkg: PackageId) -> bool {
+        self.ops.is_crate_downloaded(pkg)
+    }
+
     /// Unpacks a downloaded package into a location where it's ready to be
     /// compiled.
     ///
@@ -595,6 +672,7 @@ impl<'cfg> RegistrySource<'cfg> {
         let unpack_dir = path.parent().unwrap();
         if let Ok(meta) = path.metadata() {
             if meta.len() > 0 {
+                gc::record_use(unpack_dir);
                 return Ok(unpack_dir.to_path_buf());
             }
         }
@@ -650,6 +728,7 @@ impl<'cfg> RegistrySource<'cfg> {
         // Write to the lock file to indicate that unpacking was successful.
         write!(ok, "ok")?;
 
+        gc::record_use(unpack_dir);
         Ok(unpack_dir.to_path_buf())
     }
 
@@ -728,6 +807,10 @@ impl<'cfg> Source for RegistrySource<'cfg> {
             .query_inner(dep, &mut *self.ops, &self.yanked_whitelist, f)
     }
 
+    fn prefetch(&mut self, dep: &Dependency) -> CargoResult<()> {
+        self.index.prefetch(dep.package_name(), &mut *self.ops)
+    }
+
     fn supports_checksums(&self) -> bool {
         true
     }
@@ -1,9 +1,10 @@
+use crate::core::gc;
 use crate::core::{GitReference, PackageId, SourceId};
 use crate::sources::git;
 use crate::sources::registry::MaybeLock;
 use crate::sources::registry::{
-    RegistryConfig, RegistryData, CHECKSUM_TEMPLATE, CRATE_TEMPLATE, LOWER_PREFIX_TEMPLATE,
-    PREFIX_TEMPLATE, VERSION_TEMPLATE,
+    LoadResponse, RegistryConfig, RegistryData, CHECKSUM_TEMPLATE, CRATE_TEMPLATE,
+    LOWER_PREFIX_TEMPLATE, PREFIX_TEMPLATE, VERSION_TEMPLATE,
 };
 use crate::util::errors::CargoResult;
 use crate::util::interning::InternedString;
@@ -133,6 +134,36 @@ impl<'cfg> RemoteRegistry<'cfg> {
         Ok(Ref::map(self.tree.borrow(), |s| s.as_ref().unwrap()))
     }
 
+    /// Returns the SHA hash of the git index on disk, or `None` if the index
+    /// hasn't been downloaded yet.
+    ///
+    /// This is used as the version of each index file in the summary cache,
+    /// so that the cache is invalidated whenever the index itself updates.
+    fn current_version(&self) -> Option<InternedString> {
+        if let Some(sha) = self.current_sha.get() {
+            return Some(sha);
+        }
+        let sha = InternedString::new(&self.head().ok()?.to_string());
+        self.current_sha.set(Some(sha));
+        Some(sha)
+    }
+
+    /// Reads the contents of the file at `path` in the git index.
+    fn load_blob(&self, path: &Path) -> CargoResult<Vec<u8>> {
+        // Note that the index calls this method and the filesystem is locked
+        // in the index, so we don't need to worry about an `update_index`
+        // happening in a different process.
+        let repo = self.repo()?;
+        let tree = self.tree()?;
+        let entry = tree.get_path(path)?;
+        let object = entry.to_object(repo)?;
+        let blob = match object.as_blob() {
+            Some(blob) => blob,
+            None => anyhow::bail!("path `{}` is not a blob in the git repo", path.display()),
+        };
+        Ok(blob.content().to_vec())
+    }
+
     fn filename(&self, pkg: PackageId) -> String {
         format!("{}-{}.crate", pkg.name(), pkg.version())
     }
@@ -154,46 +185,42 @@ impl<'cfg> RegistryData for RemoteRegistry<'cfg> {
         self.config.assert_package_cache_locked(path)
     }
 
-    fn current_version(&self) -> Option<InternedString> {
-        if let Some(sha) = self.current_sha.get() {
-            return Some(sha);
-        }
-        let sha = InternedString::new(&self.head().ok()?.to_string());
-        self.current_sha.set(Some(sha));
-        Some(sha)
-    }
-
     fn load(
-        &self,
+        &mut self,
         _root: &Path,
         path: &Path,
-        data: &mut dyn FnMut(&[u8]) -> CargoResult<()>,
-    ) -> CargoResult<()> {
-        // Note that the index calls this method and the filesystem is locked
-        // in the index, so we don't need to worry about an `update_index`
-        // happening in a different process.
-        let repo = self.repo()?;
-        let tree = self.tree()?;
-        let entry = tree.get_path(path)?;
-        let object = entry.to_object(repo)?;
-        let blob = match object.as_blob() {
-            Some(blob) => blob,
-            None => anyhow::bail!("path `{}` is not a blob in the git repo", path.display()),
-        };
-        data(blob.content())
+        index_version: Option<&str>,
+    ) -> CargoResult<LoadResponse> {
+        // Check if the cache is valid.
+        let current_version = self.current_version();
+        if let (Some(c), Some(i)) = (current_version, index_version) {
+            if c.as_str() == i {
+                return Ok(LoadResponse::CacheValid);
+            }
+        }
+
+        // Lookup failures are just crates which don't exist or which we
+        // haven't updated the registry for yet.
+        match self.load_blob(path) {
+            Ok(raw_data) => Ok(LoadResponse::Data {
+                raw_data,
+                index_version: current_version.map(|v| v.to_string()),
+            }),
+            Err(e) => {
+                debug!("failed to load {:?} from the index: {}", path, e);
+                Ok(LoadResponse::NotFound)
+            }
+        }
     }
 
     fn config(&mut self) -> CargoResult<Option<RegistryConfig>> {
         debug!("loading config");
         self.prepare()?;
         self.config.assert_package_cache_locked(&self.index_path);
-        let mut config = None;
-        self.load(Path::new(""), Path::new("config.json"), &mut |json| {
-            config = Some(serde_json::from_slice(json)?);
-            Ok(())
-        })?;
+        let json = self.load_blob(Path::new("config.json"))?;
+        let config = serde_json::from_slice(&json)?;
         trace!("config loaded");
-        Ok(config)
+        Ok(Some(config))
     }
 
     fn update_index(&mut self) -> CargoResult<()> {
@@ -258,6 +285,7 @@ impl<'cfg> RegistryData for RemoteRegistry<'cfg> {
         if let Ok(dst) = File::open(&path) {
             let meta = dst.metadata()?;
             if meta.len() > 0 {
+                gc::record_use(path);
                 return Ok(MaybeLock::Ready(dst));
             }
         }
@@ -65,6 +65,15 @@ impl<'cfg> Source for ReplacedSource<'cfg> {
         Ok(())
     }
 
+    fn prefetch(&mut self, dep: &Dependency) -> CargoResult<()> {
+        let (replace_with, to_replace) = (self.replace_with, self.to_replace);
+        let dep = dep.clone().map_source(to_replace, replace_with);
+
+        self.inner
+            .prefetch(&dep)
+            .with_context(|| format!("failed to query replaced source {}", self.to_replace))
+    }
+
     fn update(&mut self) -> CargoResult<()> {
         self.inner
             .update()
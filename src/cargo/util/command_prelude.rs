@@ -1,4 +1,5 @@
 use crate::core::compiler::{BuildConfig, MessageFormat};
+use crate::core::dependency::DepKind;
 use crate::core::resolver::CliFeatures;
 use crate::core::{Edition, Workspace};
 use crate::ops::{CompileFilter, CompileOptions, NewOptions, Packages, VersionControl};
@@ -7,6 +8,7 @@ use crate::util::important_paths::find_root_manifest_for_wd;
 use crate::util::interning::InternedString;
 use crate::util::restricted_names::is_glob_pattern;
 use crate::util::toml::{StringOrVec, TomlProfile};
+use crate::util::toml_mut::manifest::DepTable;
 use crate::util::validate_package_name;
 use crate::util::{
     print_available_benches, print_available_binaries, print_available_examples,
@@ -625,6 +627,29 @@ pub trait ArgMatchesExt {
         Ok(index)
     }
 
+    /// The dependency table selected by `--dev`, `--build` and `--target`,
+    /// as used by `cargo add` and `cargo remove`.
+    fn dep_table(&self) -> CargoResult<DepTable> {
+        let kind = if self._is_present("dev") {
+            DepKind::Development
+        } else if self._is_present("build") {
+            DepKind::Build
+        } else {
+            DepKind::Normal
+        };
+
+        let mut table = DepTable::new().set_kind(kind);
+
+        if let Some(target) = self._value_of("target") {
+            if target.is_empty() {
+                bail!("target specification may not be empty");
+            }
+            table = table.set_target(target);
+        }
+
+        Ok(table)
+    }
+
     fn check_optional_opts(
         &self,
         workspace: &Workspace<'_>,
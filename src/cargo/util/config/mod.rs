@@ -50,7 +50,7 @@
 //! desired type.
 
 use std::borrow::Cow;
-use std::cell::{RefCell, RefMut};
+use std::cell::{Cell, RefCell, RefMut};
 use std::collections::hash_map::Entry::{Occupied, Vacant};
 use std::collections::{HashMap, HashSet};
 use std::env;
@@ -181,6 +181,9 @@ pub struct Config {
     future_incompat_config: LazyCell<CargoFutureIncompatConfig>,
     net_config: LazyCell<CargoNetConfig>,
     build_config: LazyCell<CargoBuildConfig>,
+    /// Whether the warning that `build.build-dir` is ignored has been shown.
+    build_dir_warned: Cell<bool>,
+    resolver_config: LazyCell<CargoResolverConfig>,
     target_cfgs: LazyCell<Vec<(String, TargetCfgConfig)>>,
     doc_extern_map: LazyCell<RustdocExternMap>,
     progress_config: ProgressConfig,
@@ -279,6 +282,8 @@ impl Config {
             future_incompat_config: LazyCell::new(),
             net_config: LazyCell::new(),
             build_config: LazyCell::new(),
+            build_dir_warned: Cell::new(false),
+            resolver_config: LazyCell::new(),
             target_cfgs: LazyCell::new(),
             doc_extern_map: LazyCell::new(),
             progress_config: ProgressConfig::default(),
@@ -1447,11 +1452,26 @@ impl Config {
             .try_borrow_with(|| self.get::<CargoNetConfig>("net"))
     }
 
+    pub fn resolver_config(&self) -> CargoResult<&CargoResolverConfig> {
+        self.resolver_config
+            .try_borrow_with(|| self.get::<CargoResolverConfig>("resolver"))
+    }
+
     pub fn build_config(&self) -> CargoResult<&CargoBuildConfig> {
         self.build_config
             .try_borrow_with(|| self.get::<CargoBuildConfig>("build"))
     }
 
+    /// Warns that `build.build-dir` is ignored without `-Z build-dir`, at most
+    /// once since workspaces may be loaded several times.
+    pub fn warn_build_dir_ignored(&self) -> CargoResult<()> {
+        if self.build_dir_warned.replace(true) {
+            return Ok(());
+        }
+        self.shell()
+            .warn("ignoring `build.build-dir` config, pass `-Z build-dir` to enable it")
+    }
+
     pub fn progress_config(&self) -> &ProgressConfig {
         &self.progress_config
     }
@@ -2071,6 +2091,25 @@ impl Default for CargoFutureIncompatFrequencyConfig {
     }
 }
 
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct CargoResolverConfig {
+    pub rust_version_policy: Option<RustVersionPolicy>,
+}
+
+/// How the resolver treats dependency versions whose `rust-version` is newer
+/// than the one the workspace targets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum RustVersionPolicy {
+    /// Never select incompatible versions.
+    Required,
+    /// Select incompatible versions only if nothing compatible exists.
+    Preferred,
+    /// Ignore `rust-version` during resolution.
+    Off,
+}
+
 /// Configuration for `ssl-version` in `http` section
 /// There are two ways to configure:
 ///
@@ -2111,6 +2150,7 @@ pub struct CargoBuildConfig {
     pub pipelining: Option<bool>,
     pub dep_info_basedir: Option<ConfigRelativePath>,
     pub target_dir: Option<ConfigRelativePath>,
+    pub build_dir: Option<ConfigRelativePath>,
     pub incremental: Option<bool>,
     pub target: Option<ConfigRelativePath>,
     pub jobs: Option<u32>,
@@ -2121,6 +2161,8 @@ pub struct CargoBuildConfig {
     pub rustc: Option<ConfigRelativePath>,
     pub rustdoc: Option<ConfigRelativePath>,
     pub out_dir: Option<ConfigRelativePath>,
+    pub cache_dir: Option<ConfigRelativePath>,
+    pub cache_max_size: Option<u64>,
 }
 
 #[derive(Deserialize, Default)]
@@ -190,6 +190,10 @@ fn parse_links_overrides(
                     let list = value.list(key)?;
                     output.cfgs.extend(list.iter().map(|v| v.0.clone()));
                 }
+                "rustc-check-cfg" => {
+                    let list = value.list(key)?;
+                    output.check_cfgs.extend(list.iter().map(|v| v.0.clone()));
+                }
                 "rustc-env" => {
                     for (name, val) in value.table(key)?.0 {
                         let val = val.string(name)?.0;
@@ -177,6 +177,24 @@ impl Filesystem {
             State::Exclusive,
             config,
             msg,
+            true,
+        )
+        .map(|lock| lock.expect("blocking locks are always acquired"))
+    }
+
+    /// Like [`Filesystem::open_rw`], but returns `None` instead of blocking
+    /// if another process holds a lock on the file.
+    pub fn try_open_rw<P>(&self, path: P, config: &Config) -> CargoResult<Option<FileLock>>
+    where
+        P: AsRef<Path>,
+    {
+        self.open(
+            path.as_ref(),
+            OpenOptions::new().read(true).write(true).create(true),
+            State::Exclusive,
+            config,
+            "",
+            false,
         )
     }
 
@@ -199,9 +217,13 @@ impl Filesystem {
             State::Shared,
             config,
             msg,
+            true,
         )
+        .map(|lock| lock.expect("blocking locks are always acquired"))
     }
 
+    /// Opens and locks the file at `path`, returning `None` if `block` is
+    /// false and the lock is held by another process.
     fn open(
         &self,
         path: &Path,
@@ -209,7 +231,8 @@ impl Filesystem {
         state: State,
         config: &Config,
         msg: &str,
-    ) -> CargoResult<FileLock> {
+        block: bool,
+    ) -> CargoResult<Option<FileLock>> {
         let path = self.root.join(path);
 
         // If we want an exclusive lock then if we fail because of NotFound it's
@@ -226,24 +249,33 @@ impl Filesystem {
                 }
             })
             .with_context(|| format!("failed to open: {}", path.display()))?;
-        match state {
-            State::Exclusive => {
-                acquire(config, msg, &path, &|| try_lock_exclusive(&f), &|| {
-                    lock_exclusive(&f)
-                })?;
-            }
-            State::Shared => {
-                acquire(config, msg, &path, &|| try_lock_shared(&f), &|| {
-                    lock_shared(&f)
-                })?;
-            }
-            State::Unlocked => {}
+        let lock_exclusive = || lock_exclusive(&f);
+        let lock_shared = || lock_shared(&f);
+        let acquired = match state {
+            State::Exclusive => acquire(
+                config,
+                msg,
+                &path,
+                &|| try_lock_exclusive(&f),
+                if block { Some(&lock_exclusive) } else { None },
+            )?,
+            State::Shared => acquire(
+                config,
+                msg,
+                &path,
+                &|| try_lock_shared(&f),
+                if block { Some(&lock_shared) } else { None },
+            )?,
+            State::Unlocked => true,
+        };
+        if !acquired {
+            return Ok(None);
         }
-        Ok(FileLock {
+        Ok(Some(FileLock {
             f: Some(f),
             path,
             state,
-        })
+        }))
     }
 }
 
@@ -270,7 +302,8 @@ impl PartialEq<Filesystem> for Path {
 /// to the console if we have to wait for it. It will first attempt to use `try`
 /// to acquire a lock on the crate, and in the case of contention it will emit a
 /// status message based on `msg` to `config`'s shell, and then use `block` to
-/// block waiting to acquire a lock.
+/// block waiting to acquire a lock. Without `block`, `false` is returned
+/// instead of waiting.
 ///
 /// Returns an error if the lock could not be acquired or if any error other
 /// than a contention error happens.
@@ -279,8 +312,8 @@ fn acquire(
     msg: &str,
     path: &Path,
     lock_try: &dyn Fn() -> io::Result<()>,
-    lock_block: &dyn Fn() -> io::Result<()>,
-) -> CargoResult<()> {
+    lock_block: Option<&dyn Fn() -> io::Result<()>>,
+) -> CargoResult<bool> {
     // File locking on Unix is currently implemented via `flock`, which is known
     // to be broken on NFS. We could in theory just ignore errors that happen on
     // NFS, but apparently the failure mode [1] for `flock` on NFS is **blocking
@@ -292,16 +325,16 @@ fn acquire(
     //
     // [1]: https://github.com/rust-lang/cargo/issues/2615
     if is_on_nfs_mount(path) {
-        return Ok(());
+        return Ok(true);
     }
 
     match lock_try() {
-        Ok(()) => return Ok(()),
+        Ok(()) => return Ok(true),
 
         // In addition to ignoring NFS which is commonly not working we also
         // just ignore locking on filesystems that look like they don't
         // implement file locking.
-        Err(e) if error_unsupported(&e) => return Ok(()),
+        Err(e) if error_unsupported(&e) => return Ok(true),
 
         Err(e) => {
             if !error_contended(&e) {
@@ -311,11 +344,15 @@ fn acquire(
             }
         }
     }
+    let lock_block = match lock_block {
+        Some(lock_block) => lock_block,
+        None => return Ok(false),
+    };
     let msg = format!("waiting for file lock on {}", msg);
     config.shell().status_with_color("Blocking", &msg, Cyan)?;
 
     lock_block().with_context(|| format!("failed to lock file: {}", path.display()))?;
-    return Ok(());
+    return Ok(true);
 
     #[cfg(all(target_os = "linux", not(target_env = "musl")))]
     fn is_on_nfs_mount(path: &Path) -> bool {
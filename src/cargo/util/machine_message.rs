@@ -4,7 +4,9 @@ use serde::ser;
 use serde::Serialize;
 use serde_json::{self, json, value::RawValue};
 
-use crate::core::{compiler::CompileMode, PackageId, Target};
+use crate::core::compiler::{CompileMode, DirtyReason};
+use crate::core::resolver::ResolveConflict;
+use crate::core::{PackageId, Target};
 
 pub trait Message: ser::Serialize {
     fn reason(&self) -> &str;
@@ -40,6 +42,9 @@ pub struct Artifact<'a> {
     pub features: Vec<String>,
     pub filenames: Vec<PathBuf>,
     pub executable: Option<PathBuf>,
+    /// The SBOM files written next to `filenames` with `-Z sbom`.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub sboms: Vec<PathBuf>,
     pub fresh: bool,
 }
 
@@ -49,6 +54,24 @@ impl<'a> Message for Artifact<'a> {
     }
 }
 
+/// Emitted with `--explain-rebuilds` for every unit that is rebuilt, except
+/// units that were never built before.
+#[derive(Serialize)]
+pub struct UnitDirty<'a> {
+    pub package_id: PackageId,
+    pub manifest_path: &'a Path,
+    pub target: &'a Target,
+    /// The human readable form of `dirty_reason`.
+    pub message: String,
+    pub dirty_reason: &'a DirtyReason,
+}
+
+impl<'a> Message for UnitDirty<'a> {
+    fn reason(&self) -> &str {
+        "unit-dirty"
+    }
+}
+
 /// This is different from the regular `Profile` to maintain backwards
 /// compatibility (in particular, `test` is no longer in `Profile`, but we
 /// still want it to be included here).
@@ -103,3 +126,20 @@ impl Message for BuildFinished {
         "build-finished"
     }
 }
+
+#[derive(Serialize)]
+pub struct ResolveFailed<'a> {
+    /// The error as it is displayed to the user.
+    pub message: String,
+    #[serde(flatten)]
+    pub conflict: Option<&'a ResolveConflict>,
+    /// The packages from the one whose requirements could not be resolved up
+    /// to the root.
+    pub package_path: &'a [PackageId],
+}
+
+impl<'a> Message for ResolveFailed<'a> {
+    fn reason(&self) -> &str {
+        "resolve-error"
+    }
+}
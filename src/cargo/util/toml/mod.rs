@@ -8,6 +8,7 @@ use std::str;
 use anyhow::{anyhow, bail, Context as _};
 use cargo_platform::Platform;
 use cargo_util::paths;
+use lazycell::LazyCell;
 use log::{debug, trace};
 use semver::{self, VersionReq};
 use serde::de;
@@ -16,7 +17,7 @@ use serde::{Deserialize, Serialize};
 use url::Url;
 
 use crate::core::compiler::{CompileKind, CompileTarget};
-use crate::core::dependency::DepKind;
+use crate::core::dependency::{Artifact, DepKind};
 use crate::core::manifest::{ManifestMetadata, TargetSourcePath, Warnings};
 use crate::core::resolver::ResolveBehavior;
 use crate::core::{Dependency, Manifest, PackageId, Summary, Target};
@@ -261,6 +262,20 @@ pub struct DetailedTomlDependency<P = String> {
     default_features2: Option<bool>,
     package: Option<String>,
     public: Option<bool>,
+    /// Whether this dependency is inherited from `[workspace.dependencies]`.
+    ///
+    /// Only `features` and `optional` may be specified alongside it. This is
+    /// replaced with the workspace's definition when the manifest is loaded.
+    workspace: Option<bool>,
+    /// One or more of `bin`, `bin:<name>`, `cdylib` or `staticlib`, the
+    /// artifacts of the dependency that are built and made available to the
+    /// dependent.
+    artifact: Option<StringOrVec>,
+    /// Whether the library of an artifact dependency is also used.
+    lib: Option<bool>,
+    /// The target to build an artifact dependency for, or `"target"` to build
+    /// a build-dependency for the target the dependent is built for.
+    target: Option<String>,
 }
 
 // Explicit implementation so we avoid pulling in P: Default
@@ -281,12 +296,16 @@ impl<P> Default for DetailedTomlDependency<P> {
             default_features2: Default::default(),
             package: Default::default(),
             public: Default::default(),
+            workspace: Default::default(),
+            artifact: Default::default(),
+            lib: Default::default(),
+            target: Default::default(),
         }
     }
 }
 
 /// This type is used to deserialize `Cargo.toml` files.
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(rename_all = "kebab-case")]
 pub struct TomlManifest {
     cargo_features: Option<Vec<String>>,
@@ -311,6 +330,7 @@ pub struct TomlManifest {
     patch: Option<BTreeMap<String, BTreeMap<String, TomlDependency>>>,
     workspace: Option<TomlWorkspace>,
     badges: Option<BTreeMap<String, BTreeMap<String, String>>>,
+    lints: Option<MaybeWorkspace<TomlLints>>,
 }
 
 #[derive(Deserialize, Serialize, Clone, Debug, Default)]
@@ -413,6 +433,8 @@ pub struct TomlProfile {
     pub dir_name: Option<InternedString>,
     pub inherits: Option<InternedString>,
     pub strip: Option<StringOrBool>,
+    pub rustflags: Option<Vec<InternedString>>,
+    pub trim_paths: Option<bool>,
     // These two fields must be last because they are sub-tables, and TOML
     // requires all non-tables to be listed first.
     pub package: Option<BTreeMap<ProfilePackageSpec, TomlProfile>>,
@@ -534,6 +556,14 @@ impl TomlProfile {
             features.require(Feature::strip())?;
         }
 
+        if self.rustflags.is_some() {
+            features.require(Feature::profile_rustflags())?;
+        }
+
+        if self.trim_paths.is_some() {
+            features.require(Feature::trim_paths())?;
+        }
+
         if let Some(codegen_backend) = &self.codegen_backend {
             features.require(Feature::codegen_backend())?;
             if codegen_backend.contains(|c: char| !c.is_ascii_alphanumeric() && c != '_') {
@@ -646,6 +676,12 @@ impl TomlProfile {
         if self.codegen_backend.is_some() {
             features.require(Feature::codegen_backend())?;
         }
+        if self.rustflags.is_some() {
+            features.require(Feature::profile_rustflags())?;
+        }
+        if self.trim_paths.is_some() {
+            features.require(Feature::trim_paths())?;
+        }
         Ok(())
     }
 
@@ -729,6 +765,14 @@ impl TomlProfile {
         if let Some(v) = &profile.strip {
             self.strip = Some(v.clone());
         }
+
+        if let Some(v) = &profile.rustflags {
+            self.rustflags = Some(v.clone());
+        }
+
+        if let Some(v) = profile.trim_paths {
+            self.trim_paths = Some(v);
+        }
     }
 }
 
@@ -825,14 +869,16 @@ impl<'de> de::Deserialize<'de> for VecStringOrBool {
     }
 }
 
-fn version_trim_whitespace<'de, D>(deserializer: D) -> Result<semver::Version, D::Error>
+fn version_trim_whitespace<'de, D>(
+    deserializer: D,
+) -> Result<MaybeWorkspace<semver::Version>, D::Error>
 where
     D: de::Deserializer<'de>,
 {
     struct Visitor;
 
     impl<'de> de::Visitor<'de> for Visitor {
-        type Value = semver::Version;
+        type Value = MaybeWorkspace<semver::Version>;
 
         fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
             formatter.write_str("SemVer version")
@@ -842,11 +888,83 @@ where
         where
             E: de::Error,
         {
-            string.trim().parse().map_err(de::Error::custom)
+            match string.trim().parse().map_err(de::Error::custom) {
+                Ok(parsed) => Ok(MaybeWorkspace::Defined(parsed)),
+                Err(e) => Err(e),
+            }
+        }
+
+        fn visit_map<V>(self, map: V) -> Result<Self::Value, V::Error>
+        where
+            V: de::MapAccess<'de>,
+        {
+            let mvd = de::value::MapAccessDeserializer::new(map);
+            TomlWorkspaceField::deserialize(mvd).map(MaybeWorkspace::Workspace)
+        }
+    }
+
+    deserializer.deserialize_any(Visitor)
+}
+
+/// A `[package]` value which may be inherited from the workspace root with
+/// `key.workspace = true`.
+///
+/// Inherited values are replaced with the ones from `[workspace.package]` by
+/// `TomlManifest::inherit_from_workspace` before the manifest is otherwise
+/// interpreted, see [`MaybeWorkspace::defined`].
+#[derive(Serialize, Clone, Debug)]
+#[serde(untagged)]
+pub enum MaybeWorkspace<T> {
+    Defined(T),
+    Workspace(TomlWorkspaceField),
+}
+
+impl<'de, T: de::DeserializeOwned> de::Deserialize<'de> for MaybeWorkspace<T> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        let value = toml::Value::deserialize(deserializer)?;
+        let inherited = value
+            .as_table()
+            .map_or(false, |t| t.contains_key("workspace"));
+        if inherited {
+            TomlWorkspaceField::deserialize(value)
+                .map(MaybeWorkspace::Workspace)
+                .map_err(de::Error::custom)
+        } else {
+            T::deserialize(value)
+                .map(MaybeWorkspace::Defined)
+                .map_err(de::Error::custom)
         }
     }
+}
 
-    deserializer.deserialize_str(Visitor)
+impl<T> MaybeWorkspace<T> {
+    /// Returns the value once it's been inherited from the workspace.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called before `TomlManifest::inherit_from_workspace`.
+    fn defined(&self) -> &T {
+        match self {
+            MaybeWorkspace::Defined(value) => value,
+            MaybeWorkspace::Workspace(_) => {
+                panic!("value should have been inherited from the workspace")
+            }
+        }
+    }
+}
+
+/// The `{ workspace = true }` of an inherited `[package]` value.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct TomlWorkspaceField {
+    workspace: bool,
+}
+
+/// Returns the (already inherited) value of an optional `[package]` field.
+fn defined<T: Clone>(value: &Option<MaybeWorkspace<T>>) -> Option<T> {
+    value.as_ref().map(|v| v.defined().clone())
 }
 
 /// Represents the `package`/`project` sections of a `Cargo.toml`.
@@ -858,12 +976,12 @@ where
 #[derive(Deserialize, Serialize, Clone, Debug)]
 #[serde(rename_all = "kebab-case")]
 pub struct TomlProject {
-    edition: Option<String>,
-    rust_version: Option<String>,
+    edition: Option<MaybeWorkspace<String>>,
+    rust_version: Option<MaybeWorkspace<String>>,
     name: InternedString,
     #[serde(deserialize_with = "version_trim_whitespace")]
-    version: semver::Version,
-    authors: Option<Vec<String>>,
+    version: MaybeWorkspace<semver::Version>,
+    authors: Option<MaybeWorkspace<Vec<String>>>,
     build: Option<StringOrBool>,
     metabuild: Option<StringOrVec>,
     #[serde(rename = "default-target")]
@@ -871,9 +989,9 @@ pub struct TomlProject {
     #[serde(rename = "forced-target")]
     forced_target: Option<String>,
     links: Option<String>,
-    exclude: Option<Vec<String>>,
-    include: Option<Vec<String>>,
-    publish: Option<VecStringOrBool>,
+    exclude: Option<MaybeWorkspace<Vec<String>>>,
+    include: Option<MaybeWorkspace<Vec<String>>>,
+    publish: Option<MaybeWorkspace<VecStringOrBool>>,
     workspace: Option<String>,
     im_a_teapot: Option<bool>,
     autobins: Option<bool>,
@@ -883,15 +1001,15 @@ pub struct TomlProject {
     default_run: Option<String>,
 
     // Package metadata.
-    description: Option<String>,
-    homepage: Option<String>,
-    documentation: Option<String>,
-    readme: Option<StringOrBool>,
-    keywords: Option<Vec<String>>,
-    categories: Option<Vec<String>>,
-    license: Option<String>,
-    license_file: Option<String>,
-    repository: Option<String>,
+    description: Option<MaybeWorkspace<String>>,
+    homepage: Option<MaybeWorkspace<String>>,
+    documentation: Option<MaybeWorkspace<String>>,
+    readme: Option<MaybeWorkspace<StringOrBool>>,
+    keywords: Option<MaybeWorkspace<Vec<String>>>,
+    categories: Option<MaybeWorkspace<Vec<String>>>,
+    license: Option<MaybeWorkspace<String>>,
+    license_file: Option<MaybeWorkspace<String>>,
+    repository: Option<MaybeWorkspace<String>>,
     resolver: Option<String>,
 
     // Note that this field must come last due to the way toml serialization
@@ -899,7 +1017,7 @@ pub struct TomlProject {
     metadata: Option<toml::Value>,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct TomlWorkspace {
     members: Option<Vec<String>>,
     #[serde(rename = "default-members")]
@@ -907,17 +1025,316 @@ pub struct TomlWorkspace {
     exclude: Option<Vec<String>>,
     resolver: Option<String>,
 
+    // Values members can inherit with `key.workspace = true`.
+    package: Option<TomlWorkspacePackage>,
+    dependencies: Option<BTreeMap<String, TomlDependency>>,
+    lints: Option<TomlLints>,
+
     // Note that this field must come last due to the way toml serialization
     // works which requires tables to be emitted after all values.
     metadata: Option<toml::Value>,
 }
 
+/// The `[workspace.package]` table, with the `[package]` values members can
+/// inherit.
+///
+/// Paths are relative to the workspace root, and are rebased onto the member
+/// when inherited.
+#[derive(Deserialize, Serialize, Clone, Debug, Default)]
+#[serde(rename_all = "kebab-case")]
+pub struct TomlWorkspacePackage {
+    version: Option<semver::Version>,
+    authors: Option<Vec<String>>,
+    description: Option<String>,
+    homepage: Option<String>,
+    documentation: Option<String>,
+    readme: Option<StringOrBool>,
+    keywords: Option<Vec<String>>,
+    categories: Option<Vec<String>>,
+    license: Option<String>,
+    license_file: Option<String>,
+    repository: Option<String>,
+    publish: Option<VecStringOrBool>,
+    edition: Option<String>,
+    rust_version: Option<String>,
+    exclude: Option<Vec<String>>,
+    include: Option<Vec<String>>,
+}
+
+/// The `[lints]` table, with the lint levels of each tool (`rust`, `clippy`
+/// or `rustdoc`).
+pub type TomlLints = BTreeMap<String, BTreeMap<String, TomlLint>>;
+
+/// A single lint of `[lints]`, either just its level or a table that also
+/// sets its priority.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(untagged)]
+pub enum TomlLint {
+    Level(TomlLintLevel),
+    Config {
+        level: TomlLintLevel,
+        #[serde(default)]
+        priority: i8,
+    },
+}
+
+impl TomlLint {
+    fn level(&self) -> TomlLintLevel {
+        match self {
+            TomlLint::Level(level) | TomlLint::Config { level, .. } => *level,
+        }
+    }
+
+    fn priority(&self) -> i8 {
+        match self {
+            TomlLint::Level(_) => 0,
+            TomlLint::Config { priority, .. } => *priority,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone, Copy, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub enum TomlLintLevel {
+    Forbid,
+    Deny,
+    Warn,
+    Allow,
+}
+
+impl TomlLintLevel {
+    fn flag(self) -> &'static str {
+        match self {
+            TomlLintLevel::Forbid => "-F",
+            TomlLintLevel::Deny => "-D",
+            TomlLintLevel::Warn => "-W",
+            TomlLintLevel::Allow => "-A",
+        }
+    }
+}
+
+/// Converts `[lints]` into the flags that set the lint levels for rustc and
+/// rustdoc.
+///
+/// Flags are sorted by priority first so that a lint with a higher priority
+/// overrides a lower one, e.g. a single lint out of a lint group.
+fn lints_to_rustflags(lints: &TomlLints) -> CargoResult<Vec<String>> {
+    let mut flags = Vec::new();
+    for (tool, tool_lints) in lints {
+        if !matches!(tool.as_str(), "rust" | "clippy" | "rustdoc") {
+            bail!(
+                "unsupported tool `lints.{}`, expected one of `rust`, `clippy` or `rustdoc`",
+                tool
+            );
+        }
+        for (name, lint) in tool_lints {
+            if name.contains("::") {
+                bail!(
+                    "`lints.{}.{}` must not contain a tool prefix, \
+                     use `lints.<tool>.<name>` instead",
+                    tool,
+                    name
+                );
+            }
+            let name = if tool == "rust" {
+                name.clone()
+            } else {
+                format!("{}::{}", tool, name)
+            };
+            flags.push((lint.priority(), name, lint.level()));
+        }
+    }
+    flags.sort_by(|a, b| a.0.cmp(&b.0).then_with(|| a.1.cmp(&b.1)));
+    Ok(flags
+        .into_iter()
+        .map(|(_, name, level)| format!("{}{}", level.flag(), name))
+        .collect())
+}
+
 impl TomlProject {
     pub fn to_package_id(&self, source_id: SourceId) -> CargoResult<PackageId> {
-        PackageId::new(self.name, self.version.clone(), source_id)
+        PackageId::new(self.name, self.version.defined().clone(), source_id)
     }
 }
 
+/// The values of a workspace root that its members can inherit.
+struct InheritableFields {
+    package: TomlWorkspacePackage,
+    dependencies: BTreeMap<String, TomlDependency>,
+    lints: Option<TomlLints>,
+    /// The directory containing the workspace root manifest.
+    root: PathBuf,
+}
+
+impl InheritableFields {
+    fn new(ws: &TomlWorkspace, root: &Path) -> CargoResult<InheritableFields> {
+        let dependencies = ws.dependencies.clone().unwrap_or_default();
+        for (name, dep) in &dependencies {
+            if let TomlDependency::Detailed(d) = dep {
+                if d.workspace.is_some() {
+                    bail!(
+                        "`workspace.dependencies.{}` cannot itself be inherited with `workspace`",
+                        name
+                    );
+                }
+                if d.optional.is_some() {
+                    bail!(
+                        "`workspace.dependencies.{}` cannot specify `optional`, \
+                         members decide whether an inherited dependency is optional",
+                        name
+                    );
+                }
+            }
+        }
+        Ok(InheritableFields {
+            package: ws.package.clone().unwrap_or_default(),
+            dependencies,
+            lints: ws.lints.clone(),
+            root: root.to_path_buf(),
+        })
+    }
+
+    /// Loads the inheritable values of the workspace that the package `me`
+    /// in `package_root` belongs to.
+    fn load(
+        me: &TomlManifest,
+        package_root: &Path,
+        config: &Config,
+    ) -> CargoResult<InheritableFields> {
+        if let Some(ws) = &me.workspace {
+            return InheritableFields::new(ws, package_root);
+        }
+        let root_link = me
+            .project
+            .as_ref()
+            .or_else(|| me.package.as_ref())
+            .and_then(|p| p.workspace.as_deref());
+        let root_manifest =
+            find_workspace_root(&package_root.join("Cargo.toml"), root_link, config)?
+                .ok_or_else(|| anyhow!("failed to find a workspace root"))?;
+        match read_toml_manifest(&root_manifest, config)?.workspace {
+            Some(ws) => InheritableFields::new(&ws, root_manifest.parent().unwrap()),
+            None => bail!(
+                "root of a workspace inferred but wasn't a root: {}",
+                root_manifest.display()
+            ),
+        }
+    }
+
+    /// Converts `path`, relative to the workspace root, to be relative to
+    /// `package_root` instead.
+    fn rebase_path(&self, path: &str, package_root: &Path) -> String {
+        let rebased = paths::relative_path(package_root, &self.root.join(path));
+        // Manifests are shared between platforms, so always use forward slashes.
+        rebased.to_string_lossy().replace('\\', "/")
+    }
+}
+
+/// Finds the root manifest of the workspace of the package whose manifest is
+/// at `manifest_path`.
+///
+/// This follows the same rules as `Workspace::find_root`, but only parses
+/// the manifests it looks at instead of fully loading them, since it's used
+/// while loading the package itself.
+fn find_workspace_root(
+    manifest_path: &Path,
+    root_link: Option<&str>,
+    config: &Config,
+) -> CargoResult<Option<PathBuf>> {
+    fn read_root_pointer(member_manifest: &Path, root_link: &str) -> PathBuf {
+        let path = member_manifest
+            .parent()
+            .unwrap()
+            .join(root_link)
+            .join("Cargo.toml");
+        paths::normalize_path(&path)
+    }
+
+    if let Some(root_link) = root_link {
+        return Ok(Some(read_root_pointer(manifest_path, root_link)));
+    }
+
+    for path in paths::ancestors(manifest_path, None).skip(2) {
+        if path.ends_with("target/package") {
+            break;
+        }
+
+        let ances_manifest_path = path.join("Cargo.toml");
+        if ances_manifest_path.exists() {
+            let manifest = read_toml_manifest(&ances_manifest_path, config)?;
+            if let Some(ws) = &manifest.workspace {
+                let root_config = WorkspaceRootConfig::new(
+                    path,
+                    &ws.members,
+                    &ws.default_members,
+                    &ws.exclude,
+                    &ws.metadata,
+                );
+                if !root_config.is_excluded(manifest_path) {
+                    return Ok(Some(ances_manifest_path));
+                }
+            } else if let Some(root_link) = manifest
+                .project
+                .as_ref()
+                .or_else(|| manifest.package.as_ref())
+                .and_then(|p| p.workspace.as_deref())
+            {
+                return Ok(Some(read_root_pointer(&ances_manifest_path, root_link)));
+            }
+        }
+
+        // See `Workspace::find_root` for why `CARGO_HOME` isn't crossed.
+        if config.home() == path {
+            break;
+        }
+    }
+
+    Ok(None)
+}
+
+/// Parses the manifest at `path`, without interpreting it.
+fn read_toml_manifest(path: &Path, config: &Config) -> CargoResult<TomlManifest> {
+    let contents = paths::read(path)?;
+    let toml = parse(&contents, path, config)
+        .and_then(|toml| Ok(toml.try_into::<TomlManifest>()?))
+        .with_context(|| format!("failed to parse manifest at `{}`", path.display()))?;
+    Ok(toml)
+}
+
+/// Replaces `value` with the one from `[workspace.package]` if it's
+/// inherited.
+fn inherit<T>(
+    value: &mut Option<MaybeWorkspace<T>>,
+    label: &str,
+    get_ws: impl FnOnce() -> CargoResult<Option<T>>,
+) -> CargoResult<()> {
+    if let Some(MaybeWorkspace::Workspace(field)) = value {
+        let inherited = inherit_value(field, label, get_ws)?;
+        *value = Some(MaybeWorkspace::Defined(inherited));
+    }
+    Ok(())
+}
+
+fn inherit_value<T>(
+    field: &TomlWorkspaceField,
+    label: &str,
+    get_ws: impl FnOnce() -> CargoResult<Option<T>>,
+) -> CargoResult<T> {
+    if !field.workspace {
+        bail!("`{}.workspace` cannot be false", label);
+    }
+    get_ws()
+        .and_then(|value| {
+            value.ok_or_else(|| anyhow!("`workspace.package.{}` was not defined", label))
+        })
+        .with_context(|| {
+            format!(
+                "error inheriting `{}` from workspace root manifest's `workspace.package.{}`",
+                label, label
+            )
+        })
+}
+
 struct Context<'a, 'b> {
     deps: &'a mut Vec<Dependency>,
     source_id: SourceId,
@@ -947,20 +1364,20 @@ impl TomlManifest {
             .clone();
         package.workspace = None;
         package.resolver = ws.resolve_behavior().to_manifest();
-        if let Some(license_file) = &package.license_file {
+        if let Some(license_file) = defined(&package.license_file) {
             let license_path = Path::new(&license_file);
             let abs_license_path = paths::normalize_path(&package_root.join(license_path));
             if abs_license_path.strip_prefix(package_root).is_err() {
                 // This path points outside of the package root. `cargo package`
                 // will copy it into the root, so adjust the path to this location.
-                package.license_file = Some(
+                package.license_file = Some(MaybeWorkspace::Defined(
                     license_path
                         .file_name()
                         .unwrap()
                         .to_str()
                         .unwrap()
                         .to_string(),
-                );
+                ));
             }
         }
         let all = |_d: &TomlDependency| true;
@@ -1028,6 +1445,7 @@ impl TomlManifest {
             patch: None,
             workspace: None,
             badges: self.badges.clone(),
+            lints: self.lints.clone(),
             cargo_features: self.cargo_features.clone(),
         });
 
@@ -1074,6 +1492,144 @@ impl TomlManifest {
         }
     }
 
+    /// Replaces every value the package inherits from its workspace with
+    /// `key.workspace = true` with the one defined by the workspace root.
+    ///
+    /// The workspace root is only looked up if something is inherited,
+    /// otherwise `me` is returned as-is.
+    fn inherit_from_workspace(
+        me: &Rc<TomlManifest>,
+        package_root: &Path,
+        config: &Config,
+        features: &Features,
+    ) -> CargoResult<Rc<TomlManifest>> {
+        let inheritable = LazyCell::new();
+        let get_ws = || {
+            inheritable.try_borrow_with(|| {
+                features.require(Feature::workspace_inheritance())?;
+                InheritableFields::load(me, package_root, config)
+            })
+        };
+
+        let mut resolved = (**me).clone();
+        let project = match (&mut resolved.project, &mut resolved.package) {
+            (Some(project), _) | (None, Some(project)) => project,
+            (None, None) => return Ok(Rc::clone(me)),
+        };
+
+        if let MaybeWorkspace::Workspace(field) = &project.version {
+            let version =
+                inherit_value(field, "version", || Ok(get_ws()?.package.version.clone()))?;
+            project.version = MaybeWorkspace::Defined(version);
+        }
+        inherit(&mut project.authors, "authors", || {
+            Ok(get_ws()?.package.authors.clone())
+        })?;
+        inherit(&mut project.edition, "edition", || {
+            Ok(get_ws()?.package.edition.clone())
+        })?;
+        inherit(&mut project.rust_version, "rust-version", || {
+            Ok(get_ws()?.package.rust_version.clone())
+        })?;
+        inherit(&mut project.exclude, "exclude", || {
+            Ok(get_ws()?.package.exclude.clone())
+        })?;
+        inherit(&mut project.include, "include", || {
+            Ok(get_ws()?.package.include.clone())
+        })?;
+        inherit(&mut project.publish, "publish", || {
+            Ok(get_ws()?.package.publish.clone())
+        })?;
+        inherit(&mut project.description, "description", || {
+            Ok(get_ws()?.package.description.clone())
+        })?;
+        inherit(&mut project.homepage, "homepage", || {
+            Ok(get_ws()?.package.homepage.clone())
+        })?;
+        inherit(&mut project.documentation, "documentation", || {
+            Ok(get_ws()?.package.documentation.clone())
+        })?;
+        inherit(&mut project.readme, "readme", || {
+            let ws = get_ws()?;
+            Ok(ws.package.readme.clone().map(|readme| match readme {
+                StringOrBool::String(path) => {
+                    StringOrBool::String(ws.rebase_path(&path, package_root))
+                }
+                StringOrBool::Bool(true) => {
+                    StringOrBool::String(ws.rebase_path("README.md", package_root))
+                }
+                StringOrBool::Bool(false) => StringOrBool::Bool(false),
+            }))
+        })?;
+        inherit(&mut project.keywords, "keywords", || {
+            Ok(get_ws()?.package.keywords.clone())
+        })?;
+        inherit(&mut project.categories, "categories", || {
+            Ok(get_ws()?.package.categories.clone())
+        })?;
+        inherit(&mut project.license, "license", || {
+            Ok(get_ws()?.package.license.clone())
+        })?;
+        inherit(&mut project.license_file, "license-file", || {
+            let ws = get_ws()?;
+            Ok(ws
+                .package
+                .license_file
+                .as_ref()
+                .map(|path| ws.rebase_path(path, package_root)))
+        })?;
+        inherit(&mut project.repository, "repository", || {
+            Ok(get_ws()?.package.repository.clone())
+        })?;
+
+        let mut dep_tables = vec![
+            &mut resolved.dependencies,
+            &mut resolved.dev_dependencies,
+            &mut resolved.dev_dependencies2,
+            &mut resolved.build_dependencies,
+            &mut resolved.build_dependencies2,
+        ];
+        for platform in resolved.target.iter_mut().flat_map(|t| t.values_mut()) {
+            dep_tables.extend([
+                &mut platform.dependencies,
+                &mut platform.dev_dependencies,
+                &mut platform.dev_dependencies2,
+                &mut platform.build_dependencies,
+                &mut platform.build_dependencies2,
+            ]);
+        }
+        for (name, dep) in dep_tables.into_iter().flatten().flat_map(|t| t.iter_mut()) {
+            if dep.is_inherited() {
+                *dep = dep.inherit(name, package_root, &get_ws)?;
+            }
+        }
+
+        if let Some(MaybeWorkspace::Workspace(field)) = &resolved.lints {
+            // Unlike the other values, `[lints]` comes from `[workspace.lints]`.
+            features.require(Feature::lints())?;
+            if !field.workspace {
+                bail!("`lints.workspace` cannot be false");
+            }
+            let lints = inheritable
+                .try_borrow_with(|| InheritableFields::load(me, package_root, config))
+                .and_then(|ws| {
+                    ws.lints
+                        .clone()
+                        .ok_or_else(|| anyhow!("`workspace.lints` was not defined"))
+                })
+                .context(
+                    "error inheriting `lints` from workspace root manifest's `workspace.lints`",
+                )?;
+            resolved.lints = Some(MaybeWorkspace::Defined(lints));
+        }
+
+        if inheritable.filled() {
+            Ok(Rc::new(resolved))
+        } else {
+            Ok(Rc::clone(me))
+        }
+    }
+
     pub fn to_real_manifest(
         me: &Rc<TomlManifest>,
         source_id: SourceId,
@@ -1089,6 +1645,20 @@ impl TomlManifest {
         let cargo_features = me.cargo_features.as_ref().unwrap_or(&empty);
         let features = Features::new(cargo_features, config, &mut warnings, source_id.is_path())?;
 
+        if me.workspace.as_ref().map_or(false, |ws| {
+            ws.package.is_some() || ws.dependencies.is_some()
+        }) {
+            features.require(Feature::workspace_inheritance())?;
+        }
+        if let Some(lints) = me.workspace.as_ref().and_then(|ws| ws.lints.as_ref()) {
+            features.require(Feature::lints())?;
+            lints_to_rustflags(lints)?;
+        }
+
+        // Everything below works with the inherited values, including the
+        // manifest that `cargo package` writes out.
+        let me = &TomlManifest::inherit_from_workspace(me, package_root, config, &features)?;
+
         let project = me.project.as_ref().or_else(|| me.package.as_ref());
         let project = project.ok_or_else(|| anyhow!("no `package` section found"))?;
 
@@ -1101,7 +1671,7 @@ impl TomlManifest {
 
         let pkgid = project.to_package_id(source_id)?;
 
-        let edition = if let Some(ref edition) = project.edition {
+        let edition = if let Some(edition) = defined(&project.edition) {
             features
                 .require(Feature::edition())
                 .with_context(|| "editions are unstable")?;
@@ -1121,8 +1691,8 @@ impl TomlManifest {
             )));
         }
 
-        let rust_version = if let Some(rust_version) = &project.rust_version {
-            let req = match semver::VersionReq::parse(rust_version) {
+        let rust_version = if let Some(rust_version) = defined(&project.rust_version) {
+            let req = match semver::VersionReq::parse(&rust_version) {
                 // Exclude semver operators like `^` and pre-release identifiers
                 Ok(req) if rust_version.chars().all(|c| c.is_ascii_digit() || c == '.') => req,
                 _ => bail!("`rust-version` must be a value like \"1.32\""),
@@ -1140,7 +1710,7 @@ impl TomlManifest {
                     )
                 }
             }
-            Some(rust_version.clone())
+            Some(rust_version)
         } else {
             None
         };
@@ -1292,31 +1862,32 @@ impl TomlManifest {
             }
         }
 
-        let exclude = project.exclude.clone().unwrap_or_default();
-        let include = project.include.clone().unwrap_or_default();
+        let exclude = defined(&project.exclude).unwrap_or_default();
+        let include = defined(&project.include).unwrap_or_default();
         let empty_features = BTreeMap::new();
 
-        let summary = Summary::new(
+        let mut summary = Summary::new(
             config,
             pkgid,
             deps,
             me.features.as_ref().unwrap_or(&empty_features),
             project.links.as_deref(),
         )?;
+        summary.set_rust_version(rust_version.as_deref());
         let unstable = config.cli_unstable();
         summary.unstable_gate(unstable.namespaced_features, unstable.weak_dep_features)?;
 
         let metadata = ManifestMetadata {
-            description: project.description.clone(),
-            homepage: project.homepage.clone(),
-            documentation: project.documentation.clone(),
+            description: defined(&project.description),
+            homepage: defined(&project.homepage),
+            documentation: defined(&project.documentation),
             readme: readme_for_project(package_root, project),
-            authors: project.authors.clone().unwrap_or_default(),
-            license: project.license.clone(),
-            license_file: project.license_file.clone(),
-            repository: project.repository.clone(),
-            keywords: project.keywords.clone().unwrap_or_default(),
-            categories: project.categories.clone().unwrap_or_default(),
+            authors: defined(&project.authors).unwrap_or_default(),
+            license: defined(&project.license),
+            license_file: defined(&project.license_file),
+            repository: defined(&project.repository),
+            keywords: defined(&project.keywords).unwrap_or_default(),
+            categories: defined(&project.categories).unwrap_or_default(),
             badges: me.badges.clone().unwrap_or_default(),
             links: project.links.clone(),
         };
@@ -1341,8 +1912,8 @@ impl TomlManifest {
         if let Some(profiles) = &profiles {
             profiles.validate(&features, &mut warnings)?;
         }
-        let publish = match project.publish {
-            Some(VecStringOrBool::VecString(ref vecstring)) => Some(vecstring.clone()),
+        let publish = match defined(&project.publish) {
+            Some(VecStringOrBool::VecString(vecstring)) => Some(vecstring),
             Some(VecStringOrBool::Bool(false)) => Some(vec![]),
             None | Some(VecStringOrBool::Bool(true)) => None,
         };
@@ -1380,6 +1951,14 @@ impl TomlManifest {
             .transpose()?
             .map(CompileKind::Target);
 
+        let lint_rustflags = match defined(&me.lints) {
+            Some(lints) => {
+                features.require(Feature::lints())?;
+                lints_to_rustflags(&lints)?
+            }
+            None => Vec::new(),
+        };
+
         let custom_metadata = project.metadata.clone();
         let mut manifest = Manifest::new(
             summary,
@@ -1404,6 +1983,7 @@ impl TomlManifest {
             Rc::clone(me),
             project.metabuild.clone().map(|sov| sov.0),
             resolve_behavior,
+            lint_rustflags,
         );
         if project.license_file.is_some() && project.license.is_some() {
             manifest.warnings_mut().add_warning(
@@ -1473,6 +2053,9 @@ impl TomlManifest {
         if me.badges.is_some() {
             bail!("this virtual manifest specifies a [badges] section, which is not allowed");
         }
+        if me.lints.is_some() {
+            bail!("this virtual manifest specifies a [lints] section, which is not allowed");
+        }
 
         let mut nested_paths = Vec::new();
         let mut warnings = Vec::new();
@@ -1481,6 +2064,16 @@ impl TomlManifest {
         let cargo_features = me.cargo_features.as_ref().unwrap_or(&empty);
         let features = Features::new(cargo_features, config, &mut warnings, source_id.is_path())?;
 
+        if me.workspace.as_ref().map_or(false, |ws| {
+            ws.package.is_some() || ws.dependencies.is_some()
+        }) {
+            features.require(Feature::workspace_inheritance())?;
+        }
+        if let Some(lints) = me.workspace.as_ref().and_then(|ws| ws.lints.as_ref()) {
+            features.require(Feature::lints())?;
+            lints_to_rustflags(lints)?;
+        }
+
         let (replace, patch) = {
             let mut cx = Context {
                 deps: &mut deps,
@@ -1634,12 +2227,12 @@ impl TomlManifest {
 
 /// Returns the name of the README file for a `TomlProject`.
 fn readme_for_project(package_root: &Path, project: &TomlProject) -> Option<String> {
-    match &project.readme {
+    match defined(&project.readme) {
         None => default_readme_from_package_root(package_root),
         Some(value) => match value {
             StringOrBool::Bool(false) => None,
             StringOrBool::Bool(true) => Some("README.md".to_string()),
-            StringOrBool::String(v) => Some(v.clone()),
+            StringOrBool::String(v) => Some(v),
         },
     }
 }
@@ -1726,6 +2319,81 @@ impl<P: ResolveToPath> TomlDependency<P> {
     }
 }
 
+impl TomlDependency {
+    fn is_inherited(&self) -> bool {
+        matches!(self, TomlDependency::Detailed(d) if d.workspace.is_some())
+    }
+
+    /// Replaces `name = { workspace = true }` with the definition from
+    /// `[workspace.dependencies]`, along with the `features` and `optional`
+    /// keys the member may add to it.
+    fn inherit<'a>(
+        &self,
+        name: &str,
+        package_root: &Path,
+        get_ws: impl Fn() -> CargoResult<&'a InheritableFields>,
+    ) -> CargoResult<TomlDependency> {
+        let member = match self {
+            TomlDependency::Detailed(d) => d,
+            TomlDependency::Simple(_) => return Ok(self.clone()),
+        };
+        let inherit = || -> CargoResult<TomlDependency> {
+            if member.workspace == Some(false) {
+                bail!("`workspace` cannot be false");
+            }
+            let other_keys = [
+                ("version", member.version.is_some()),
+                ("registry", member.registry.is_some()),
+                ("registry-index", member.registry_index.is_some()),
+                ("path", member.path.is_some()),
+                ("git", member.git.is_some()),
+                ("branch", member.branch.is_some()),
+                ("tag", member.tag.is_some()),
+                ("rev", member.rev.is_some()),
+                (
+                    "default-features",
+                    member.default_features.is_some() || member.default_features2.is_some(),
+                ),
+                ("package", member.package.is_some()),
+                ("public", member.public.is_some()),
+            ];
+            if let Some((key, _)) = other_keys.iter().find(|(_, specified)| *specified) {
+                bail!(
+                    "`{}` cannot be specified alongside `workspace = true`, \
+                     only `features` and `optional` can",
+                    key
+                );
+            }
+
+            let ws = get_ws()?;
+            let mut dep = match ws.dependencies.get(name) {
+                Some(TomlDependency::Simple(version)) => DetailedTomlDependency {
+                    version: Some(version.clone()),
+                    ..Default::default()
+                },
+                Some(TomlDependency::Detailed(d)) => d.clone(),
+                None => bail!("`workspace.dependencies.{}` was not defined", name),
+            };
+            if let Some(path) = &dep.path {
+                dep.path = Some(ws.rebase_path(path, package_root));
+            }
+            if let Some(features) = &member.features {
+                dep.features
+                    .get_or_insert_with(Vec::new)
+                    .extend(features.iter().cloned());
+            }
+            dep.optional = member.optional;
+            Ok(TomlDependency::Detailed(dep))
+        };
+        inherit().with_context(|| {
+            format!(
+                "error inheriting `{}` from workspace root manifest's `workspace.dependencies.{}`",
+                name, name
+            )
+        })
+    }
+}
+
 impl<P: ResolveToPath> DetailedTomlDependency<P> {
     fn to_dependency(
         &self,
@@ -1733,6 +2401,14 @@ impl<P: ResolveToPath> DetailedTomlDependency<P> {
         cx: &mut Context<'_, '_>,
         kind: Option<DepKind>,
     ) -> CargoResult<Dependency> {
+        if self.workspace.is_some() {
+            bail!(
+                "dependency ({}) cannot be inherited with `workspace` here, \
+                 only the dependency tables of a workspace member can inherit dependencies",
+                name_in_toml
+            );
+        }
+
         if self.version.is_none() && self.path.is_none() && self.git.is_none() {
             let msg = format!(
                 "dependency ({}) specified without \
@@ -1930,6 +2606,29 @@ impl<P: ResolveToPath> DetailedTomlDependency<P> {
 
             dep.set_public(p);
         }
+
+        match (&self.artifact, self.lib, &self.target) {
+            (Some(artifact), lib, target) => {
+                if !cx.config.cli_unstable().bindeps {
+                    bail!("`artifact = ...` requires `-Z bindeps` ({})", name_in_toml);
+                }
+                let artifact = artifact.iter().collect::<Vec<_>>();
+                let artifact = Artifact::parse(&artifact, lib.unwrap_or(false), target.as_deref())
+                    .with_context(|| {
+                        format!("failed to parse artifact dependency `{}`", name_in_toml)
+                    })?;
+                dep.set_artifact(artifact);
+            }
+            (None, Some(_), _) => bail!(
+                "`lib` cannot be used without `artifact = ...` ({})",
+                name_in_toml
+            ),
+            (None, None, Some(_)) => bail!(
+                "`target` cannot be used without `artifact = ...` ({})",
+                name_in_toml
+            ),
+            (None, None, None) => {}
+        }
         Ok(dep)
     }
 }
@@ -1985,7 +2684,7 @@ impl ser::Serialize for PathValue {
 }
 
 /// Corresponds to a `target` entry, but `TomlTarget` is already used.
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 struct TomlPlatform {
     dependencies: Option<BTreeMap<String, TomlDependency>>,
     #[serde(rename = "build-dependencies")]
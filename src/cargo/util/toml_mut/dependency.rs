@@ -0,0 +1,652 @@
+//! Reading and writing a single dependency entry of a manifest.
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use cargo_util::paths;
+
+use crate::util::errors::CargoResult;
+
+/// A dependency as it appears in a `Cargo.toml` dependency table.
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+pub struct Dependency {
+    /// The name of the dependency (as it is set in its `Cargo.toml` and known
+    /// to crates.io).
+    pub name: String,
+    /// Whether the dependency is opted-in with a feature flag.
+    pub optional: Option<bool>,
+    /// Features to enable, or `None` if the entry has no `features` key.
+    pub features: Option<Vec<String>>,
+    /// Whether default features are enabled.
+    pub default_features: Option<bool>,
+    /// Where the dependency comes from.
+    pub source: Option<Source>,
+    /// Non-default registry.
+    pub registry: Option<String>,
+    /// If the dependency is renamed, this is the new name for the dependency
+    /// as a string. `None` if it is not renamed.
+    pub rename: Option<String>,
+}
+
+impl Dependency {
+    /// Create a new dependency with a name.
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.into(),
+            optional: None,
+            features: None,
+            default_features: None,
+            source: None,
+            registry: None,
+            rename: None,
+        }
+    }
+
+    /// Set dependency to a given version.
+    pub fn set_source(mut self, source: impl Into<Source>) -> Self {
+        self.source = Some(source.into());
+        self
+    }
+
+    /// Set whether the dependency is optional.
+    pub fn set_optional(mut self, opt: bool) -> Self {
+        self.optional = Some(opt);
+        self
+    }
+
+    /// Set features as an array of string (does some basic parsing).
+    pub fn set_features(mut self, features: Vec<String>) -> Self {
+        self.features = Some(features);
+        self
+    }
+
+    /// Set the value of default-features for the dependency.
+    pub fn set_default_features(mut self, default_features: bool) -> Self {
+        self.default_features = Some(default_features);
+        self
+    }
+
+    /// Set the alias for the dependency.
+    pub fn set_rename(mut self, rename: &str) -> Self {
+        self.rename = Some(rename.into());
+        self
+    }
+
+    /// Set the value of the registry for the dependency.
+    pub fn set_registry(mut self, registry: impl Into<String>) -> Self {
+        self.registry = Some(registry.into());
+        self
+    }
+
+    /// Get the dependency source.
+    pub fn source(&self) -> Option<&Source> {
+        self.source.as_ref()
+    }
+
+    /// Get version of dependency.
+    pub fn version(&self) -> Option<&str> {
+        match self.source()? {
+            Source::Registry(src) => Some(src.version.as_str()),
+            Source::Path(src) => src.version.as_deref(),
+            Source::Git(src) => src.version.as_deref(),
+        }
+    }
+
+    /// Get the alias for the dependency (if any).
+    pub fn rename(&self) -> Option<&str> {
+        self.rename.as_deref()
+    }
+
+    /// Whether default features are activated.
+    pub fn default_features(&self) -> Option<bool> {
+        self.default_features
+    }
+
+    /// Get whether the dependency is optional.
+    pub fn optional(&self) -> Option<bool> {
+        self.optional
+    }
+
+    /// Get the name of the key the dependency is stored under in the table.
+    pub fn toml_key(&self) -> &str {
+        self.rename().unwrap_or(&self.name)
+    }
+
+    /// Create a dependency from a TOML table entry.
+    pub fn from_toml(crate_root: &Path, key: &str, item: &toml_edit::Item) -> CargoResult<Self> {
+        if let Some(version) = item.as_str() {
+            let dep = Self::new(key).set_source(RegistrySource::new(version));
+            Ok(dep)
+        } else if let Some(table) = item.as_table_like() {
+            let (name, rename) = if let Some(value) = table.get("package") {
+                (
+                    value
+                        .as_str()
+                        .ok_or_else(|| invalid_type(key, "package", value.type_name(), "string"))?
+                        .to_owned(),
+                    Some(key.to_owned()),
+                )
+            } else {
+                (key.to_owned(), None)
+            };
+
+            let source: Source =
+                if let Some(git) = table.get("git") {
+                    let mut src = GitSource::new(
+                        git.as_str()
+                            .ok_or_else(|| invalid_type(key, "git", git.type_name(), "string"))?,
+                    );
+                    if let Some(value) = table.get("branch") {
+                        src = src.set_branch(value.as_str().ok_or_else(|| {
+                            invalid_type(key, "branch", value.type_name(), "string")
+                        })?);
+                    }
+                    if let Some(value) = table.get("tag") {
+                        src = src.set_tag(value.as_str().ok_or_else(|| {
+                            invalid_type(key, "tag", value.type_name(), "string")
+                        })?);
+                    }
+                    if let Some(value) = table.get("rev") {
+                        src = src.set_rev(value.as_str().ok_or_else(|| {
+                            invalid_type(key, "rev", value.type_name(), "string")
+                        })?);
+                    }
+                    if let Some(value) = table.get("version") {
+                        src = src.set_version(value.as_str().ok_or_else(|| {
+                            invalid_type(key, "version", value.type_name(), "string")
+                        })?);
+                    }
+                    src.into()
+                } else if let Some(path) = table.get("path") {
+                    let path = crate_root
+                        .join(path.as_str().ok_or_else(|| {
+                            invalid_type(key, "path", path.type_name(), "string")
+                        })?);
+                    let mut src = PathSource::new(path);
+                    if let Some(value) = table.get("version") {
+                        src = src.set_version(value.as_str().ok_or_else(|| {
+                            invalid_type(key, "version", value.type_name(), "string")
+                        })?);
+                    }
+                    src.into()
+                } else if let Some(version) = table.get("version") {
+                    let src = RegistrySource::new(version.as_str().ok_or_else(|| {
+                        invalid_type(key, "version", version.type_name(), "string")
+                    })?);
+                    src.into()
+                } else {
+                    anyhow::bail!("unrecognized dependency source for `{}`", key);
+                };
+            let registry = if let Some(value) = table.get("registry") {
+                Some(
+                    value
+                        .as_str()
+                        .ok_or_else(|| invalid_type(key, "registry", value.type_name(), "string"))?
+                        .to_owned(),
+                )
+            } else {
+                None
+            };
+
+            let default_features = table
+                .get("default-features")
+                .or_else(|| table.get("default_features"))
+                .and_then(|v| v.as_bool());
+
+            let features = if let Some(value) = table.get("features") {
+                Some(
+                    value
+                        .as_array()
+                        .ok_or_else(|| invalid_type(key, "features", value.type_name(), "array"))?
+                        .iter()
+                        .map(|v| {
+                            v.as_str().map(|s| s.to_owned()).ok_or_else(|| {
+                                invalid_type(key, "features", v.type_name(), "string")
+                            })
+                        })
+                        .collect::<CargoResult<Vec<String>>>()?,
+                )
+            } else {
+                None
+            };
+
+            let optional = table.get("optional").and_then(|v| v.as_bool());
+
+            let dep = Self {
+                name,
+                rename,
+                source: Some(source),
+                registry,
+                default_features,
+                features,
+                optional,
+            };
+            Ok(dep)
+        } else {
+            anyhow::bail!("unrecognized dependency entry for `{}`", key);
+        }
+    }
+
+    /// Convert dependency to a TOML item.
+    ///
+    /// Simple registry dependencies are written as a plain version string,
+    /// everything else becomes an inline table.
+    pub fn to_toml(&self, crate_root: &Path) -> toml_edit::Item {
+        assert!(
+            crate_root.is_absolute(),
+            "Absolute path needed, got: {}",
+            crate_root.display()
+        );
+        let table: toml_edit::Item = match (
+            self.optional.unwrap_or(false),
+            self.features.as_ref(),
+            self.default_features.unwrap_or(true),
+            self.source.as_ref(),
+            self.registry.as_ref(),
+            self.rename.as_ref(),
+        ) {
+            // Extra short when version flag only
+            (
+                false,
+                None,
+                true,
+                Some(Source::Registry(RegistrySource { version: v })),
+                None,
+                None,
+            ) => toml_edit::value(v),
+            // Other cases are represented as an inline table
+            (_, _, _, _, _, _) => {
+                let mut table = toml_edit::InlineTable::default();
+
+                match &self.source {
+                    Some(Source::Registry(src)) => {
+                        table.insert("version", src.version.as_str().into());
+                    }
+                    Some(Source::Path(src)) => {
+                        let relpath = path_field(crate_root, &src.path);
+                        if let Some(r) = src.version.as_deref() {
+                            table.insert("version", r.into());
+                        }
+                        table.insert("path", relpath.into());
+                    }
+                    Some(Source::Git(src)) => {
+                        table.insert("git", src.git.as_str().into());
+                        if let Some(branch) = src.branch.as_deref() {
+                            table.insert("branch", branch.into());
+                        }
+                        if let Some(tag) = src.tag.as_deref() {
+                            table.insert("tag", tag.into());
+                        }
+                        if let Some(rev) = src.rev.as_deref() {
+                            table.insert("rev", rev.into());
+                        }
+                        if let Some(r) = src.version.as_deref() {
+                            table.insert("version", r.into());
+                        }
+                    }
+                    None => {}
+                }
+                if table.contains_key("version") {
+                    if let Some(r) = self.registry.as_deref() {
+                        table.insert("registry", r.into());
+                    }
+                }
+
+                if self.rename.is_some() {
+                    table.insert("package", self.name.as_str().into());
+                }
+                if let Some(v) = self.default_features {
+                    table.insert("default-features", v.into());
+                }
+                if let Some(features) = self.features.as_ref() {
+                    let features: toml_edit::Value = features.iter().cloned().collect();
+                    table.insert("features", features);
+                }
+                if let Some(v) = self.optional {
+                    table.insert("optional", v.into());
+                }
+
+                toml_edit::value(toml_edit::Value::InlineTable(table))
+            }
+        };
+
+        table
+    }
+
+    /// Modify existing entry to match this dependency.
+    ///
+    /// Keys which are not managed by this type, as well as comments and
+    /// whitespace around the entry, are left as they are.
+    pub fn update_toml(&self, crate_root: &Path, item: &mut toml_edit::Item) {
+        if str_or_1_len_table(item) {
+            // Nothing to preserve
+            let decor = item.as_value().map(|v| v.decor().clone());
+            *item = self.to_toml(crate_root);
+            if let (Some(decor), Some(value)) = (decor, item.as_value_mut()) {
+                *value.decor_mut() = decor;
+            }
+        } else if let Some(table) = item.as_table_like_mut() {
+            match &self.source {
+                Some(Source::Registry(src)) => {
+                    table.insert("version", toml_edit::value(src.version.as_str()));
+
+                    for key in ["path", "git", "branch", "tag", "rev"] {
+                        table.remove(key);
+                    }
+                }
+                Some(Source::Path(src)) => {
+                    let relpath = path_field(crate_root, &src.path);
+                    table.insert("path", toml_edit::value(relpath));
+                    if let Some(r) = src.version.as_deref() {
+                        table.insert("version", toml_edit::value(r));
+                    } else {
+                        table.remove("version");
+                    }
+
+                    for key in ["git", "branch", "tag", "rev"] {
+                        table.remove(key);
+                    }
+                }
+                Some(Source::Git(src)) => {
+                    table.insert("git", toml_edit::value(src.git.as_str()));
+                    for (key, value) in [
+                        ("branch", &src.branch),
+                        ("tag", &src.tag),
+                        ("rev", &src.rev),
+                        ("version", &src.version),
+                    ] {
+                        match value {
+                            Some(v) => {
+                                table.insert(key, toml_edit::value(v.as_str()));
+                            }
+                            None => {
+                                table.remove(key);
+                            }
+                        }
+                    }
+
+                    table.remove("path");
+                }
+                None => {}
+            }
+            if table.contains_key("version") {
+                if let Some(r) = self.registry.as_deref() {
+                    table.insert("registry", toml_edit::value(r));
+                } else {
+                    table.remove("registry");
+                }
+            } else {
+                table.remove("registry");
+            }
+
+            if self.rename.is_some() {
+                table.insert("package", toml_edit::value(self.name.as_str()));
+            }
+            table.remove("default_features");
+            match self.default_features {
+                Some(v) => {
+                    table.insert("default-features", toml_edit::value(v));
+                }
+                None => {
+                    table.remove("default-features");
+                }
+            }
+            match self.features.as_ref() {
+                Some(features) => {
+                    let features: toml_edit::Value = features.iter().cloned().collect();
+                    table.insert("features", toml_edit::value(features));
+                }
+                None => {
+                    table.remove("features");
+                }
+            }
+            match self.optional {
+                Some(v) => {
+                    table.insert("optional", toml_edit::value(v));
+                }
+                None => {
+                    table.remove("optional");
+                }
+            }
+
+            table.fmt();
+        } else {
+            unreachable!("Invalid dependency type: {}", item.type_name());
+        }
+    }
+}
+
+fn invalid_type(dep: &str, key: &str, actual: &str, expected: &str) -> anyhow::Error {
+    anyhow::format_err!(
+        "`{}` for `{}` is a {}, expected {}",
+        key,
+        dep,
+        actual,
+        expected
+    )
+}
+
+fn str_or_1_len_table(item: &toml_edit::Item) -> bool {
+    item.is_str() || item.as_table_like().map(|t| t.len() == 1).unwrap_or(false)
+}
+
+/// Renders the path of a path dependency relative to the manifest directory.
+fn path_field(crate_root: &Path, abs_path: &Path) -> String {
+    let relpath = paths::relative_path(crate_root, abs_path);
+    let relpath = relpath.to_str().unwrap();
+    // Manifests are shared between platforms, so always use forward slashes.
+    relpath.replace('\\', "/")
+}
+
+impl fmt::Display for Dependency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(source) = self.source() {
+            write!(f, "{}@{}", self.name, source)
+        } else {
+            self.toml_key().fmt(f)
+        }
+    }
+}
+
+/// Primary location of a dependency.
+#[derive(Debug, Hash, PartialEq, Eq, Clone)]
+pub enum Source {
+    /// Dependency from a registry.
+    Registry(RegistrySource),
+    /// Dependency from a local path.
+    Path(PathSource),
+    /// Dependency from a git repo.
+    Git(GitSource),
+}
+
+impl Source {
+    /// Access the registry source, if present.
+    pub fn as_registry(&self) -> Option<&RegistrySource> {
+        match self {
+            Self::Registry(src) => Some(src),
+            _ => None,
+        }
+    }
+
+    /// Access the path source, if present.
+    pub fn as_path(&self) -> Option<&PathSource> {
+        match self {
+            Self::Path(src) => Some(src),
+            _ => None,
+        }
+    }
+
+    /// Access the git source, if present.
+    pub fn as_git(&self) -> Option<&GitSource> {
+        match self {
+            Self::Git(src) => Some(src),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Registry(src) => src.fmt(f),
+            Self::Path(src) => src.fmt(f),
+            Self::Git(src) => src.fmt(f),
+        }
+    }
+}
+
+impl From<RegistrySource> for Source {
+    fn from(inner: RegistrySource) -> Self {
+        Self::Registry(inner)
+    }
+}
+
+impl From<PathSource> for Source {
+    fn from(inner: PathSource) -> Self {
+        Self::Path(inner)
+    }
+}
+
+impl From<GitSource> for Source {
+    fn from(inner: GitSource) -> Self {
+        Self::Git(inner)
+    }
+}
+
+/// Dependency from a registry.
+#[derive(Debug, Hash, PartialEq, Eq, Clone)]
+#[non_exhaustive]
+pub struct RegistrySource {
+    /// Version requirement.
+    pub version: String,
+}
+
+impl RegistrySource {
+    /// Specify dependency by version requirement.
+    pub fn new(version: impl AsRef<str>) -> Self {
+        // Versions might have semver metadata appended which we do not want to
+        // store in the manifest because it is ignored when resolving.
+        let version = version.as_ref().split('+').next().unwrap();
+        Self {
+            version: version.to_owned(),
+        }
+    }
+}
+
+impl fmt::Display for RegistrySource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.version.fmt(f)
+    }
+}
+
+/// Dependency from a local path.
+#[derive(Debug, Hash, PartialEq, Eq, Clone)]
+#[non_exhaustive]
+pub struct PathSource {
+    /// Local, absolute path.
+    pub path: PathBuf,
+    /// Version requirement for when published.
+    pub version: Option<String>,
+}
+
+impl PathSource {
+    /// Specify dependency from a path.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            version: None,
+        }
+    }
+
+    /// Set an optional version requirement.
+    pub fn set_version(mut self, version: impl AsRef<str>) -> Self {
+        // Versions might have semver metadata appended which we do not want to
+        // store in the manifest because it is ignored when resolving.
+        let version = version.as_ref().split('+').next().unwrap();
+        self.version = Some(version.to_owned());
+        self
+    }
+}
+
+impl fmt::Display for PathSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.path.display().fmt(f)
+    }
+}
+
+/// Dependency from a git repo.
+#[derive(Debug, Hash, PartialEq, Eq, Clone)]
+#[non_exhaustive]
+pub struct GitSource {
+    /// Repository URL.
+    pub git: String,
+    /// Select specific branch.
+    pub branch: Option<String>,
+    /// Select specific tag.
+    pub tag: Option<String>,
+    /// Select specific rev.
+    pub rev: Option<String>,
+    /// Version requirement for when published.
+    pub version: Option<String>,
+}
+
+impl GitSource {
+    /// Specify dependency from a git repo.
+    pub fn new(git: impl Into<String>) -> Self {
+        Self {
+            git: git.into(),
+            branch: None,
+            tag: None,
+            rev: None,
+            version: None,
+        }
+    }
+
+    /// Specify an optional branch.
+    pub fn set_branch(mut self, branch: impl Into<String>) -> Self {
+        self.branch = Some(branch.into());
+        self.tag = None;
+        self.rev = None;
+        self
+    }
+
+    /// Specify an optional tag.
+    pub fn set_tag(mut self, tag: impl Into<String>) -> Self {
+        self.branch = None;
+        self.tag = Some(tag.into());
+        self.rev = None;
+        self
+    }
+
+    /// Specify an optional rev.
+    pub fn set_rev(mut self, rev: impl Into<String>) -> Self {
+        self.branch = None;
+        self.tag = None;
+        self.rev = Some(rev.into());
+        self
+    }
+
+    /// Set an optional version requirement.
+    pub fn set_version(mut self, version: impl AsRef<str>) -> Self {
+        // Versions might have semver metadata appended which we do not want to
+        // store in the manifest because it is ignored when resolving.
+        let version = version.as_ref().split('+').next().unwrap();
+        self.version = Some(version.to_owned());
+        self
+    }
+}
+
+impl fmt::Display for GitSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.git)?;
+        if let Some(branch) = &self.branch {
+            write!(f, "?branch={}", branch)
+        } else if let Some(tag) = &self.tag {
+            write!(f, "?tag={}", tag)
+        } else if let Some(rev) = &self.rev {
+            write!(f, "?rev={}", rev)
+        } else {
+            Ok(())
+        }
+    }
+}
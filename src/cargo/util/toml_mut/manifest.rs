@@ -0,0 +1,463 @@
+//! Parsing and editing of a whole `Cargo.toml` document.
+
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use anyhow::Context as _;
+use cargo_util::paths;
+
+use super::dependency::Dependency;
+use crate::core::dependency::DepKind;
+use crate::util::errors::CargoResult;
+
+/// A dependency table, such as `[dev-dependencies]` or
+/// `[target.'cfg(unix)'.dependencies]`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct DepTable {
+    kind: DepKind,
+    target: Option<String>,
+}
+
+impl DepTable {
+    const KINDS: &'static [DepKind] = &[DepKind::Normal, DepKind::Development, DepKind::Build];
+
+    /// Reference to the `[dependencies]` table.
+    pub fn new() -> Self {
+        Self {
+            kind: DepKind::Normal,
+            target: None,
+        }
+    }
+
+    /// Choose the type of dependency.
+    pub fn set_kind(mut self, kind: DepKind) -> Self {
+        self.kind = kind;
+        self
+    }
+
+    /// Choose the platform for the dependency.
+    pub fn set_target(mut self, target: impl Into<String>) -> Self {
+        self.target = Some(target.into());
+        self
+    }
+
+    /// Type of dependency.
+    pub fn kind(&self) -> DepKind {
+        self.kind
+    }
+
+    /// Platform for the dependency.
+    pub fn target(&self) -> Option<&str> {
+        self.target.as_deref()
+    }
+
+    /// Keys to the table, starting from the document root.
+    pub fn to_table(&self) -> Vec<&str> {
+        if let Some(target) = &self.target {
+            vec!["target", target, self.kind_table()]
+        } else {
+            vec![self.kind_table()]
+        }
+    }
+
+    fn kind_table(&self) -> &'static str {
+        match self.kind {
+            DepKind::Normal => "dependencies",
+            DepKind::Development => "dev-dependencies",
+            DepKind::Build => "build-dependencies",
+        }
+    }
+}
+
+impl Default for DepTable {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl From<DepKind> for DepTable {
+    fn from(other: DepKind) -> Self {
+        Self::new().set_kind(other)
+    }
+}
+
+impl fmt::Display for DepTable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.target {
+            Some(target) => write!(f, "{} for target `{}`", self.kind_table(), target),
+            None => write!(f, "{}", self.kind_table()),
+        }
+    }
+}
+
+/// An editable `Cargo.toml` document.
+#[derive(Debug, Clone)]
+pub struct Manifest {
+    /// Manifest contents as TOML data.
+    pub data: toml_edit::Document,
+}
+
+impl Manifest {
+    /// Returns the name of the package, if this is not a virtual manifest.
+    pub fn package_name(&self) -> CargoResult<&str> {
+        self.data
+            .get("package")
+            .and_then(|p| p.get("name"))
+            .and_then(|n| n.as_str())
+            .ok_or_else(|| anyhow::format_err!("manifest has no `package.name`"))
+    }
+
+    /// Get the specified table from the manifest.
+    pub fn get_table<'a>(&'a self, table_path: &[String]) -> CargoResult<&'a toml_edit::Item> {
+        fn descend<'a>(
+            input: &'a toml_edit::Item,
+            path: &[String],
+        ) -> CargoResult<&'a toml_edit::Item> {
+            if let Some(segment) = path.get(0) {
+                let value = input
+                    .get(segment)
+                    .ok_or_else(|| non_existent_table_err(segment))?;
+
+                if value.is_table_like() {
+                    descend(value, &path[1..])
+                } else {
+                    Err(non_existent_table_err(segment))
+                }
+            } else {
+                Ok(input)
+            }
+        }
+
+        descend(self.data.as_item(), table_path)
+    }
+
+    /// Get the specified table from the manifest, creating any tables along
+    /// the way that are missing.
+    pub fn get_table_mut<'a>(
+        &'a mut self,
+        table_path: &[String],
+    ) -> CargoResult<&'a mut toml_edit::Item> {
+        fn descend<'a>(
+            input: &'a mut toml_edit::Item,
+            path: &[String],
+        ) -> CargoResult<&'a mut toml_edit::Item> {
+            if let Some(segment) = path.get(0) {
+                let mut default_table = toml_edit::Table::new();
+                default_table.set_implicit(true);
+                let value = input[segment].or_insert(toml_edit::Item::Table(default_table));
+
+                if value.is_table_like() {
+                    descend(value, &path[1..])
+                } else {
+                    Err(non_existent_table_err(segment))
+                }
+            } else {
+                Ok(input)
+            }
+        }
+
+        descend(self.data.as_item_mut(), table_path)
+    }
+
+    /// Get all dependency tables in the manifest, including the
+    /// platform-specific ones.
+    pub fn get_sections(&self) -> Vec<(DepTable, toml_edit::Item)> {
+        let mut sections = Vec::new();
+
+        for &kind in DepTable::KINDS {
+            let table = DepTable::from(kind);
+            let dependency_type = table.kind_table();
+            // Dependencies can be in the three standard sections...
+            if self
+                .data
+                .get(dependency_type)
+                .map(|t| t.is_table_like())
+                .unwrap_or(false)
+            {
+                sections.push((table.clone(), self.data[dependency_type].clone()))
+            }
+
+            // ... and in `target.<target>.(build-/dev-)dependencies`.
+            let target_sections = self
+                .data
+                .as_table()
+                .get("target")
+                .and_then(toml_edit::Item::as_table_like)
+                .into_iter()
+                .flat_map(toml_edit::TableLike::iter)
+                .filter_map(|(target_name, target_table)| {
+                    let dependency_table = target_table.get(dependency_type)?;
+                    dependency_table.as_table_like().map(|_| {
+                        (
+                            table.clone().set_target(target_name),
+                            dependency_table.clone(),
+                        )
+                    })
+                });
+
+            sections.extend(target_sections);
+        }
+
+        sections
+    }
+}
+
+impl FromStr for Manifest {
+    type Err = anyhow::Error;
+
+    fn from_str(input: &str) -> CargoResult<Self> {
+        let d: toml_edit::Document = input.parse()?;
+        Ok(Manifest { data: d })
+    }
+}
+
+impl fmt::Display for Manifest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.data.fmt(f)
+    }
+}
+
+/// A `Cargo.toml` on disk which can be edited and written back.
+#[derive(Debug)]
+pub struct LocalManifest {
+    /// Path to the manifest.
+    pub path: PathBuf,
+    /// Manifest contents.
+    pub manifest: Manifest,
+}
+
+impl Deref for LocalManifest {
+    type Target = Manifest;
+
+    fn deref(&self) -> &Manifest {
+        &self.manifest
+    }
+}
+
+impl DerefMut for LocalManifest {
+    fn deref_mut(&mut self) -> &mut Manifest {
+        &mut self.manifest
+    }
+}
+
+impl LocalManifest {
+    /// Reads the manifest at `path`.
+    pub fn try_new(path: &Path) -> CargoResult<Self> {
+        if !path.is_absolute() {
+            anyhow::bail!("can only edit absolute paths, got {}", path.display());
+        }
+        let data = paths::read(path)?;
+        let manifest = data
+            .parse()
+            .with_context(|| format!("failed to parse manifest at `{}`", path.display()))?;
+        Ok(LocalManifest {
+            manifest,
+            path: path.to_owned(),
+        })
+    }
+
+    /// Writes the manifest back to disk.
+    pub fn write(&self) -> CargoResult<()> {
+        if !self.manifest.data.contains_key("package")
+            && !self.manifest.data.contains_key("project")
+        {
+            if self.manifest.data.contains_key("workspace") {
+                anyhow::bail!(
+                    "found virtual manifest at {}, but this command requires running against an \
+                     actual package in this workspace.",
+                    self.path.display()
+                );
+            } else {
+                anyhow::bail!(
+                    "missing expected `package` or `project` fields in {}",
+                    self.path.display()
+                );
+            }
+        }
+
+        let s = self.manifest.data.to_string();
+        paths::write(&self.path, s.as_bytes())
+    }
+
+    /// Iterates over every existing declaration of `dep_key` across all
+    /// dependency tables.
+    pub fn get_dependency_versions<'s>(
+        &'s self,
+        dep_key: &'s str,
+    ) -> impl Iterator<Item = (DepTable, CargoResult<Dependency>)> + 's {
+        let crate_root = self.path.parent().expect("manifest path is absolute");
+        self.get_sections()
+            .into_iter()
+            .filter_map(move |(table_path, table)| {
+                let table = table.into_table().ok()?;
+                Some(
+                    table
+                        .into_iter()
+                        .filter_map(|(key, item)| {
+                            if key.as_str() == dep_key {
+                                Some((table_path.clone(), key, item))
+                            } else {
+                                None
+                            }
+                        })
+                        .collect::<Vec<_>>(),
+                )
+            })
+            .flatten()
+            .map(move |(table_path, dep_key, dep_item)| {
+                let dep = Dependency::from_toml(crate_root, &dep_key, &dep_item);
+                (table_path, dep)
+            })
+    }
+
+    /// Adds or updates the entry for `dep` in the table at `table_path`.
+    pub fn insert_into_table(
+        &mut self,
+        table_path: &[String],
+        dep: &Dependency,
+    ) -> CargoResult<()> {
+        let crate_root = self
+            .path
+            .parent()
+            .expect("manifest path is absolute")
+            .to_owned();
+        let dep_key = dep.toml_key();
+
+        let table = self.get_table_mut(table_path)?;
+        if let Some(dep_item) = table.as_table_like_mut().unwrap().get_mut(dep_key) {
+            dep.update_toml(&crate_root, dep_item);
+        } else {
+            let was_sorted = table
+                .as_table_like()
+                .map(|t| is_sorted(t.iter().map(|(name, _)| name)))
+                .unwrap_or(true);
+            let new_dependency = dep.to_toml(&crate_root);
+            table[dep_key] = new_dependency;
+            if was_sorted {
+                if let Some(table) = table.as_table_like_mut() {
+                    table.sort_values();
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Removes the entry for `name` from the table at `table_path`.
+    ///
+    /// If the dependency is no longer declared in any dependency table, any
+    /// references to it from `[features]` are removed as well.
+    pub fn remove_from_table(&mut self, table_path: &[String], name: &str) -> CargoResult<()> {
+        let table_name = table_path.join(".");
+        self.get_table(table_path)
+            .map_err(|_| non_existent_table_err(&table_name))?;
+        let parent_table = self
+            .get_table_mut(table_path)?
+            .as_table_like_mut()
+            .expect("checked to be table-like above");
+
+        if parent_table.remove(name).is_none() {
+            anyhow::bail!(
+                "the dependency `{}` could not be found in `{}`.",
+                name,
+                table_name
+            );
+        }
+
+        if !self.contains_dependency(name) {
+            self.gc_feature_references(name);
+        }
+
+        Ok(())
+    }
+
+    /// Whether `name` is declared in any dependency table.
+    fn contains_dependency(&self, name: &str) -> bool {
+        self.get_sections().iter().any(|(_, table)| {
+            table
+                .as_table_like()
+                .map(|t| t.contains_key(name))
+                .unwrap_or(false)
+        })
+    }
+
+    /// Removes activations of the dependency `dep_key` from `[features]`.
+    ///
+    /// This covers `dep:name`, `name/feature`, and `name?/feature`, as well
+    /// as the implicit feature `name` of an optional dependency (unless
+    /// `[features]` declares a feature of the same name).
+    fn gc_feature_references(&mut self, dep_key: &str) {
+        let explicit_dep_activation = format!("dep:{}", dep_key);
+        let strong_prefix = format!("{}/", dep_key);
+        let weak_prefix = format!("{}?/", dep_key);
+
+        let features = match self
+            .data
+            .get_mut("features")
+            .and_then(|t| t.as_table_like_mut())
+        {
+            Some(features) => features,
+            None => return,
+        };
+        let has_named_feature = features.contains_key(dep_key);
+
+        for (_, activations) in features.iter_mut() {
+            let activations = match activations.as_array_mut() {
+                Some(activations) => activations,
+                None => continue,
+            };
+            for i in (0..activations.len()).rev() {
+                let references_dep = match activations.get(i).and_then(|v| v.as_str()) {
+                    Some(value) => {
+                        value == explicit_dep_activation
+                            || value.starts_with(&strong_prefix)
+                            || value.starts_with(&weak_prefix)
+                            || (value == dep_key && !has_named_feature)
+                    }
+                    None => false,
+                };
+                if !references_dep {
+                    continue;
+                }
+                let removed = activations.remove(i);
+                // Keep the formatting of the array intact when the first
+                // element is the one being removed.
+                if i == 0 {
+                    if let (Some(prefix), Some(first)) =
+                        (removed.decor().prefix(), activations.get_mut(0))
+                    {
+                        let prefix = prefix.to_owned();
+                        first.decor_mut().set_prefix(prefix);
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl fmt::Display for LocalManifest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.manifest.fmt(f)
+    }
+}
+
+fn is_sorted<'a>(mut it: impl Iterator<Item = &'a str>) -> bool {
+    let mut last = match it.next() {
+        Some(e) => e,
+        None => return true,
+    };
+
+    for curr in it {
+        if curr < last {
+            return false;
+        }
+        last = curr;
+    }
+
+    true
+}
+
+fn non_existent_table_err(table: impl fmt::Display) -> anyhow::Error {
+    anyhow::format_err!("the table `{}` could not be found.", table)
+}
@@ -0,0 +1,11 @@
+//! Format-preserving editing of `Cargo.toml` manifests.
+//!
+//! The structures in [`crate::util::toml`] are only ever deserialized through
+//! serde, which throws away comments, whitespace, and the order of keys. Any
+//! command which needs to write a manifest back to disk (such as `cargo add`)
+//! goes through the types in this module instead, which are backed by a
+//! [`toml_edit::Document`] so that everything the user did not ask to change
+//! is left untouched.
+
+pub mod dependency;
+pub mod manifest;
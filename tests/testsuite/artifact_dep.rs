@@ -0,0 +1,419 @@
+//! Tests for artifact dependencies with `-Z bindeps`.
+
+use cargo_test_support::registry;
+use cargo_test_support::{basic_bin_manifest, basic_manifest, project};
+
+#[cargo_test]
+fn gated() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+
+                [dependencies]
+                bar = { path = "bar", artifact = "bin" }
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file("bar/Cargo.toml", &basic_bin_manifest("bar"))
+        .file("bar/src/main.rs", "fn main() {}")
+        .build();
+
+    p.cargo("build")
+        .masquerade_as_nightly_cargo()
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] failed to parse manifest at `[CWD]/Cargo.toml`
+
+Caused by:
+  `artifact = ...` requires `-Z bindeps` (bar)
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn lib_and_target_require_artifact() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+
+                [dependencies]
+                bar = { path = "bar", lib = true }
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file("bar/Cargo.toml", &basic_manifest("bar", "0.5.0"))
+        .file("bar/src/lib.rs", "")
+        .build();
+
+    p.cargo("build -Z bindeps")
+        .masquerade_as_nightly_cargo()
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] failed to parse manifest at `[CWD]/Cargo.toml`
+
+Caused by:
+  `lib` cannot be used without `artifact = ...` (bar)
+",
+        )
+        .run();
+
+    p.change_file(
+        "Cargo.toml",
+        r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+
+            [dependencies]
+            bar = { path = "bar", target = "target" }
+        "#,
+    );
+    p.cargo("build -Z bindeps")
+        .masquerade_as_nightly_cargo()
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] failed to parse manifest at `[CWD]/Cargo.toml`
+
+Caused by:
+  `target` cannot be used without `artifact = ...` (bar)
+",
+        )
+        .run();
+
+    p.change_file(
+        "Cargo.toml",
+        r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+
+            [dependencies]
+            bar = { path = "bar", artifact = "dylib" }
+        "#,
+    );
+    p.cargo("build -Z bindeps")
+        .masquerade_as_nightly_cargo()
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] failed to parse manifest at `[CWD]/Cargo.toml`
+
+Caused by:
+  failed to parse artifact dependency `bar`
+
+Caused by:
+  `artifact` must be one of `bin`, `bin:<name>`, `cdylib` or `staticlib`, found `dylib`
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn build_script_runs_bin_artifact() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+
+                [build-dependencies]
+                codegen = { path = "codegen", artifact = "bin" }
+            "#,
+        )
+        .file(
+            "build.rs",
+            r#"
+                use std::env;
+                use std::path::Path;
+                use std::process::Command;
+
+                fn main() {
+                    let codegen = env::var("CARGO_BIN_FILE_CODEGEN").unwrap();
+                    assert_eq!(codegen, env::var("CARGO_BIN_FILE_CODEGEN_codegen").unwrap());
+                    let out_dir = env::var("OUT_DIR").unwrap();
+                    let status = Command::new(&codegen)
+                        .arg(Path::new(&out_dir).join("generated.rs"))
+                        .status()
+                        .unwrap();
+                    assert!(status.success());
+                }
+            "#,
+        )
+        .file(
+            "src/main.rs",
+            r#"
+                include!(concat!(env!("OUT_DIR"), "/generated.rs"));
+
+                fn main() {
+                    println!("{}", generated());
+                }
+            "#,
+        )
+        .file("codegen/Cargo.toml", &basic_bin_manifest("codegen"))
+        .file(
+            "codegen/src/main.rs",
+            r#"
+                fn main() {
+                    let out = std::env::args().nth(1).unwrap();
+                    std::fs::write(out, "fn generated() -> &'static str { \"generated\" }").unwrap();
+                }
+            "#,
+        )
+        .build();
+
+    p.cargo("run -Z bindeps")
+        .masquerade_as_nightly_cargo()
+        .with_stdout("generated")
+        .with_stderr_unordered(
+            "\
+[COMPILING] codegen v0.5.0 ([CWD]/codegen)
+[COMPILING] foo v0.1.0 ([CWD])
+[FINISHED] [..]
+[RUNNING] `target/debug/foo[EXE]`
+",
+        )
+        .run();
+    // Binaries of dependencies are not uplifted next to the ones of the
+    // workspace.
+    assert!(!p.bin("codegen").exists());
+}
+
+#[cargo_test]
+fn selected_bin_artifact_for_rustc() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+
+                [dependencies]
+                bar = { path = "bar", artifact = "bin:baz" }
+            "#,
+        )
+        .file(
+            "src/main.rs",
+            r#"
+                fn main() {
+                    let baz = env!("CARGO_BIN_FILE_BAR_baz");
+                    assert!(std::path::Path::new(baz).is_file());
+                    assert!(option_env!("CARGO_BIN_FILE_BAR_bar").is_none());
+                }
+            "#,
+        )
+        .file("bar/Cargo.toml", &basic_manifest("bar", "0.5.0"))
+        .file("bar/src/main.rs", "fn main() {}")
+        .file("bar/src/bin/baz.rs", "fn main() {}")
+        .build();
+
+    // `bar` has no lib, but that's fine for an artifact dependency.
+    p.cargo("run -Z bindeps")
+        .masquerade_as_nightly_cargo()
+        .with_stderr_unordered(
+            "\
+[COMPILING] bar v0.5.0 ([CWD]/bar)
+[COMPILING] foo v0.1.0 ([CWD])
+[FINISHED] [..]
+[RUNNING] `target/debug/foo[EXE]`
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn cdylib_and_staticlib_artifacts() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+
+                [build-dependencies]
+                bar = { path = "bar", artifact = ["cdylib", "staticlib"] }
+            "#,
+        )
+        .file(
+            "build.rs",
+            r#"
+                use std::env;
+                use std::path::Path;
+
+                fn main() {
+                    for var in [
+                        "CARGO_CDYLIB_FILE_BAR",
+                        "CARGO_CDYLIB_FILE_BAR_bar",
+                        "CARGO_STATICLIB_FILE_BAR",
+                        "CARGO_STATICLIB_FILE_BAR_bar",
+                    ] {
+                        let path = env::var(var).unwrap();
+                        assert!(Path::new(&path).is_file(), "{} {}", var, path);
+                    }
+                    assert_ne!(
+                        env::var("CARGO_CDYLIB_FILE_BAR").unwrap(),
+                        env::var("CARGO_STATICLIB_FILE_BAR").unwrap()
+                    );
+                }
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file(
+            "bar/Cargo.toml",
+            r#"
+                [package]
+                name = "bar"
+                version = "0.5.0"
+
+                [lib]
+                crate-type = ["cdylib", "staticlib"]
+            "#,
+        )
+        .file(
+            "bar/src/lib.rs",
+            "#[no_mangle] pub extern \"C\" fn bar() {}",
+        )
+        .build();
+
+    p.cargo("build -Z bindeps")
+        .masquerade_as_nightly_cargo()
+        .with_stderr_unordered(
+            "\
+[COMPILING] bar v0.5.0 ([CWD]/bar)
+[COMPILING] foo v0.1.0 ([CWD])
+[FINISHED] [..]
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn artifact_with_lib() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+
+                [dependencies]
+                bar = { path = "bar", artifact = "bin", lib = true }
+            "#,
+        )
+        .file(
+            "src/main.rs",
+            r#"
+                fn main() {
+                    bar::hello();
+                    assert!(std::path::Path::new(env!("CARGO_BIN_FILE_BAR")).is_file());
+                }
+            "#,
+        )
+        .file("bar/Cargo.toml", &basic_manifest("bar", "0.5.0"))
+        .file("bar/src/lib.rs", "pub fn hello() { println!(\"hello\"); }")
+        .file("bar/src/main.rs", "fn main() { bar::hello() }")
+        .build();
+
+    p.cargo("run -Z bindeps")
+        .masquerade_as_nightly_cargo()
+        .with_stdout("hello")
+        .run();
+
+    // Without `lib = true` the library isn't available.
+    p.change_file(
+        "Cargo.toml",
+        r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+
+            [dependencies]
+            bar = { path = "bar", artifact = "bin" }
+        "#,
+    );
+    p.cargo("build -Z bindeps")
+        .masquerade_as_nightly_cargo()
+        .with_status(101)
+        .with_stderr_contains("error[..]`bar`[..]")
+        .with_stderr_contains("[ERROR] could not compile `foo`[..]")
+        .run();
+}
+
+#[cargo_test]
+fn missing_artifact() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+
+                [dependencies]
+                bar = { path = "bar", artifact = "cdylib" }
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file("bar/Cargo.toml", &basic_manifest("bar", "0.5.0"))
+        .file("bar/src/lib.rs", "")
+        .build();
+
+    p.cargo("build -Z bindeps")
+        .masquerade_as_nightly_cargo()
+        .with_status(101)
+        .with_stderr(
+            "[ERROR] dependency `bar` in package `foo` requires a `cdylib` artifact to be present",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn publish_not_supported() {
+    registry::init();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                license = "MIT"
+                description = "foo"
+
+                [build-dependencies]
+                bar = { path = "bar", version = "0.5", artifact = "bin" }
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file("bar/Cargo.toml", &basic_bin_manifest("bar"))
+        .file("bar/src/main.rs", "fn main() {}")
+        .build();
+
+    p.cargo("publish -Z bindeps --no-verify --token sekrit")
+        .masquerade_as_nightly_cargo()
+        .with_status(101)
+        .with_stderr(
+            "\
+[UPDATING] [..]
+[ERROR] artifact dependencies cannot be published yet, `bar` needs to be a regular dependency
+",
+        )
+        .run();
+}
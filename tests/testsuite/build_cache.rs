@@ -0,0 +1,288 @@
+//! Tests for the `-Z build-cache` flag.
+
+use crate::config::ConfigBuilder;
+use cargo::util::Filesystem;
+use cargo_test_support::paths;
+use cargo_test_support::registry::Package;
+use cargo_test_support::{basic_manifest, git, project};
+
+fn publish_deps() {
+    Package::new("bar", "1.0.0")
+        .file("src/lib.rs", "pub fn bar() -> u32 { 1 }")
+        .publish();
+    // Crates with build scripts are never cached.
+    Package::new("baz", "1.0.0")
+        .file("build.rs", "fn main() {}")
+        .file("src/lib.rs", "pub fn baz() -> u32 { 2 }")
+        .publish();
+}
+
+fn cache_entries() -> Vec<String> {
+    let dir = paths::home().join(".cargo/build-cache");
+    let mut entries: Vec<_> = dir
+        .read_dir()
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .filter(|name| !name.starts_with('.'))
+        .collect();
+    entries.sort();
+    entries
+}
+
+#[cargo_test]
+fn gated() {
+    let p = project()
+        .file("Cargo.toml", &basic_manifest("foo", "0.1.0"))
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("build -Z build-cache")
+        .with_status(101)
+        .with_stderr(
+            "error: the `-Z` flag is only accepted on the nightly channel of Cargo, \
+             but this is the `stable` channel\n[..]",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn shared_across_projects() {
+    publish_deps();
+    let p1 = project()
+        .at("foo")
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+
+                [dependencies]
+                bar = "1.0"
+                baz = "1.0"
+            "#,
+        )
+        .file(
+            "src/main.rs",
+            "fn main() { println!(\"{} {}\", bar::bar(), baz::baz()); }",
+        )
+        .build();
+    p1.cargo("build -v -Z build-cache")
+        .masquerade_as_nightly_cargo()
+        .with_stderr_contains("[RUNNING] `rustc --crate-name bar [..]")
+        .with_stderr_contains("[RUNNING] `rustc --crate-name baz [..]")
+        .run();
+
+    let entries = cache_entries();
+    assert_eq!(entries.len(), 1, "{:?}", entries);
+    assert!(entries[0].starts_with("bar-"), "{:?}", entries);
+
+    // Another project gets `bar` from the cache, while `baz` is compiled.
+    let p2 = project()
+        .at("other")
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "other"
+                version = "0.1.0"
+
+                [dependencies]
+                bar = "1.0"
+                baz = "1.0"
+            "#,
+        )
+        .file(
+            "src/main.rs",
+            "fn main() { println!(\"{} {}\", bar::bar(), baz::baz()); }",
+        )
+        .build();
+    p2.cargo("build -v -Z build-cache")
+        .masquerade_as_nightly_cargo()
+        .with_stderr_does_not_contain("[RUNNING] `rustc --crate-name bar [..]")
+        .with_stderr_contains("[COMPILING] bar v1.0.0")
+        .with_stderr_contains("[RUNNING] `rustc --crate-name baz [..]")
+        .run();
+    p2.process(&p2.bin("other")).with_stdout("1 2").run();
+
+    p2.cargo("build -v -Z build-cache")
+        .masquerade_as_nightly_cargo()
+        .with_stderr_contains("[FRESH] bar v1.0.0")
+        .run();
+
+    // Different flags make for a different entry.
+    p2.cargo("build -v -Z build-cache")
+        .env("RUSTFLAGS", "-C debug-assertions")
+        .masquerade_as_nightly_cargo()
+        .with_stderr_contains("[RUNNING] `rustc --crate-name bar [..]")
+        .run();
+    assert_eq!(cache_entries().len(), 2);
+}
+
+#[cargo_test]
+fn eviction() {
+    publish_deps();
+    let p1 = project()
+        .at("foo")
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+
+                [dependencies]
+                bar = "1.0"
+                baz = "1.0"
+            "#,
+        )
+        .file(
+            "src/main.rs",
+            "fn main() { println!(\"{} {}\", bar::bar(), baz::baz()); }",
+        )
+        .build();
+    p1.cargo("build -Z build-cache")
+        .masquerade_as_nightly_cargo()
+        .run();
+    assert_eq!(cache_entries().len(), 1);
+
+    // Over the limit, entries are evicted before the build, so `bar` is
+    // compiled again and stored.
+    let p2 = project()
+        .at("other")
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "other"
+                version = "0.1.0"
+
+                [dependencies]
+                bar = "1.0"
+                baz = "1.0"
+            "#,
+        )
+        .file(
+            "src/main.rs",
+            "fn main() { println!(\"{} {}\", bar::bar(), baz::baz()); }",
+        )
+        .build();
+    p2.cargo("build -v -Z build-cache")
+        .env("CARGO_BUILD_CACHE_MAX_SIZE", "0")
+        .masquerade_as_nightly_cargo()
+        .with_stderr_contains("[RUNNING] `rustc --crate-name bar [..]")
+        .run();
+    assert_eq!(cache_entries().len(), 1);
+}
+
+#[cargo_test]
+fn no_eviction_while_in_use() {
+    publish_deps();
+    let p1 = project()
+        .at("foo")
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+
+                [dependencies]
+                bar = "1.0"
+                baz = "1.0"
+            "#,
+        )
+        .file(
+            "src/main.rs",
+            "fn main() { println!(\"{} {}\", bar::bar(), baz::baz()); }",
+        )
+        .build();
+    p1.cargo("build -Z build-cache")
+        .masquerade_as_nightly_cargo()
+        .run();
+    assert_eq!(cache_entries().len(), 1);
+
+    // Another build holding the cache doesn't block this one, which leaves
+    // the entries alone and uses them.
+    let config = ConfigBuilder::new().build();
+    let lock = Filesystem::new(paths::home().join(".cargo/build-cache"))
+        .open_ro(".cache-lock", &config, "build cache")
+        .unwrap();
+    let p2 = project()
+        .at("other")
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "other"
+                version = "0.1.0"
+
+                [dependencies]
+                bar = "1.0"
+                baz = "1.0"
+            "#,
+        )
+        .file(
+            "src/main.rs",
+            "fn main() { println!(\"{} {}\", bar::bar(), baz::baz()); }",
+        )
+        .build();
+    p2.cargo("build -v -Z build-cache")
+        .env("CARGO_BUILD_CACHE_MAX_SIZE", "0")
+        .masquerade_as_nightly_cargo()
+        .with_stderr_does_not_contain("[..]--crate-name bar [..]")
+        .with_stderr_does_not_contain("[BLOCKING] [..]")
+        .run();
+    assert_eq!(cache_entries().len(), 1);
+
+    drop(lock);
+    p2.cargo("clean").run();
+    p2.cargo("build -v -Z build-cache")
+        .env("CARGO_BUILD_CACHE_MAX_SIZE", "0")
+        .masquerade_as_nightly_cargo()
+        .with_stderr_contains("[RUNNING] `rustc --crate-name bar [..]")
+        .run();
+}
+
+#[cargo_test]
+fn git_revisions_are_distinct() {
+    let (git_project, repo) = git::new_repo("dep", |project| {
+        project
+            .file("Cargo.toml", &basic_manifest("dep", "0.1.0"))
+            .file("src/lib.rs", "pub fn dep() -> u32 { 1 }")
+    });
+    let p = project()
+        .file(
+            "Cargo.toml",
+            &format!(
+                r#"
+                    [package]
+                    name = "foo"
+                    version = "0.1.0"
+
+                    [dependencies]
+                    dep = {{ git = '{}' }}
+                "#,
+                git_project.url()
+            ),
+        )
+        .file("src/main.rs", "fn main() { println!(\"{}\", dep::dep()); }")
+        .build();
+
+    p.cargo("run -Z build-cache")
+        .masquerade_as_nightly_cargo()
+        .with_stdout("1")
+        .run();
+
+    // The same version at another commit isn't taken from the cache.
+    git_project.change_file("src/lib.rs", "pub fn dep() -> u32 { 2 }");
+    git::add(&repo);
+    git::commit(&repo);
+    p.cargo("update").run();
+    p.cargo("run -v -Z build-cache")
+        .masquerade_as_nightly_cargo()
+        .with_stdout("2")
+        .with_stderr_contains("[RUNNING] `rustc --crate-name dep [..]")
+        .run();
+    assert_eq!(cache_entries().len(), 2);
+}
@@ -0,0 +1,246 @@
+//! Tests for `build.build-dir` with `-Z build-dir`.
+
+use cargo_test_support::install::{assert_has_installed_exe, cargo_home};
+use cargo_test_support::paths;
+use cargo_test_support::registry::Package;
+use cargo_test_support::{basic_manifest, cargo_process, project};
+use std::env::consts::EXE_SUFFIX;
+use std::fs;
+
+#[cargo_test]
+fn ignored_without_flag() {
+    let p = project()
+        .file("Cargo.toml", &basic_manifest("foo", "0.1.0"))
+        .file("src/lib.rs", "")
+        .file(
+            ".cargo/config.toml",
+            r#"
+                [build]
+                build-dir = "build-dir"
+            "#,
+        )
+        .build();
+
+    p.cargo("build")
+        .with_stderr(
+            "\
+[WARNING] ignoring `build.build-dir` config, pass `-Z build-dir` to enable it
+[COMPILING] foo v0.1.0 ([CWD])
+[FINISHED] [..]
+",
+        )
+        .run();
+    assert!(p.root().join("target/debug/deps").is_dir());
+    assert!(!p.root().join("build-dir").exists());
+}
+
+#[cargo_test]
+fn ignored_without_flag_warns_once() {
+    // `cargo package` loads a second workspace to verify the package.
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                license = "MIT"
+                description = "foo"
+                homepage = "https://example.com"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file(
+            ".cargo/config.toml",
+            r#"
+                [build]
+                build-dir = "build-dir"
+            "#,
+        )
+        .build();
+
+    p.cargo("package --allow-dirty")
+        .with_stderr(
+            "\
+[WARNING] ignoring `build.build-dir` config, pass `-Z build-dir` to enable it
+[PACKAGING] foo v0.1.0 ([CWD])
+[VERIFYING] foo v0.1.0 ([CWD])
+[COMPILING] foo v0.1.0 ([CWD]/target/package/foo-0.1.0)
+[FINISHED] [..]
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn intermediates_in_build_dir() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+
+                [dependencies]
+                bar = { path = "bar" }
+            "#,
+        )
+        .file(
+            "src/main.rs",
+            r#"
+                include!(concat!(env!("OUT_DIR"), "/generated.rs"));
+                fn main() { println!("{}", bar::bar() + generated()); }
+            "#,
+        )
+        .file(
+            "build.rs",
+            r#"
+                fn main() {
+                    let out = std::env::var("OUT_DIR").unwrap();
+                    let out = std::path::Path::new(&out).join("generated.rs");
+                    std::fs::write(out, "fn generated() -> u32 { 1 }").unwrap();
+                }
+            "#,
+        )
+        .file("bar/Cargo.toml", &basic_manifest("bar", "0.1.0"))
+        .file("bar/src/lib.rs", "pub fn bar() -> u32 { 41 }")
+        .file(
+            ".cargo/config.toml",
+            r#"
+                [build]
+                build-dir = "../build-dir"
+            "#,
+        )
+        .build();
+    let build_dir = p.root().parent().unwrap().join("build-dir");
+
+    p.cargo("build -Z build-dir")
+        .masquerade_as_nightly_cargo()
+        .run();
+
+    let bin = format!("target/debug/foo{}", EXE_SUFFIX);
+    assert!(p.root().join(&bin).is_file());
+    for dir in &["deps", "build", ".fingerprint", "incremental"] {
+        assert!(!p.root().join("target/debug").join(dir).exists(), "{}", dir);
+        assert!(build_dir.join("debug").join(dir).is_dir(), "{}", dir);
+    }
+    p.process(p.root().join(&bin)).with_stdout("42").run();
+
+    p.cargo("build -Z build-dir")
+        .masquerade_as_nightly_cargo()
+        .with_stderr("[FINISHED] [..]")
+        .run();
+
+    p.cargo("doc -Z build-dir")
+        .masquerade_as_nightly_cargo()
+        .run();
+    assert!(p.root().join("target/doc/foo/index.html").is_file());
+    assert!(!build_dir.join("doc").exists());
+
+    p.cargo("clean -Z build-dir")
+        .masquerade_as_nightly_cargo()
+        .run();
+    assert!(!p.root().join("target").exists());
+    assert!(!build_dir.exists());
+}
+
+#[cargo_test]
+fn install() {
+    Package::new("foo", "0.1.0")
+        .file("src/main.rs", "fn main() {}")
+        .publish();
+    // Installing from a registry only reads the config in `CARGO_HOME`.
+    let config = cargo_home().join("config");
+    let mut contents = fs::read_to_string(&config).unwrap();
+    contents.push_str(
+        r#"
+            [build]
+            build-dir = "{cargo-home}/build-dir"
+        "#,
+    );
+    fs::write(&config, contents).unwrap();
+
+    cargo_process("install foo -Z build-dir")
+        .masquerade_as_nightly_cargo()
+        .run();
+    assert_has_installed_exe(cargo_home(), "foo");
+    assert!(cargo_home().join("build-dir/release/.fingerprint").is_dir());
+}
+
+#[cargo_test]
+fn templates() {
+    let p = project()
+        .file("Cargo.toml", &basic_manifest("foo", "0.1.0"))
+        .file("src/lib.rs", "")
+        .file(
+            ".cargo/config.toml",
+            r#"
+                [build]
+                build-dir = "{cargo-home}/build-dirs/{workspace-path-hash}"
+            "#,
+        )
+        .build();
+
+    p.cargo("build -Z build-dir")
+        .masquerade_as_nightly_cargo()
+        .run();
+
+    let build_dirs: Vec<_> = paths::home()
+        .join(".cargo/build-dirs")
+        .read_dir()
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect();
+    assert_eq!(build_dirs.len(), 1);
+    assert!(build_dirs[0].join("debug/.fingerprint").is_dir());
+    assert!(!p.root().join("target/debug/.fingerprint").exists());
+}
+
+#[cargo_test]
+fn unknown_template() {
+    let p = project()
+        .file("Cargo.toml", &basic_manifest("foo", "0.1.0"))
+        .file("src/lib.rs", "")
+        .file(
+            ".cargo/config.toml",
+            r#"
+                [build]
+                build-dir = "{workspace-root}/{profile}"
+            "#,
+        )
+        .build();
+
+    p.cargo("build -Z build-dir")
+        .masquerade_as_nightly_cargo()
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] unknown template `{profile}` in the build directory set in [..]config.toml, \
+expected one of `{workspace-root}`, `{cargo-home}` or `{workspace-path-hash}`
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn braces_in_expanded_paths() {
+    // Templates in the paths they expand to are left alone.
+    let p = project()
+        .at("foo{cargo-home}")
+        .file("Cargo.toml", &basic_manifest("foo", "0.1.0"))
+        .file("src/lib.rs", "")
+        .file(
+            ".cargo/config.toml",
+            r#"
+                [build]
+                build-dir = "{workspace-root}/build-dir"
+            "#,
+        )
+        .build();
+
+    p.cargo("build -Z build-dir")
+        .masquerade_as_nightly_cargo()
+        .run();
+    assert!(p.root().join("build-dir/debug/.fingerprint").is_dir());
+}
@@ -0,0 +1,213 @@
+//! Tests for running build scripts with `-Z build-script-sandbox`.
+
+use cargo_test_support::project;
+
+#[cargo_test]
+fn scrubbed_env() {
+    let p = project()
+        .file("src/main.rs", "fn main() {}")
+        .file(
+            "build.rs",
+            r#"
+                use std::env;
+
+                fn main() {
+                    assert!(env::var_os("PATH").is_some());
+                    assert_eq!(env::var("LANG").unwrap(), "C.UTF-8");
+                    assert!(env::var_os("OUT_DIR").is_some());
+                    assert!(env::var_os("CARGO_PKG_NAME").is_some());
+                    assert!(env::var_os("SECRET").is_none());
+                }
+            "#,
+        )
+        .build();
+
+    p.cargo("build -Z build-script-sandbox")
+        .masquerade_as_nightly_cargo()
+        .env("SECRET", "hunter2")
+        .env("LANG", "C.UTF-8")
+        .with_stderr(
+            "\
+[COMPILING] foo v0.0.1 ([..])
+[FINISHED] [..]
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn newly_declared_env_is_reported() {
+    let p = project()
+        .file(
+            "src/main.rs",
+            r#"fn main() { println!("{}", env!("FOO_VALUE")); }"#,
+        )
+        .file(
+            "build.rs",
+            r#"
+                fn main() {
+                    println!("cargo:rerun-if-env-changed=FOO");
+                    let foo = std::env::var("FOO").unwrap_or_default();
+                    println!("cargo:rustc-env=FOO_VALUE={}", foo);
+                }
+            "#,
+        )
+        .build();
+
+    // The script runs only once, without the variable.
+    p.cargo("run -Z build-script-sandbox")
+        .masquerade_as_nightly_cargo()
+        .env("FOO", "bar")
+        .with_stdout("\n")
+        .with_stderr(
+            "\
+[COMPILING] foo v0.0.1 ([..])
+[WARNING] the build script declared the environment variable `FOO`, \
+but ran without it since it wasn't declared before; \
+it will be rerun with it set by the next build
+[FINISHED] [..]
+[RUNNING] [..]
+",
+        )
+        .run();
+
+    // The variable was declared by the last run now.
+    p.cargo("run -Z build-script-sandbox")
+        .masquerade_as_nightly_cargo()
+        .env("FOO", "bar")
+        .with_stdout("bar")
+        .with_stderr(
+            "\
+[COMPILING] foo v0.0.1 ([..])
+[FINISHED] [..]
+[RUNNING] [..]
+",
+        )
+        .run();
+    p.cargo("run -Z build-script-sandbox")
+        .masquerade_as_nightly_cargo()
+        .env("FOO", "baz")
+        .with_stdout("baz")
+        .with_stderr(
+            "\
+[COMPILING] foo v0.0.1 ([..])
+[FINISHED] [..]
+[RUNNING] [..]
+",
+        )
+        .run();
+    p.cargo("run -Z build-script-sandbox")
+        .masquerade_as_nightly_cargo()
+        .env("FOO", "baz")
+        .with_stdout("baz")
+        .with_stderr(
+            "\
+[FINISHED] [..]
+[RUNNING] [..]
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn declared_env_is_kept_after_failure() {
+    let p = project()
+        .file("src/main.rs", "fn main() {}")
+        .file(
+            "build.rs",
+            r#"
+                fn main() {
+                    println!("cargo:rerun-if-env-changed=FOO");
+                    std::env::var("FOO").expect("FOO is required");
+                }
+            "#,
+        )
+        .build();
+
+    p.cargo("build -Z build-script-sandbox")
+        .masquerade_as_nightly_cargo()
+        .env("FOO", "bar")
+        .with_status(101)
+        .with_stderr_contains(
+            "\
+[WARNING] the build script declared the environment variable `FOO`, \
+but ran without it since it wasn't declared before; \
+it will be rerun with it set by the next build",
+        )
+        .with_stderr_contains("[..]FOO is required[..]")
+        .run();
+
+    p.cargo("build -Z build-script-sandbox")
+        .masquerade_as_nightly_cargo()
+        .env("FOO", "bar")
+        .with_stderr(
+            "\
+[COMPILING] foo v0.0.1 ([..])
+[FINISHED] [..]
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn writes_outside_out_dir() {
+    let p = project()
+        .file("src/main.rs", "fn main() {}")
+        .file(
+            "build.rs",
+            r#"
+                use std::env;
+                use std::fs;
+                use std::path::Path;
+
+                fn main() {
+                    let out_dir = env::var("OUT_DIR").unwrap();
+                    fs::write(Path::new(&out_dir).join("fine.rs"), "").unwrap();
+                    fs::write("generated.rs", "").unwrap();
+                    // Version control directories aren't checked.
+                    fs::create_dir_all(".git").unwrap();
+                    fs::write(".git/index", "").unwrap();
+                }
+            "#,
+        )
+        .build();
+
+    p.cargo("build -Z build-script-sandbox")
+        .masquerade_as_nightly_cargo()
+        .with_stderr(
+            "\
+[COMPILING] foo v0.0.1 ([..])
+[WARNING] the build script wrote outside of `OUT_DIR`:
+  generated.rs
+[FINISHED] [..]
+",
+        )
+        .run();
+
+    p.cargo("build -Z build-script-sandbox=deny")
+        .masquerade_as_nightly_cargo()
+        .env("CARGO_PROFILE_DEV_OPT_LEVEL", "1")
+        .with_status(101)
+        .with_stderr(
+            "\
+[COMPILING] foo v0.0.1 ([..])
+[ERROR] build script of `foo v0.0.1 ([..])` wrote outside of `OUT_DIR`:
+  generated.rs
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn invalid_mode() {
+    let p = project().file("src/main.rs", "fn main() {}").build();
+
+    p.cargo("build -Z build-script-sandbox=strict")
+        .masquerade_as_nightly_cargo()
+        .with_status(101)
+        .with_stderr(
+            "[ERROR] -Z build-script-sandbox takes either `warn` or `deny` as an argument, \
+             found `strict`",
+        )
+        .run();
+}
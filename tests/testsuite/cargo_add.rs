@@ -0,0 +1,326 @@
+//! Tests for the `cargo add` command.
+
+use cargo_test_support::registry::{self, Package};
+use cargo_test_support::{basic_manifest, project};
+
+#[cargo_test]
+fn gated() {
+    let p = project().file("src/lib.rs", "").build();
+
+    p.cargo("add my-package")
+        .masquerade_as_nightly_cargo()
+        .with_status(101)
+        .with_stderr(
+            "\
+error: the `cargo add` command is unstable, pass `-Z unstable-options` to enable it
+See https://github.com/rust-lang/cargo/issues/5586 for more information about the `cargo add` command.
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn preserves_formatting() {
+    Package::new("my-package", "0.1.0").publish();
+    Package::new("my-package", "0.4.1").publish();
+    Package::new("my-package", "0.5.0-alpha.1").publish();
+    Package::new("another", "1.2.3").publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+
+                # Keep this comment.
+                [dependencies]
+                zebra = { path = "zebra" } # and this one
+                another = "1.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file("zebra/Cargo.toml", &basic_manifest("zebra", "0.1.0"))
+        .file("zebra/src/lib.rs", "")
+        .build();
+
+    p.cargo("add my-package -Zunstable-options")
+        .masquerade_as_nightly_cargo()
+        .with_stderr(
+            "\
+[UPDATING] `[..]` index
+[ADDING] my-package v0.4.1 to dependencies
+",
+        )
+        .run();
+
+    assert_eq!(
+        p.read_file("Cargo.toml"),
+        r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+
+                # Keep this comment.
+                [dependencies]
+                zebra = { path = "zebra" } # and this one
+                another = "1.0"
+my-package = "0.4.1"
+            "#
+    );
+    assert!(p.read_lockfile().contains("name = \"my-package\""));
+}
+
+#[cargo_test]
+fn sorted_table_stays_sorted() {
+    Package::new("bbb", "0.1.0").publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+
+                [dependencies]
+                aaa = { path = "aaa" }
+                ccc = { path = "ccc" }
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file("aaa/Cargo.toml", &basic_manifest("aaa", "0.1.0"))
+        .file("aaa/src/lib.rs", "")
+        .file("ccc/Cargo.toml", &basic_manifest("ccc", "0.1.0"))
+        .file("ccc/src/lib.rs", "")
+        .build();
+
+    p.cargo("add bbb@0.1 -Zunstable-options")
+        .masquerade_as_nightly_cargo()
+        .run();
+
+    assert_eq!(
+        p.read_file("Cargo.toml"),
+        r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+
+                [dependencies]
+                aaa = { path = "aaa" }
+bbb = "0.1"
+                ccc = { path = "ccc" }
+            "#
+    );
+}
+
+#[cargo_test]
+fn features_dev_and_target() {
+    Package::new("my-package", "1.0.0")
+        .feature("default", &["std"])
+        .feature("std", &[])
+        .feature("derive", &[])
+        .publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("add my-package -F derive --dev --target cfg(unix) -Zunstable-options")
+        .masquerade_as_nightly_cargo()
+        .with_stderr(
+            "\
+[UPDATING] `[..]` index
+[ADDING] my-package v1.0.0 to dev-dependencies for target `cfg(unix)`
+             Features:
+             + derive
+             + std
+",
+        )
+        .run();
+
+    assert_eq!(
+        p.read_file("Cargo.toml"),
+        r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+
+[target."cfg(unix)".dev-dependencies]
+my-package = { version = "1.0.0", features = ["derive"] }
+            "#
+    );
+}
+
+#[cargo_test]
+fn empty_target() {
+    let p = project().file("src/lib.rs", "").build();
+
+    p.cargo("add my-package --target= -Zunstable-options")
+        .masquerade_as_nightly_cargo()
+        .with_status(101)
+        .with_stderr("error: target specification may not be empty")
+        .run();
+}
+
+#[cargo_test]
+fn update_existing_keeps_requirement() {
+    Package::new("my-package", "1.0.0")
+        .feature("a", &[])
+        .publish();
+    Package::new("my-package", "1.2.0")
+        .feature("a", &[])
+        .publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+
+                [dependencies]
+                my-package = "1.0" # pinned on purpose
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("add my-package --features a --optional -Zunstable-options")
+        .masquerade_as_nightly_cargo()
+        .with_stderr(
+            "\
+[UPDATING] `[..]` index
+[ADDING] my-package v1.0 to optional dependencies
+             Features:
+             + a
+",
+        )
+        .run();
+
+    assert_eq!(
+        p.read_file("Cargo.toml"),
+        r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+
+                [dependencies]
+                my-package = { version = "1.0", features = ["a"], optional = true } # pinned on purpose
+            "#
+    );
+}
+
+#[cargo_test]
+fn path_and_rename() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+
+                [build-dependencies]
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file("../bar/Cargo.toml", &basic_manifest("bar", "0.3.0"))
+        .file("../bar/src/lib.rs", "")
+        .build();
+
+    p.cargo("add --path ../bar --rename baz --build -Zunstable-options")
+        .masquerade_as_nightly_cargo()
+        .with_stderr("[ADDING] bar (as baz) (v0.3.0) to build-dependencies")
+        .run();
+
+    assert_eq!(
+        p.read_file("Cargo.toml"),
+        r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+
+                [build-dependencies]
+baz = { path = "../bar", package = "bar" }
+            "#
+    );
+}
+
+#[cargo_test]
+fn alt_registry() {
+    registry::alt_init();
+    Package::new("my-package", "0.2.0")
+        .alternative(true)
+        .publish();
+
+    let p = project()
+        .file("Cargo.toml", &basic_manifest("foo", "0.0.1"))
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("add my-package --registry alternative -Zunstable-options")
+        .masquerade_as_nightly_cargo()
+        .run();
+
+    assert!(p
+        .read_file("Cargo.toml")
+        .contains(r#"my-package = { version = "0.2.0", registry = "alternative" }"#));
+}
+
+#[cargo_test]
+fn unknown_feature() {
+    Package::new("my-package", "1.0.0")
+        .feature("a", &[])
+        .publish();
+
+    let p = project()
+        .file("Cargo.toml", &basic_manifest("foo", "0.0.1"))
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("add my-package -F a,nope -Zunstable-options")
+        .masquerade_as_nightly_cargo()
+        .with_status(101)
+        .with_stderr(
+            "\
+[UPDATING] `[..]` index
+[ERROR] unrecognized feature for crate `my-package`: `nope`
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn dry_run() {
+    Package::new("my-package", "1.0.0").publish();
+
+    let manifest = basic_manifest("foo", "0.0.1");
+    let p = project()
+        .file("Cargo.toml", &manifest)
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("add my-package --dry-run -Zunstable-options")
+        .masquerade_as_nightly_cargo()
+        .with_stderr(
+            "\
+[UPDATING] `[..]` index
+[ADDING] my-package v1.0.0 to dependencies
+[WARNING] aborting add due to dry run
+",
+        )
+        .run();
+
+    assert_eq!(p.read_file("Cargo.toml"), manifest);
+}
@@ -0,0 +1,199 @@
+//! Tests for the `cargo info` command.
+
+use cargo_test_support::registry::Package;
+use cargo_test_support::{cargo_process, project};
+
+fn publish_foo() {
+    Package::new("foo", "0.1.0").publish();
+    Package::new("foo", "0.2.0")
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.2.0"
+                description = "A package for testing"
+                license = "MIT OR Apache-2.0"
+                rust-version = "1.56"
+                repository = "https://example.com/foo"
+
+                [features]
+                default = ["std"]
+                std = []
+                extra = ["serde/derive"]
+
+                [dependencies]
+                bar = "0.1"
+                serde = { version = "1.0", optional = true }
+
+                [target.'cfg(unix)'.dependencies]
+                baz = { version = "0.3", package = "baz-sys" }
+
+                [build-dependencies]
+                cc = "1.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .publish();
+    Package::new("foo", "0.3.0").yanked(true).publish();
+    Package::new("foo", "0.4.0-alpha.1").publish();
+}
+
+#[cargo_test]
+fn gated() {
+    publish_foo();
+
+    cargo_process("info foo")
+        .masquerade_as_nightly_cargo()
+        .with_status(101)
+        .with_stderr(
+            "\
+error: the `cargo info` command is unstable, pass `-Z unstable-options` to enable it
+See https://github.com/rust-lang/cargo/issues/948 for more information about the `cargo info` command.
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn latest_version() {
+    publish_foo();
+
+    cargo_process("info foo -Z unstable-options")
+        .masquerade_as_nightly_cargo()
+        .with_stdout(
+            "\
+foo v0.2.0
+A package for testing
+license: MIT OR Apache-2.0
+rust-version: 1.56
+repository: https://example.com/foo
+features:
+  default = [\"std\"]
+  extra = [\"serde/derive\"]
+  serde = [\"dep:serde\"]
+  std = []
+dependencies:
+  bar = \"^0.1\"
+  baz = \"^0.3\" (package `baz-sys`, cfg(unix))
+  serde = \"^1.0\" (optional)
+build-dependencies:
+  cc = \"^1.0\"
+versions: 0.1.0, 0.2.0, 0.3.0 (yanked), 0.4.0-alpha.1
+",
+        )
+        .with_stderr(
+            "\
+[UPDATING] `dummy-registry` index
+[DOWNLOADING] crates ...
+[DOWNLOADED] foo v0.2.0 (registry `dummy-registry`)
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn explicit_version() {
+    publish_foo();
+
+    cargo_process("info foo@0.1 -Z unstable-options")
+        .masquerade_as_nightly_cargo()
+        .with_stdout(
+            "\
+foo v0.1.0
+license: unknown
+versions: 0.1.0, 0.2.0, 0.3.0 (yanked), 0.4.0-alpha.1
+",
+        )
+        .run();
+
+    cargo_process("info foo@0.5 -Z unstable-options")
+        .masquerade_as_nightly_cargo()
+        .with_status(101)
+        .with_stderr_contains(
+            "[ERROR] could not find `foo` in registry `crates-io` matching `^0.5`",
+        )
+        .run();
+
+    cargo_process("info missing -Z unstable-options")
+        .masquerade_as_nightly_cargo()
+        .with_status(101)
+        .with_stderr_contains("[ERROR] could not find `missing` in registry `crates-io`")
+        .run();
+}
+
+#[cargo_test]
+fn locked_version_in_workspace() {
+    publish_foo();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "bar"
+                version = "0.0.1"
+
+                [dependencies]
+                foo = "0.1"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+    p.cargo("generate-lockfile").run();
+
+    p.cargo("info foo -Z unstable-options")
+        .masquerade_as_nightly_cargo()
+        .with_stdout(
+            "\
+foo v0.1.0
+license: unknown
+versions: 0.1.0 (locked), 0.2.0, 0.3.0 (yanked), 0.4.0-alpha.1
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn offline_uses_downloaded_versions() {
+    publish_foo();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "bar"
+                version = "0.0.1"
+
+                [dependencies]
+                foo = "0.1"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+    p.cargo("fetch").run();
+
+    cargo_process("info foo --offline -Z unstable-options")
+        .masquerade_as_nightly_cargo()
+        .with_stdout(
+            "\
+foo v0.1.0
+license: unknown
+versions: 0.1.0, 0.2.0, 0.3.0 (yanked), 0.4.0-alpha.1
+",
+        )
+        .with_stderr("")
+        .run();
+
+    cargo_process("info foo@0.2 --offline -Z unstable-options")
+        .masquerade_as_nightly_cargo()
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] no matching version of `foo@0.2` has been downloaded from registry `crates-io`, \
+which is required to display it in offline mode
+",
+        )
+        .run();
+}
@@ -0,0 +1,306 @@
+//! Tests for the `cargo remove` command.
+
+use cargo_test_support::registry::Package;
+use cargo_test_support::{basic_manifest, project};
+
+#[cargo_test]
+fn gated() {
+    let p = project().file("src/lib.rs", "").build();
+
+    p.cargo("remove my-package")
+        .masquerade_as_nightly_cargo()
+        .with_status(101)
+        .with_stderr(
+            "\
+error: the `cargo remove` command is unstable, pass `-Z unstable-options` to enable it
+See https://github.com/rust-lang/cargo/issues/5586 for more information about the `cargo remove` command.
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn empty_target() {
+    let p = project().file("src/lib.rs", "").build();
+
+    p.cargo("remove my-package --target= -Zunstable-options")
+        .masquerade_as_nightly_cargo()
+        .with_status(101)
+        .with_stderr("error: target specification may not be empty")
+        .run();
+}
+
+#[cargo_test]
+fn preserves_formatting() {
+    Package::new("my-package", "0.1.0").publish();
+    Package::new("another", "1.2.3").publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+
+                # Keep this comment.
+                [dependencies]
+                another = "1.0" # and this one
+                my-package = "0.1"
+
+                [dev-dependencies]
+                my-package = "0.1"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("remove my-package -Zunstable-options")
+        .masquerade_as_nightly_cargo()
+        .with_stderr(
+            "\
+[REMOVING] my-package from dependencies
+[UPDATING] `[..]` index
+",
+        )
+        .run();
+
+    assert_eq!(
+        p.read_file("Cargo.toml"),
+        r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+
+                # Keep this comment.
+                [dependencies]
+                another = "1.0" # and this one
+
+                [dev-dependencies]
+                my-package = "0.1"
+            "#
+    );
+}
+
+#[cargo_test]
+fn cleans_up_features() {
+    Package::new("my-package", "0.1.0")
+        .feature("std", &[])
+        .publish();
+    Package::new("other", "0.1.0").feature("std", &[]).publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+
+                [features]
+                default = ["my-package", "other"]
+                std = ["my-package/std", "other/std"]
+                weak = ["my-package?/std"]
+
+                [dependencies]
+                my-package = { version = "0.1", optional = true }
+                other = { version = "0.1", optional = true }
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("remove my-package -Zunstable-options -Znamespaced-features -Zweak-dep-features")
+        .masquerade_as_nightly_cargo()
+        .run();
+
+    assert_eq!(
+        p.read_file("Cargo.toml"),
+        r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+
+                [features]
+                default = ["other"]
+                std = ["other/std"]
+                weak = []
+
+                [dependencies]
+                other = { version = "0.1", optional = true }
+            "#
+    );
+}
+
+#[cargo_test]
+fn cleans_up_namespaced_features() {
+    Package::new("my-package", "0.1.0").publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+
+                [features]
+                serde = ["dep:my-package", "std"]
+                std = []
+
+                [dependencies]
+                my-package = { version = "0.1", optional = true }
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("remove my-package -Zunstable-options -Znamespaced-features")
+        .masquerade_as_nightly_cargo()
+        .run();
+
+    assert_eq!(
+        p.read_file("Cargo.toml"),
+        r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+
+                [features]
+                serde = ["std"]
+                std = []
+
+                [dependencies]
+            "#
+    );
+}
+
+#[cargo_test]
+fn keeps_features_while_still_declared() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+
+                [features]
+                unix = ["bar/unix"]
+
+                [dependencies]
+                bar = { path = "bar" }
+
+                [target.'cfg(unix)'.dependencies]
+                bar = { path = "bar", features = ["unix"] }
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file(
+            "bar/Cargo.toml",
+            r#"
+                [package]
+                name = "bar"
+                version = "0.1.0"
+
+                [features]
+                unix = []
+            "#,
+        )
+        .file("bar/src/lib.rs", "")
+        .build();
+
+    p.cargo("remove bar --target cfg(unix) -Zunstable-options")
+        .masquerade_as_nightly_cargo()
+        .with_stderr("[REMOVING] bar from dependencies for target `cfg(unix)`")
+        .run();
+
+    assert_eq!(
+        p.read_file("Cargo.toml"),
+        r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+
+                [features]
+                unix = ["bar/unix"]
+
+                [dependencies]
+                bar = { path = "bar" }
+
+                [target.'cfg(unix)'.dependencies]
+            "#
+    );
+}
+
+#[cargo_test]
+fn missing_dependency() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+
+                [dependencies]
+                bar = { path = "bar" }
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file("bar/Cargo.toml", &basic_manifest("bar", "0.1.0"))
+        .file("bar/src/lib.rs", "")
+        .build();
+
+    p.cargo("remove bar --dev -Zunstable-options")
+        .masquerade_as_nightly_cargo()
+        .with_status(101)
+        .with_stderr(
+            "\
+[REMOVING] bar from dev-dependencies
+[ERROR] the table `dev-dependencies` could not be found.
+",
+        )
+        .run();
+
+    p.cargo("remove baz -Zunstable-options")
+        .masquerade_as_nightly_cargo()
+        .with_status(101)
+        .with_stderr(
+            "\
+[REMOVING] baz from dependencies
+[ERROR] the dependency `baz` could not be found in `dependencies`.
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn dry_run() {
+    let manifest = r#"
+        [package]
+        name = "foo"
+        version = "0.0.1"
+
+        [dependencies]
+        bar = { path = "bar" }
+    "#;
+    let p = project()
+        .file("Cargo.toml", manifest)
+        .file("src/lib.rs", "")
+        .file("bar/Cargo.toml", &basic_manifest("bar", "0.1.0"))
+        .file("bar/src/lib.rs", "")
+        .build();
+
+    p.cargo("remove bar --dry-run -Zunstable-options")
+        .masquerade_as_nightly_cargo()
+        .with_stderr(
+            "\
+[REMOVING] bar from dependencies
+[WARNING] aborting remove due to dry run
+",
+        )
+        .run();
+
+    assert_eq!(p.read_file("Cargo.toml"), manifest);
+}
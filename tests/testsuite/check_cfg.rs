@@ -0,0 +1,203 @@
+//! Tests for `-Z check-cfg`.
+
+use cargo_test_support::registry::Package;
+use cargo_test_support::{basic_manifest, project};
+
+#[cargo_test]
+fn features() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+
+                [features]
+                default = ["serde"]
+                serde = []
+            "#,
+        )
+        .file(
+            "src/lib.rs",
+            r#"
+                #[cfg(feature = "serde")]
+                pub fn serde() {}
+                #[cfg(feature = "serde1")]
+                pub fn serde1() {}
+            "#,
+        )
+        .build();
+
+    p.cargo("check -v -Z check-cfg")
+        .masquerade_as_nightly_cargo()
+        .with_stderr_contains(
+            "[RUNNING] `rustc [..]--check-cfg 'cfg(feature, values(\"default\", \"serde\"))' [..]`",
+        )
+        .with_stderr_contains("[..]unexpected `cfg` condition value: `serde1`")
+        .run();
+}
+
+#[cargo_test]
+fn features_doc() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+
+                [features]
+                serde = []
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("doc -v -Z check-cfg")
+        .masquerade_as_nightly_cargo()
+        .with_stderr_contains(
+            "[RUNNING] `rustdoc [..]--check-cfg 'cfg(feature, values(\"serde\"))' [..]`",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn features_not_checked_for_upstream() {
+    Package::new("bar", "0.1.0")
+        .file(
+            "src/lib.rs",
+            r#"
+                #[cfg(feature = "nightly")]
+                pub fn nightly() {}
+            "#,
+        )
+        .publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+
+                [dependencies]
+                bar = "0.1"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("check -vv -Z check-cfg")
+        .masquerade_as_nightly_cargo()
+        .with_stderr_contains("[RUNNING] `[..]rustc --crate-name foo [..]--check-cfg[..]`")
+        .with_stderr_line_without(&["[RUNNING] `[..]rustc --crate-name bar"], &["--check-cfg"])
+        .with_stderr_does_not_contain("[..]unexpected `cfg`[..]")
+        .run();
+}
+
+#[cargo_test]
+fn not_enabled_by_default() {
+    let p = project()
+        .file("Cargo.toml", &basic_manifest("foo", "0.1.0"))
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("check -v")
+        .with_stderr_does_not_contain("[..]--check-cfg[..]")
+        .run();
+}
+
+#[cargo_test]
+fn build_script_check_cfg() {
+    let p = project()
+        .file("Cargo.toml", &basic_manifest("foo", "0.1.0"))
+        .file(
+            "build.rs",
+            r#"
+                fn main() {
+                    println!("cargo:rustc-check-cfg=cfg(has_foo)");
+                    println!("cargo:rustc-cfg=has_foo");
+                }
+            "#,
+        )
+        .file(
+            "src/lib.rs",
+            r#"
+                #[cfg(has_foo)]
+                pub fn foo() {}
+            "#,
+        )
+        .build();
+
+    p.cargo("check -v -Z check-cfg")
+        .masquerade_as_nightly_cargo()
+        .with_stderr_contains(
+            "[RUNNING] `rustc --crate-name foo [..]--cfg has_foo --check-cfg 'cfg(has_foo)'[..]`",
+        )
+        .with_stderr_does_not_contain("[..]unexpected `cfg`[..]")
+        .run();
+}
+
+#[cargo_test]
+fn build_script_check_cfg_requires_flag() {
+    let p = project()
+        .file("Cargo.toml", &basic_manifest("foo", "0.1.0"))
+        .file(
+            "build.rs",
+            r#"fn main() { println!("cargo:rustc-check-cfg=cfg(has_foo)"); }"#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("check")
+        .with_stderr(
+            "\
+[COMPILING] foo v0.1.0 ([CWD])
+[WARNING] cargo:rustc-check-cfg requires -Zcheck-cfg flag
+[FINISHED] [..]
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn toggling_rebuilds() {
+    let p = project()
+        .file("Cargo.toml", &basic_manifest("foo", "0.1.0"))
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("check")
+        .masquerade_as_nightly_cargo()
+        .with_stderr(
+            "\
+[CHECKING] foo v0.1.0 ([CWD])
+[FINISHED] [..]
+",
+        )
+        .run();
+    p.cargo("check -Z check-cfg")
+        .masquerade_as_nightly_cargo()
+        .with_stderr(
+            "\
+[CHECKING] foo v0.1.0 ([CWD])
+[FINISHED] [..]
+",
+        )
+        .run();
+    p.cargo("check -Z check-cfg")
+        .masquerade_as_nightly_cargo()
+        .with_stderr("[FINISHED] [..]")
+        .run();
+    p.cargo("check")
+        .masquerade_as_nightly_cargo()
+        .with_stderr(
+            "\
+[CHECKING] foo v0.1.0 ([CWD])
+[FINISHED] [..]
+",
+        )
+        .run();
+}
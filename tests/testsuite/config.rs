@@ -1482,6 +1482,8 @@ fn all_profile_options() {
         dir_name: Some(InternedString::new("dir_name")),
         inherits: Some(InternedString::new("debug")),
         strip: Some(toml::StringOrBool::String("symbols".to_string())),
+        rustflags: Some(vec![InternedString::new("-Ctarget-cpu=native")]),
+        trim_paths: Some(true),
         package: None,
         build_override: None,
     };
@@ -2659,3 +2659,224 @@ fn changing_linker() {
         )
         .run();
 }
+
+#[cargo_test]
+fn checksum_freshness() {
+    // A restored target directory with a fresh checkout, where every mtime
+    // changed, shouldn't rebuild anything with `-Zchecksum-freshness`.
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+
+                [dependencies]
+                bar = { path = "bar" }
+            "#,
+        )
+        .file("src/main.rs", "fn main() { bar::bar(); }")
+        .file("bar/Cargo.toml", &basic_manifest("bar", "0.0.1"))
+        .file("bar/src/lib.rs", "pub fn bar() {}")
+        .build();
+
+    p.cargo("build -Zchecksum-freshness")
+        .masquerade_as_nightly_cargo()
+        .run();
+
+    // Makes everything in the target directory older than the sources, with
+    // the outputs of `bar` newer than those of `foo`, everything still being
+    // older than the next build.
+    let set_mtimes = || {
+        fn set_all(path: &Path, mtime: FileTime) {
+            if path.is_dir() {
+                for entry in fs::read_dir(path).unwrap() {
+                    set_all(&entry.unwrap().path(), mtime);
+                }
+            } else {
+                filetime::set_file_mtime(path, mtime).unwrap();
+            }
+        }
+        let now = FileTime::from_system_time(SystemTime::now()).unix_seconds();
+        set_all(
+            &p.target_debug_dir(),
+            FileTime::from_unix_time(now - 3000, 0),
+        );
+        for entry in fs::read_dir(p.target_debug_dir().join("deps")).unwrap() {
+            let path = entry.unwrap().path();
+            if path
+                .file_name()
+                .unwrap()
+                .to_str()
+                .unwrap()
+                .starts_with("libbar")
+            {
+                set_all(&path, FileTime::from_unix_time(now - 2000, 0));
+            }
+        }
+        for path in ["src/main.rs", "bar/src/lib.rs"] {
+            set_all(
+                &p.root().join(path),
+                FileTime::from_unix_time(now - 1000, 0),
+            );
+        }
+    };
+    set_mtimes();
+
+    p.cargo("build -Zchecksum-freshness -v")
+        .masquerade_as_nightly_cargo()
+        .with_stderr_unordered(
+            "\
+[FRESH] bar v0.0.1 ([..])
+[FRESH] foo v0.0.1 ([..])
+[FINISHED] [..]
+",
+        )
+        .run();
+
+    // The unchanged sources aren't newer than the dep-info files anymore, so
+    // they aren't hashed again.
+    let source_mtime =
+        FileTime::from_last_modification_time(&fs::metadata(p.root().join("src/main.rs")).unwrap());
+    for entry in fs::read_dir(p.target_debug_dir().join(".fingerprint")).unwrap() {
+        for file in fs::read_dir(entry.unwrap().path()).unwrap() {
+            let file = file.unwrap();
+            if file.file_name().to_str().unwrap().starts_with("dep-") {
+                let mtime = FileTime::from_last_modification_time(&file.metadata().unwrap());
+                assert!(mtime >= source_mtime, "{:?}", file.path());
+            }
+        }
+    }
+
+    // Changing the contents of a dependency rebuilds it, and what depends on
+    // it.
+    p.change_file("bar/src/lib.rs", "pub fn bar() {} pub fn baz() {}");
+    p.cargo("build -Zchecksum-freshness")
+        .masquerade_as_nightly_cargo()
+        .with_stderr(
+            "\
+[COMPILING] bar v0.0.1 ([..])
+[COMPILING] foo v0.0.1 ([..])
+[FINISHED] [..]
+",
+        )
+        .run();
+
+    // Dep-info files written without checksums are rebuilt once.
+    set_mtimes();
+    p.cargo("build")
+        .with_stderr("[COMPILING] [..]\n[COMPILING] [..]\n[FINISHED] [..]\n")
+        .run();
+    p.cargo("build -Zchecksum-freshness")
+        .masquerade_as_nightly_cargo()
+        .with_stderr(
+            "\
+[COMPILING] bar v0.0.1 ([..])
+[COMPILING] foo v0.0.1 ([..])
+[FINISHED] [..]
+",
+        )
+        .run();
+    p.cargo("build -Zchecksum-freshness")
+        .masquerade_as_nightly_cargo()
+        .with_stderr("[FINISHED] [..]")
+        .run();
+}
+
+#[cargo_test]
+fn explain_rebuilds() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+
+                [dependencies]
+                bar = { path = "bar" }
+            "#,
+        )
+        .file(
+            "src/main.rs",
+            r#"fn main() { bar::bar(); println!("{:?}", option_env!("FOO")); }"#,
+        )
+        .file("bar/Cargo.toml", &basic_manifest("bar", "0.0.1"))
+        .file("bar/src/lib.rs", "pub fn bar() {}")
+        .build();
+
+    p.cargo("build --explain-rebuilds")
+        .masquerade_as_nightly_cargo()
+        .with_status(101)
+        .with_stderr_contains(
+            "error: the `--explain-rebuilds` flag is unstable, pass `-Z unstable-options` to enable it",
+        )
+        .run();
+
+    // Units built for the first time have nothing to explain.
+    p.cargo("build -Zunstable-options --explain-rebuilds")
+        .masquerade_as_nightly_cargo()
+        .with_stderr(
+            "\
+[COMPILING] bar v0.0.1 ([..])
+[COMPILING] foo v0.0.1 ([..])
+[FINISHED] [..]
+",
+        )
+        .run();
+
+    sleep_ms(1000);
+    p.change_file("bar/src/lib.rs", "pub fn bar() { }");
+    p.cargo("build -Zunstable-options --explain-rebuilds")
+        .masquerade_as_nightly_cargo()
+        .with_stderr_unordered(
+            "\
+[DIRTY] bar: file src/lib.rs changed
+[DIRTY] foo: dependency bar rebuilt
+[COMPILING] bar v0.0.1 ([..])
+[COMPILING] foo v0.0.1 ([..])
+[FINISHED] [..]
+",
+        )
+        .run();
+
+    p.cargo("build -Zunstable-options --explain-rebuilds")
+        .env("FOO", "1")
+        .masquerade_as_nightly_cargo()
+        .with_stderr(
+            "\
+[DIRTY] foo: env var FOO changed
+[COMPILING] foo v0.0.1 ([..])
+[FINISHED] [..]
+",
+        )
+        .run();
+
+    p.cargo("build -Zunstable-options --explain-rebuilds --message-format=json")
+        .env("FOO", "1")
+        .env("RUSTFLAGS", "-C debug-assertions")
+        .masquerade_as_nightly_cargo()
+        .with_json_contains_unordered(
+            r#"
+{
+  "reason": "unit-dirty",
+  "package_id": "bar 0.0.1 ([..])",
+  "manifest_path": "[..]/foo/bar/Cargo.toml",
+  "target": "{...}",
+  "message": "rustflags changed",
+  "dirty_reason": { "kind": "setting-changed", "setting": "rustflags" }
+}
+
+{
+  "reason": "unit-dirty",
+  "package_id": "foo 0.0.1 ([..])",
+  "manifest_path": "[..]/foo/Cargo.toml",
+  "target": "{...}",
+  "message": "rustflags changed",
+  "dirty_reason": { "kind": "setting-changed", "setting": "rustflags" }
+}
+"#,
+        )
+        .run();
+}
@@ -0,0 +1,351 @@
+//! Tests for cleaning the global cache with `cargo gc` and `-Z gc`.
+
+use cargo_test_support::registry::Package;
+use cargo_test_support::{basic_manifest, git, paths, project, Project};
+use filetime::FileTime;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// Returns the cache entries named `name` at `depth` levels below
+/// `CARGO_HOME/<dir>`.
+fn cache_entries(dir: &str, depth: usize, name: &str) -> Vec<PathBuf> {
+    fn walk(dir: &Path, depth: usize, name: &str, found: &mut Vec<PathBuf>) {
+        let entries = match fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(_) => return,
+        };
+        for entry in entries {
+            let path = entry.unwrap().path();
+            if depth > 1 {
+                walk(&path, depth - 1, name, found);
+            } else if path
+                .file_name()
+                .unwrap()
+                .to_str()
+                .unwrap()
+                .starts_with(name)
+            {
+                found.push(path);
+            }
+        }
+    }
+    let mut found = Vec::new();
+    walk(
+        &paths::home().join(".cargo").join(dir),
+        depth,
+        name,
+        &mut found,
+    );
+    found
+}
+
+/// Returns the `.crate` file and the extracted source of registry package
+/// `name`.
+fn registry_entries(name: &str) -> Vec<PathBuf> {
+    let mut entries = cache_entries("registry/cache", 2, name);
+    entries.extend(cache_entries("registry/src", 2, name));
+    entries
+}
+
+/// Makes it look like `paths` were last used `days` days ago.
+fn set_last_use(paths: &[PathBuf], days: u64) {
+    let time = SystemTime::now() - Duration::from_secs(days * 24 * 60 * 60);
+    for path in paths {
+        filetime::set_file_mtime(path, FileTime::from_system_time(time)).unwrap();
+    }
+}
+
+fn project_with_deps() -> Project {
+    Package::new("bar", "1.0.0").publish();
+    Package::new("baz", "1.0.0").publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+
+                [dependencies]
+                bar = "1.0"
+                baz = "1.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+    p.cargo("fetch").run();
+    assert_eq!(registry_entries("bar").len(), 2);
+    assert_eq!(registry_entries("baz").len(), 2);
+    p
+}
+
+#[cargo_test]
+fn gated() {
+    let p = project().file("src/lib.rs", "").build();
+
+    p.cargo("gc")
+        .masquerade_as_nightly_cargo()
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] the `cargo gc` command is unstable, pass `-Z unstable-options` to enable it
+See https://github.com/rust-lang/cargo/issues/12633 for more information about the `cargo gc` command.
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn max_age() {
+    let p = project_with_deps();
+    set_last_use(&registry_entries("bar"), 100);
+    set_last_use(&registry_entries("baz"), 10);
+
+    p.cargo("gc -Zunstable-options --max-age 30days -v")
+        .masquerade_as_nightly_cargo()
+        .with_stderr(
+            "\
+[REMOVING] [..]/registry/cache/[..]/bar-1.0.0.crate
+[REMOVING] [..]/registry/src/[..]/bar-1.0.0
+[REMOVED] 2 cache entries, [..]
+",
+        )
+        .run();
+
+    assert!(registry_entries("bar").is_empty());
+    assert_eq!(registry_entries("baz").len(), 2);
+
+    // The removed package is downloaded again when it's needed.
+    p.cargo("build")
+        .with_stderr(
+            "\
+[DOWNLOADING] crates ...
+[DOWNLOADED] bar v1.0.0 (registry `dummy-registry`)
+[COMPILING] [..]
+[COMPILING] [..]
+[COMPILING] foo v0.1.0 ([CWD])
+[FINISHED] [..]
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn use_is_recorded() {
+    let p = project_with_deps();
+    set_last_use(&registry_entries("bar"), 100);
+    set_last_use(&registry_entries("baz"), 100);
+
+    p.cargo("build").run();
+
+    p.cargo("gc -Zunstable-options --max-age 30days")
+        .masquerade_as_nightly_cargo()
+        .with_stderr("[REMOVED] 0 cache entries, 0 B")
+        .run();
+    assert_eq!(registry_entries("bar").len(), 2);
+    assert_eq!(registry_entries("baz").len(), 2);
+}
+
+#[cargo_test]
+fn max_size() {
+    let p = project_with_deps();
+    set_last_use(&registry_entries("bar"), 5);
+    set_last_use(&registry_entries("baz"), 2);
+
+    p.cargo("gc -Zunstable-options --max-size 0 --dry-run -v")
+        .masquerade_as_nightly_cargo()
+        .with_stderr(
+            "\
+[REMOVING] [..]/bar-1.0.0[..]
+[REMOVING] [..]/bar-1.0.0[..]
+[REMOVING] [..]/baz-1.0.0[..]
+[REMOVING] [..]/baz-1.0.0[..]
+[SUMMARY] 4 cache entries, [..] would be removed
+[WARNING] no files deleted due to --dry-run
+",
+        )
+        .run();
+    assert_eq!(registry_entries("bar").len(), 2);
+    assert_eq!(registry_entries("baz").len(), 2);
+
+    p.cargo("gc -Zunstable-options --max-size 1GiB")
+        .masquerade_as_nightly_cargo()
+        .with_stderr("[REMOVED] 0 cache entries, 0 B")
+        .run();
+
+    p.cargo("gc -Zunstable-options --max-size 0")
+        .masquerade_as_nightly_cargo()
+        .with_stderr("[REMOVED] 4 cache entries, [..]")
+        .run();
+    assert!(registry_entries("bar").is_empty());
+    assert!(registry_entries("baz").is_empty());
+}
+
+#[cargo_test]
+fn max_size_keeps_recently_used() {
+    let p = project_with_deps();
+    set_last_use(&registry_entries("bar"), 5);
+
+    // `baz` was just used, so a concurrent build may still be reading it.
+    p.cargo("gc -Zunstable-options --max-size 0")
+        .masquerade_as_nightly_cargo()
+        .with_stderr("[REMOVED] 2 cache entries, [..]")
+        .run();
+    assert!(registry_entries("bar").is_empty());
+    assert_eq!(registry_entries("baz").len(), 2);
+}
+
+#[cargo_test]
+fn git_entries() {
+    let git_project = git::new("dep", |project| {
+        project
+            .file("Cargo.toml", &basic_manifest("dep", "0.5.0"))
+            .file("src/lib.rs", "")
+    });
+    let p = project()
+        .file(
+            "Cargo.toml",
+            &format!(
+                r#"
+                    [package]
+                    name = "foo"
+                    version = "0.1.0"
+
+                    [dependencies]
+                    dep = {{ git = '{}' }}
+                "#,
+                git_project.url()
+            ),
+        )
+        .file("src/lib.rs", "")
+        .build();
+    p.cargo("fetch").run();
+
+    let mut entries = cache_entries("git/db", 1, "dep-");
+    entries.extend(cache_entries("git/checkouts", 2, ""));
+    assert_eq!(entries.len(), 2);
+
+    p.cargo("gc -Zunstable-options --max-age 30days")
+        .masquerade_as_nightly_cargo()
+        .with_stderr("[REMOVED] 0 cache entries, 0 B")
+        .run();
+
+    set_last_use(&entries, 40);
+    p.cargo("gc -Zunstable-options --max-age 30days")
+        .masquerade_as_nightly_cargo()
+        .with_stderr("[REMOVED] 2 cache entries, [..]")
+        .run();
+    assert!(entries.iter().all(|path| !path.exists()));
+}
+
+#[cargo_test]
+fn invalid_args() {
+    let p = project().file("src/lib.rs", "").build();
+
+    p.cargo("gc -Zunstable-options --max-age soon")
+        .masquerade_as_nightly_cargo()
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] expected a duration like \"30 days\" or \"6 months\", found `soon`
+
+Caused by:
+  [..]
+",
+        )
+        .run();
+
+    p.cargo("gc -Zunstable-options --max-size lots")
+        .masquerade_as_nightly_cargo()
+        .with_status(101)
+        .with_stderr("[ERROR] expected a size like \"500 MB\" or \"10 GiB\", found `lots`")
+        .run();
+}
+
+#[cargo_test]
+fn auto_gc() {
+    let p = project_with_deps();
+    p.change_file(
+        "Cargo.toml",
+        r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+
+            [dependencies]
+            baz = "1.0"
+        "#,
+    );
+    p.change_file(
+        ".cargo/config.toml",
+        r#"
+            [gc.auto]
+            max-age = "30 days"
+        "#,
+    );
+    set_last_use(&registry_entries("bar"), 100);
+    set_last_use(&registry_entries("baz"), 100);
+
+    // Without `-Z gc` nothing is cleaned.
+    p.cargo("build").run();
+    assert_eq!(registry_entries("bar").len(), 2);
+
+    p.cargo("build -Zgc")
+        .masquerade_as_nightly_cargo()
+        .with_stderr(
+            "\
+[FINISHED] [..]
+[REMOVED] 2 cache entries, [..]
+",
+        )
+        .run();
+    assert!(registry_entries("bar").is_empty());
+    assert_eq!(registry_entries("baz").len(), 2);
+
+    // Commands that don't download anything don't clean the cache.
+    set_last_use(&registry_entries("baz"), 100);
+    set_last_use(&[paths::home().join(".cargo/.last-gc")], 2);
+    p.cargo("metadata -Zgc --format-version=1")
+        .masquerade_as_nightly_cargo()
+        .with_stderr("")
+        .run();
+    assert_eq!(registry_entries("baz").len(), 2);
+    set_last_use(&[paths::home().join(".cargo/.last-gc")], 0);
+
+    // It isn't run again until `gc.auto.frequency` has passed.
+    let other = project()
+        .at("other")
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "other"
+                version = "0.1.0"
+
+                [dependencies]
+                bar = "1.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+    other.cargo("fetch").run();
+    set_last_use(&registry_entries("bar"), 100);
+    p.cargo("build -Zgc")
+        .masquerade_as_nightly_cargo()
+        .with_stderr("[FINISHED] [..]")
+        .run();
+    assert_eq!(registry_entries("bar").len(), 2);
+
+    set_last_use(&[paths::home().join(".cargo/.last-gc")], 2);
+    p.cargo("build -Zgc")
+        .masquerade_as_nightly_cargo()
+        .with_stderr(
+            "\
+[FINISHED] [..]
+[REMOVED] 2 cache entries, [..]
+",
+        )
+        .run();
+    assert!(registry_entries("bar").is_empty());
+}
@@ -0,0 +1,475 @@
+//! Tests for inheriting Cargo.toml fields with `{ workspace = true }`.
+
+use cargo_test_support::publish::validate_crate_contents;
+use cargo_test_support::registry::Package;
+use cargo_test_support::{basic_lib_manifest, project};
+use std::fs::File;
+
+#[cargo_test]
+fn gated_workspace_tables() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [workspace]
+                members = ["bar"]
+
+                [workspace.package]
+                version = "1.2.3"
+            "#,
+        )
+        .file("bar/Cargo.toml", &basic_lib_manifest("bar"))
+        .file("bar/src/lib.rs", "")
+        .build();
+
+    p.cargo("build")
+        .masquerade_as_nightly_cargo()
+        .with_status(101)
+        .with_stderr_contains("[..]feature `workspace-inheritance` is required")
+        .run();
+}
+
+#[cargo_test]
+fn gated_member() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                cargo-features = ["workspace-inheritance"]
+
+                [workspace]
+                members = ["bar"]
+
+                [workspace.package]
+                version = "1.2.3"
+            "#,
+        )
+        .file(
+            "bar/Cargo.toml",
+            r#"
+                [package]
+                name = "bar"
+                version.workspace = true
+            "#,
+        )
+        .file("bar/src/lib.rs", "")
+        .build();
+
+    p.cargo("build")
+        .masquerade_as_nightly_cargo()
+        .with_status(101)
+        .with_stderr_contains("[..]feature `workspace-inheritance` is required")
+        .run();
+}
+
+#[cargo_test]
+fn inherit_own_workspace_fields() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                cargo-features = ["workspace-inheritance"]
+
+                [package]
+                name = "foo"
+                version.workspace = true
+                edition.workspace = true
+                description.workspace = true
+
+                [workspace]
+
+                [workspace.package]
+                version = "1.2.3"
+                edition = "2018"
+                description = "This is a crate"
+            "#,
+        )
+        .file(
+            "src/lib.rs",
+            r#"
+                pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+                pub const DESCRIPTION: &str = env!("CARGO_PKG_DESCRIPTION");
+            "#,
+        )
+        .build();
+
+    p.cargo("build -v")
+        .masquerade_as_nightly_cargo()
+        .with_stderr(
+            "\
+[COMPILING] foo v1.2.3 ([CWD])
+[RUNNING] `rustc --crate-name foo --edition=2018 src/lib.rs [..]`
+[FINISHED] [..]
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn inherit_package_fields() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                cargo-features = ["workspace-inheritance"]
+
+                [workspace]
+                members = ["crates/*"]
+
+                [workspace.package]
+                version = "1.2.3"
+                authors = ["Rustaceans"]
+                description = "This is a crate"
+                documentation = "https://www.rust-lang.org/learn"
+                homepage = "https://www.rust-lang.org"
+                repository = "https://github.com/example/example"
+                license-file = "LICENSE"
+                keywords = ["cli"]
+                categories = ["development-tools"]
+                edition = "2018"
+                exclude = ["foo.txt"]
+            "#,
+        )
+        .file(
+            "crates/bar/Cargo.toml",
+            r#"
+                cargo-features = ["workspace-inheritance"]
+
+                [package]
+                name = "bar"
+                version.workspace = true
+                authors.workspace = true
+                description.workspace = true
+                documentation.workspace = true
+                homepage.workspace = true
+                repository.workspace = true
+                license-file.workspace = true
+                keywords.workspace = true
+                categories.workspace = true
+                edition.workspace = true
+                exclude.workspace = true
+            "#,
+        )
+        .file("LICENSE", "license")
+        .file("crates/bar/src/lib.rs", "")
+        .file("crates/bar/foo.txt", "")
+        .build();
+
+    p.cargo("package --no-verify")
+        .masquerade_as_nightly_cargo()
+        .cwd("crates/bar")
+        .run();
+
+    let f = File::open(&p.root().join("target/package/bar-1.2.3.crate")).unwrap();
+    let rewritten_toml = format!(
+        r#"{}
+cargo-features = ["workspace-inheritance"]
+
+[package]
+edition = "2018"
+name = "bar"
+version = "1.2.3"
+authors = ["Rustaceans"]
+exclude = ["foo.txt"]
+description = "This is a crate"
+homepage = "https://www.rust-lang.org"
+documentation = "https://www.rust-lang.org/learn"
+keywords = ["cli"]
+categories = ["development-tools"]
+license-file = "LICENSE"
+repository = "https://github.com/example/example"
+"#,
+        cargo::core::package::MANIFEST_PREAMBLE
+    );
+    validate_crate_contents(
+        f,
+        "bar-1.2.3.crate",
+        &["Cargo.toml", "Cargo.toml.orig", "src/lib.rs", "LICENSE"],
+        &[("Cargo.toml", &rewritten_toml)],
+    );
+}
+
+#[cargo_test]
+fn inherit_dependencies() {
+    Package::new("dep", "0.1.2").feature("fancy", &[]).publish();
+    Package::new("dep-build", "0.8.2").publish();
+    Package::new("dep-dev", "0.5.2").publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                cargo-features = ["workspace-inheritance"]
+
+                [workspace]
+                members = ["bar"]
+
+                [workspace.dependencies]
+                dep = { version = "0.1", default-features = false }
+                dep-build = "0.8"
+                dep-dev = "0.5.2"
+                baz = { path = "baz", version = "0.5" }
+            "#,
+        )
+        .file(
+            "bar/Cargo.toml",
+            r#"
+                cargo-features = ["workspace-inheritance"]
+
+                [package]
+                name = "bar"
+                version = "0.2.0"
+
+                [features]
+                extra = ["dep-build"]
+
+                [dependencies]
+                dep = { workspace = true, features = ["fancy"] }
+                baz.workspace = true
+
+                [build-dependencies]
+                dep-build = { workspace = true, optional = true }
+
+                [target.'cfg(unix)'.dev-dependencies]
+                dep-dev.workspace = true
+            "#,
+        )
+        .file("bar/src/lib.rs", "")
+        .file("baz/Cargo.toml", &basic_lib_manifest("baz"))
+        .file("baz/src/lib.rs", "")
+        .build();
+
+    p.cargo("tree -p bar -e normal,build -f {p}/{f}")
+        .masquerade_as_nightly_cargo()
+        .with_stdout(
+            "\
+bar v0.2.0 ([CWD]/bar)/
+├── baz v0.5.0 ([CWD]/baz)/
+└── dep v0.1.2/fancy
+",
+        )
+        .run();
+
+    p.cargo("package --no-verify -p bar")
+        .masquerade_as_nightly_cargo()
+        .run();
+
+    let f = File::open(&p.root().join("target/package/bar-0.2.0.crate")).unwrap();
+    let rewritten_toml = format!(
+        r#"{}
+cargo-features = ["workspace-inheritance"]
+
+[package]
+name = "bar"
+version = "0.2.0"
+[dependencies.baz]
+version = "0.5"
+
+[dependencies.dep]
+version = "0.1"
+features = ["fancy"]
+default-features = false
+[build-dependencies.dep-build]
+version = "0.8"
+optional = true
+
+[features]
+extra = ["dep-build"]
+[target."cfg(unix)".dev-dependencies.dep-dev]
+version = "0.5.2"
+"#,
+        cargo::core::package::MANIFEST_PREAMBLE
+    );
+    validate_crate_contents(
+        f,
+        "bar-0.2.0.crate",
+        &["Cargo.toml", "Cargo.toml.orig", "src/lib.rs"],
+        &[("Cargo.toml", &rewritten_toml)],
+    );
+}
+
+#[cargo_test]
+fn error_workspace_false() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                cargo-features = ["workspace-inheritance"]
+
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                description = { workspace = false }
+
+                [workspace]
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("build")
+        .masquerade_as_nightly_cargo()
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] failed to parse manifest at `[CWD]/Cargo.toml`
+
+Caused by:
+  `description.workspace` cannot be false
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn error_no_root_workspace() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                cargo-features = ["workspace-inheritance"]
+
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                description.workspace = true
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("build")
+        .masquerade_as_nightly_cargo()
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] failed to parse manifest at `[CWD]/Cargo.toml`
+
+Caused by:
+  error inheriting `description` from workspace root manifest's `workspace.package.description`
+
+Caused by:
+  failed to find a workspace root
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn error_field_not_defined() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                cargo-features = ["workspace-inheritance"]
+
+                [workspace]
+                members = ["bar"]
+
+                [workspace.package]
+                version = "1.2.3"
+            "#,
+        )
+        .file(
+            "bar/Cargo.toml",
+            r#"
+                cargo-features = ["workspace-inheritance"]
+
+                [package]
+                name = "bar"
+                version.workspace = true
+                license.workspace = true
+            "#,
+        )
+        .file("bar/src/lib.rs", "")
+        .build();
+
+    p.cargo("build")
+        .masquerade_as_nightly_cargo()
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] failed to load manifest for workspace member `[CWD]/bar`
+
+Caused by:
+  failed to parse manifest at `[CWD]/bar/Cargo.toml`
+
+Caused by:
+  error inheriting `license` from workspace root manifest's `workspace.package.license`
+
+Caused by:
+  `workspace.package.license` was not defined
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn error_dependency_not_defined_or_extra_keys() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                cargo-features = ["workspace-inheritance"]
+
+                [package]
+                name = "foo"
+                version = "0.1.0"
+
+                [dependencies]
+                dep.workspace = true
+
+                [workspace]
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("build")
+        .masquerade_as_nightly_cargo()
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] failed to parse manifest at `[CWD]/Cargo.toml`
+
+Caused by:
+  error inheriting `dep` from workspace root manifest's `workspace.dependencies.dep`
+
+Caused by:
+  `workspace.dependencies.dep` was not defined
+",
+        )
+        .run();
+
+    p.change_file(
+        "Cargo.toml",
+        r#"
+            cargo-features = ["workspace-inheritance"]
+
+            [package]
+            name = "foo"
+            version = "0.1.0"
+
+            [dependencies]
+            dep = { workspace = true, version = "0.2" }
+
+            [workspace.dependencies]
+            dep = "0.1"
+        "#,
+    );
+
+    p.cargo("build")
+        .masquerade_as_nightly_cargo()
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] failed to parse manifest at `[CWD]/Cargo.toml`
+
+Caused by:
+  error inheriting `dep` from workspace root manifest's `workspace.dependencies.dep`
+
+Caused by:
+  `version` cannot be specified alongside `workspace = true`, only `features` and `optional` can
+",
+        )
+        .run();
+}
@@ -0,0 +1,324 @@
+//! Tests for the `[lints]` table.
+
+use cargo_test_support::project;
+use cargo_test_support::registry::Package;
+
+#[cargo_test]
+fn gated() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+
+                [lints.rust]
+                unsafe_code = "forbid"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("check")
+        .masquerade_as_nightly_cargo()
+        .with_status(101)
+        .with_stderr_contains("[..]feature `lints` is required")
+        .run();
+}
+
+#[cargo_test]
+fn rustc_lint_levels() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                cargo-features = ["lints"]
+
+                [package]
+                name = "foo"
+                version = "0.1.0"
+
+                [lints.rust]
+                unsafe_code = "deny"
+                unused = { level = "deny", priority = -1 }
+                unused_variables = "allow"
+
+                [lints.clippy]
+                pedantic = { level = "warn", priority = -1 }
+
+                [lints.rustdoc]
+                broken_intra_doc_links = "allow"
+            "#,
+        )
+        .file("src/lib.rs", "pub fn foo() { let x = 1; }")
+        .build();
+
+    p.cargo("check -v")
+        .masquerade_as_nightly_cargo()
+        .with_stderr(
+            "\
+[CHECKING] foo v0.1.0 ([CWD])
+[RUNNING] `rustc --crate-name foo [..]-Wclippy::pedantic[..] -Dunused \
+[..]-Arustdoc::broken_intra_doc_links[..] -Dunsafe_code -Aunused_variables [..]`
+[FINISHED] [..]
+",
+        )
+        .run();
+
+    p.change_file("src/lib.rs", "pub fn foo() { unsafe {} }");
+    p.cargo("check")
+        .masquerade_as_nightly_cargo()
+        .with_status(101)
+        .with_stderr_contains("error: usage of an `unsafe` block")
+        .run();
+}
+
+#[cargo_test]
+fn rustdoc_lint_levels() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                cargo-features = ["lints"]
+
+                [package]
+                name = "foo"
+                version = "0.1.0"
+
+                [lints.rustdoc]
+                broken_intra_doc_links = "deny"
+            "#,
+        )
+        .file("src/lib.rs", "/// [`Missing`]\npub fn foo() {}")
+        .build();
+
+    p.cargo("doc -v")
+        .masquerade_as_nightly_cargo()
+        .with_status(101)
+        .with_stderr_contains("[RUNNING] `rustdoc [..]-Drustdoc::broken_intra_doc_links[..]`")
+        .with_stderr_contains("error: unresolved link to `Missing`")
+        .run();
+}
+
+#[cargo_test]
+fn invalid_tool() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                cargo-features = ["lints"]
+
+                [package]
+                name = "foo"
+                version = "0.1.0"
+
+                [lints.rustfmt]
+                max_width = "deny"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("check")
+        .masquerade_as_nightly_cargo()
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] failed to parse manifest at `[CWD]/Cargo.toml`
+
+Caused by:
+  unsupported tool `lints.rustfmt`, expected one of `rust`, `clippy` or `rustdoc`
+",
+        )
+        .run();
+
+    p.change_file(
+        "Cargo.toml",
+        r#"
+            cargo-features = ["lints"]
+
+            [package]
+            name = "foo"
+            version = "0.1.0"
+
+            [lints.rust]
+            "clippy::pedantic" = "deny"
+        "#,
+    );
+    p.cargo("check")
+        .masquerade_as_nightly_cargo()
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] failed to parse manifest at `[CWD]/Cargo.toml`
+
+Caused by:
+  `lints.rust.clippy::pedantic` must not contain a tool prefix, \
+use `lints.<tool>.<name>` instead
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn inherit_from_workspace() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                cargo-features = ["lints"]
+
+                [workspace]
+                members = ["bar"]
+
+                [workspace.lints.rust]
+                unsafe_code = "deny"
+            "#,
+        )
+        .file(
+            "bar/Cargo.toml",
+            r#"
+                cargo-features = ["lints"]
+
+                [package]
+                name = "bar"
+                version = "0.1.0"
+
+                [lints]
+                workspace = true
+            "#,
+        )
+        .file("bar/src/lib.rs", "pub fn bar() { unsafe {} }")
+        .build();
+
+    p.cargo("check -v")
+        .masquerade_as_nightly_cargo()
+        .with_status(101)
+        .with_stderr_contains("[RUNNING] `rustc --crate-name bar [..]-Dunsafe_code[..]`")
+        .with_stderr_contains("error: usage of an `unsafe` block")
+        .run();
+
+    p.change_file(
+        "Cargo.toml",
+        r#"
+            cargo-features = ["lints"]
+
+            [workspace]
+            members = ["bar"]
+        "#,
+    );
+    p.cargo("check")
+        .masquerade_as_nightly_cargo()
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] failed to load manifest for workspace member `[CWD]/bar`
+
+Caused by:
+  failed to parse manifest at `[CWD]/bar/Cargo.toml`
+
+Caused by:
+  error inheriting `lints` from workspace root manifest's `workspace.lints`
+
+Caused by:
+  `workspace.lints` was not defined
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn not_applied_to_registry_deps() {
+    Package::new("bar", "0.1.0")
+        .file(
+            "Cargo.toml",
+            r#"
+                cargo-features = ["lints"]
+
+                [package]
+                name = "bar"
+                version = "0.1.0"
+
+                [lints.rust]
+                unsafe_code = "forbid"
+            "#,
+        )
+        .file("src/lib.rs", "pub fn bar() { unsafe {} }")
+        .publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+
+                [dependencies]
+                bar = "0.1"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("check -v")
+        .masquerade_as_nightly_cargo()
+        .with_stderr_contains("[RUNNING] `rustc --crate-name bar [..]--cap-lints allow`")
+        .with_stderr_does_not_contain("[..]unsafe_code[..]")
+        .run();
+}
+
+#[cargo_test]
+fn changing_lints_rebuilds() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                cargo-features = ["lints"]
+
+                [package]
+                name = "foo"
+                version = "0.1.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("check")
+        .masquerade_as_nightly_cargo()
+        .with_stderr(
+            "\
+[CHECKING] foo v0.1.0 ([CWD])
+[FINISHED] [..]
+",
+        )
+        .run();
+
+    p.change_file(
+        "Cargo.toml",
+        r#"
+            cargo-features = ["lints"]
+
+            [package]
+            name = "foo"
+            version = "0.1.0"
+
+            [lints.rust]
+            unsafe_code = "deny"
+        "#,
+    );
+    p.cargo("check")
+        .masquerade_as_nightly_cargo()
+        .with_stderr(
+            "\
+[CHECKING] foo v0.1.0 ([CWD])
+[FINISHED] [..]
+",
+        )
+        .run();
+    p.cargo("check")
+        .masquerade_as_nightly_cargo()
+        .with_stderr("[FINISHED] [..]")
+        .run();
+}
@@ -691,6 +691,270 @@ dependencies = [
     assert_match_exact(&lockfile, &lock);
 }
 
+#[cargo_test]
+fn v4_and_git() {
+    let (git_project, repo) = git::new_repo("dep1", |project| {
+        project
+            .file("Cargo.toml", &basic_lib_manifest("dep1"))
+            .file("src/lib.rs", "")
+    });
+    let head_id = repo.head().unwrap().target().unwrap();
+
+    let lockfile = format!(
+        r#"# This file is automatically @generated by Cargo.
+# It is not intended for manual editing.
+version = 4
+
+[[package]]
+name = "dep1"
+version = "0.5.0"
+source = "git+{}?branch=master"
+rev = "{}"
+
+[[package]]
+name = "foo"
+version = "0.0.1"
+dependencies = [
+ "dep1",
+]
+"#,
+        git_project.url(),
+        head_id,
+    );
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            &format!(
+                r#"
+                    [project]
+                    name = "foo"
+                    version = "0.0.1"
+                    authors = []
+
+                    [dependencies]
+                    dep1 = {{ git = '{}', branch = 'master' }}
+                "#,
+                git_project.url(),
+            ),
+        )
+        .file("src/lib.rs", "")
+        .file("Cargo.lock", "version = 4")
+        .build();
+
+    p.cargo("fetch").run();
+
+    let lock = p.read_lockfile();
+    assert_match_exact(&lockfile, &lock);
+
+    // The revision is read back from its own field.
+    p.cargo("fetch --locked").run();
+}
+
+#[cargo_test]
+fn next_lockfile_bump() {
+    Package::new("bar", "0.1.0").publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [project]
+                name = "foo"
+                version = "0.0.1"
+                authors = []
+
+                [dependencies]
+                bar = "0.1.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("generate-lockfile").run();
+    assert!(p.read_lockfile().contains("\nversion = 3\n"));
+
+    p.cargo("generate-lockfile -Z next-lockfile-bump")
+        .masquerade_as_nightly_cargo()
+        .run();
+    assert!(p.read_lockfile().contains("\nversion = 4\n"));
+
+    // Once bumped, the format is preserved.
+    p.cargo("update").run();
+    assert!(p.read_lockfile().contains("\nversion = 4\n"));
+}
+
+#[cargo_test]
+fn merge_conflicts_are_resolved() {
+    let bar = Package::new("bar", "0.1.0").publish();
+    let baz = Package::new("baz", "0.1.0").publish();
+    Package::new("bar", "0.1.1").publish();
+    Package::new("baz", "0.1.1").publish();
+
+    let conflicted = format!(
+        r#"# This file is automatically @generated by Cargo.
+# It is not intended for manual editing.
+version = 3
+
+<<<<<<< HEAD
+[[package]]
+name = "bar"
+version = "0.1.0"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+checksum = "{bar}"
+||||||| merged common ancestors
+=======
+[[package]]
+name = "baz"
+version = "0.1.0"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+checksum = "{baz}"
+>>>>>>> feature
+
+[[package]]
+name = "foo"
+version = "0.0.1"
+dependencies = [
+<<<<<<< HEAD
+ "bar",
+=======
+ "baz",
+>>>>>>> feature
+]
+"#
+    );
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [project]
+                name = "foo"
+                version = "0.0.1"
+                authors = []
+
+                [dependencies]
+                bar = "0.1.0"
+                baz = "0.1.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file("Cargo.lock", &conflicted)
+        .build();
+
+    // Nothing can be written with `--locked`.
+    p.cargo("fetch --locked")
+        .with_status(101)
+        .with_stderr_contains(
+            "[WARNING] `[CWD]/Cargo.lock` has merge conflicts, resolving them from both sides",
+        )
+        .with_stderr_contains("[ERROR] the lock file [CWD]/Cargo.lock needs to be updated[..]")
+        .run();
+
+    p.cargo("fetch")
+        .with_stderr_contains(
+            "[WARNING] `[CWD]/Cargo.lock` has merge conflicts, resolving them from both sides",
+        )
+        .run();
+
+    // Both sides keep the versions they had locked.
+    let lockfile = format!(
+        r#"# This file is automatically @generated by Cargo.
+# It is not intended for manual editing.
+version = 3
+
+[[package]]
+name = "bar"
+version = "0.1.0"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+checksum = "{bar}"
+
+[[package]]
+name = "baz"
+version = "0.1.0"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+checksum = "{baz}"
+
+[[package]]
+name = "foo"
+version = "0.0.1"
+dependencies = [
+ "bar",
+ "baz",
+]
+"#
+    );
+    assert_match_exact(&lockfile, &p.read_lockfile());
+
+    p.cargo("fetch")
+        .with_stderr_does_not_contain("[..]merge conflicts[..]")
+        .run();
+}
+
+#[cargo_test]
+fn merge_conflicts_with_different_checksums() {
+    Package::new("bar", "0.1.0").publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [project]
+                name = "foo"
+                version = "0.0.1"
+                authors = []
+
+                [dependencies]
+                bar = "0.1.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file(
+            "Cargo.lock",
+            r#"
+version = 3
+
+[[package]]
+name = "bar"
+version = "0.1.0"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+<<<<<<< HEAD
+checksum = "aaaa"
+=======
+checksum = "bbbb"
+>>>>>>> feature
+
+[[package]]
+name = "foo"
+version = "0.0.1"
+dependencies = [
+ "bar",
+]
+"#,
+        )
+        .build();
+
+    p.cargo("fetch")
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] failed to parse lock file at: [CWD]/Cargo.lock
+
+Caused by:
+  checksum for `bar v0.1.0` changed between lock files
+
+  this could be indicative of a few possible errors:
+
+      * the lock file is corrupt
+      * a replacement source in use (e.g., a mirror) returned a different checksum
+      * the source itself may be corrupt in one way or another
+
+  unable to verify that `bar v0.1.0` is the same as when the lockfile was generated
+",
+        )
+        .run();
+}
+
 #[cargo_test]
 fn lock_from_the_future() {
     let p = project()
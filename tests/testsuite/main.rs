@@ -10,24 +10,32 @@ extern crate cargo_test_macro;
 
 mod advanced_env;
 mod alt_registry;
+mod artifact_dep;
 mod bad_config;
 mod bad_manifest_path;
 mod bench;
 mod binary_name;
 mod build;
+mod build_cache;
+mod build_dir;
 mod build_plan;
 mod build_script;
 mod build_script_env;
 mod build_script_extra_link_arg;
+mod build_script_sandbox;
 mod cache_messages;
+mod cargo_add;
 mod cargo_alias_config;
 mod cargo_command;
 mod cargo_config;
 mod cargo_env_config;
 mod cargo_features;
+mod cargo_info;
+mod cargo_remove;
 mod cargo_targets;
 mod cfg;
 mod check;
+mod check_cfg;
 mod clean;
 mod collisions;
 mod concurrent;
@@ -52,6 +60,7 @@ mod fetch;
 mod fix;
 mod freshness;
 mod future_incompat_report;
+mod gc;
 mod generate_lockfile;
 mod git;
 mod git_auth;
@@ -59,9 +68,11 @@ mod git_gc;
 mod glob_targets;
 mod help;
 mod init;
+mod inheritable_workspace_fields;
 mod install;
 mod install_upgrade;
 mod jobserver;
+mod lints;
 mod list_availables;
 mod local_registry;
 mod locate_project;
@@ -95,6 +106,7 @@ mod profile_config;
 mod profile_custom;
 mod profile_overrides;
 mod profile_targets;
+mod profile_trim_paths;
 mod profiles;
 mod progress;
 mod pub_priv;
@@ -104,6 +116,7 @@ mod read_manifest;
 mod registry;
 mod rename_deps;
 mod replace;
+mod report_licenses;
 mod required_features;
 mod run;
 mod rust_version;
@@ -113,8 +126,10 @@ mod rustdoc;
 mod rustdoc_extern_html;
 mod rustdocflags;
 mod rustflags;
+mod sbom;
 mod search;
 mod shell_quoting;
+mod sparse_registry;
 mod standard_lib;
 mod test;
 mod timings;
@@ -1,5 +1,6 @@
 //! Tests for --message-format flag.
 
+use cargo_test_support::registry::Package;
 use cargo_test_support::{basic_lib_manifest, basic_manifest, project};
 
 #[cargo_test]
@@ -131,3 +132,87 @@ fn cargo_renders_doctests() {
         .with_stdout_contains("[..]src/lib.rs - bar (line 1)[..]")
         .run();
 }
+
+#[cargo_test]
+fn resolve_error_json() {
+    Package::new("dep", "1.0.0").publish();
+    Package::new("dep", "1.0.1").publish();
+    Package::new("bar", "0.1.0").dep("dep", "=1.0.1").publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+
+                [dependencies]
+                bar = "0.1"
+                dep = "=1.0.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("check --message-format json")
+        .with_status(101)
+        .with_json(
+            r#"
+                {
+                    "reason": "resolve-error",
+                    "message": "{...}",
+                    "dependency": "dep",
+                    "requirement": "=1.0.1",
+                    "required_by": "bar 0.1.0 (registry+https://github.com/rust-lang/crates.io-index)",
+                    "candidates": ["1.0.1"],
+                    "available": [],
+                    "conflicts": [
+                        {
+                            "package_id": "dep 1.0.0 (registry+https://github.com/rust-lang/crates.io-index)",
+                            "reason": {"kind": "semver"}
+                        }
+                    ],
+                    "package_path": [
+                        "bar 0.1.0 (registry+https://github.com/rust-lang/crates.io-index)",
+                        "foo 0.1.0 (path+file:///[..]/foo)"
+                    ]
+                }
+            "#,
+        )
+        .with_stderr_contains("[ERROR] failed to select a version for `dep`.")
+        .run();
+
+    p.change_file(
+        "Cargo.toml",
+        r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+
+            [dependencies]
+            dep = "2.0"
+        "#,
+    );
+    p.cargo("check --message-format json")
+        .with_status(101)
+        .with_json(
+            r#"
+                {
+                    "reason": "resolve-error",
+                    "message": "{...}",
+                    "dependency": "dep",
+                    "requirement": "^2.0",
+                    "required_by": "foo 0.1.0 (path+file:///[..]/foo)",
+                    "candidates": [],
+                    "available": ["1.0.1", "1.0.0"],
+                    "conflicts": [],
+                    "package_path": ["foo 0.1.0 (path+file:///[..]/foo)"]
+                }
+            "#,
+        )
+        .run();
+
+    // Only emitted with `--message-format json`.
+    p.cargo("check").with_status(101).with_stdout("").run();
+}
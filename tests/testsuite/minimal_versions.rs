@@ -36,3 +36,133 @@ fn minimal_version_cli() {
 
     assert!(!lock.contains("1.1.0"));
 }
+
+#[cargo_test]
+fn direct_minimal_versions() {
+    Package::new("direct", "1.0.0").publish();
+    Package::new("direct", "1.1.0").publish();
+    Package::new("indirect", "2.0.0").publish();
+    Package::new("indirect", "2.1.0").publish();
+    Package::new("direct", "1.2.0")
+        .dep("indirect", "2.0")
+        .publish();
+    Package::new("other", "0.1.0")
+        .dep("indirect", "2.0")
+        .publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+
+                [dependencies]
+                direct = "1.0"
+                other = "0.1"
+            "#,
+        )
+        .file("src/main.rs", "fn main() {}")
+        .build();
+
+    p.cargo("generate-lockfile -Zdirect-minimal-versions")
+        .masquerade_as_nightly_cargo()
+        .run();
+
+    let lock = p.read_lockfile();
+    assert!(lock.contains("name = \"direct\"\nversion = \"1.0.0\""));
+    // Only direct dependencies are resolved to their minimum.
+    assert!(lock.contains("name = \"indirect\"\nversion = \"2.1.0\""));
+
+    p.cargo("generate-lockfile -Zdirect-minimal-versions -Zminimal-versions")
+        .masquerade_as_nightly_cargo()
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] `-Z minimal-versions` and `-Z direct-minimal-versions` are mutually exclusive
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn direct_minimal_versions_report() {
+    Package::new("dep", "1.0.0")
+        .file("src/lib.rs", "this does not compile")
+        .publish();
+    Package::new("dep", "1.0.1").publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+
+                [dependencies]
+                dep = "1.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("check -Zdirect-minimal-versions")
+        .masquerade_as_nightly_cargo()
+        .with_status(101)
+        .with_stderr_contains(
+            "\
+[NOTE] the minimum versions of these direct dependencies failed to compile:
+  dep v1.0.0 (`dep = \"^1.0\"` in foo v0.0.1 ([CWD]))
+consider raising their lower bounds in `Cargo.toml`
+",
+        )
+        .run();
+
+    p.cargo("update -p dep").masquerade_as_nightly_cargo().run();
+    p.cargo("check -Zdirect-minimal-versions")
+        .masquerade_as_nightly_cargo()
+        .run();
+}
+
+#[cargo_test]
+fn direct_minimal_versions_report_transitive() {
+    // The minimum version of `dep` allows a version of `inner` it doesn't
+    // build with.
+    Package::new("inner", "0.1.0").publish();
+    Package::new("inner", "0.1.1")
+        .file("src/lib.rs", "this does not compile")
+        .publish();
+    Package::new("dep", "1.0.0").dep("inner", "0.1").publish();
+    Package::new("dep", "1.0.1")
+        .dep("inner", "=0.1.0")
+        .publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+
+                [dependencies]
+                dep = "1.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("check -Zdirect-minimal-versions")
+        .masquerade_as_nightly_cargo()
+        .with_status(101)
+        .with_stderr_contains(
+            "\
+[NOTE] the minimum versions of these direct dependencies failed to compile:
+  dep v1.0.0 (`dep = \"^1.0\"` in foo v0.0.1 ([CWD])), as its dependency inner v0.1.1 failed
+consider raising their lower bounds in `Cargo.toml`
+",
+        )
+        .run();
+}
@@ -2208,3 +2208,142 @@ See [..]
     assert!(p.root().join("target/package/foo-0.0.1.crate").is_file());
     assert!(p.root().join("target/package/bar-0.0.1.crate").is_file());
 }
+
+#[cargo_test]
+fn workspace_with_unpublished_dep() {
+    registry::init();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [workspace]
+                members = ["foo", "bar"]
+            "#,
+        )
+        .file(
+            "foo/Cargo.toml",
+            r#"
+                [project]
+                name = "foo"
+                version = "0.0.1"
+                authors = []
+                license = "MIT"
+                description = "foo"
+
+                [dependencies]
+                bar = { path = "../bar", version = "0.0.1" }
+            "#,
+        )
+        .file("foo/src/main.rs", "fn main() { bar::bar(); }")
+        .file(
+            "bar/Cargo.toml",
+            r#"
+                [project]
+                name = "bar"
+                version = "0.0.1"
+                authors = []
+                license = "MIT"
+                description = "bar"
+            "#,
+        )
+        .file("bar/src/lib.rs", "pub fn bar() {}")
+        .build();
+
+    // `bar` isn't in the registry, so `foo` can only be verified against the
+    // tarball of `bar` that was just packaged.
+    p.cargo("package --workspace -Z package-workspace")
+        .masquerade_as_nightly_cargo()
+        .with_stderr(
+            "\
+[WARNING] manifest has no documentation, [..]
+See [..]
+[PACKAGING] bar v0.0.1 ([CWD]/bar)
+[VERIFYING] bar v0.0.1 ([CWD]/bar)
+[COMPILING] bar v0.0.1 ([CWD][..])
+[FINISHED] dev [unoptimized + debuginfo] target(s) in [..]
+[WARNING] manifest has no documentation, [..]
+See [..]
+[PACKAGING] foo v0.0.1 ([CWD]/foo)
+[UPDATING] `dummy-registry` index
+[VERIFYING] foo v0.0.1 ([CWD]/foo)
+[UNPACKING] bar v0.0.1 (registry `[CWD]/target/package/tmp-registry`)
+[COMPILING] bar v0.0.1
+[COMPILING] foo v0.0.1 ([CWD][..])
+[FINISHED] dev [unoptimized + debuginfo] target(s) in [..]
+",
+        )
+        .run();
+
+    assert!(p.root().join("target/package/foo-0.0.1.crate").is_file());
+    assert!(p.root().join("target/package/bar-0.0.1.crate").is_file());
+
+    // Without the overlay, `bar` can't be found.
+    p.cargo("package -p foo")
+        .with_status(101)
+        .with_stderr_contains("[..]no matching package named `bar` found")
+        .run();
+}
+
+#[cargo_test]
+fn workspace_cyclic_deps() {
+    registry::init();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [workspace]
+                members = ["foo", "bar"]
+            "#,
+        )
+        .file(
+            "foo/Cargo.toml",
+            r#"
+                [project]
+                name = "foo"
+                version = "0.0.1"
+
+                [dependencies]
+                bar = { path = "../bar", version = "0.0.1" }
+            "#,
+        )
+        .file("foo/src/lib.rs", "")
+        .file(
+            "bar/Cargo.toml",
+            r#"
+                [project]
+                name = "bar"
+                version = "0.0.1"
+
+                [dev-dependencies]
+                foo = { path = "../foo", version = "0.0.1" }
+            "#,
+        )
+        .file("bar/src/lib.rs", "")
+        .build();
+
+    // Dev-dependencies don't take part in the ordering.
+    p.cargo("package --workspace --no-verify -Z package-workspace")
+        .masquerade_as_nightly_cargo()
+        .run();
+    assert!(p.root().join("target/package/foo-0.0.1.crate").is_file());
+    assert!(p.root().join("target/package/bar-0.0.1.crate").is_file());
+
+    p.change_file(
+        "bar/Cargo.toml",
+        r#"
+            [project]
+            name = "bar"
+            version = "0.0.1"
+
+            [dependencies]
+            foo = { path = "../foo", version = "0.0.1" }
+        "#,
+    );
+    p.cargo("package --workspace --no-verify -Z package-workspace")
+        .masquerade_as_nightly_cargo()
+        .with_status(101)
+        .with_stderr_contains("[ERROR] cyclic dependency between the packages [..]")
+        .run();
+}
@@ -0,0 +1,188 @@
+//! Tests for the `trim-paths` profile option.
+
+use cargo_test_support::paths;
+use cargo_test_support::project;
+use cargo_test_support::registry::Package;
+
+#[cargo_test]
+fn gated() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+
+                [profile.release]
+                trim-paths = true
+            "#,
+        )
+        .file("src/main.rs", "fn main() {}")
+        .build();
+
+    p.cargo("build --release")
+        .masquerade_as_nightly_cargo()
+        .with_status(101)
+        .with_stderr_contains("[..]feature `trim-paths` is required")
+        .run();
+}
+
+#[cargo_test]
+fn remaps_workspace_and_registry_paths() {
+    Package::new("bar", "0.1.0")
+        .file(
+            "src/lib.rs",
+            r#"pub fn location() -> &'static str { file!() }"#,
+        )
+        .publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                cargo-features = ["trim-paths"]
+
+                [package]
+                name = "foo"
+                version = "0.1.0"
+
+                [dependencies]
+                bar = "0.1"
+
+                [profile.release]
+                trim-paths = true
+            "#,
+        )
+        .file(
+            "src/main.rs",
+            r#"
+                fn main() {
+                    println!("{}", bar::location());
+                    println!("{}", file!());
+                }
+            "#,
+        )
+        .build();
+
+    let registry_src = paths::home().join(".cargo/registry/src");
+    p.cargo("run --release -v")
+        .masquerade_as_nightly_cargo()
+        .with_stdout("bar-0.1.0/src/lib.rs\nsrc/main.rs")
+        .with_stderr_contains(
+            "[RUNNING] `rustc --crate-name bar [..]\
+             --remap-path-prefix=[..]/lib/rustlib/src/rust=/rustc/[..] \
+             --remap-path-prefix=[CWD]= \
+             --remap-path-prefix=[..]/bar-0.1.0=bar-0.1.0 [..]`",
+        )
+        .with_stderr_contains(
+            "[RUNNING] `rustc --crate-name foo [..]\
+             --remap-path-prefix=[..]/lib/rustlib/src/rust=/rustc/[..] \
+             --remap-path-prefix=[CWD]= [..]`",
+        )
+        .run();
+
+    let bin = std::fs::read(p.release_bin("foo")).unwrap();
+    let needle = registry_src.to_str().unwrap().as_bytes();
+    assert!(!bin.windows(needle.len()).any(|w| w == needle));
+
+    // Not enabled for the dev profile.
+    p.cargo("run")
+        .masquerade_as_nightly_cargo()
+        .with_stdout_contains("[..]/bar-0.1.0/src/lib.rs")
+        .run();
+}
+
+#[cargo_test]
+fn build_script_and_doctest() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                cargo-features = ["trim-paths"]
+
+                [package]
+                name = "foo"
+                version = "0.1.0"
+
+                [profile.dev]
+                trim-paths = true
+            "#,
+        )
+        .file("build.rs", "fn main() {}")
+        .file(
+            "src/lib.rs",
+            r#"
+                /// ```
+                /// assert_eq!(foo::foo(), 1);
+                /// ```
+                pub fn foo() -> i32 { 1 }
+            "#,
+        )
+        .build();
+
+    p.cargo("test --doc -v")
+        .masquerade_as_nightly_cargo()
+        .env("RUSTC_BOOTSTRAP", "1")
+        .with_stderr_contains(
+            "[RUNNING] `rustc --crate-name build_script_build [..]--remap-path-prefix=[CWD]= [..]`",
+        )
+        .with_stderr_contains(
+            "[RUNNING] `rustdoc [..]--test [..]--remap-path-prefix=[CWD]= [..]-Zunstable-options[..]`",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn changing_trim_paths_rebuilds() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                cargo-features = ["trim-paths"]
+
+                [package]
+                name = "foo"
+                version = "0.1.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("build")
+        .masquerade_as_nightly_cargo()
+        .with_stderr(
+            "\
+[COMPILING] foo v0.1.0 ([CWD])
+[FINISHED] [..]
+",
+        )
+        .run();
+
+    p.change_file(
+        "Cargo.toml",
+        r#"
+            cargo-features = ["trim-paths"]
+
+            [package]
+            name = "foo"
+            version = "0.1.0"
+
+            [profile.dev]
+            trim-paths = true
+        "#,
+    );
+    p.cargo("build")
+        .masquerade_as_nightly_cargo()
+        .with_stderr(
+            "\
+[COMPILING] foo v0.1.0 ([CWD])
+[FINISHED] [..]
+",
+        )
+        .run();
+    p.cargo("build")
+        .masquerade_as_nightly_cargo()
+        .with_stderr("[FINISHED] [..]")
+        .run();
+}
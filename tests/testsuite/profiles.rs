@@ -2,7 +2,7 @@
 
 use std::env;
 
-use cargo_test_support::{is_nightly, project};
+use cargo_test_support::{basic_lib_manifest, is_nightly, project};
 
 #[cargo_test]
 fn profile_overrides() {
@@ -649,3 +649,214 @@ fn strip_accepts_false_to_disable_strip() {
         .with_stderr_does_not_contain("-Z strip")
         .run();
 }
+
+#[cargo_test]
+fn rustflags_works() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                cargo-features = ["profile-rustflags"]
+
+                [package]
+                name = "foo"
+                version = "0.1.0"
+
+                [profile.release]
+                rustflags = ["-C", "target-cpu=native"]
+            "#,
+        )
+        .file("src/main.rs", "fn main() {}")
+        .build();
+
+    p.cargo("build --release -v")
+        .masquerade_as_nightly_cargo()
+        .with_stderr(
+            "\
+[COMPILING] foo [..]
+[RUNNING] `rustc --crate-name foo [..] -C target-cpu=native`
+[FINISHED] [..]
+",
+        )
+        .run();
+
+    // Only the release profile sets them.
+    p.cargo("build -v")
+        .masquerade_as_nightly_cargo()
+        .with_stderr_does_not_contain("[..]target-cpu=native[..]")
+        .run();
+}
+
+#[cargo_test]
+fn rustflags_requires_cargo_feature() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+
+                [profile.release]
+                rustflags = ["-C", "target-cpu=native"]
+            "#,
+        )
+        .file("src/main.rs", "fn main() {}")
+        .build();
+
+    p.cargo("build --release -v")
+        .masquerade_as_nightly_cargo()
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] failed to parse manifest at `[CWD]/Cargo.toml`
+
+Caused by:
+  feature `profile-rustflags` is required
+
+  The package requires the Cargo feature called `profile-rustflags`, but that feature is \
+  not stabilized in this version of Cargo (1.[..]).
+  Consider adding `cargo-features = [\"profile-rustflags\"]` to the top of Cargo.toml \
+  (above the [package] table) to tell Cargo you are opting in to use this unstable feature.
+  See https://doc.rust-lang.org/nightly/cargo/reference/unstable.html#profile-rustflags-option \
+  for more information about the status of this feature.
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn rustflags_package_and_build_override() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                cargo-features = ["profile-rustflags"]
+
+                [package]
+                name = "foo"
+                version = "0.1.0"
+
+                [dependencies]
+                bar = { path = "bar" }
+
+                [profile.dev]
+                rustflags = ["--cfg", "from_profile"]
+
+                [profile.dev.package.bar]
+                rustflags = ["--cfg", "from_package"]
+
+                [profile.dev.build-override]
+                rustflags = ["--cfg", "from_build_override"]
+            "#,
+        )
+        .file("build.rs", "fn main() {}")
+        .file("src/lib.rs", "")
+        .file("bar/Cargo.toml", &basic_lib_manifest("bar"))
+        .file("bar/src/lib.rs", "")
+        .build();
+
+    p.cargo("build -v")
+        .masquerade_as_nightly_cargo()
+        .with_stderr_unordered(
+            "\
+[COMPILING] bar [..]
+[COMPILING] foo [..]
+[RUNNING] `rustc --crate-name bar [..] --cfg from_package`
+[RUNNING] `rustc --crate-name build_script_build [..] --cfg from_build_override`
+[RUNNING] `[..]/build-script-build`
+[RUNNING] `rustc --crate-name foo [..] --cfg from_profile`
+[FINISHED] [..]
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn rustflags_appended_to_rustflags_env() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                cargo-features = ["profile-rustflags"]
+
+                [package]
+                name = "foo"
+                version = "0.1.0"
+
+                [profile.dev]
+                rustflags = ["--cfg", "from_profile"]
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("build -v")
+        .masquerade_as_nightly_cargo()
+        .env("RUSTFLAGS", "--cfg from_env")
+        .with_stderr(
+            "\
+[COMPILING] foo [..]
+[RUNNING] `rustc --crate-name foo [..] --cfg from_env --cfg from_profile`
+[FINISHED] [..]
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn changing_rustflags_rebuilds() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                cargo-features = ["profile-rustflags"]
+
+                [package]
+                name = "foo"
+                version = "0.1.0"
+
+                [profile.dev]
+                rustflags = ["--cfg", "one"]
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("build")
+        .masquerade_as_nightly_cargo()
+        .with_stderr(
+            "\
+[COMPILING] foo [..]
+[FINISHED] [..]
+",
+        )
+        .run();
+    p.cargo("build")
+        .masquerade_as_nightly_cargo()
+        .with_stderr("[FINISHED] [..]")
+        .run();
+
+    p.change_file(
+        "Cargo.toml",
+        r#"
+            cargo-features = ["profile-rustflags"]
+
+            [package]
+            name = "foo"
+            version = "0.1.0"
+
+            [profile.dev]
+            rustflags = ["--cfg", "two"]
+        "#,
+    );
+    p.cargo("build")
+        .masquerade_as_nightly_cargo()
+        .with_stderr(
+            "\
+[COMPILING] foo [..]
+[FINISHED] [..]
+",
+        )
+        .run();
+}
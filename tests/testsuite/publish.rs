@@ -1730,3 +1730,231 @@ See [..]
 
     validate_upload_bar();
 }
+
+#[cargo_test]
+fn workspace_requires_package_workspace() {
+    registry::init();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [workspace]
+                members = ["foo", "bar"]
+            "#,
+        )
+        .file("foo/Cargo.toml", &basic_manifest("foo", "0.0.1"))
+        .file("foo/src/lib.rs", "")
+        .file("bar/Cargo.toml", &basic_manifest("bar", "0.0.1"))
+        .file("bar/src/lib.rs", "")
+        .build();
+
+    p.cargo("publish --workspace --token sekrit")
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] publishing multiple packages requires `-Z package-workspace`
+select a single package to publish with `-p`
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn workspace_conflicting_publish_registries() {
+    registry::init();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [workspace]
+                members = ["foo", "bar"]
+            "#,
+        )
+        .file(
+            "foo/Cargo.toml",
+            r#"
+                [project]
+                name = "foo"
+                version = "0.0.1"
+                publish = ["alternative"]
+            "#,
+        )
+        .file("foo/src/lib.rs", "")
+        .file(
+            "bar/Cargo.toml",
+            r#"
+                [project]
+                name = "bar"
+                version = "0.0.1"
+                publish = ["crates-io"]
+            "#,
+        )
+        .file("bar/src/lib.rs", "")
+        .build();
+
+    p.cargo("publish --workspace --dry-run --token sekrit -Z package-workspace")
+        .masquerade_as_nightly_cargo()
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] the packages to publish have different registries in their `publish` values: \
+`alternative`, `crates-io`
+select a registry to publish to with `--registry`
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn workspace_dry_run_in_dependency_order() {
+    registry::init();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [workspace]
+                members = ["foo", "bar", "baz"]
+            "#,
+        )
+        .file(
+            "foo/Cargo.toml",
+            r#"
+                [project]
+                name = "foo"
+                version = "0.0.1"
+                authors = []
+                license = "MIT"
+                description = "foo"
+                documentation = "foo"
+
+                [dependencies]
+                bar = { path = "../bar", version = "0.0.1" }
+            "#,
+        )
+        .file("foo/src/lib.rs", "pub fn foo() { bar::bar(); }")
+        .file(
+            "bar/Cargo.toml",
+            r#"
+                [project]
+                name = "bar"
+                version = "0.0.1"
+                authors = []
+                license = "MIT"
+                description = "bar"
+                documentation = "bar"
+            "#,
+        )
+        .file("bar/src/lib.rs", "pub fn bar() {}")
+        .file(
+            "baz/Cargo.toml",
+            r#"
+                [project]
+                name = "baz"
+                version = "0.0.1"
+                publish = false
+            "#,
+        )
+        .file("baz/src/lib.rs", "")
+        .build();
+
+    p.cargo("publish --workspace --dry-run --token sekrit -Z package-workspace")
+        .masquerade_as_nightly_cargo()
+        .with_stderr(
+            "\
+[UPDATING] `dummy-registry` index
+[PACKAGING] bar v0.0.1 ([CWD]/bar)
+[VERIFYING] bar v0.0.1 ([CWD]/bar)
+[COMPILING] bar v0.0.1 ([CWD]/target/package/bar-0.0.1)
+[FINISHED] dev [unoptimized + debuginfo] target(s) in [..]
+[PACKAGING] foo v0.0.1 ([CWD]/foo)
+[VERIFYING] foo v0.0.1 ([CWD]/foo)
+[UNPACKING] bar v0.0.1 (registry `[CWD]/target/package/tmp-registry`)
+[COMPILING] bar v0.0.1
+[COMPILING] foo v0.0.1 ([CWD]/target/package/foo-0.0.1)
+[FINISHED] dev [unoptimized + debuginfo] target(s) in [..]
+[UPLOADING] bar v0.0.1 ([CWD]/bar)
+[WARNING] aborting upload due to dry run
+[UPLOADING] foo v0.0.1 ([CWD]/foo)
+[WARNING] aborting upload due to dry run
+",
+        )
+        .run();
+
+    assert!(!registry::api_path().join("api/v1/crates/new").exists());
+}
+
+#[cargo_test]
+fn workspace_waits_for_index() {
+    registry::init();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [workspace]
+                members = ["foo", "bar"]
+            "#,
+        )
+        .file(
+            "foo/Cargo.toml",
+            r#"
+                [project]
+                name = "foo"
+                version = "0.0.1"
+                authors = []
+                license = "MIT"
+                description = "foo"
+                documentation = "foo"
+
+                [dependencies]
+                bar = { path = "../bar", version = "0.0.1" }
+            "#,
+        )
+        .file("foo/src/lib.rs", "")
+        .file(
+            "bar/Cargo.toml",
+            r#"
+                [project]
+                name = "bar"
+                version = "0.0.1"
+                authors = []
+                license = "MIT"
+                description = "bar"
+                documentation = "bar"
+            "#,
+        )
+        .file("bar/src/lib.rs", "")
+        .file(
+            ".cargo/config",
+            r#"
+                [publish]
+                timeout = 0
+            "#,
+        )
+        .build();
+
+    // The test registry never adds uploaded packages to its index.
+    p.cargo("publish --workspace --no-verify --token sekrit -Z package-workspace")
+        .masquerade_as_nightly_cargo()
+        .with_stderr(
+            "\
+[UPDATING] `dummy-registry` index
+[PACKAGING] bar v0.0.1 ([CWD]/bar)
+[PACKAGING] foo v0.0.1 ([CWD]/foo)
+[UPLOADING] bar v0.0.1 ([CWD]/bar)
+[WAITING] for `bar v0.0.1` to be available in registry `dummy-registry`
+[UPDATING] `dummy-registry` index
+[WARNING] timed out waiting for `bar v0.0.1` to be available in registry `dummy-registry`
+The packages that depend on it may fail to be published until it is.
+[UPLOADING] foo v0.0.1 ([CWD]/foo)
+[WAITING] for `foo v0.0.1` to be available in registry `dummy-registry`
+[UPDATING] `dummy-registry` index
+[WARNING] timed out waiting for `foo v0.0.1` to be available in registry `dummy-registry`
+The packages that depend on it may fail to be published until it is.
+",
+        )
+        .run();
+}
@@ -0,0 +1,400 @@
+//! Tests for the `cargo report licenses` command.
+
+use cargo_test_support::registry::Package;
+use cargo_test_support::{basic_manifest, project};
+
+fn licensed_package(name: &str, license: &str) -> String {
+    Package::new(name, "1.0.0")
+        .file(
+            "Cargo.toml",
+            &format!(
+                r#"
+                    [package]
+                    name = "{}"
+                    version = "1.0.0"
+                    license = "{}"
+                    repository = "https://example.com/{0}"
+                "#,
+                name, license
+            ),
+        )
+        .file("src/lib.rs", "")
+        .publish()
+}
+
+#[cargo_test]
+fn gated() {
+    let p = project()
+        .file("Cargo.toml", &basic_manifest("foo", "0.1.0"))
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("report licenses")
+        .masquerade_as_nightly_cargo()
+        .with_status(101)
+        .with_stderr_contains(
+            "error: the `cargo report licenses` command is unstable, \
+             pass `-Z unstable-options` to enable it",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn text_report() {
+    let bar = licensed_package("bar", "MIT/Apache-2.0");
+    licensed_package("dev", "GPL-3.0");
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                license = "Proprietary"
+
+                [dependencies]
+                bar = "1.0"
+                local = { path = "local" }
+
+                [dev-dependencies]
+                dev = "1.0"
+
+                [workspace]
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file(
+            "local/Cargo.toml",
+            r#"
+                [package]
+                name = "local"
+                version = "0.1.0"
+                license-file = "LICENSE"
+            "#,
+        )
+        .file("local/src/lib.rs", "")
+        .build();
+
+    p.cargo("report licenses -Z unstable-options")
+        .masquerade_as_nightly_cargo()
+        .with_stdout(&format!(
+            "\
+bar v1.0.0
+    license: MIT OR Apache-2.0
+    source: registry+https://github.com/rust-lang/crates.io-index
+    checksum: {}
+    repository: https://example.com/bar
+
+foo v0.1.0
+    license: Proprietary
+    source: path+file://[..]/foo
+
+local v0.1.0
+    license: NOASSERTION
+    license-file: LICENSE
+    source: path+file://[..]/foo/local
+",
+            bar
+        ))
+        .run();
+
+    // Dev-dependencies are only included on request.
+    p.cargo("report licenses -Z unstable-options -e dev")
+        .masquerade_as_nightly_cargo()
+        .with_stdout_contains("dev v1.0.0\n    license: GPL-3.0")
+        .with_stdout_does_not_contain("bar v1.0.0")
+        .run();
+}
+
+#[cargo_test]
+fn spdx_report() {
+    let bar = licensed_package("bar", "MIT/Apache-2.0");
+    licensed_package("dev", "GPL-3.0");
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                license = "Proprietary"
+
+                [dependencies]
+                bar = "1.0"
+                local = { path = "local" }
+
+                [dev-dependencies]
+                dev = "1.0"
+
+                [workspace]
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file(
+            "local/Cargo.toml",
+            r#"
+                [package]
+                name = "local"
+                version = "0.1.0"
+                license-file = "LICENSE"
+            "#,
+        )
+        .file("local/src/lib.rs", "")
+        .build();
+
+    p.cargo("report licenses -Z unstable-options --format spdx")
+        .masquerade_as_nightly_cargo()
+        .with_stdout(&format!(
+            "\
+SPDXVersion: SPDX-2.3
+DataLicense: CC0-1.0
+SPDXID: SPDXRef-DOCUMENT
+DocumentName: foo
+DocumentNamespace: https://spdx.org/spdxdocs/foo-[..]
+Creator: Tool: cargo-[..]
+Created: [..]T[..]Z
+
+PackageName: bar
+SPDXID: SPDXRef-Package-0
+PackageVersion: 1.0.0
+PackageDownloadLocation: https://crates.io/api/v1/crates/bar/1.0.0/download
+FilesAnalyzed: false
+PackageChecksum: SHA256: {}
+PackageHomePage: https://example.com/bar
+PackageLicenseConcluded: NOASSERTION
+PackageLicenseDeclared: MIT OR Apache-2.0
+PackageCopyrightText: NOASSERTION
+
+PackageName: foo
+SPDXID: SPDXRef-Package-1
+PackageVersion: 0.1.0
+PackageDownloadLocation: NONE
+FilesAnalyzed: false
+PackageLicenseConcluded: NOASSERTION
+PackageLicenseDeclared: Proprietary
+PackageCopyrightText: NOASSERTION
+Relationship: SPDXRef-DOCUMENT DESCRIBES SPDXRef-Package-1
+
+PackageName: local
+SPDXID: SPDXRef-Package-2
+PackageVersion: 0.1.0
+PackageDownloadLocation: NONE
+FilesAnalyzed: false
+PackageLicenseConcluded: NOASSERTION
+PackageLicenseDeclared: NOASSERTION
+PackageCopyrightText: NOASSERTION
+Relationship: SPDXRef-DOCUMENT DESCRIBES SPDXRef-Package-2
+",
+            bar
+        ))
+        .run();
+}
+
+#[cargo_test]
+fn json_report() {
+    let bar = licensed_package("bar", "MIT OR Apache-2.0");
+    licensed_package("dev", "MIT");
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                license = "Proprietary"
+
+                [dependencies]
+                bar = "1.0"
+                local = { path = "local" }
+
+                [dev-dependencies]
+                dev = "1.0"
+
+                [workspace]
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file(
+            "local/Cargo.toml",
+            r#"
+                [package]
+                name = "local"
+                version = "0.1.0"
+                license-file = "LICENSE"
+            "#,
+        )
+        .file("local/src/lib.rs", "")
+        .build();
+
+    p.cargo("report licenses -Z unstable-options --format json")
+        .masquerade_as_nightly_cargo()
+        .with_json(&format!(
+            r#"
+{{
+  "packages": [
+    {{
+      "name": "bar",
+      "version": "1.0.0",
+      "id": "bar 1.0.0 (registry+https://github.com/rust-lang/crates.io-index)",
+      "source": "registry+https://github.com/rust-lang/crates.io-index",
+      "license": "MIT OR Apache-2.0",
+      "license_file": null,
+      "checksum": "{}",
+      "repository": "https://example.com/bar"
+    }},
+    {{
+      "name": "foo",
+      "version": "0.1.0",
+      "id": "foo 0.1.0 (path+file://[..]/foo)",
+      "source": "path+file://[..]/foo",
+      "license": "Proprietary",
+      "license_file": null,
+      "checksum": null,
+      "repository": null
+    }},
+    {{
+      "name": "local",
+      "version": "0.1.0",
+      "id": "local 0.1.0 (path+file://[..]/foo/local)",
+      "source": "path+file://[..]/foo/local",
+      "license": null,
+      "license_file": "LICENSE",
+      "checksum": null,
+      "repository": null
+    }}
+  ]
+}}
+"#,
+            bar
+        ))
+        .run();
+}
+
+#[cargo_test]
+fn policy_violations() {
+    licensed_package("bar", "(MIT OR GPL-3.0) AND BSD-3-Clause");
+    Package::new("dev", "1.0.0").publish();
+
+    // Workspace members, like `foo` and `local`, are not checked.
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                license = "Proprietary"
+
+                [dependencies]
+                bar = "1.0"
+                local = { path = "local" }
+
+                [dev-dependencies]
+                dev = "1.0"
+
+                [workspace.metadata.licenses]
+                allow = ["MIT", "Apache-2.0", "BSD-3-Clause"]
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file(
+            "local/Cargo.toml",
+            r#"
+                [package]
+                name = "local"
+                version = "0.1.0"
+                license-file = "LICENSE"
+            "#,
+        )
+        .file("local/src/lib.rs", "")
+        .build();
+    p.cargo("report licenses -Z unstable-options")
+        .masquerade_as_nightly_cargo()
+        .with_stdout_contains("bar v1.0.0")
+        .run();
+    p.cargo("report licenses -Z unstable-options -e normal,dev")
+        .masquerade_as_nightly_cargo()
+        .with_status(101)
+        .with_stdout_contains("dev v1.0.0")
+        .with_stderr(
+            "\
+[DOWNLOADING] crates ...
+[DOWNLOADED] dev v1.0.0 (registry `dummy-registry`)
+[ERROR] 1 package does not satisfy the license policy of `workspace.metadata.licenses`:
+  dev v1.0.0: no license expression is declared
+",
+        )
+        .run();
+
+    p.change_file(
+        "Cargo.toml",
+        r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+
+            [dependencies]
+            bar = "1.0"
+
+            [dev-dependencies]
+            dev = "1.0"
+
+            [workspace.metadata.licenses]
+            deny = ["MIT", "GPL-3.0"]
+        "#,
+    );
+    p.cargo("report licenses -Z unstable-options -e normal,dev")
+        .masquerade_as_nightly_cargo()
+        .with_status(101)
+        .with_stderr_contains(
+            "\
+[ERROR] 1 package does not satisfy the license policy of `workspace.metadata.licenses`:
+  bar v1.0.0: license `(MIT OR GPL-3.0) AND BSD-3-Clause` is not allowed
+",
+        )
+        .run();
+
+    p.change_file(
+        "Cargo.toml",
+        r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+
+            [dependencies]
+            bar = "1.0"
+
+            [dev-dependencies]
+            dev = "1.0"
+
+            [workspace.metadata.licenses]
+            deny = ["GPL-3.0"]
+        "#,
+    );
+    p.cargo("report licenses -Z unstable-options")
+        .masquerade_as_nightly_cargo()
+        .run();
+
+    p.change_file(
+        "Cargo.toml",
+        r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+
+            [dependencies]
+            bar = "1.0"
+
+            [dev-dependencies]
+            dev = "1.0"
+
+            [workspace.metadata.licenses]
+            allow = "MIT"
+        "#,
+    );
+    p.cargo("report licenses -Z unstable-options")
+        .masquerade_as_nightly_cargo()
+        .with_status(101)
+        .with_stderr_contains("[ERROR] invalid `workspace.metadata.licenses`: [..]")
+        .run();
+}
@@ -189,3 +189,163 @@ fn rust_version_older_than_edition() {
         )
         .run();
 }
+
+fn msrv_policy_project(rust_version: Option<&str>) -> cargo_test_support::Project {
+    let rust_version = rust_version
+        .map(|v| format!("rust-version = \"{}\"", v))
+        .unwrap_or_default();
+    project()
+        .file(
+            "Cargo.toml",
+            &format!(
+                r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+                {}
+
+                [dependencies]
+                bar = "1"
+            "#,
+                rust_version
+            ),
+        )
+        .file("src/main.rs", "fn main() {}")
+        .build()
+}
+
+fn assert_locked_bar(p: &cargo_test_support::Project, version: &str) {
+    let lock = p.read_lockfile();
+    assert!(
+        lock.contains(&format!("name = \"bar\"\nversion = \"{}\"", version)),
+        "expected bar {} in lockfile:\n{}",
+        version,
+        lock
+    );
+}
+
+#[cargo_test]
+fn msrv_policy_prefers_compatible_versions() {
+    Package::new("bar", "1.0.0").rust_version("1.50").publish();
+    Package::new("bar", "1.1.0").rust_version("1.70").publish();
+    Package::new("bar", "1.2.0")
+        .rust_version("1.9999")
+        .publish();
+    let p = msrv_policy_project(Some("1.60"));
+
+    p.cargo("generate-lockfile").run();
+    assert_locked_bar(&p, "1.2.0");
+
+    p.cargo("generate-lockfile -Z msrv-policy")
+        .masquerade_as_nightly_cargo()
+        .run();
+    assert_locked_bar(&p, "1.0.0");
+
+    // Without a `rust-version` in the workspace, the active rustc is used.
+    let p = msrv_policy_project(None);
+    p.cargo("generate-lockfile -Z msrv-policy")
+        .masquerade_as_nightly_cargo()
+        .run();
+    assert_locked_bar(&p, "1.1.0");
+}
+
+#[cargo_test]
+fn msrv_policy_oldest_member_rust_version() {
+    Package::new("bar", "1.0.0").rust_version("1.50").publish();
+    Package::new("bar", "1.1.0").rust_version("1.60").publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [workspace]
+                members = ["foo", "baz"]
+            "#,
+        )
+        .file(
+            "foo/Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+                rust-version = "1.65"
+
+                [dependencies]
+                bar = "1"
+            "#,
+        )
+        .file("foo/src/lib.rs", "")
+        .file(
+            "baz/Cargo.toml",
+            r#"
+                [package]
+                name = "baz"
+                version = "0.0.1"
+                rust-version = "1.55"
+            "#,
+        )
+        .file("baz/src/lib.rs", "")
+        .build();
+
+    p.cargo("generate-lockfile -Z msrv-policy")
+        .masquerade_as_nightly_cargo()
+        .run();
+    assert_locked_bar(&p, "1.0.0");
+}
+
+#[cargo_test]
+fn msrv_policy_falls_back_to_incompatible() {
+    Package::new("bar", "1.0.0").rust_version("1.70").publish();
+    let p = msrv_policy_project(Some("1.60"));
+
+    p.cargo("generate-lockfile -Z msrv-policy")
+        .masquerade_as_nightly_cargo()
+        .run();
+    assert_locked_bar(&p, "1.0.0");
+
+    p.cargo("generate-lockfile -Z msrv-policy")
+        .masquerade_as_nightly_cargo()
+        .env("CARGO_RESOLVER_RUST_VERSION_POLICY", "required")
+        .with_status(101)
+        .with_stderr(
+            "\
+[UPDATING] `dummy-registry` index
+[ERROR] failed to select a version for the requirement `bar = \"^1\"`
+candidate versions found which didn't match: 1.0.0
+location searched: `dummy-registry` index (which is replacing registry `crates-io`)
+required by package `foo v0.0.1 ([CWD])`
+the matching versions require a newer Rust version, \
+which `resolver.rust-version-policy = \"required\"` doesn't allow
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn msrv_policy_off() {
+    Package::new("bar", "1.0.0").rust_version("1.50").publish();
+    Package::new("bar", "1.1.0").rust_version("1.70").publish();
+    let p = msrv_policy_project(Some("1.60"));
+    p.change_file(
+        ".cargo/config.toml",
+        r#"
+            [resolver]
+            rust-version-policy = "off"
+        "#,
+    );
+
+    p.cargo("generate-lockfile -Z msrv-policy")
+        .masquerade_as_nightly_cargo()
+        .run();
+    assert_locked_bar(&p, "1.1.0");
+
+    // The policy is ignored without `-Z msrv-policy`.
+    p.change_file(
+        ".cargo/config.toml",
+        r#"
+            [resolver]
+            rust-version-policy = "required"
+        "#,
+    );
+    p.cargo("generate-lockfile").run();
+    assert_locked_bar(&p, "1.1.0");
+}
@@ -0,0 +1,174 @@
+//! Tests for the `-Z sbom` flag.
+
+use cargo_test_support::compare::find_json_mismatch;
+use cargo_test_support::registry::Package;
+use cargo_test_support::{basic_bin_manifest, project, rustc_host};
+use std::env::consts::EXE_SUFFIX;
+
+fn sbom_path(bin: &str) -> String {
+    format!("target/debug/{}{}.cargo-sbom.json", bin, EXE_SUFFIX)
+}
+
+#[cargo_test]
+fn gated() {
+    let p = project()
+        .file("Cargo.toml", &basic_bin_manifest("foo"))
+        .file("src/main.rs", "fn main() {}")
+        .build();
+
+    p.cargo("build").run();
+    assert!(!p.root().join(sbom_path("foo")).exists());
+
+    p.cargo("build -Z sbom")
+        .with_status(101)
+        .with_stderr(
+            "error: the `-Z` flag is only accepted on the nightly channel of Cargo, \
+             but this is the `stable` channel\n[..]",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn sbom_of_reachable_units() {
+    Package::new("bar", "1.0.0")
+        .feature("std", &[])
+        .file("src/lib.rs", "")
+        .publish();
+    Package::new("cc", "1.0.0").publish();
+    Package::new("unused", "1.0.0").publish();
+    Package::new("pmdep", "1.0.0").publish();
+    Package::new("pm", "1.0.0")
+        .proc_macro(true)
+        .dep("pmdep", "1.0")
+        .publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+
+                [dependencies]
+                bar = { version = "1.0", features = ["std"] }
+                pm = "1.0"
+
+                [target.'cfg(any())'.dependencies]
+                unused = "1.0"
+
+                [build-dependencies]
+                cc = "1.0"
+            "#,
+        )
+        .file("src/main.rs", "fn main() {}")
+        .file("build.rs", "fn main() {}")
+        .build();
+
+    p.cargo("build -Z sbom").masquerade_as_nightly_cargo().run();
+
+    let sbom = p.read_file(&sbom_path("foo"));
+    let expected = r#"
+{
+  "version": 1,
+  "root": 0,
+  "crates": [
+    {
+      "id": "foo 0.1.0 (path+file://[..]/foo)",
+      "target": "foo",
+      "kind": ["bin"],
+      "features": [],
+      "dependencies": [
+        { "index": 1, "kind": "normal" },
+        { "index": 2, "kind": "build" },
+        { "index": 4, "kind": "proc-macro" }
+      ]
+    },
+    {
+      "id": "bar 1.0.0 (registry+https://github.com/rust-lang/crates.io-index)",
+      "target": "bar",
+      "kind": ["lib"],
+      "features": ["std"],
+      "dependencies": []
+    },
+    {
+      "id": "foo 0.1.0 (path+file://[..]/foo)",
+      "target": "build-script-build",
+      "kind": ["custom-build"],
+      "features": [],
+      "dependencies": [{ "index": 3, "kind": "build" }]
+    },
+    {
+      "id": "cc 1.0.0 (registry+https://github.com/rust-lang/crates.io-index)",
+      "target": "cc",
+      "kind": ["lib"],
+      "features": [],
+      "dependencies": []
+    },
+    {
+      "id": "pm 1.0.0 (registry+https://github.com/rust-lang/crates.io-index)",
+      "target": "pm",
+      "kind": ["proc-macro"],
+      "features": [],
+      "dependencies": [{ "index": 5, "kind": "normal" }]
+    },
+    {
+      "id": "pmdep 1.0.0 (registry+https://github.com/rust-lang/crates.io-index)",
+      "target": "pmdep",
+      "kind": ["lib"],
+      "features": [],
+      "dependencies": []
+    }
+  ],
+  "rustc": {
+    "version": "[..]",
+    "commit_hash": "{...}",
+    "host": "[HOST]",
+    "verbose_version": "{...}"
+  },
+  "target": "[HOST]"
+}
+"#
+    .replace("[HOST]", rustc_host());
+    find_json_mismatch(
+        &serde_json::from_str(&expected).unwrap(),
+        &serde_json::from_str(&sbom).unwrap(),
+        None,
+    )
+    .unwrap();
+}
+
+#[cargo_test]
+fn artifact_message_points_to_sbom() {
+    let p = project()
+        .file("Cargo.toml", &basic_bin_manifest("foo"))
+        .file("src/main.rs", "fn main() {}")
+        .build();
+
+    p.cargo("build -Z sbom --message-format=json")
+        .masquerade_as_nightly_cargo()
+        .with_json_contains_unordered(
+            r#"
+{
+  "reason": "compiler-artifact",
+  "package_id": "foo 0.5.0 ([..])",
+  "manifest_path": "[..]",
+  "target": "{...}",
+  "profile": "{...}",
+  "features": [],
+  "filenames": ["[..]/foo/target/debug/foo[EXE]"],
+  "executable": "[..]/foo/target/debug/foo[EXE]",
+  "sboms": ["[..]/foo/target/debug/foo[EXE].cargo-sbom.json"],
+  "fresh": false
+}
+
+{"reason": "build-finished", "success": true}
+"#,
+        )
+        .run();
+
+    // The SBOM is written again on fresh builds.
+    let sbom = p.root().join(sbom_path("foo"));
+    std::fs::remove_file(&sbom).unwrap();
+    p.cargo("build -Z sbom").masquerade_as_nightly_cargo().run();
+    assert!(sbom.exists());
+}
@@ -0,0 +1,211 @@
+//! Tests for registries whose index is fetched over plain HTTP.
+
+use cargo_test_support::registry::{Package, RegistryBuilder};
+use cargo_test_support::{basic_manifest, project};
+
+fn project_with_dep(dep: &str) -> cargo_test_support::Project {
+    project()
+        .file(
+            "Cargo.toml",
+            &format!(
+                r#"
+                    [project]
+                    name = "foo"
+                    version = "0.0.1"
+                    authors = []
+
+                    [dependencies]
+                    {} = "*"
+                "#,
+                dep
+            ),
+        )
+        .file("src/main.rs", "fn main() {}")
+        .build()
+}
+
+#[cargo_test]
+fn gated() {
+    let _server = RegistryBuilder::new().build_http_index();
+    Package::new("bar", "0.0.1").publish();
+    let p = project_with_dep("bar");
+
+    p.cargo("build")
+        .masquerade_as_nightly_cargo()
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] failed to get `bar` as a dependency of package `foo v0.0.1 ([..])`
+
+Caused by:
+  failed to load source for dependency `bar`
+
+Caused by:
+  Unable to update registry `crates-io`
+
+Caused by:
+  usage of sparse registry `sparse+http://[..]/` requires `-Z sparse-registry`
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn simple() {
+    let server = RegistryBuilder::new().build_http_index();
+    Package::new("baz", "0.0.1").publish();
+    Package::new("bar", "0.0.1").dep("baz", "*").publish();
+    let p = project_with_dep("bar");
+
+    p.cargo("build -Z sparse-registry")
+        .masquerade_as_nightly_cargo()
+        .with_stderr(
+            "\
+[UPDATING] `dummy-registry` index
+[DOWNLOADING] crates ...
+[DOWNLOADED] [..] v0.0.1 (registry `dummy-registry`)
+[DOWNLOADED] [..] v0.0.1 (registry `dummy-registry`)
+[COMPILING] baz v0.0.1
+[COMPILING] bar v0.0.1
+[COMPILING] foo v0.0.1 ([CWD])
+[FINISHED] dev [unoptimized + debuginfo] target(s) in [..]s
+",
+        )
+        .run();
+    assert_eq!(
+        server.take_requests(),
+        [
+            "GET /3/b/bar 200",
+            "GET /3/b/baz 200",
+            "GET /config.json 200"
+        ]
+    );
+
+    // With a lock file, the cached index files are used as-is.
+    p.cargo("clean").run();
+    p.cargo("build -Z sparse-registry")
+        .masquerade_as_nightly_cargo()
+        .with_stderr(
+            "\
+[COMPILING] baz v0.0.1
+[COMPILING] bar v0.0.1
+[COMPILING] foo v0.0.1 ([CWD])
+[FINISHED] dev [unoptimized + debuginfo] target(s) in [..]s
+",
+        )
+        .run();
+    assert!(server.take_requests().is_empty());
+}
+
+#[cargo_test]
+fn only_fetches_needed_files() {
+    let server = RegistryBuilder::new().build_http_index();
+    Package::new("bar", "0.0.1").publish();
+    for name in ["unused", "also-unused", "a", "ab", "abc"] {
+        Package::new(name, "1.0.0").publish();
+    }
+    let p = project_with_dep("bar");
+
+    p.cargo("generate-lockfile -Z sparse-registry")
+        .masquerade_as_nightly_cargo()
+        .run();
+    assert_eq!(server.take_requests(), ["GET /3/b/bar 200"]);
+}
+
+#[cargo_test]
+fn update_uses_conditional_requests() {
+    let server = RegistryBuilder::new().build_http_index();
+    Package::new("baz", "0.0.1").publish();
+    Package::new("bar", "0.0.1").dep("baz", "*").publish();
+    let p = project_with_dep("bar");
+
+    p.cargo("generate-lockfile -Z sparse-registry")
+        .masquerade_as_nightly_cargo()
+        .run();
+    server.take_requests();
+
+    // Nothing changed, so nothing is downloaded again.
+    p.cargo("update -Z sparse-registry")
+        .masquerade_as_nightly_cargo()
+        .with_stderr("[UPDATING] `dummy-registry` index")
+        .run();
+    assert_eq!(
+        server.take_requests(),
+        ["GET /3/b/bar 304", "GET /3/b/baz 304"]
+    );
+
+    Package::new("baz", "0.0.2").publish();
+    p.cargo("update -Z sparse-registry")
+        .masquerade_as_nightly_cargo()
+        .with_stderr(
+            "\
+[UPDATING] `dummy-registry` index
+[UPDATING] baz v0.0.1 -> v0.0.2
+",
+        )
+        .run();
+    assert_eq!(
+        server.take_requests(),
+        ["GET /3/b/bar 304", "GET /3/b/baz 200"]
+    );
+}
+
+#[cargo_test]
+fn offline() {
+    let server = RegistryBuilder::new().build_http_index();
+    Package::new("bar", "0.0.1").publish();
+    let p = project_with_dep("bar");
+
+    p.cargo("fetch -Z sparse-registry")
+        .masquerade_as_nightly_cargo()
+        .run();
+    server.take_requests();
+
+    let p2 = project()
+        .at("foo2")
+        .file("Cargo.toml", &basic_manifest("foo2", "0.0.1"))
+        .file("src/main.rs", "fn main() {}")
+        .build();
+    p2.change_file(
+        "Cargo.toml",
+        r#"
+            [project]
+            name = "foo2"
+            version = "0.0.1"
+
+            [dependencies]
+            bar = "0.0.1"
+        "#,
+    );
+    p2.cargo("build --offline -Z sparse-registry")
+        .masquerade_as_nightly_cargo()
+        .with_stderr(
+            "\
+[COMPILING] bar v0.0.1
+[COMPILING] foo2 v0.0.1 ([CWD])
+[FINISHED] dev [unoptimized + debuginfo] target(s) in [..]s
+",
+        )
+        .run();
+    assert!(server.take_requests().is_empty());
+}
+
+#[cargo_test]
+fn missing_crate() {
+    let server = RegistryBuilder::new().build_http_index();
+    let p = project_with_dep("nope");
+
+    p.cargo("generate-lockfile -Z sparse-registry")
+        .masquerade_as_nightly_cargo()
+        .with_status(101)
+        .with_stderr(
+            "\
+[UPDATING] `dummy-registry` index
+[ERROR] no matching package named `nope` found
+location searched: registry `crates-io`
+required by package `foo v0.0.1 ([..])`
+",
+        )
+        .run();
+    assert_eq!(server.take_requests(), ["GET /no/pe/nope 404"]);
+}
@@ -2047,3 +2047,239 @@ foo v1.0.0 ([ROOT]/foo)
         )
         .run();
 }
+
+#[cargo_test]
+fn graph_formats_gated() {
+    let p = make_simple_proj();
+
+    p.cargo("tree --format json")
+        .masquerade_as_nightly_cargo()
+        .with_status(101)
+        .with_stderr_contains(
+            "[ERROR] the `--format json` flag is unstable, pass `-Z unstable-options` to enable it",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn json_format() {
+    let p = make_simple_proj();
+
+    p.cargo("tree --format json -Z unstable-options")
+        .masquerade_as_nightly_cargo()
+        .with_json(
+            r#"
+{
+  "version": 1,
+  "roots": [0],
+  "nodes": [
+    {
+      "kind": "package",
+      "id": "foo 0.1.0 (path+file:///[..]/foo)",
+      "name": "foo",
+      "version": "0.1.0",
+      "features": [],
+      "platform": null,
+      "proc_macro": false,
+      "duplicate": false
+    },
+    {
+      "kind": "package",
+      "id": "a 1.0.0 (registry+https://github.com/rust-lang/crates.io-index)",
+      "name": "a",
+      "version": "1.0.0",
+      "features": [],
+      "platform": null,
+      "proc_macro": false,
+      "duplicate": false
+    },
+    {
+      "kind": "package",
+      "id": "c 1.0.0 (registry+https://github.com/rust-lang/crates.io-index)",
+      "name": "c",
+      "version": "1.0.0",
+      "features": [],
+      "platform": null,
+      "proc_macro": false,
+      "duplicate": false
+    },
+    {
+      "kind": "package",
+      "id": "bdep 1.0.0 (registry+https://github.com/rust-lang/crates.io-index)",
+      "name": "bdep",
+      "version": "1.0.0",
+      "features": [],
+      "platform": null,
+      "proc_macro": false,
+      "duplicate": false
+    },
+    {
+      "kind": "package",
+      "id": "devdep 1.0.0 (registry+https://github.com/rust-lang/crates.io-index)",
+      "name": "devdep",
+      "version": "1.0.0",
+      "features": [],
+      "platform": null,
+      "proc_macro": false,
+      "duplicate": false
+    },
+    {
+      "kind": "package",
+      "id": "b 1.0.0 (registry+https://github.com/rust-lang/crates.io-index)",
+      "name": "b",
+      "version": "1.0.0",
+      "features": [],
+      "platform": null,
+      "proc_macro": false,
+      "duplicate": false
+    }
+  ],
+  "edges": [
+    {"from": 0, "to": 1, "kind": "normal"},
+    {"from": 0, "to": 2, "kind": "normal"},
+    {"from": 0, "to": 3, "kind": "build"},
+    {"from": 0, "to": 4, "kind": "dev"},
+    {"from": 1, "to": 5, "kind": "normal"},
+    {"from": 3, "to": 5, "kind": "normal"},
+    {"from": 4, "to": 5, "kind": "normal"},
+    {"from": 5, "to": 2, "kind": "normal"}
+  ]
+}
+"#,
+        )
+        .run();
+
+    p.cargo("tree --format json -Z unstable-options -e features -e no-dev -e no-build --depth 2")
+        .masquerade_as_nightly_cargo()
+        .with_json(
+            r#"
+{
+  "version": 1,
+  "roots": [0],
+  "nodes": [
+    {
+      "kind": "package",
+      "id": "foo 0.1.0 (path+file:///[..]/foo)",
+      "name": "foo",
+      "version": "0.1.0",
+      "features": [],
+      "platform": null,
+      "proc_macro": false,
+      "duplicate": false
+    },
+    {
+      "kind": "feature",
+      "package": "a 1.0.0 (registry+https://github.com/rust-lang/crates.io-index)",
+      "name": "default",
+      "cli": false
+    },
+    {
+      "kind": "feature",
+      "package": "c 1.0.0 (registry+https://github.com/rust-lang/crates.io-index)",
+      "name": "default",
+      "cli": false
+    },
+    {
+      "kind": "package",
+      "id": "a 1.0.0 (registry+https://github.com/rust-lang/crates.io-index)",
+      "name": "a",
+      "version": "1.0.0",
+      "features": [],
+      "platform": null,
+      "proc_macro": false,
+      "duplicate": false
+    },
+    {
+      "kind": "package",
+      "id": "c 1.0.0 (registry+https://github.com/rust-lang/crates.io-index)",
+      "name": "c",
+      "version": "1.0.0",
+      "features": [],
+      "platform": null,
+      "proc_macro": false,
+      "duplicate": false
+    }
+  ],
+  "edges": [
+    {"from": 0, "to": 1, "kind": "normal"},
+    {"from": 0, "to": 2, "kind": "normal"},
+    {"from": 1, "to": 3, "kind": "feature"},
+    {"from": 2, "to": 4, "kind": "feature"}
+  ]
+}
+"#,
+        )
+        .run();
+}
+
+#[cargo_test]
+fn graph_formats_reject_text_options() {
+    let p = make_simple_proj();
+
+    for args in &["--prefix depth", "--no-dedupe", "--charset ascii"] {
+        p.cargo(&format!("tree --format json -Z unstable-options {}", args))
+            .masquerade_as_nightly_cargo()
+            .with_status(101)
+            .with_stderr(format!(
+                "[ERROR] `--format json` does not support `{}`",
+                args.split(' ').next().unwrap()
+            ))
+            .run();
+    }
+
+    p.cargo("tree --format dot -Z unstable-options --prefix indent")
+        .masquerade_as_nightly_cargo()
+        .with_status(101)
+        .with_stderr("[ERROR] `--format dot` does not support `--prefix`")
+        .run();
+}
+
+#[cargo_test]
+fn dot_format() {
+    let p = make_simple_proj();
+
+    p.cargo("tree --format dot -Z unstable-options -e normal --prune c")
+        .masquerade_as_nightly_cargo()
+        .with_stdout(
+            "\
+digraph {
+    0 [label=\"foo v0.1.0 ([..]/foo)\"];
+    1 [label=\"a v1.0.0\"];
+    2 [label=\"b v1.0.0\"];
+    0 -> 1;
+    1 -> 2;
+}
+",
+        )
+        .run();
+
+    p.cargo("tree --format dot -Z unstable-options -i b --depth 1")
+        .masquerade_as_nightly_cargo()
+        .with_stdout(
+            "\
+digraph {
+    0 [label=\"b v1.0.0\"];
+    1 [label=\"a v1.0.0\"];
+    2 [label=\"bdep v1.0.0\"];
+    3 [label=\"devdep v1.0.0\"];
+    0 -> 1;
+    0 -> 2;
+    0 -> 3;
+}
+",
+        )
+        .run();
+
+    p.cargo("tree --format dot -Z unstable-options -e build --depth 1")
+        .masquerade_as_nightly_cargo()
+        .with_stdout(
+            "\
+digraph {
+    0 [label=\"foo v0.1.0 ([..]/foo)\"];
+    1 [label=\"bdep v1.0.0\"];
+    0 -> 1 [label=\"build\", style=dashed];
+}
+",
+        )
+        .run();
+}
@@ -386,7 +386,9 @@ fn update_precise() {
         .with_stderr(
             "\
 [UPDATING] `[..]` index
-[UPDATING] serde v0.2.1 -> v0.2.0
+[DOWNGRADING] serde v0.2.1 -> v0.2.0
+[NOTE] 1 dependency is held back by a semver-incompatible newer release
+pass `--verbose` to see why
 ",
         )
         .run();
@@ -420,7 +422,7 @@ fn update_precise_first_run() {
         .with_stderr(
             "\
 [UPDATING] `[..]` index
-[UPDATING] serde v0.2.1 -> v0.2.0
+[DOWNGRADING] serde v0.2.1 -> v0.2.0
 ",
         )
         .run();
@@ -656,6 +658,91 @@ fn dry_run_update() {
     assert_eq!(old_lockfile, new_lockfile)
 }
 
+#[cargo_test]
+fn precise_downgrade() {
+    Package::new("serde", "0.1.0").publish();
+    Package::new("serde", "0.1.1").publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "bar"
+                version = "0.0.1"
+
+                [dependencies]
+                serde = "0.1"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("generate-lockfile").run();
+    p.cargo("update -p serde --precise 0.1.0")
+        .with_stderr(
+            "\
+[UPDATING] `[..]` index
+[DOWNGRADING] serde v0.1.1 -> v0.1.0
+",
+        )
+        .run();
+    p.cargo("update -p serde")
+        .with_stderr(
+            "\
+[UPDATING] `[..]` index
+[UPDATING] serde v0.1.0 -> v0.1.1
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn held_back_dependencies() {
+    Package::new("log", "0.1.0").publish();
+    Package::new("log", "0.2.0").publish();
+    Package::new("serde", "0.1.0").publish();
+    Package::new("serde", "0.1.1").publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "bar"
+                version = "0.0.1"
+
+                [dependencies]
+                log = "0.1"
+                serde = "=0.1.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("generate-lockfile").run();
+    p.cargo("update --dry-run")
+        .with_stderr(
+            "\
+[UPDATING] `[..]` index
+[NOTE] 1 dependency is held back by a semver-incompatible newer release
+pass `--verbose` to see why
+[WARNING] not updating lockfile due to dry run
+",
+        )
+        .run();
+    p.cargo("update --verbose")
+        .with_stderr(
+            "\
+[UPDATING] `[..]` index
+[UNCHANGED] log v0.1.0 (latest: v0.2.0), required by bar v0.0.1 ([CWD]) as `log = \"^0.1\"`
+[UNCHANGED] serde v0.1.0 (latest: v0.1.1), required by bar v0.0.1 ([CWD]) as `serde = \"=0.1.0\"`
+[NOTE] 1 dependency is held back by a semver-incompatible newer release
+",
+        )
+        .run();
+}
+
 #[cargo_test]
 fn workspace_only() {
     let p = project().file("src/main.rs", "fn main() {}").build();